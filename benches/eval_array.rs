@@ -85,3 +85,52 @@ fn bench_eval_array_loop(bench: &mut Bencher) {
 
     bench.iter(|| engine.consume_ast(&ast).unwrap());
 }
+
+#[bench]
+fn bench_eval_array_pass_to_function(bench: &mut Bencher) {
+    let script = r#"
+            fn first(list) { list[0] }
+
+            let list = [];
+
+            for i in range(0, 1_000) {
+                list.push(i);
+            }
+
+            let sum = 0;
+
+            for i in range(0, 1_000) {
+                sum += first(list);
+            }
+        "#;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.consume_ast(&ast).unwrap());
+}
+
+#[bench]
+fn bench_eval_array_copy(bench: &mut Bencher) {
+    let script = r#"
+            let list = [];
+
+            for i in range(0, 1_000) {
+                list.push(i);
+            }
+
+            for i in range(0, 1_000) {
+                let copy = list;
+                copy[0];
+            }
+        "#;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.consume_ast(&ast).unwrap());
+}
@@ -0,0 +1,43 @@
+#![feature(test)]
+
+///! Test creating, cloning and casting `Dynamic` values
+extern crate test;
+
+use rhai::{Dynamic, Engine, OptimizationLevel, INT};
+use test::Bencher;
+
+#[bench]
+fn bench_dynamic_from_cast(bench: &mut Bencher) {
+    bench.iter(|| {
+        (0..1_000 as INT)
+            .map(|x| Dynamic::from(x).cast::<INT>())
+            .sum::<INT>()
+    });
+}
+
+#[bench]
+fn bench_dynamic_vec_clone(bench: &mut Bencher) {
+    let values: Vec<Dynamic> = (0..1_000 as INT).map(Dynamic::from).collect();
+
+    bench.iter(|| values.clone());
+}
+
+#[bench]
+fn bench_dynamic_large_array(bench: &mut Bencher) {
+    let script = r#"
+            let list = [];
+
+            for i in range(0, 10_000) {
+                list.push(i);
+                list.push("x");
+                list.push(());
+            }
+        "#;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.consume_ast(&ast).unwrap());
+}
@@ -69,3 +69,52 @@ fn bench_eval_map_large_set(bench: &mut Bencher) {
 
     bench.iter(|| engine.consume_ast(&ast).unwrap());
 }
+
+#[bench]
+fn bench_eval_map_pass_to_function(bench: &mut Bencher) {
+    let script = r#"
+            fn get_a(map) { map.a }
+
+            let map = #{ a: 42 };
+
+            for i in range(0, 1_000) {
+                map["k" + i] = i;
+            }
+
+            let sum = 0;
+
+            for i in range(0, 1_000) {
+                sum += get_a(map);
+            }
+        "#;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.consume_ast(&ast).unwrap());
+}
+
+#[bench]
+fn bench_eval_map_copy(bench: &mut Bencher) {
+    let script = r#"
+            let map = #{ a: 42 };
+
+            for i in range(0, 1_000) {
+                map["k" + i] = i;
+            }
+
+            for i in range(0, 1_000) {
+                let copy = map;
+                copy.a;
+            }
+        "#;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.consume_ast(&ast).unwrap());
+}
@@ -75,3 +75,119 @@ fn bench_eval_scope_complex(bench: &mut Bencher) {
 
     bench.iter(|| engine.consume_ast_with_scope(&mut scope, &ast).unwrap());
 }
+
+#[bench]
+fn bench_eval_scope_closure_captured(bench: &mut Bencher) {
+    let script = r#"
+            let a = 1;
+            let b = 2;
+            let c = 3;
+
+            let f = |n| {
+                let x1 = 0; let x2 = 0; let x3 = 0; let x4 = 0; let x5 = 0;
+                let x6 = 0; let x7 = 0; let x8 = 0; let x9 = 0; let x10 = 0;
+                let sum = 0;
+
+                for i in range(0, n) {
+                    sum += a + b + c;
+                }
+
+                sum
+            };
+
+            f.call(1_000)
+        "#;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.consume_ast(&ast).unwrap());
+}
+
+#[bench]
+fn bench_eval_scope_function_locals(bench: &mut Bencher) {
+    let script = r#"
+            fn sum(n, requests_made, requests_failed, requests_retried) {
+                let requests_by_client_1 = 0; let requests_by_client_2 = 0;
+                let requests_by_client_3 = 0; let requests_by_client_4 = 0;
+                let requests_by_client_5 = 0; let requests_by_client_6 = 0;
+                let requests_by_client_7 = 0; let requests_by_client_8 = 0;
+                let requests_by_client_9 = 0; let requests_by_client_10 = 0;
+                let requests_by_client_11 = 0; let requests_by_client_12 = 0;
+                let requests_by_client_13 = 0; let requests_by_client_14 = 0;
+                let requests_by_client_15 = 0; let requests_by_client_16 = 0;
+                let requests_by_client_17 = 0; let requests_by_client_18 = 0;
+                let requests_by_client_19 = 0; let requests_by_client_20 = 0;
+                let requests_by_client_21 = 0; let requests_by_client_22 = 0;
+                let requests_by_client_23 = 0; let requests_by_client_24 = 0;
+                let requests_by_client_25 = 0; let requests_by_client_26 = 0;
+                let requests_by_client_27 = 0; let requests_by_client_28 = 0;
+                let requests_by_client_29 = 0; let requests_by_client_30 = 0;
+                let requests_by_client_31 = 0; let requests_by_client_32 = 0;
+                let requests_sum = 0;
+
+                for i in range(0, n) {
+                    let total = requests_made + requests_failed + requests_retried;
+                    requests_sum += total + requests_made + requests_failed + requests_retried;
+                }
+
+                requests_sum
+            }
+
+            sum(1_000, 1, 2, 3)
+        "#;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.consume_ast(&ast).unwrap());
+}
+
+#[bench]
+fn bench_eval_scope_function_locals_after_eval(bench: &mut Bencher) {
+    // Same as `bench_eval_scope_function_locals`, but the `eval` defines a variable.
+    // Variables defined before the `eval` are still accessed via their offsets,
+    // and only the variable defined by the `eval` is searched by name.
+    let script = r#"
+            fn sum(n, requests_made, requests_failed, requests_retried) {
+                let requests_by_client_1 = 0; let requests_by_client_2 = 0;
+                let requests_by_client_3 = 0; let requests_by_client_4 = 0;
+                let requests_by_client_5 = 0; let requests_by_client_6 = 0;
+                let requests_by_client_7 = 0; let requests_by_client_8 = 0;
+                let requests_by_client_9 = 0; let requests_by_client_10 = 0;
+                let requests_by_client_11 = 0; let requests_by_client_12 = 0;
+                let requests_by_client_13 = 0; let requests_by_client_14 = 0;
+                let requests_by_client_15 = 0; let requests_by_client_16 = 0;
+                let requests_by_client_17 = 0; let requests_by_client_18 = 0;
+                let requests_by_client_19 = 0; let requests_by_client_20 = 0;
+                let requests_by_client_21 = 0; let requests_by_client_22 = 0;
+                let requests_by_client_23 = 0; let requests_by_client_24 = 0;
+                let requests_by_client_25 = 0; let requests_by_client_26 = 0;
+                let requests_by_client_27 = 0; let requests_by_client_28 = 0;
+                let requests_by_client_29 = 0; let requests_by_client_30 = 0;
+                let requests_by_client_31 = 0; let requests_by_client_32 = 0;
+                let requests_sum = 0;
+                eval("let requests_0 = 0;");
+
+                for i in range(0, n) {
+                    let total = requests_made + requests_failed + requests_retried;
+                    requests_sum += total + requests_made + requests_failed + requests_retried;
+                }
+
+                requests_sum
+            }
+
+            sum(1_000, 1, 2, 3)
+        "#;
+
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.consume_ast(&ast).unwrap());
+}
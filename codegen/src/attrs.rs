@@ -143,3 +143,24 @@ pub(crate) fn deny_cfg_attr(attrs: &Vec<syn::Attribute>) -> syn::Result<()> {
         Ok(())
     }
 }
+
+/// Collect the doc comments of an item (`#[doc = "..."]` attributes, which include those written
+/// as `///` or `/** */`), as `///` lines.
+pub(crate) fn doc_comments(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|a| a.path.get_ident().map(|i| *i == "doc").unwrap_or(false))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(ref s),
+                ..
+            })) => Some(s.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.lines()
+                .map(|line| format!("///{}", line))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
@@ -12,7 +12,7 @@ use std::format;
 
 use std::borrow::Cow;
 
-use quote::{quote, quote_spanned};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::{
     parse::{Parse, ParseStream, Parser},
     spanned::Spanned,
@@ -77,6 +77,19 @@ pub(crate) fn flatten_type_groups(ty: &syn::Type) -> &syn::Type {
     }
 }
 
+/// Print a type as written in source code, e.g. `&mut INT`.
+pub(crate) fn print_type(ty: &syn::Type) -> String {
+    ty.to_token_stream()
+        .to_string()
+        .replace(" , ", ", ")
+        .replace("& mut ", "&mut ")
+        .replace("& ", "&")
+        .replace(" :: ", "::")
+        .replace(" < ", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct ExportedFnParams {
     pub name: Option<Vec<String>>,
@@ -229,6 +242,7 @@ pub(crate) struct ExportedFn {
     return_dynamic: bool,
     mut_receiver: bool,
     params: ExportedFnParams,
+    comments: Vec<String>,
 }
 
 impl Parse for ExportedFn {
@@ -364,6 +378,7 @@ impl Parse for ExportedFn {
             return_dynamic,
             mut_receiver,
             params: Default::default(),
+            comments: crate::attrs::doc_comments(&fn_all.attrs),
         })
     }
 }
@@ -407,6 +422,10 @@ impl ExportedFn {
         &self.entire_span
     }
 
+    pub(crate) fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
     pub(crate) fn name(&self) -> &syn::Ident {
         &self.signature.ident
     }
@@ -463,6 +482,44 @@ impl ExportedFn {
         }
     }
 
+    /// Names and types of the parameters, in the format `name: type`, followed by the
+    /// return type.
+    pub(crate) fn param_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .arg_list()
+            .map(|arg| match arg {
+                syn::FnArg::Receiver(_) => panic!("internal error: receiver fn outside impl!?"),
+                syn::FnArg::Typed(syn::PatType {
+                    ref pat, ref ty, ..
+                }) => {
+                    let name = match pat.as_ref() {
+                        syn::Pat::Ident(syn::PatIdent { ref ident, .. }) => ident.to_string(),
+                        _ => "_".to_string(),
+                    };
+                    format!("{}: {}", name, print_type(ty))
+                }
+            })
+            .collect();
+
+        // Raw functions return `Result<T, Box<EvalAltResult>>`
+        let return_type = match self.return_type() {
+            Some(syn::Type::Path(syn::TypePath { ref path, .. })) if self.params.return_raw => {
+                match path.segments.last().map(|s| &s.arguments) {
+                    Some(syn::PathArguments::AngleBracketed(ref args)) => match args.args.first() {
+                        Some(syn::GenericArgument::Type(ref ty)) => print_type(ty),
+                        _ => print_type(self.return_type().unwrap()),
+                    },
+                    _ => print_type(self.return_type().unwrap()),
+                }
+            }
+            Some(ty) => print_type(ty),
+            None => "()".to_string(),
+        };
+
+        names.push(return_type);
+        names
+    }
+
     pub fn set_params(&mut self, mut params: ExportedFnParams) -> syn::Result<()> {
         // Several issues are checked here to avoid issues with diagnostics caused by raising them
         // later.
@@ -546,6 +603,7 @@ impl ExportedFn {
         let impl_block = self.generate_impl("Token");
         let callable_block = self.generate_callable("Token");
         let input_types_block = self.generate_input_types("Token");
+        let doc_comments_block = self.generate_doc_comments("Token");
         let dyn_result_fn_block = self.generate_dynamic_fn();
         quote! {
             #[allow(unused)]
@@ -555,6 +613,7 @@ impl ExportedFn {
                 #impl_block
                 #callable_block
                 #input_types_block
+                #doc_comments_block
                 #dyn_result_fn_block
             }
         }
@@ -637,6 +696,19 @@ impl ExportedFn {
         }
     }
 
+    pub fn generate_doc_comments(&self, on_type_name: &str) -> proc_macro2::TokenStream {
+        let doc_comments_fn_name: syn::Ident = syn::Ident::new(
+            format!("{}_doc_comments", on_type_name.to_lowercase()).as_str(),
+            self.name().span(),
+        );
+        let comments = self.comments.iter();
+        quote! {
+            pub fn #doc_comments_fn_name() -> &'static [&'static str] {
+                &[#(#comments),*]
+            }
+        }
+    }
+
     pub fn generate_impl(&self, on_type_name: &str) -> proc_macro2::TokenStream {
         let sig_name = self.name().clone();
         let name = self.params.name.as_ref().map_or_else(
@@ -668,7 +740,7 @@ impl ExportedFn {
                         p => p,
                     };
                     let downcast_span = quote_spanned!(
-                        arg_type.span()=> &mut args[0usize].write_lock::<#arg_type>().unwrap());
+                        arg_type.span().resolved_at(proc_macro2::Span::call_site())=> &mut args[0usize].write_lock::<#arg_type>().unwrap());
                     unpack_stmts.push(
                         syn::parse2::<syn::Stmt>(quote! {
                             let #var = #downcast_span;
@@ -711,7 +783,7 @@ impl ExportedFn {
                             syn::Type::Path(ref p) if p.path == str_type_path => {
                                 is_string = true;
                                 is_ref = true;
-                                quote_spanned!(arg_type.span()=>
+                                quote_spanned!(arg_type.span().resolved_at(proc_macro2::Span::call_site())=>
                                                mem::take(args[#i]).take_immutable_string().unwrap())
                             }
                             _ => panic!("internal error: why wasn't this found earlier!?"),
@@ -719,13 +791,13 @@ impl ExportedFn {
                         syn::Type::Path(ref p) if p.path == string_type_path => {
                             is_string = true;
                             is_ref = false;
-                            quote_spanned!(arg_type.span()=>
+                            quote_spanned!(arg_type.span().resolved_at(proc_macro2::Span::call_site())=>
                                            mem::take(args[#i]).take_string().unwrap())
                         }
                         _ => {
                             is_string = false;
                             is_ref = false;
-                            quote_spanned!(arg_type.span()=>
+                            quote_spanned!(arg_type.span().resolved_at(proc_macro2::Span::call_site())=>
                                            mem::take(args[#i]).cast::<#arg_type>())
                         }
                     };
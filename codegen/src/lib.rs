@@ -339,9 +339,12 @@ pub fn set_exported_fn(args: proc_macro::TokenStream) -> proc_macro::TokenStream
     };
     let gen_mod_path = crate::register::generated_module_path(&rust_modpath);
     let tokens = quote! {
-        #module_expr.set_fn(#export_name, FnAccess::Public,
-                            #gen_mod_path::token_input_types().as_ref(),
-                            #gen_mod_path::token_callable());
+        {
+            let hash = (#module_expr).set_fn(#export_name, FnAccess::Public,
+                                             #gen_mod_path::token_input_types().as_ref(),
+                                             #gen_mod_path::token_callable());
+            (#module_expr).update_fn_comments(hash, #gen_mod_path::token_doc_comments());
+        }
     };
     proc_macro::TokenStream::from(tokens)
 }
@@ -123,14 +123,36 @@ pub(crate) fn generate_body(
             })
             .collect();
 
+        let fn_param_names: Vec<syn::LitStr> = function
+            .param_names()
+            .iter()
+            .map(|name| syn::LitStr::new(name, proc_macro2::Span::call_site()))
+            .collect();
+
+        let fn_comments = function.comments();
+
         for fn_literal in reg_names {
             set_fn_stmts.push(
                 syn::parse2::<syn::Stmt>(quote! {
-                    m.set_fn(#fn_literal, FnAccess::Public, &[#(#fn_input_types),*],
-                             #fn_token_name().into());
+                    let hash = m.set_fn(#fn_literal, FnAccess::Public, &[#(#fn_input_types),*],
+                                        #fn_token_name().into());
                 })
                 .unwrap(),
             );
+            set_fn_stmts.push(
+                syn::parse2::<syn::Stmt>(quote! {
+                    m.update_fn_metadata(hash, &[#(#fn_param_names),*]);
+                })
+                .unwrap(),
+            );
+            if !fn_comments.is_empty() {
+                set_fn_stmts.push(
+                    syn::parse2::<syn::Stmt>(quote! {
+                        m.update_fn_comments(hash, &[#(#fn_comments),*]);
+                    })
+                    .unwrap(),
+                );
+            }
         }
 
         gen_fn_tokens.push(quote! {
@@ -296,6 +296,9 @@ mod generate_tests {
                 pub fn token_input_types() -> Box<[TypeId]> {
                     Token().input_types()
                 }
+                pub fn token_doc_comments() -> &'static [&'static str] {
+                    &[]
+                }
                 pub fn dynamic_result_fn() -> Result<Dynamic, Box<EvalAltResult> > {
                     Ok(Dynamic::from(do_nothing()))
                 }
@@ -338,6 +341,9 @@ mod generate_tests {
                 pub fn token_input_types() -> Box<[TypeId]> {
                     Token().input_types()
                 }
+                pub fn token_doc_comments() -> &'static [&'static str] {
+                    &[]
+                }
                 pub fn dynamic_result_fn(x: usize) -> Result<Dynamic, Box<EvalAltResult> > {
                     Ok(Dynamic::from(do_something(x)))
                 }
@@ -380,6 +386,9 @@ mod generate_tests {
                 pub fn token_input_types() -> Box<[TypeId]> {
                     Token().input_types()
                 }
+                pub fn token_doc_comments() -> &'static [&'static str] {
+                    &[]
+                }
                 pub fn dynamic_result_fn(context: NativeCallContext, x: usize) -> Result<Dynamic, Box<EvalAltResult> > {
                     Ok(Dynamic::from(do_something(context, x)))
                 }
@@ -424,6 +433,9 @@ mod generate_tests {
                 pub fn token_input_types() -> Box<[TypeId]> {
                     Token().input_types()
                 }
+                pub fn token_doc_comments() -> &'static [&'static str] {
+                    &[]
+                }
                 pub fn dynamic_result_fn() -> Result<Dynamic, Box<EvalAltResult> > {
                     Ok(return_dynamic())
                 }
@@ -496,6 +508,9 @@ mod generate_tests {
                 pub fn token_input_types() -> Box<[TypeId]> {
                     Token().input_types()
                 }
+                pub fn token_doc_comments() -> &'static [&'static str] {
+                    &[]
+                }
                 pub fn dynamic_result_fn(x: usize, y: usize) -> Result<Dynamic, Box<EvalAltResult> > {
                     Ok(Dynamic::from(add_together(x, y)))
                 }
@@ -540,6 +555,9 @@ mod generate_tests {
                 pub fn token_input_types() -> Box<[TypeId]> {
                     Token().input_types()
                 }
+                pub fn token_doc_comments() -> &'static [&'static str] {
+                    &[]
+                }
                 pub fn dynamic_result_fn(x: &mut usize, y: usize) -> Result<Dynamic, Box<EvalAltResult> > {
                     Ok(Dynamic::from(increment(x, y)))
                 }
@@ -583,6 +601,9 @@ mod generate_tests {
                 pub fn token_input_types() -> Box<[TypeId]> {
                     Token().input_types()
                 }
+                pub fn token_doc_comments() -> &'static [&'static str] {
+                    &[]
+                }
                 pub fn dynamic_result_fn(message: &str) -> Result<Dynamic, Box<EvalAltResult> > {
                     Ok(Dynamic::from(special_print(message)))
                 }
@@ -295,8 +295,9 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("get_mystic_number", FnAccess::Public, &[],
+                    let hash = m.set_fn("get_mystic_number", FnAccess::Public, &[],
                              get_mystic_number_token().into());
+                    m.update_fn_metadata(hash, &["INT"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -355,8 +356,76 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("add_one_to", FnAccess::Public, &[core::any::TypeId::of::<INT>()],
+                    let hash = m.set_fn("add_one_to", FnAccess::Public, &[core::any::TypeId::of::<INT>()],
                              add_one_to_token().into());
+                    m.update_fn_metadata(hash, &["x: INT", "INT"]);
+                    if flatten {} else {}
+                }
+                #[allow(non_camel_case_types)]
+                struct add_one_to_token();
+                impl PluginFunction for add_one_to_token {
+                    fn call(&self, context: NativeCallContext, args: &mut [&mut Dynamic]) -> Result<Dynamic, Box<EvalAltResult>> {
+                        debug_assert_eq!(args.len(), 1usize,
+                                            "wrong arg count: {} != {}", args.len(), 1usize);
+                        let arg0 = mem::take(args[0usize]).cast::<INT>();
+                        Ok(Dynamic::from(add_one_to(arg0)))
+                    }
+
+                    fn is_method_call(&self) -> bool { false }
+                    fn is_variadic(&self) -> bool { false }
+                    fn clone_boxed(&self) -> Box<dyn PluginFunction> {
+                        Box::new(add_one_to_token())
+                    }
+                    fn input_types(&self) -> Box<[TypeId]> {
+                        new_vec![TypeId::of::<INT>()].into_boxed_slice()
+                    }
+                }
+                pub fn add_one_to_token_callable() -> CallableFunction {
+                    add_one_to_token().into()
+                }
+                pub fn add_one_to_token_input_types() -> Box<[TypeId]> {
+                    add_one_to_token().input_types()
+                }
+            }
+        };
+
+        let item_mod = syn::parse2::<Module>(input_tokens).unwrap();
+        assert_streams_eq(item_mod.generate(), expected_tokens);
+    }
+
+    #[test]
+    fn one_fn_with_doc_comments_module() {
+        let input_tokens: TokenStream = quote! {
+            pub mod one_fn {
+                /// Add one to a number.
+                /// Overflows are not checked.
+                pub fn add_one_to(x: INT) -> INT {
+                    x + 1
+                }
+            }
+        };
+
+        let expected_tokens = quote! {
+            pub mod one_fn {
+                /// Add one to a number.
+                /// Overflows are not checked.
+                pub fn add_one_to(x: INT) -> INT {
+                    x + 1
+                }
+                #[allow(unused_imports)]
+                use super::*;
+
+                pub fn rhai_module_generate() -> Module {
+                    let mut m = Module::new();
+                    rhai_generate_into_module(&mut m, false);
+                    m
+                }
+                #[allow(unused_mut)]
+                pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
+                    let hash = m.set_fn("add_one_to", FnAccess::Public, &[core::any::TypeId::of::<INT>()],
+                             add_one_to_token().into());
+                    m.update_fn_metadata(hash, &["x: INT", "INT"]);
+                    m.update_fn_comments(hash, &["/// Add one to a number.", "/// Overflows are not checked."]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -427,11 +496,13 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("add_n", FnAccess::Public, &[core::any::TypeId::of::<INT>()],
+                    let hash = m.set_fn("add_n", FnAccess::Public, &[core::any::TypeId::of::<INT>()],
                              add_one_to_token().into());
-                    m.set_fn("add_n", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
+                    m.update_fn_metadata(hash, &["x: INT", "INT"]);
+                    let hash = m.set_fn("add_n", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
                                                           core::any::TypeId::of::<INT>()],
                              add_n_to_token().into());
+                    m.update_fn_metadata(hash, &["x: INT", "y: INT", "INT"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -519,9 +590,10 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("add_together", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
+                    let hash = m.set_fn("add_together", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
                                                                  core::any::TypeId::of::<INT>()],
                              add_together_token().into());
+                    m.update_fn_metadata(hash, &["x: INT", "y: INT", "INT"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -584,15 +656,18 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("add", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
+                    let hash = m.set_fn("add", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
                                                                  core::any::TypeId::of::<INT>()],
                              add_together_token().into());
-                    m.set_fn("+", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
+                    m.update_fn_metadata(hash, &["x: INT", "y: INT", "INT"]);
+                    let hash = m.set_fn("+", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
                                                                  core::any::TypeId::of::<INT>()],
                              add_together_token().into());
-                    m.set_fn("add_together", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
+                    m.update_fn_metadata(hash, &["x: INT", "y: INT", "INT"]);
+                    let hash = m.set_fn("add_together", FnAccess::Public, &[core::any::TypeId::of::<INT>(),
                                                                  core::any::TypeId::of::<INT>()],
                              add_together_token().into());
+                    m.update_fn_metadata(hash, &["x: INT", "y: INT", "INT"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -831,8 +906,9 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("get_mystic_number", FnAccess::Public, &[],
+                    let hash = m.set_fn("get_mystic_number", FnAccess::Public, &[],
                              get_mystic_number_token().into());
+                    m.update_fn_metadata(hash, &["INT"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -921,9 +997,10 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("print_out_to", FnAccess::Public,
+                    let hash = m.set_fn("print_out_to", FnAccess::Public,
                              &[core::any::TypeId::of::<ImmutableString>()],
                              print_out_to_token().into());
+                    m.update_fn_metadata(hash, &["x: &str", "()"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -983,9 +1060,10 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("print_out_to", FnAccess::Public,
+                    let hash = m.set_fn("print_out_to", FnAccess::Public,
                              &[core::any::TypeId::of::<ImmutableString>()],
                              print_out_to_token().into());
+                    m.update_fn_metadata(hash, &["x: String", "()"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1045,9 +1123,10 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("increment", FnAccess::Public,
+                    let hash = m.set_fn("increment", FnAccess::Public,
                              &[core::any::TypeId::of::<FLOAT>()],
                              increment_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut FLOAT", "()"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1110,9 +1189,10 @@ mod generate_tests {
                     }
                     #[allow(unused_mut)]
                     pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                        m.set_fn("increment", FnAccess::Public,
+                        let hash = m.set_fn("increment", FnAccess::Public,
                                  &[core::any::TypeId::of::<FLOAT>()],
                                  increment_token().into());
+                        m.update_fn_metadata(hash, &["x: &mut FLOAT", "()"]);
                         if flatten {} else {}
                     }
                     #[allow(non_camel_case_types)]
@@ -1195,9 +1275,10 @@ mod generate_tests {
                     }
                     #[allow(unused_mut)]
                     pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                        m.set_fn("increment", FnAccess::Public,
+                        let hash = m.set_fn("increment", FnAccess::Public,
                                  &[core::any::TypeId::of::<FLOAT>()],
                                  increment_token().into());
+                        m.update_fn_metadata(hash, &["x: &mut FLOAT", "()"]);
                         if flatten {} else {}
                     }
                     #[allow(non_camel_case_types)]
@@ -1279,8 +1360,9 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("get$square", FnAccess::Public, &[core::any::TypeId::of::<u64>()],
+                    let hash = m.set_fn("get$square", FnAccess::Public, &[core::any::TypeId::of::<u64>()],
                              int_foo_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut u64", "u64"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1341,10 +1423,12 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("square", FnAccess::Public, &[core::any::TypeId::of::<u64>()],
+                    let hash = m.set_fn("square", FnAccess::Public, &[core::any::TypeId::of::<u64>()],
                              int_foo_token().into());
-                    m.set_fn("get$square", FnAccess::Public, &[core::any::TypeId::of::<u64>()],
+                    m.update_fn_metadata(hash, &["x: &mut u64", "u64"]);
+                    let hash = m.set_fn("get$square", FnAccess::Public, &[core::any::TypeId::of::<u64>()],
                              int_foo_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut u64", "u64"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1405,10 +1489,11 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("set$squared", FnAccess::Public,
+                    let hash = m.set_fn("set$squared", FnAccess::Public,
                              &[core::any::TypeId::of::<u64>(),
                                core::any::TypeId::of::<u64>()],
                              int_foo_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut u64", "y: u64", "()"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1470,14 +1555,16 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("set_sq", FnAccess::Public,
+                    let hash = m.set_fn("set_sq", FnAccess::Public,
                              &[core::any::TypeId::of::<u64>(),
                                core::any::TypeId::of::<u64>()],
                              int_foo_token().into());
-                    m.set_fn("set$squared", FnAccess::Public,
+                    m.update_fn_metadata(hash, &["x: &mut u64", "y: u64", "()"]);
+                    let hash = m.set_fn("set$squared", FnAccess::Public,
                              &[core::any::TypeId::of::<u64>(),
                                core::any::TypeId::of::<u64>()],
                              int_foo_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut u64", "y: u64", "()"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1539,10 +1626,11 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("index$get$", FnAccess::Public,
+                    let hash = m.set_fn("index$get$", FnAccess::Public,
                              &[core::any::TypeId::of::<MyCollection>(),
                                core::any::TypeId::of::<u64>()],
                              get_by_index_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut MyCollection", "i: u64", "FLOAT"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1605,14 +1693,16 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("get", FnAccess::Public,
+                    let hash = m.set_fn("get", FnAccess::Public,
                              &[core::any::TypeId::of::<MyCollection>(),
                                core::any::TypeId::of::<u64>()],
                              get_by_index_token().into());
-                    m.set_fn("index$get$", FnAccess::Public,
+                    m.update_fn_metadata(hash, &["x: &mut MyCollection", "i: u64", "FLOAT"]);
+                    let hash = m.set_fn("index$get$", FnAccess::Public,
                              &[core::any::TypeId::of::<MyCollection>(),
                                core::any::TypeId::of::<u64>()],
                              get_by_index_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut MyCollection", "i: u64", "FLOAT"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1675,11 +1765,12 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("index$set$", FnAccess::Public,
+                    let hash = m.set_fn("index$set$", FnAccess::Public,
                              &[core::any::TypeId::of::<MyCollection>(),
                                core::any::TypeId::of::<u64>(),
                                core::any::TypeId::of::<FLOAT>()],
                              set_by_index_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut MyCollection", "i: u64", "item: FLOAT", "()"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
@@ -1744,16 +1835,18 @@ mod generate_tests {
                 }
                 #[allow(unused_mut)]
                 pub fn rhai_generate_into_module(m: &mut Module, flatten: bool) {
-                    m.set_fn("set", FnAccess::Public,
+                    let hash = m.set_fn("set", FnAccess::Public,
                              &[core::any::TypeId::of::<MyCollection>(),
                                core::any::TypeId::of::<u64>(),
                                core::any::TypeId::of::<FLOAT>()],
                              set_by_index_token().into());
-                    m.set_fn("index$set$", FnAccess::Public,
+                    m.update_fn_metadata(hash, &["x: &mut MyCollection", "i: u64", "item: FLOAT", "()"]);
+                    let hash = m.set_fn("index$set$", FnAccess::Public,
                              &[core::any::TypeId::of::<MyCollection>(),
                                core::any::TypeId::of::<u64>(),
                                core::any::TypeId::of::<FLOAT>()],
                              set_by_index_token().into());
+                    m.update_fn_metadata(hash, &["x: &mut MyCollection", "i: u64", "item: FLOAT", "()"]);
                     if flatten {} else {}
                 }
                 #[allow(non_camel_case_types)]
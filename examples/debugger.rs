@@ -0,0 +1,192 @@
+use rhai::{
+    BreakPoint, Debugger, DebuggerCommand, DebuggerEvent, Dynamic, Engine, EvalAltResult, Position,
+};
+
+#[cfg(not(feature = "no_optimize"))]
+use rhai::OptimizationLevel;
+
+use std::{
+    env,
+    fs::File,
+    io::{stdin, stdout, Read, Write},
+    process::exit,
+};
+
+fn print_help() {
+    println!("help                => print this help");
+    println!("step, s             => step into the next statement");
+    println!("expr, e             => step into the next expression");
+    println!("next, n             => step over function calls to the next statement");
+    println!("out, o              => step out of the current function");
+    println!("continue, c         => continue until the next break-point");
+    println!("break, b <line>     => set a break-point at a line");
+    println!("break, b <function> => set a break-point at a function");
+    println!("list, l             => list all break-points");
+    println!("delete, d <n>       => delete a break-point");
+    println!("scope               => print all variables in the scope");
+    println!("print, p <variable> => print the value of a variable");
+    println!("set <variable> <expression> => set the value of a variable");
+    println!("this                => print the value of 'this'");
+    println!("backtrace, bt       => print the call stack");
+    println!("quit, q             => stop the script");
+    println!();
+}
+
+fn print_source(lines: &[String], pos: Position) {
+    let line = match pos.line() {
+        Some(line) if line <= lines.len() => line,
+        _ => return,
+    };
+
+    println!("{}: {}", line, lines[line - 1]);
+    println!(
+        "{0:>1$}",
+        "^",
+        format!("{}: ", line).len() + pos.position().unwrap()
+    );
+}
+
+fn main() {
+    let filename = match env::args().nth(1) {
+        Some(filename) => filename,
+        None => {
+            eprintln!("Usage: debugger <script file>");
+            exit(1);
+        }
+    };
+
+    let mut contents = String::new();
+
+    if let Err(err) = File::open(&filename).and_then(|mut f| f.read_to_string(&mut contents)) {
+        eprintln!("Error reading script file: {}\n{}", filename, err);
+        exit(1);
+    }
+
+    let lines: Vec<String> = contents.lines().map(String::from).collect();
+
+    let mut engine = Engine::new();
+
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    // A separate engine for evaluating expressions while paused,
+    // so that the evaluation is not itself debugged
+    let expr_engine = Engine::new();
+
+    engine.register_debugger(
+        // Stop at the first statement
+        || Debugger::new(Default::default(), DebuggerCommand::StepInto),
+        move |context, debugger, event, pos| {
+            if let DebuggerEvent::BreakPoint(n) = event {
+                println!("Break-point #{} ({}) hit.", n, debugger.break_points()[n]);
+            }
+
+            print_source(&lines, pos);
+
+            loop {
+                print!("debug> ");
+                stdout().flush().expect("couldn't flush stdout");
+
+                let mut input = String::new();
+
+                match stdin().read_line(&mut input) {
+                    Ok(0) => return Ok(DebuggerCommand::Continue),
+                    Ok(_) => (),
+                    Err(err) => panic!("input error: {}", err),
+                }
+
+                let mut words = input.trim().splitn(3, ' ');
+
+                match (words.next().unwrap_or(""), words.next(), words.next()) {
+                    ("help", None, None) => print_help(),
+                    ("step", None, None) | ("s", None, None) => {
+                        return Ok(DebuggerCommand::StepInto)
+                    }
+                    ("expr", None, None) | ("e", None, None) => {
+                        return Ok(DebuggerCommand::StepExpression)
+                    }
+                    ("next", None, None) | ("n", None, None) => {
+                        return Ok(DebuggerCommand::StepOver)
+                    }
+                    ("out", None, None) | ("o", None, None) => return Ok(DebuggerCommand::StepOut),
+                    ("continue", None, None) | ("c", None, None) => {
+                        return Ok(DebuggerCommand::Continue)
+                    }
+                    ("break", Some(target), None) | ("b", Some(target), None) => {
+                        let bp = match target.parse::<usize>() {
+                            Ok(line) => BreakPoint::AtLine { source: None, line },
+                            Err(_) => BreakPoint::AtFunction {
+                                name: target.into(),
+                            },
+                        };
+                        println!(
+                            "Break-point #{} ({}) added.",
+                            debugger.break_points().len(),
+                            bp
+                        );
+                        debugger.break_points_mut().push(bp);
+                    }
+                    ("list", None, None) | ("l", None, None) => {
+                        for (n, bp) in debugger.break_points().iter().enumerate() {
+                            println!("#{}: {}", n, bp);
+                        }
+                    }
+                    ("delete", Some(n), None) | ("d", Some(n), None) => match n.parse::<usize>() {
+                        Ok(n) if n < debugger.break_points().len() => {
+                            debugger.break_points_mut().remove(n);
+                        }
+                        _ => println!("No such break-point: {}", n),
+                    },
+                    ("scope", None, None) => {
+                        for (name, constant, value) in context.scope.iter() {
+                            let kind = if constant { "const" } else { "let" };
+                            println!("{} {} = {:?}", kind, name, value);
+                        }
+                    }
+                    ("print", Some(name), None) | ("p", Some(name), None) => {
+                        match context.scope.get_value::<Dynamic>(name) {
+                            Some(value) => println!("{} = {:?}", name, value),
+                            None => println!("Variable not found: {}", name),
+                        }
+                    }
+                    ("set", Some(name), Some(expr)) => {
+                        let value = match expr_engine.eval_expression::<Dynamic>(expr) {
+                            Ok(value) => value,
+                            Err(err) => {
+                                println!("{}", err);
+                                continue;
+                            }
+                        };
+                        match context.scope.get_value_mut(name) {
+                            Some(v) => *v = value,
+                            None => println!("Variable not found or constant: {}", name),
+                        }
+                    }
+                    ("this", None, None) => match context.this_ptr() {
+                        Some(value) => println!("this = {:?}", value),
+                        None => println!("'this' is not bound"),
+                    },
+                    ("backtrace", None, None) | ("bt", None, None) => {
+                        for frame in debugger.call_stack() {
+                            println!("{}", frame);
+                        }
+                    }
+                    ("quit", None, None) | ("q", None, None) => {
+                        return Err(EvalAltResult::ErrorTerminated(().into(), pos.into()).into())
+                    }
+                    ("", None, None) => (),
+                    _ => println!("Unknown command. Type 'help' for a list of commands."),
+                }
+            }
+        },
+    );
+
+    println!("Rhai Debugger");
+    println!("=============");
+    print_help();
+
+    if let Err(err) = engine.consume(&contents) {
+        eprintln!("{}", err);
+        exit(1);
+    }
+}
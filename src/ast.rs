@@ -4,11 +4,14 @@ use crate::dynamic::{Dynamic, Union};
 use crate::fn_native::{FnPtr, Shared};
 use crate::module::{Module, ModuleRef};
 use crate::syntax::FnCustomSyntaxEval;
-use crate::token::{Position, Token, NO_POS};
+use crate::token::{Position, Span, Token, NO_POS};
 use crate::utils::ImmutableString;
 use crate::StaticVec;
 use crate::INT;
 
+#[cfg(not(feature = "no_function"))]
+use crate::utils::{calc_param_types_hash, calc_script_fn_hash};
+
 #[cfg(not(feature = "no_float"))]
 use crate::FLOAT;
 
@@ -22,15 +25,18 @@ use crate::stdlib::{
     any::TypeId,
     borrow::Cow,
     boxed::Box,
-    fmt,
+    fmt, format,
     hash::{Hash, Hasher},
     num::NonZeroUsize,
     ops::{Add, AddAssign},
-    string::String,
+    string::{String, ToString},
     vec,
     vec::Vec,
 };
 
+#[cfg(not(feature = "no_function"))]
+use crate::stdlib::iter::empty;
+
 #[cfg(not(feature = "no_float"))]
 use crate::stdlib::ops::Neg;
 
@@ -93,6 +99,18 @@ pub struct ScriptFnDef {
     pub access: FnAccess,
     /// Names of function parameters.
     pub params: StaticVec<String>,
+    /// Type annotations of function parameters, if any.
+    pub param_types: StaticVec<Option<ImmutableString>>,
+    /// Type annotation of the return value, if any.
+    pub return_type: Option<ImmutableString>,
+    /// Location of the function definition in the source, from the `fn` keyword
+    /// (or the opening `|` of a closure) to the closing `}` of its body.
+    pub span: Span,
+    /// Name of the source (e.g. the script file path) the function is defined in, if any.
+    pub source: Option<ImmutableString>,
+    /// Doc comments (`///` lines or `/** */` blocks, including the markers) preceding the
+    /// function definition, if any.
+    pub comments: StaticVec<String>,
     /// Access to external variables. Boxed because it occurs rarely.
     #[cfg(not(feature = "no_closure"))]
     pub externals: Option<Box<HashSet<String>>>,
@@ -102,7 +120,7 @@ impl fmt::Display for ScriptFnDef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{}{}({})",
+            "{}{}({}){}",
             if self.access.is_private() {
                 "private "
             } else {
@@ -111,13 +129,48 @@ impl fmt::Display for ScriptFnDef {
             self.name,
             self.params
                 .iter()
-                .map(|s| s.as_str())
+                .zip(self.param_types.iter())
+                .map(|(name, typ)| match typ {
+                    Some(typ) => format!("{}: {}", name, typ),
+                    None => name.to_string(),
+                })
                 .collect::<Vec<_>>()
-                .join(",")
+                .join(","),
+            match self.return_type {
+                Some(ref typ) => format!(" -> {}", typ),
+                None => "".to_string(),
+            }
         )
     }
 }
 
+impl ScriptFnDef {
+    /// Does the function have any parameter type annotations?
+    #[inline(always)]
+    pub fn is_typed(&self) -> bool {
+        self.param_types.iter().any(Option::is_some)
+    }
+    /// Calculate the hash key of the function.
+    ///
+    /// Functions with parameter type annotations are overloads of the function with the same name
+    /// and number of parameters, and their hashes also depend on the annotations.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) fn calc_hash(&self) -> u64 {
+        let hash_script = calc_script_fn_hash(empty(), &self.name, self.params.len());
+
+        if self.is_typed() {
+            hash_script
+                ^ calc_param_types_hash(
+                    self.param_types
+                        .iter()
+                        .map(|typ| typ.as_ref().map_or("", |s| s.as_str())),
+                )
+        } else {
+            hash_script
+        }
+    }
+}
+
 /// Compiled AST (abstract syntax tree) of a Rhai script.
 ///
 /// # Thread Safety
@@ -154,6 +207,12 @@ impl AST {
         &self.0
     }
 
+    /// Get the statements, without the deprecation warning under the `internals` feature.
+    #[inline(always)]
+    pub(crate) fn stmts(&self) -> &[Stmt] {
+        &self.0
+    }
+
     /// Get a mutable reference to the statements.
     #[cfg(not(feature = "no_optimize"))]
     #[inline(always)]
@@ -378,6 +437,9 @@ impl AST {
         let ast = match (statements.is_empty(), other.0.is_empty()) {
             (false, false) => {
                 let mut statements = statements.clone();
+                if needs_source_reset(&statements, &other.0) {
+                    statements.push(Stmt::Source(None));
+                }
                 statements.extend(other.0.iter().cloned());
                 statements
             }
@@ -449,6 +511,9 @@ impl AST {
         mut filter: impl FnMut(FnAccess, &str, usize) -> bool,
     ) -> &mut Self {
         let Self(ref mut statements, ref mut functions) = self;
+        if needs_source_reset(statements, &other.0) {
+            statements.push(Stmt::Source(None));
+        }
         statements.extend(other.0.into_iter());
         functions.merge_filtered(&other.1, &mut filter);
         self
@@ -492,6 +557,40 @@ impl AST {
         self.1.iter_script_fn()
     }
 
+    /// Get the doc comments (`///` lines or `/** */` blocks, including the markers) preceding
+    /// the definition of a function based on name and number of parameters.
+    ///
+    /// Returns `None` if the function does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile(
+    ///     "
+    ///         /// Add two numbers.
+    ///         /// Overflows are errors.
+    ///         fn add(x, y) { x + y }
+    ///     ",
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     ast.get_fn_comments("add", 2).unwrap(),
+    ///     ["/// Add two numbers.", "/// Overflows are errors."]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn get_fn_comments(&self, name: &str, num_params: usize) -> Option<&[String]> {
+        self.1.get_fn_comments(name, num_params)
+    }
+
     /// Clear all function definitions in the `AST`.
     #[cfg(not(feature = "no_function"))]
     #[inline(always)]
@@ -548,6 +647,10 @@ impl Ident {
     pub fn new(name: String, pos: Position) -> Self {
         Self { name, pos }
     }
+    /// Get the `Position` of the last character of this identifier.
+    pub(crate) fn end_position(&self) -> Position {
+        end_of(self.pos, &self.name)
+    }
 }
 
 /// An identifier containing an immutable name and a position.
@@ -574,6 +677,10 @@ impl IdentX {
             pos,
         }
     }
+    /// Get the `Position` of the last character of this identifier.
+    pub(crate) fn end_position(&self) -> Position {
+        end_of(self.pos, &self.name)
+    }
 }
 
 /// _[INTERNALS]_ A type encapsulating the mode of a `return`/`throw` statement.
@@ -588,6 +695,8 @@ pub enum ReturnType {
     Return,
     /// `throw` statement.
     Exception,
+    /// `throw` statement without a value inside a `catch` block, re-throwing the caught error.
+    Rethrow,
 }
 
 /// _[INTERNALS]_ A statement.
@@ -607,17 +716,26 @@ pub enum Stmt {
     /// loop { stmt }
     Loop(Box<Stmt>, Position),
     /// for id in expr { stmt }
-    For(Expr, Box<(String, Stmt)>, Position),
-    /// let id = expr
-    Let(Box<Ident>, Option<Expr>, Position),
-    /// const id = expr
-    Const(Box<Ident>, Option<Expr>, Position),
+    For(Expr, Box<(Ident, Stmt)>, Position),
+    /// let id: type = expr
+    Let(Box<(Ident, Option<IdentX>)>, Option<Expr>, Position),
+    /// const id: type = expr
+    Const(Box<(Ident, Option<IdentX>)>, Option<Expr>, Position),
     /// expr op= expr
-    Assignment(Box<(Expr, Cow<'static, str>, Expr)>, Position),
+    ///
+    /// Also holds the type annotation of the variable assigned to, if any.
+    Assignment(
+        Box<(Expr, Cow<'static, str>, Expr, Option<ImmutableString>)>,
+        Position,
+    ),
     /// { stmt; ... }
-    Block(Vec<Stmt>, Position),
-    /// try { stmt; ... } catch ( var ) { stmt; ... }
-    TryCatch(Box<(Stmt, Option<Ident>, Stmt)>, Position, Position),
+    Block(Vec<Stmt>, Span),
+    /// try { stmt; ... } catch ( var, stack ) { stmt; ... } finally { stmt; ... }
+    TryCatch(
+        Box<(Stmt, Option<Ident>, Option<Ident>, Stmt, Option<Stmt>)>,
+        Position,
+        Position,
+    ),
     /// expr
     Expr(Expr),
     /// continue
@@ -632,9 +750,13 @@ pub enum Stmt {
     /// export var as var, ...
     #[cfg(not(feature = "no_module"))]
     Export(Vec<(Ident, Option<Ident>)>, Position),
-    /// Convert a variable to shared.
+    /// Convert a variable (with its offset, if known) to shared.
     #[cfg(not(feature = "no_closure"))]
-    Share(Box<Ident>),
+    Share(Box<(Ident, Option<NonZeroUsize>)>),
+    /// Set the name of the source (e.g. the script file path) of the following statements.
+    ///
+    /// Only appears at global level, at the start of the statements of each named script.
+    Source(Option<ImmutableString>),
 }
 
 impl Default for Stmt {
@@ -659,7 +781,6 @@ impl Stmt {
             Self::Noop(pos)
             | Self::Continue(pos)
             | Self::Break(pos)
-            | Self::Block(_, pos)
             | Self::Assignment(_, pos)
             | Self::IfThenElse(_, _, pos)
             | Self::While(_, _, pos)
@@ -667,7 +788,9 @@ impl Stmt {
             | Self::For(_, _, pos)
             | Self::ReturnWithVal((_, pos), _, _) => *pos,
 
-            Self::Let(x, _, _) | Self::Const(x, _, _) => x.pos,
+            Self::Block(_, span) => span.start(),
+
+            Self::Let(x, _, _) | Self::Const(x, _, _) => x.0.pos,
             Self::TryCatch(_, pos, _) => *pos,
 
             Self::Expr(x) => x.position(),
@@ -678,17 +801,76 @@ impl Stmt {
             Self::Export(_, pos) => *pos,
 
             #[cfg(not(feature = "no_closure"))]
-            Self::Share(x) => x.pos,
+            Self::Share(x) => x.0.pos,
+
+            Self::Source(_) => NO_POS,
+        }
+    }
+
+    /// Get the `Position` of the start of this statement in the source.
+    ///
+    /// This differs from `position` for `let`/`const` statements (which are positioned at the
+    /// variable name) and for statements starting with operands of binary operators.
+    pub(crate) fn start_position(&self) -> Position {
+        match self {
+            Self::Let(_, _, pos) | Self::Const(_, _, pos) => *pos,
+            Self::Assignment(x, _) => x.0.start_position(),
+            Self::Expr(x) => x.start_position(),
+            _ => self.position(),
+        }
+    }
+
+    /// Get the `Position` of the last character of this statement in the source.
+    pub(crate) fn end_position(&self) -> Position {
+        match self {
+            Self::Noop(pos) => *pos,
+            Self::IfThenElse(_, x, _) => x.1.as_ref().unwrap_or(&x.0).end_position(),
+            Self::While(_, x, _) | Self::Loop(x, _) => x.end_position(),
+            Self::For(_, x, _) => x.1.end_position(),
+            Self::Let(x, expr, _) | Self::Const(x, expr, _) => match (expr, &x.1) {
+                (Some(expr), _) => expr.end_position(),
+                (None, Some(typ)) => typ.end_position(),
+                (None, None) => x.0.end_position(),
+            },
+            Self::Assignment(x, _) => x.2.end_position(),
+            Self::Block(_, span) => span.end(),
+            Self::TryCatch(x, _, _) => x.4.as_ref().unwrap_or(&x.3).end_position(),
+            Self::Expr(x) => x.end_position(),
+            Self::Continue(pos) => end_of(*pos, "continue"),
+            Self::Break(pos) => end_of(*pos, "break"),
+            Self::ReturnWithVal(_, Some(expr), _) => expr.end_position(),
+            Self::ReturnWithVal((ReturnType::Return, pos), None, _) => end_of(*pos, "return"),
+            Self::ReturnWithVal((_, pos), None, _) => end_of(*pos, "throw"),
+
+            #[cfg(not(feature = "no_module"))]
+            Self::Import(_, Some(alias), _) => alias.end_position(),
+            #[cfg(not(feature = "no_module"))]
+            Self::Import(expr, None, _) => expr.end_position(),
+            #[cfg(not(feature = "no_module"))]
+            Self::Export(list, pos) => match list.last() {
+                Some((_, Some(alias))) => alias.end_position(),
+                Some((name, None)) => name.end_position(),
+                None => end_of(*pos, "export"),
+            },
+
+            #[cfg(not(feature = "no_closure"))]
+            Self::Share(x) => x.0.end_position(),
+
+            Self::Source(_) => NO_POS,
         }
     }
 
+    /// Get the `Span` of this statement in the source.
+    pub fn span(&self) -> Span {
+        make_span(self.start_position(), self.end_position())
+    }
+
     /// Override the `Position` of this statement.
     pub fn set_position(&mut self, new_pos: Position) -> &mut Self {
         match self {
             Self::Noop(pos)
             | Self::Continue(pos)
             | Self::Break(pos)
-            | Self::Block(_, pos)
             | Self::Assignment(_, pos)
             | Self::IfThenElse(_, _, pos)
             | Self::While(_, _, pos)
@@ -696,7 +878,9 @@ impl Stmt {
             | Self::For(_, _, pos)
             | Self::ReturnWithVal((_, pos), _, _) => *pos = new_pos,
 
-            Self::Let(x, _, _) | Self::Const(x, _, _) => x.pos = new_pos,
+            Self::Block(_, span) => *span = Span::new(new_pos, span.end()),
+
+            Self::Let(x, _, _) | Self::Const(x, _, _) => x.0.pos = new_pos,
             Self::TryCatch(_, pos, _) => *pos = new_pos,
 
             Self::Expr(x) => {
@@ -709,7 +893,9 @@ impl Stmt {
             Self::Export(_, pos) => *pos = new_pos,
 
             #[cfg(not(feature = "no_closure"))]
-            Self::Share(x) => x.pos = new_pos,
+            Self::Share(x) => x.0.pos = new_pos,
+
+            Self::Source(_) => (),
         }
 
         self
@@ -723,7 +909,8 @@ impl Stmt {
             | Self::Loop(_, _)
             | Self::For(_, _, _)
             | Self::Block(_, _)
-            | Self::TryCatch(_, _, _) => true,
+            | Self::TryCatch(_, _, _)
+            | Self::Source(_) => true,
 
             // A No-op requires a semicolon in order to know it is an empty statement!
             Self::Noop(_) => false,
@@ -759,7 +946,9 @@ impl Stmt {
             Self::Let(_, _, _) | Self::Const(_, _, _) | Self::Assignment(_, _) => false,
             Self::Block(block, _) => block.iter().all(|stmt| stmt.is_pure()),
             Self::Continue(_) | Self::Break(_) | Self::ReturnWithVal(_, _, _) => false,
-            Self::TryCatch(x, _, _) => x.0.is_pure() && x.2.is_pure(),
+            Self::TryCatch(x, _, _) => {
+                x.0.is_pure() && x.3.is_pure() && x.4.as_ref().map(Stmt::is_pure).unwrap_or(true)
+            }
 
             #[cfg(not(feature = "no_module"))]
             Self::Import(_, _, _) => false,
@@ -768,10 +957,52 @@ impl Stmt {
 
             #[cfg(not(feature = "no_closure"))]
             Self::Share(_) => false,
+
+            // Keep source names for the following statements
+            Self::Source(_) => false,
         }
     }
 }
 
+/// Do statements joined to the end of other statements need a `Stmt::Source(None)` in between,
+/// so that they do not take on the source name of the statements before them?
+fn needs_source_reset(statements: &[Stmt], other: &[Stmt]) -> bool {
+    let named = statements
+        .iter()
+        .rev()
+        .find_map(|stmt| match stmt {
+            Stmt::Source(source) => Some(source.is_some()),
+            _ => None,
+        })
+        .unwrap_or(false);
+
+    named && !matches!(other.first(), None | Some(Stmt::Source(_)))
+}
+
+/// Get the `Position` of the last character of a piece of text on a single line, starting at a
+/// `Position`.
+fn end_of(pos: Position, text: &str) -> Position {
+    let mut end = pos;
+
+    if !end.is_none() {
+        text.chars().skip(1).for_each(|_| end.advance());
+    }
+
+    end
+}
+
+/// Make a `Span` from start and end `Position`s, with an end that is not before the start.
+///
+/// The end is unknown for nodes made up during parsing or optimization, so the `Span` then only
+/// covers the start.
+fn make_span(start: Position, end: Position) -> Span {
+    if start.is_none() {
+        Span::NONE
+    } else {
+        Span::new(start, end.max(start))
+    }
+}
+
 /// _[INTERNALS]_ A type wrapping a custom syntax definition.
 /// Exported under the `internals` feature only.
 ///
@@ -879,6 +1110,9 @@ pub struct FnCallInfo {
     pub native_only: bool,
     /// Does this function call capture the parent scope?
     pub capture: bool,
+    /// Is this a self-recursive call in tail position of a script-defined function?
+    /// If so, the call re-uses the current function call frame instead of nesting a new one.
+    pub tail_call: bool,
     /// Default value when the function is not found, mostly used to provide a default for comparison functions.
     /// Type is `bool` in order for `FnCallInfo` to be `Hash`
     pub def_value: Option<bool>,
@@ -895,40 +1129,43 @@ pub struct FnCallInfo {
 /// _[INTERNALS]_ An expression sub-tree.
 /// Exported under the `internals` feature only.
 ///
+/// Variants that cannot tell where they end from their sub-expressions also hold the `Position`
+/// of their last character, as their last field.
+///
 /// ## WARNING
 ///
 /// This type is volatile and may change.
 #[derive(Debug, Clone, Hash)]
 pub enum Expr {
     /// Integer constant.
-    IntegerConstant(INT, Position),
+    IntegerConstant(INT, Position, Position),
     /// Floating-point constant.
     #[cfg(not(feature = "no_float"))]
-    FloatConstant(FloatWrapper, Position),
+    FloatConstant(FloatWrapper, Position, Position),
     /// Character constant.
-    CharConstant(char, Position),
+    CharConstant(char, Position, Position),
     /// String constant.
-    StringConstant(Box<IdentX>),
+    StringConstant(Box<IdentX>, Position),
     /// FnPtr constant.
-    FnPointer(Box<IdentX>),
+    FnPointer(Box<IdentX>, Position),
     /// Variable access - (optional index, optional modules, hash, variable name)
     Variable(Box<(Option<NonZeroUsize>, Option<Box<ModuleRef>>, u64, Ident)>),
     /// Property access - (getter, setter), prop
     Property(Box<((String, String), IdentX)>),
     /// { stmt }
-    Stmt(Box<StaticVec<Stmt>>, Position),
+    Stmt(Box<StaticVec<Stmt>>, Position, Position),
     /// Wrapped expression - should not be optimized away.
     Expr(Box<Expr>),
     /// func(expr, ... )
-    FnCall(Box<FnCallInfo>, Position),
+    FnCall(Box<FnCallInfo>, Position, Position),
     /// lhs.rhs
     Dot(Box<BinaryExpr>, Position),
     /// expr[expr]
-    Index(Box<BinaryExpr>, Position),
+    Index(Box<BinaryExpr>, Position, Position),
     /// [ expr, ... ]
-    Array(Box<StaticVec<Expr>>, Position),
+    Array(Box<StaticVec<Expr>>, Position, Position),
     /// #{ name:expr, ... }
-    Map(Box<StaticVec<(IdentX, Expr)>>, Position),
+    Map(Box<StaticVec<(IdentX, Expr)>>, Position, Position),
     /// lhs in rhs
     In(Box<BinaryExpr>, Position),
     /// lhs && rhs
@@ -940,15 +1177,15 @@ pub enum Expr {
     /// false
     False(Position),
     /// ()
-    Unit(Position),
+    Unit(Position, Position),
     /// Custom syntax
-    Custom(Box<CustomExpr>, Position),
+    Custom(Box<CustomExpr>, Position, Position),
 }
 
 impl Default for Expr {
     #[inline(always)]
     fn default() -> Self {
-        Self::Unit(NO_POS)
+        Self::Unit(NO_POS, NO_POS)
     }
 }
 
@@ -960,22 +1197,22 @@ impl Expr {
         Some(match self {
             Self::Expr(x) => return x.get_type_id(),
 
-            Self::IntegerConstant(_, _) => TypeId::of::<INT>(),
+            Self::IntegerConstant(_, _, _) => TypeId::of::<INT>(),
             #[cfg(not(feature = "no_float"))]
-            Self::FloatConstant(_, _) => TypeId::of::<FLOAT>(),
-            Self::CharConstant(_, _) => TypeId::of::<char>(),
-            Self::StringConstant(_) => TypeId::of::<ImmutableString>(),
-            Self::FnPointer(_) => TypeId::of::<FnPtr>(),
+            Self::FloatConstant(_, _, _) => TypeId::of::<FLOAT>(),
+            Self::CharConstant(_, _, _) => TypeId::of::<char>(),
+            Self::StringConstant(_, _) => TypeId::of::<ImmutableString>(),
+            Self::FnPointer(_, _) => TypeId::of::<FnPtr>(),
             Self::True(_) | Self::False(_) | Self::In(_, _) | Self::And(_, _) | Self::Or(_, _) => {
                 TypeId::of::<bool>()
             }
-            Self::Unit(_) => TypeId::of::<()>(),
+            Self::Unit(_, _) => TypeId::of::<()>(),
 
             #[cfg(not(feature = "no_index"))]
-            Self::Array(_, _) => TypeId::of::<Array>(),
+            Self::Array(_, _, _) => TypeId::of::<Array>(),
 
             #[cfg(not(feature = "no_object"))]
-            Self::Map(_, _) => TypeId::of::<Map>(),
+            Self::Map(_, _, _) => TypeId::of::<Map>(),
 
             _ => return None,
         })
@@ -988,27 +1225,27 @@ impl Expr {
         Some(match self {
             Self::Expr(x) => return x.get_constant_value(),
 
-            Self::IntegerConstant(x, _) => (*x).into(),
+            Self::IntegerConstant(x, _, _) => (*x).into(),
             #[cfg(not(feature = "no_float"))]
-            Self::FloatConstant(x, _) => x.0.into(),
-            Self::CharConstant(x, _) => (*x).into(),
-            Self::StringConstant(x) => x.name.clone().into(),
-            Self::FnPointer(x) => Dynamic(Union::FnPtr(Box::new(FnPtr::new_unchecked(
+            Self::FloatConstant(x, _, _) => x.0.into(),
+            Self::CharConstant(x, _, _) => (*x).into(),
+            Self::StringConstant(x, _) => x.name.clone().into(),
+            Self::FnPointer(x, _) => Dynamic(Union::FnPtr(Box::new(FnPtr::new_unchecked(
                 x.name.clone(),
                 Default::default(),
             )))),
             Self::True(_) => true.into(),
             Self::False(_) => false.into(),
-            Self::Unit(_) => ().into(),
+            Self::Unit(_, _) => ().into(),
 
             #[cfg(not(feature = "no_index"))]
-            Self::Array(x, _) if x.iter().all(Self::is_constant) => Dynamic(Union::Array(
-                Box::new(x.iter().map(|v| v.get_constant_value().unwrap()).collect()),
+            Self::Array(x, _, _) if x.iter().all(Self::is_constant) => Dynamic(Union::Array(
+                Shared::new(x.iter().map(|v| v.get_constant_value().unwrap()).collect()),
             )),
 
             #[cfg(not(feature = "no_object"))]
-            Self::Map(x, _) if x.iter().all(|(_, v)| v.is_constant()) => {
-                Dynamic(Union::Map(Box::new(
+            Self::Map(x, _, _) if x.iter().all(|(_, v)| v.is_constant()) => {
+                Dynamic(Union::Map(Shared::new(
                     x.iter()
                         .map(|(k, v)| (k.name.clone(), v.get_constant_value().unwrap()))
                         .collect(),
@@ -1027,32 +1264,96 @@ impl Expr {
         }
     }
 
+    /// Get the `Position` of the start of the expression in the source.
+    ///
+    /// This differs from `position` for binary operators, which are positioned at the operator.
+    pub(crate) fn start_position(&self) -> Position {
+        match self {
+            Self::Expr(x) => x.start_position(),
+            Self::Variable(x) => match x.1 {
+                Some(ref modules) if !modules.is_empty() => modules[0].pos,
+                _ => (x.3).pos,
+            },
+            Self::FnCall(x, pos, _) if x.args.len() == 2 => {
+                let start = x.args[0].start_position();
+                if start < *pos {
+                    start
+                } else {
+                    *pos
+                }
+            }
+            Self::Dot(x, _)
+            | Self::Index(x, _, _)
+            | Self::In(x, _)
+            | Self::And(x, _)
+            | Self::Or(x, _) => x.lhs.start_position(),
+            _ => self.position(),
+        }
+    }
+
+    /// Get the `Position` of the last character of the expression in the source.
+    pub(crate) fn end_position(&self) -> Position {
+        match self {
+            Self::Expr(x) => x.end_position(),
+
+            #[cfg(not(feature = "no_float"))]
+            Self::FloatConstant(_, _, end) => *end,
+
+            Self::IntegerConstant(_, _, end)
+            | Self::CharConstant(_, _, end)
+            | Self::StringConstant(_, end)
+            | Self::FnPointer(_, end)
+            | Self::Stmt(_, _, end)
+            | Self::FnCall(_, _, end)
+            | Self::Index(_, _, end)
+            | Self::Array(_, _, end)
+            | Self::Map(_, _, end)
+            | Self::Unit(_, end)
+            | Self::Custom(_, _, end) => *end,
+
+            Self::Variable(x) => (x.3).end_position(),
+            Self::Property(x) => (x.1).end_position(),
+
+            Self::Dot(x, _) | Self::In(x, _) | Self::And(x, _) | Self::Or(x, _) => {
+                x.rhs.end_position()
+            }
+
+            Self::True(pos) => end_of(*pos, "true"),
+            Self::False(pos) => end_of(*pos, "false"),
+        }
+    }
+
+    /// Get the `Span` of the expression in the source.
+    pub fn span(&self) -> Span {
+        make_span(self.start_position(), self.end_position())
+    }
+
     /// Get the `Position` of the expression.
     pub fn position(&self) -> Position {
         match self {
             Self::Expr(x) => x.position(),
 
             #[cfg(not(feature = "no_float"))]
-            Self::FloatConstant(_, pos) => *pos,
-
-            Self::IntegerConstant(_, pos) => *pos,
-            Self::CharConstant(_, pos) => *pos,
-            Self::StringConstant(x) => x.pos,
-            Self::FnPointer(x) => x.pos,
-            Self::Array(_, pos) => *pos,
-            Self::Map(_, pos) => *pos,
+            Self::FloatConstant(_, pos, _) => *pos,
+
+            Self::IntegerConstant(_, pos, _) => *pos,
+            Self::CharConstant(_, pos, _) => *pos,
+            Self::StringConstant(x, _) => x.pos,
+            Self::FnPointer(x, _) => x.pos,
+            Self::Array(_, pos, _) => *pos,
+            Self::Map(_, pos, _) => *pos,
             Self::Property(x) => (x.1).pos,
-            Self::Stmt(_, pos) => *pos,
+            Self::Stmt(_, pos, _) => *pos,
             Self::Variable(x) => (x.3).pos,
-            Self::FnCall(_, pos) => *pos,
+            Self::FnCall(_, pos, _) => *pos,
 
             Self::And(x, _) | Self::Or(x, _) | Self::In(x, _) => x.lhs.position(),
 
-            Self::True(pos) | Self::False(pos) | Self::Unit(pos) => *pos,
+            Self::True(pos) | Self::False(pos) | Self::Unit(pos, _) => *pos,
 
-            Self::Dot(x, _) | Self::Index(x, _) => x.lhs.position(),
+            Self::Dot(x, _) | Self::Index(x, _, _) => x.lhs.position(),
 
-            Self::Custom(_, pos) => *pos,
+            Self::Custom(_, pos, _) => *pos,
         }
     }
 
@@ -1064,22 +1365,53 @@ impl Expr {
             }
 
             #[cfg(not(feature = "no_float"))]
-            Self::FloatConstant(_, pos) => *pos = new_pos,
-
-            Self::IntegerConstant(_, pos) => *pos = new_pos,
-            Self::CharConstant(_, pos) => *pos = new_pos,
-            Self::StringConstant(x) => x.pos = new_pos,
-            Self::FnPointer(x) => x.pos = new_pos,
-            Self::Array(_, pos) => *pos = new_pos,
-            Self::Map(_, pos) => *pos = new_pos,
+            Self::FloatConstant(_, pos, _) => *pos = new_pos,
+
+            Self::IntegerConstant(_, pos, _) => *pos = new_pos,
+            Self::CharConstant(_, pos, _) => *pos = new_pos,
+            Self::StringConstant(x, _) => x.pos = new_pos,
+            Self::FnPointer(x, _) => x.pos = new_pos,
+            Self::Array(_, pos, _) => *pos = new_pos,
+            Self::Map(_, pos, _) => *pos = new_pos,
             Self::Variable(x) => (x.3).pos = new_pos,
             Self::Property(x) => (x.1).pos = new_pos,
-            Self::Stmt(_, pos) => *pos = new_pos,
-            Self::FnCall(_, pos) => *pos = new_pos,
+            Self::Stmt(_, pos, _) => *pos = new_pos,
+            Self::FnCall(_, pos, _) => *pos = new_pos,
             Self::And(_, pos) | Self::Or(_, pos) | Self::In(_, pos) => *pos = new_pos,
-            Self::True(pos) | Self::False(pos) | Self::Unit(pos) => *pos = new_pos,
-            Self::Dot(_, pos) | Self::Index(_, pos) => *pos = new_pos,
-            Self::Custom(_, pos) => *pos = new_pos,
+            Self::True(pos) | Self::False(pos) | Self::Unit(pos, _) => *pos = new_pos,
+            Self::Dot(_, pos) | Self::Index(_, pos, _) => *pos = new_pos,
+            Self::Custom(_, pos, _) => *pos = new_pos,
+        }
+
+        self
+    }
+
+    /// Override the `Position` of the expression and, if it is stored, the `Position` of its last
+    /// character, so that the expression takes the `Span` of another expression that it replaces.
+    pub(crate) fn set_span(&mut self, span: Span) -> &mut Self {
+        self.set_position(span.start());
+
+        match self {
+            Self::Expr(x) => {
+                x.set_span(span);
+            }
+
+            #[cfg(not(feature = "no_float"))]
+            Self::FloatConstant(_, _, end) => *end = span.end(),
+
+            Self::IntegerConstant(_, _, end)
+            | Self::CharConstant(_, _, end)
+            | Self::StringConstant(_, end)
+            | Self::FnPointer(_, end)
+            | Self::Stmt(_, _, end)
+            | Self::FnCall(_, _, end)
+            | Self::Index(_, _, end)
+            | Self::Array(_, _, end)
+            | Self::Map(_, _, end)
+            | Self::Unit(_, end)
+            | Self::Custom(_, _, end) => *end = span.end(),
+
+            _ => (),
         }
 
         self
@@ -1092,15 +1424,15 @@ impl Expr {
         match self {
             Self::Expr(x) => x.is_pure(),
 
-            Self::Array(x, _) => x.iter().all(Self::is_pure),
+            Self::Array(x, _, _) => x.iter().all(Self::is_pure),
 
-            Self::Map(x, _) => x.iter().map(|(_, v)| v).all(Self::is_pure),
+            Self::Map(x, _, _) => x.iter().map(|(_, v)| v).all(Self::is_pure),
 
-            Self::Index(x, _) | Self::And(x, _) | Self::Or(x, _) | Self::In(x, _) => {
+            Self::Index(x, _, _) | Self::And(x, _) | Self::Or(x, _) | Self::In(x, _) => {
                 x.lhs.is_pure() && x.rhs.is_pure()
             }
 
-            Self::Stmt(x, _) => x.iter().all(Stmt::is_pure),
+            Self::Stmt(x, _, _) => x.iter().all(Stmt::is_pure),
 
             Self::Variable(_) => true,
 
@@ -1112,7 +1444,7 @@ impl Expr {
     #[inline(always)]
     pub fn is_unit(&self) -> bool {
         match self {
-            Self::Unit(_) => true,
+            Self::Unit(_, _) => true,
             _ => false,
         }
     }
@@ -1123,26 +1455,26 @@ impl Expr {
             Self::Expr(x) => x.is_literal(),
 
             #[cfg(not(feature = "no_float"))]
-            Self::FloatConstant(_, _) => true,
+            Self::FloatConstant(_, _, _) => true,
 
-            Self::IntegerConstant(_, _)
-            | Self::CharConstant(_, _)
-            | Self::StringConstant(_)
-            | Self::FnPointer(_)
+            Self::IntegerConstant(_, _, _)
+            | Self::CharConstant(_, _, _)
+            | Self::StringConstant(_, _)
+            | Self::FnPointer(_, _)
             | Self::True(_)
             | Self::False(_)
-            | Self::Unit(_) => true,
+            | Self::Unit(_, _) => true,
 
             // An array literal is literal if all items are literals
-            Self::Array(x, _) => x.iter().all(Self::is_literal),
+            Self::Array(x, _, _) => x.iter().all(Self::is_literal),
 
             // An map literal is literal if all items are literals
-            Self::Map(x, _) => x.iter().map(|(_, expr)| expr).all(Self::is_literal),
+            Self::Map(x, _, _) => x.iter().map(|(_, expr)| expr).all(Self::is_literal),
 
             // Check in expression
             Self::In(x, _) => match (&x.lhs, &x.rhs) {
-                (Self::StringConstant(_), Self::StringConstant(_))
-                | (Self::CharConstant(_, _), Self::StringConstant(_)) => true,
+                (Self::StringConstant(_, _), Self::StringConstant(_, _))
+                | (Self::CharConstant(_, _, _), Self::StringConstant(_, _)) => true,
                 _ => false,
             },
 
@@ -1156,26 +1488,26 @@ impl Expr {
             Self::Expr(x) => x.is_constant(),
 
             #[cfg(not(feature = "no_float"))]
-            Self::FloatConstant(_, _) => true,
+            Self::FloatConstant(_, _, _) => true,
 
-            Self::IntegerConstant(_, _)
-            | Self::CharConstant(_, _)
-            | Self::StringConstant(_)
-            | Self::FnPointer(_)
+            Self::IntegerConstant(_, _, _)
+            | Self::CharConstant(_, _, _)
+            | Self::StringConstant(_, _)
+            | Self::FnPointer(_, _)
             | Self::True(_)
             | Self::False(_)
-            | Self::Unit(_) => true,
+            | Self::Unit(_, _) => true,
 
             // An array literal is constant if all items are constant
-            Self::Array(x, _) => x.iter().all(Self::is_constant),
+            Self::Array(x, _, _) => x.iter().all(Self::is_constant),
 
             // An map literal is constant if all items are constant
-            Self::Map(x, _) => x.iter().map(|(_, expr)| expr).all(Self::is_constant),
+            Self::Map(x, _, _) => x.iter().map(|(_, expr)| expr).all(Self::is_constant),
 
             // Check in expression
             Self::In(x, _) => match (&x.lhs, &x.rhs) {
-                (Self::StringConstant(_), Self::StringConstant(_))
-                | (Self::CharConstant(_, _), Self::StringConstant(_)) => true,
+                (Self::StringConstant(_, _), Self::StringConstant(_, _))
+                | (Self::CharConstant(_, _, _), Self::StringConstant(_, _)) => true,
                 _ => false,
             },
 
@@ -1189,25 +1521,25 @@ impl Expr {
             Self::Expr(x) => x.is_valid_postfix(token),
 
             #[cfg(not(feature = "no_float"))]
-            Self::FloatConstant(_, _) => false,
+            Self::FloatConstant(_, _, _) => false,
 
-            Self::IntegerConstant(_, _)
-            | Self::CharConstant(_, _)
-            | Self::FnPointer(_)
+            Self::IntegerConstant(_, _, _)
+            | Self::CharConstant(_, _, _)
+            | Self::FnPointer(_, _)
             | Self::In(_, _)
             | Self::And(_, _)
             | Self::Or(_, _)
             | Self::True(_)
             | Self::False(_)
-            | Self::Unit(_) => false,
+            | Self::Unit(_, _) => false,
 
-            Self::StringConstant(_)
-            | Self::Stmt(_, _)
-            | Self::FnCall(_, _)
+            Self::StringConstant(_, _)
+            | Self::Stmt(_, _, _)
+            | Self::FnCall(_, _, _)
             | Self::Dot(_, _)
-            | Self::Index(_, _)
-            | Self::Array(_, _)
-            | Self::Map(_, _) => match token {
+            | Self::Index(_, _, _)
+            | Self::Array(_, _, _)
+            | Self::Map(_, _, _) => match token {
                 #[cfg(not(feature = "no_index"))]
                 Token::LeftBracket => true,
                 _ => false,
@@ -1229,7 +1561,7 @@ impl Expr {
                 _ => false,
             },
 
-            Self::Custom(_, _) => false,
+            Self::Custom(_, _, _) => false,
         }
     }
 
@@ -1258,14 +1590,45 @@ mod tests {
 
         assert_eq!(size_of::<crate::Dynamic>(), 16);
         assert_eq!(size_of::<Option<crate::Dynamic>>(), 16);
-        assert_eq!(size_of::<crate::Position>(), 4);
-        assert_eq!(size_of::<crate::ast::Expr>(), 16);
-        assert_eq!(size_of::<Option<crate::ast::Expr>>(), 16);
-        assert_eq!(size_of::<crate::ast::Stmt>(), 32);
-        assert_eq!(size_of::<Option<crate::ast::Stmt>>(), 32);
+        assert_eq!(size_of::<crate::Position>(), 6);
+        assert_eq!(size_of::<crate::ast::Expr>(), 24);
+        assert_eq!(size_of::<Option<crate::ast::Expr>>(), 24);
+        assert_eq!(size_of::<crate::ast::Stmt>(), 40);
+        assert_eq!(size_of::<Option<crate::ast::Stmt>>(), 40);
         assert_eq!(size_of::<crate::Scope>(), 72);
         assert_eq!(size_of::<crate::LexError>(), 32);
-        assert_eq!(size_of::<crate::ParseError>(), 16);
-        assert_eq!(size_of::<crate::EvalAltResult>(), 64);
+        assert_eq!(size_of::<crate::ParseError>(), 48);
+        assert_eq!(size_of::<crate::EvalAltResult>(), 96);
+    }
+
+    /// This test is to make sure that statements and expressions know where they end.
+    #[test]
+    fn check_spans() {
+        use crate::{Engine, Position, Span};
+
+        let mut engine = Engine::new();
+        #[cfg(not(feature = "no_optimize"))]
+        engine.set_optimization_level(crate::OptimizationLevel::None);
+
+        let ast = engine
+            .compile("let x = foo(1, 2);\nif x > 1 { x + 1 } else { bar(1, y) }")
+            .unwrap();
+        let span = |start: (u32, u32), end: (u32, u32)| {
+            Span::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+        };
+
+        let stmts = ast.stmts();
+        assert_eq!(stmts[0].span(), span((1, 1), (1, 17)));
+        assert_eq!(stmts[1].span(), span((2, 1), (2, 37)));
+
+        match (&stmts[0], &stmts[1]) {
+            (super::Stmt::Let(_, Some(expr), _), super::Stmt::IfThenElse(cond, x, _)) => {
+                assert_eq!(expr.span(), span((1, 9), (1, 17)));
+                assert_eq!(cond.span(), span((2, 4), (2, 8)));
+                assert_eq!(x.0.span(), span((2, 10), (2, 18)));
+                assert_eq!(x.1.as_ref().unwrap().span(), span((2, 25), (2, 37)));
+            }
+            _ => unreachable!(),
+        }
     }
 }
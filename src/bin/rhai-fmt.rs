@@ -0,0 +1,146 @@
+use rhai::{Engine, FormatOptions, ParseError};
+
+use std::{
+    env, fs,
+    io::{stdin, Read},
+    process::exit,
+};
+
+const USAGE: &str = "\
+Usage: rhai-fmt [OPTIONS] [FILES...]
+
+Format Rhai scripts in place.
+With no files, read a script from standard input and write the formatted script to standard output.
+
+Options:
+    --check       Do not write anything; list the files that are not formatted
+                  and exit with an error if there is any
+    --indent N    Indent with N spaces (default: 4)
+    --tabs        Indent with tabs
+    --width N     Maximum line width (default: 100)
+    -h, --help    Print this help message";
+
+fn eprint_error(name: &str, input: &str, err: &ParseError) {
+    let pos = err.position();
+
+    if pos.is_none() {
+        eprintln!("{}: {}", name, err);
+    } else {
+        let line = pos.line().unwrap();
+        let line_no = format!("{}:{}: ", name, line);
+        let pos_text = format!(" ({})", err.1);
+
+        eprintln!(
+            "{}{}",
+            line_no,
+            input.split('\n').nth(line - 1).unwrap_or("")
+        );
+        eprintln!(
+            "{:>1$} {2}",
+            "^",
+            line_no.len() + pos.position().unwrap(),
+            err.to_string().replace(&pos_text, "")
+        );
+    }
+}
+
+fn parse_number(option: &str, value: Option<String>) -> usize {
+    match value.as_deref().map(str::parse) {
+        Some(Ok(n)) => n,
+        _ => {
+            eprintln!("Option {} requires a number\n\n{}", option, USAGE);
+            exit(2);
+        }
+    }
+}
+
+fn main() {
+    let mut options = FormatOptions::default();
+    let mut check = false;
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--indent" => options.indent = " ".repeat(parse_number(&arg, args.next())),
+            "--tabs" => options.indent = "\t".into(),
+            "--width" => options.max_width = parse_number(&arg, args.next()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("Unknown option: {}\n\n{}", arg, USAGE);
+                exit(2);
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    let engine = Engine::new();
+
+    if files.is_empty() {
+        let mut contents = String::new();
+
+        if let Err(err) = stdin().read_to_string(&mut contents) {
+            eprintln!("Error reading standard input\n{}", err);
+            exit(1);
+        }
+
+        match engine.format(&contents, &options) {
+            Ok(output) if check => {
+                if output != contents {
+                    println!("<stdin>");
+                    exit(1);
+                }
+            }
+            Ok(output) => print!("{}", output),
+            Err(err) => {
+                eprint_error("<stdin>", &contents, &err);
+                exit(1);
+            }
+        }
+
+        return;
+    }
+
+    let mut failed = false;
+
+    for filename in files {
+        let contents = match fs::read_to_string(&filename) {
+            Ok(contents) => contents,
+            Err(err) => {
+                eprintln!("Error reading script file: {}\n{}", filename, err);
+                failed = true;
+                continue;
+            }
+        };
+
+        let output = match engine.format(&contents, &options) {
+            Ok(output) => output,
+            Err(err) => {
+                eprint_error(&filename, &contents, &err);
+                failed = true;
+                continue;
+            }
+        };
+
+        if output == contents {
+            continue;
+        }
+
+        if check {
+            println!("{}", filename);
+            failed = true;
+        } else if let Err(err) = fs::write(&filename, output) {
+            eprintln!("Error writing script file: {}\n{}", filename, err);
+            failed = true;
+        }
+    }
+
+    if failed {
+        exit(1);
+    }
+}
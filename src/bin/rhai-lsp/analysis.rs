@@ -0,0 +1,252 @@
+//! Finding definitions and references in a script via its `SyntaxTree`.
+
+use rhai::{
+    NodeKind, Position, Span, SyntaxElement, SyntaxNode, SyntaxToken, SyntaxTree, TokenKind,
+};
+
+/// A function defined in a script.
+#[derive(Debug, Clone)]
+pub struct ScriptFn {
+    /// Name of the function.
+    pub name: String,
+    /// Signature of the function, e.g. `add(x, y)`.
+    pub signature: String,
+    /// Is the function private?
+    pub private: bool,
+    /// Location of the entire function definition.
+    pub span: Span,
+    /// Location of the function name.
+    pub name_span: Span,
+}
+
+/// Type of a declaration at global level in a script.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DeclarationKind {
+    /// `let` statement.
+    Variable,
+    /// `const` statement.
+    Constant,
+    /// `import` statement with an alias.
+    Import,
+}
+
+/// A declaration at global level in a script.
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    /// Type of the declaration.
+    pub kind: DeclarationKind,
+    /// Name of the variable, constant or module alias.
+    pub name: String,
+    /// For an `import` statement, the module path if it is a string literal.
+    pub path: Option<String>,
+    /// Location of the entire statement.
+    pub span: Span,
+    /// Location of the name.
+    pub name_span: Span,
+}
+
+/// A name in a script, e.g. a variable or a function call.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    /// The name.
+    pub name: String,
+    /// The module namespace qualifying the name, e.g. `["foo", "bar"]` for `foo::bar::name`.
+    pub namespace: Vec<String>,
+    /// Location of the name.
+    pub span: Span,
+}
+
+/// Is a token significant, i.e. not whitespace or comments?
+fn is_significant(element: &SyntaxElement) -> bool {
+    match element {
+        SyntaxElement::Node(_) => true,
+        SyntaxElement::Token(token) => !token.is_trivia(),
+    }
+}
+
+/// Is a token a name, i.e. an identifier or a keyword such as `print`?
+fn is_name(token: &SyntaxToken) -> bool {
+    match token.kind() {
+        TokenKind::Identifier => true,
+        TokenKind::Keyword => token
+            .text()
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || ch == '_'),
+        _ => false,
+    }
+}
+
+/// Find all the functions defined in a script.
+pub fn find_functions(tree: &SyntaxTree) -> Vec<ScriptFn> {
+    tree.root()
+        .children()
+        .iter()
+        .filter_map(SyntaxElement::as_node)
+        .filter(|node| node.kind() == NodeKind::Function)
+        .filter_map(parse_function)
+        .collect()
+}
+
+/// Read the name and signature of a function definition.
+fn parse_function(node: &SyntaxNode) -> Option<ScriptFn> {
+    let mut elements = node.children().iter().filter(|e| is_significant(e));
+
+    let mut private = false;
+
+    let name = loop {
+        match elements.next()?.as_token()? {
+            token if token.text() == "private" => private = true,
+            token if token.text() == "fn" => (),
+            token if is_name(token) => break token,
+            _ => return None,
+        }
+    };
+
+    let mut signature = name.text().to_string();
+
+    // Parameters and return type, up to the function body
+    for element in elements {
+        match element {
+            SyntaxElement::Node(params) if params.kind() == NodeKind::Parens => {
+                signature.push_str(&collapse_whitespace(&params.text()))
+            }
+            SyntaxElement::Node(_) => break,
+            SyntaxElement::Token(token) if token.text() == "->" => signature.push_str(" -> "),
+            SyntaxElement::Token(token) => signature.push_str(token.text()),
+        }
+    }
+
+    Some(ScriptFn {
+        name: name.text().to_string(),
+        signature,
+        private,
+        span: node.span(),
+        name_span: name.span(),
+    })
+}
+
+/// Replace all whitespace (including new-lines) within a piece of text by single spaces.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Find all the variables, constants and module imports declared at global level in a script.
+pub fn find_declarations(tree: &SyntaxTree) -> Vec<Declaration> {
+    tree.root()
+        .children()
+        .iter()
+        .filter_map(SyntaxElement::as_node)
+        .filter(|node| node.kind() == NodeKind::Statement)
+        .filter_map(parse_declaration)
+        .collect()
+}
+
+/// Read the name of a `let`, `const` or `import` statement.
+fn parse_declaration(node: &SyntaxNode) -> Option<Declaration> {
+    let mut tokens = node
+        .children()
+        .iter()
+        .filter(|e| is_significant(e))
+        .map(SyntaxElement::as_token);
+
+    let (kind, path) = match tokens.next()??.text() {
+        "let" => (DeclarationKind::Variable, None),
+        "const" => (DeclarationKind::Constant, None),
+        "import" => {
+            let path = tokens.next()?.filter(|t| t.kind() == TokenKind::String);
+            let path = path.map(|t| t.text().trim_matches('"').to_string());
+
+            // Skip the module path expression, if it is not a string literal
+            tokens.by_ref().flatten().find(|t| t.text() == "as")?;
+
+            (DeclarationKind::Import, path)
+        }
+        _ => return None,
+    };
+
+    let name = tokens.next()??;
+
+    if !is_name(name) {
+        return None;
+    }
+
+    Some(Declaration {
+        kind,
+        name: name.text().to_string(),
+        path,
+        span: node.span(),
+        name_span: name.span(),
+    })
+}
+
+/// Find the module path of a module alias imported at global level.
+pub fn find_import_path(tree: &SyntaxTree, alias: &str) -> Option<String> {
+    find_declarations(tree)
+        .into_iter()
+        .find(|d| d.kind == DeclarationKind::Import && d.name == alias)
+        .and_then(|d| d.path)
+}
+
+/// Find the token at a position, or the token ending just before it (e.g. the word before the
+/// cursor).
+pub fn find_token(tree: &SyntaxTree, pos: Position) -> Option<&SyntaxToken> {
+    let line = pos.line()?;
+    let col = pos.position()?;
+
+    tree.token_at(pos)
+        .filter(|token| !token.is_trivia())
+        .or_else(|| {
+            if col > 1 {
+                tree.token_at(Position::new(line as u32, col as u32 - 1))
+            } else {
+                None
+            }
+        })
+        .filter(|token| !token.is_trivia())
+}
+
+/// Find the name at a position, together with any module namespace qualifying it.
+pub fn find_reference(tree: &SyntaxTree, pos: Position) -> Option<Reference> {
+    let line = pos.line()? as u32;
+    let col = pos.position()? as u32;
+
+    // The name may also be just before the position (e.g. the word before the cursor)
+    let target = tree
+        .token_at(pos)
+        .filter(|token| is_name(token))
+        .or_else(|| {
+            tree.token_at(Position::new(line, col.checked_sub(1)?))
+                .filter(|token| is_name(token))
+        })?;
+
+    let tokens: Vec<_> = tree.tokens().filter(|token| !token.is_trivia()).collect();
+    let index = tokens
+        .iter()
+        .position(|token| token.offset() == target.offset())?;
+
+    // Walk back through `name::` qualifiers
+    let mut namespace = Vec::new();
+    let mut index = index;
+
+    while index >= 2 && tokens[index - 1].text() == "::" && is_name(tokens[index - 2]) {
+        namespace.insert(0, tokens[index - 2].text().to_string());
+        index -= 2;
+    }
+
+    Some(Reference {
+        name: target.text().to_string(),
+        namespace,
+        span: target.span(),
+    })
+}
+
+/// Is a position within a span?
+pub fn span_contains(span: Span, pos: Position) -> bool {
+    let (start, end) = (span.start(), span.end());
+
+    let pos = (pos.line(), pos.position());
+    let start = (start.line(), start.position());
+    let end = (end.line(), end.position());
+
+    start <= pos && pos <= end
+}
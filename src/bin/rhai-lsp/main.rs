@@ -0,0 +1,923 @@
+//! A language server for Rhai scripts, speaking the Language Server Protocol over standard
+//! input/output.
+
+mod analysis;
+mod metadata;
+
+use analysis::{
+    find_declarations, find_functions, find_import_path, find_reference, find_token, span_contains,
+    DeclarationKind,
+};
+use metadata::Metadata;
+use rhai::{Engine, LintOptions, Position, Severity, Span, SyntaxToken, SyntaxTree};
+use serde_json::{json, Value};
+
+#[cfg(not(feature = "no_module"))]
+use rhai::module_resolvers::{FileModuleResolver, ModuleResolversCollection, StaticModuleResolver};
+
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    io::{self, stdin, stdout, BufRead, Write},
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+const USAGE: &str = "\
+Usage: rhai-lsp [OPTIONS]
+
+Language server for Rhai scripts, using the Language Server Protocol over standard input/output.
+
+Options:
+    --metadata FILE   Load the functions and modules registered in the engine from a JSON metadata file
+    --modules DIR     Base directory of script modules loaded by 'import' (default: the workspace root)
+    --strict          Report variables and functions that are not defined (strict mode)
+    -h, --help        Print this help message";
+
+/// Keywords offered for completion.
+const KEYWORDS: &[&str] = &[
+    "let", "const", "if", "else", "while", "loop", "for", "in", "break", "continue", "return",
+    "throw", "try", "catch", "finally", "fn", "private", "import", "export", "as", "true", "false",
+    "this",
+];
+
+/// Maximum number of operations when evaluating modules resolved in strict mode.
+#[cfg(not(feature = "unchecked"))]
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// `CompletionItemKind` and `SymbolKind` values of the Language Server Protocol.
+mod kind {
+    pub const COMPLETION_FUNCTION: u8 = 3;
+    pub const COMPLETION_VARIABLE: u8 = 6;
+    pub const COMPLETION_MODULE: u8 = 9;
+    pub const COMPLETION_KEYWORD: u8 = 14;
+    pub const COMPLETION_CONSTANT: u8 = 21;
+
+    pub const SYMBOL_MODULE: u8 = 2;
+    pub const SYMBOL_FUNCTION: u8 = 12;
+    pub const SYMBOL_VARIABLE: u8 = 13;
+    pub const SYMBOL_CONSTANT: u8 = 14;
+}
+
+/// Read the body of a message, or `None` at the end of the input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = value.trim().parse().ok();
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// Write a message.
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// Convert a file URI into a path.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match path
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    let path = String::from_utf8(decoded).ok()?;
+
+    // Windows paths look like '/C:/...'
+    #[cfg(windows)]
+    let path = path.trim_start_matches('/').to_string();
+
+    Some(path.into())
+}
+
+/// Convert a path into a file URI.
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    path.bytes().for_each(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+            uri.push(byte as char)
+        }
+        _ => uri.push_str(&format!("%{:02X}", byte)),
+    });
+
+    uri
+}
+
+/// Convert a position in a script into a zero-based LSP position, counted in UTF-16 code units.
+fn to_lsp_position(text: &str, pos: Position) -> Value {
+    let line = pos.line().unwrap_or(1) - 1;
+    let col = pos.position().unwrap_or(1) - 1;
+
+    let character: usize = text
+        .split('\n')
+        .nth(line)
+        .map_or(0, |s| s.chars().take(col).map(char::len_utf16).sum());
+
+    json!({ "line": line, "character": character })
+}
+
+/// Convert a span in a script into an LSP range.
+fn to_lsp_range(text: &str, span: Span) -> Value {
+    let end = span.end();
+
+    // The end of an LSP range is exclusive
+    let end = match (end.line(), end.position()) {
+        (Some(line), Some(col)) => Position::try_new(line as u32, col as u32 + 1).unwrap_or(end),
+        _ => end,
+    };
+
+    json!({ "start": to_lsp_position(text, span.start()), "end": to_lsp_position(text, end) })
+}
+
+/// Convert an LSP position into a position in a script.
+fn from_lsp_position(text: &str, position: &Value) -> Option<Position> {
+    let line = position["line"].as_u64()? as usize;
+    let character = position["character"].as_u64()? as usize;
+
+    let mut units = 0;
+    let col = text
+        .split('\n')
+        .nth(line)
+        .unwrap_or("")
+        .chars()
+        .take_while(|ch| {
+            units += ch.len_utf16();
+            units <= character
+        })
+        .count();
+
+    Position::try_new(line as u32 + 1, col as u32 + 1)
+}
+
+/// Create an LSP location pointing to the start of a file.
+fn file_location(path: &Path) -> Value {
+    let start = json!({ "line": 0, "character": 0 });
+    json!({ "uri": path_to_uri(path), "range": { "start": start, "end": start } })
+}
+
+/// Format the signature and documentation of a function as Markdown.
+fn fn_markdown(signature: &str, doc_comments: &[String]) -> String {
+    let mut markdown = format!("```rhai\nfn {}\n```", signature);
+
+    if !doc_comments.is_empty() {
+        markdown.push_str("\n\n");
+
+        doc_comments.iter().for_each(|line| {
+            let line = line.strip_prefix("///").unwrap_or(line);
+            markdown.push_str(line.strip_prefix(' ').unwrap_or(line));
+            markdown.push('\n');
+        });
+    }
+
+    markdown
+}
+
+/// Get the name of a function from its signature.
+fn signature_name(signature: &str) -> &str {
+    signature.split('(').next().unwrap_or(signature)
+}
+
+/// Is a name a valid identifier (i.e. not an operator, a property getter/setter or an indexer)?
+fn is_identifier(name: &str) -> bool {
+    matches!(name.chars().next(), Some(ch) if !ch.is_ascii_digit())
+        && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+}
+
+/// A function in a module.
+struct ModuleFn {
+    /// Name of the function.
+    name: String,
+    /// Signature of the function.
+    signature: String,
+    /// Documentation of the function.
+    doc_comments: Vec<String>,
+    /// LSP location of the function definition, if it is defined in a script file.
+    location: Option<Value>,
+}
+
+/// Functions and sub-modules of a module.
+#[derive(Default)]
+struct ModuleInfo {
+    /// Functions.
+    functions: Vec<ModuleFn>,
+    /// Names of sub-modules.
+    modules: Vec<String>,
+}
+
+/// Command-line options.
+#[derive(Default)]
+struct Options {
+    /// Path of the metadata file.
+    metadata: Option<PathBuf>,
+    /// Base directory of script modules.
+    modules: Option<PathBuf>,
+    /// Turn on strict mode?
+    strict: bool,
+}
+
+/// State of the language server.
+struct Server {
+    /// Command-line options.
+    options: Options,
+    /// Functions and modules registered in the engine, from the metadata file.
+    metadata: Metadata,
+    /// `Engine` used to check scripts.
+    engine: Engine,
+    /// Signatures of functions in the `Engine` (not including those from the metadata file).
+    signatures: Vec<String>,
+    /// Base directory of script modules.
+    module_path: PathBuf,
+    /// Texts of open documents by URI.
+    documents: HashMap<String, String>,
+    /// Has a shutdown request been received?
+    shutdown: bool,
+}
+
+impl Server {
+    /// Create a new `Server`.
+    fn new(options: Options, metadata: Metadata) -> Self {
+        let module_path = options.modules.clone().unwrap_or_default();
+
+        let mut server = Self {
+            options,
+            metadata,
+            engine: Engine::new_raw(),
+            signatures: Vec::new(),
+            module_path: PathBuf::new(),
+            documents: HashMap::new(),
+            shutdown: false,
+        };
+
+        server.configure(module_path);
+        server
+    }
+
+    /// Set up the `Engine` with a base directory of script modules.
+    fn configure(&mut self, module_path: PathBuf) {
+        let mut engine = Engine::new();
+
+        self.signatures = engine.gen_fn_signatures(true);
+
+        engine.set_strict_mode(self.options.strict);
+        engine.load_package(self.metadata.to_module());
+
+        #[cfg(not(feature = "unchecked"))]
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        #[cfg(not(feature = "no_module"))]
+        {
+            let mut static_modules = StaticModuleResolver::new();
+
+            self.metadata.modules.iter().for_each(|(name, module)| {
+                static_modules.insert(name.as_str(), module.to_module());
+            });
+
+            let mut resolvers = ModuleResolversCollection::new();
+            resolvers.push(static_modules);
+            resolvers.push(FileModuleResolver::new_with_path(&module_path));
+            engine.set_module_resolver(Some(resolvers));
+        }
+
+        self.engine = engine;
+        self.module_path = module_path;
+    }
+
+    /// Handle a message, writing any response or notifications.
+    fn handle(&mut self, message: &Value, output: &mut impl Write) -> io::Result<()> {
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            // Responses to requests are ignored
+            None => return Ok(()),
+        };
+
+        let params = &message["params"];
+
+        let result = match method {
+            "initialize" => Some(self.initialize(params)),
+            "shutdown" => {
+                self.shutdown = true;
+                Some(Value::Null)
+            }
+            "exit" => exit(if self.shutdown { 0 } else { 1 }),
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+
+                if let (Some(uri), Some(text)) =
+                    (document["uri"].as_str(), document["text"].as_str())
+                {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish_diagnostics(uri, output)?;
+                }
+                None
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str();
+
+                // Full text synchronization - the last change holds the whole text
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish_diagnostics(uri, output)?;
+                }
+                None
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                    self.documents.remove(uri);
+
+                    let params = json!({ "uri": uri, "diagnostics": [] });
+                    write_message(
+                        output,
+                        &notification("textDocument/publishDiagnostics", params),
+                    )?;
+                }
+                None
+            }
+            "textDocument/completion" => Some(self.completion(params)),
+            "textDocument/hover" => Some(self.hover(params)),
+            "textDocument/definition" => Some(self.definition(params)),
+            "textDocument/documentSymbol" => Some(self.document_symbols(params)),
+            _ => None,
+        };
+
+        let id = match message.get("id") {
+            Some(id) => id,
+            // Notifications have no response
+            None => return Ok(()),
+        };
+
+        let response = match result {
+            Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("Unknown method: {}", method) }
+            }),
+        };
+
+        write_message(output, &response)
+    }
+
+    /// Handle the `initialize` request.
+    fn initialize(&mut self, params: &Value) -> Value {
+        if self.options.modules.is_none() {
+            let root = params["rootUri"]
+                .as_str()
+                .and_then(uri_to_path)
+                .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+
+            if let Some(root) = root {
+                self.configure(root);
+            }
+        }
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": 1,
+                "completionProvider": { "triggerCharacters": [".", ":"] },
+                "hoverProvider": true,
+                "definitionProvider": true,
+                "documentSymbolProvider": true
+            },
+            "serverInfo": { "name": "rhai-lsp", "version": env!("CARGO_PKG_VERSION") }
+        })
+    }
+
+    /// Get the URI and text of the document of a request.
+    fn document<'a>(&'a self, params: &'a Value) -> Option<(&'a str, &'a str)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let text = self.documents.get(uri)?;
+        Some((uri, text))
+    }
+
+    /// Check a document for syntax errors and lint warnings.
+    fn publish_diagnostics(&self, uri: &str, output: &mut impl Write) -> io::Result<()> {
+        let text = &self.documents[uri];
+        let tree = self.engine.parse_syntax_tree(text);
+
+        let diagnostic = |pos: Position, severity: u8, message: String, code: Option<&str>| {
+            let span = tree
+                .token_at(pos)
+                .filter(|token| !token.is_trivia())
+                .map_or(Span::new(pos, pos), SyntaxToken::span);
+
+            let mut diagnostic = json!({
+                "range": to_lsp_range(text, span),
+                "severity": severity,
+                "source": "rhai",
+                "message": message
+            });
+
+            if let Some(code) = code {
+                diagnostic["code"] = code.into();
+            }
+
+            diagnostic
+        };
+
+        let errors = tree.errors();
+
+        let mut diagnostics: Vec<_> = errors
+            .iter()
+            .map(|err| diagnostic(err.position(), 1, err.0.to_string(), None))
+            .collect();
+
+        if errors.is_empty() {
+            if let Ok(warnings) = self.engine.lint(text, &LintOptions::new()) {
+                diagnostics.extend(warnings.into_iter().filter_map(|w| {
+                    let severity = match w.severity {
+                        Severity::Error => 1,
+                        Severity::Warning => 2,
+                        Severity::Off => return None,
+                    };
+                    Some(diagnostic(w.pos, severity, w.message, Some(w.id.name())))
+                }));
+            }
+        }
+
+        let params = json!({ "uri": uri, "diagnostics": diagnostics });
+        write_message(
+            output,
+            &notification("textDocument/publishDiagnostics", params),
+        )
+    }
+
+    /// Find the script file of a module path.
+    #[cfg(not(feature = "no_module"))]
+    fn module_file(&self, path: &str) -> Option<PathBuf> {
+        let file = FileModuleResolver::new_with_path(&self.module_path).get_file_path(path);
+
+        if file.is_file() {
+            Some(fs::canonicalize(&file).unwrap_or(file))
+        } else {
+            None
+        }
+    }
+
+    /// Find the script file of a module path.
+    #[cfg(feature = "no_module")]
+    fn module_file(&self, _: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Find the functions and sub-modules of a module namespace (e.g. `["foo", "bar"]` for
+    /// `foo::bar`) where the first name is a module imported at global level.
+    fn resolve_module(&self, tree: &SyntaxTree, namespace: &[String]) -> Option<ModuleInfo> {
+        let (alias, rest) = namespace.split_first()?;
+        let path = find_import_path(tree, alias)?;
+
+        // Modules in the metadata file
+        if let Some(module) = self.metadata.modules.get(&path) {
+            let rest: Vec<_> = rest.iter().map(String::as_str).collect();
+            let module = module.find_module(&rest)?;
+
+            return Some(ModuleInfo {
+                functions: module
+                    .functions
+                    .iter()
+                    .map(|f| ModuleFn {
+                        name: f.name.clone(),
+                        signature: f.signature.clone(),
+                        doc_comments: f.doc_comments.clone(),
+                        location: None,
+                    })
+                    .collect(),
+                modules: module.modules.keys().cloned().collect(),
+            });
+        }
+
+        // Script files
+        if !rest.is_empty() {
+            return None;
+        }
+
+        let file = self.module_file(&path)?;
+        let script = fs::read_to_string(&file).ok()?;
+        let tree = self.engine.parse_syntax_tree(&script);
+
+        Some(ModuleInfo {
+            functions: find_functions(&tree)
+                .into_iter()
+                .filter(|f| !f.private)
+                .map(|f| ModuleFn {
+                    location: Some(json!({
+                        "uri": path_to_uri(&file),
+                        "range": to_lsp_range(&script, f.name_span)
+                    })),
+                    name: f.name,
+                    signature: f.signature,
+                    doc_comments: Vec::new(),
+                })
+                .collect(),
+            modules: Vec::new(),
+        })
+    }
+
+    /// Handle the `textDocument/completion` request.
+    fn completion(&self, params: &Value) -> Value {
+        let (text, pos) = match self.document(params).and_then(|(_, text)| {
+            from_lsp_position(text, &params["position"]).map(|pos| (text, pos))
+        }) {
+            Some(x) => x,
+            None => return Value::Null,
+        };
+
+        let tree = self.engine.parse_syntax_tree(text);
+
+        // Text before the word under the cursor
+        let line = text.split('\n').nth(pos.line().unwrap() - 1).unwrap_or("");
+        let before: String = line.chars().take(pos.position().unwrap() - 1).collect();
+        let mut before = before.trim_end_matches(|ch: char| ch.is_alphanumeric() || ch == '_');
+
+        let mut items = Vec::new();
+        let mut seen = HashSet::new();
+
+        let mut add = |label: &str, kind: u8, detail: Option<&str>, doc: Option<String>| {
+            if seen.insert(label.to_string()) {
+                let mut item = json!({ "label": label, "kind": kind });
+
+                if let Some(detail) = detail {
+                    item["detail"] = detail.into();
+                }
+                if let Some(doc) = doc {
+                    item["documentation"] = json!({ "kind": "markdown", "value": doc });
+                }
+
+                items.push(item);
+            }
+        };
+
+        // Qualified names, e.g. `foo::bar::`
+        if before.ends_with("::") {
+            let mut namespace = Vec::new();
+
+            while let Some(rest) = before.strip_suffix("::") {
+                let name_start = rest
+                    .trim_end_matches(|ch: char| ch.is_alphanumeric() || ch == '_')
+                    .len();
+                namespace.insert(0, rest[name_start..].to_string());
+                before = &rest[..name_start];
+            }
+
+            if let Some(module) = self.resolve_module(&tree, &namespace) {
+                module.functions.iter().for_each(|f| {
+                    let doc = fn_markdown(&f.signature, &f.doc_comments);
+                    add(
+                        &f.name,
+                        kind::COMPLETION_FUNCTION,
+                        Some(&f.signature),
+                        Some(doc),
+                    );
+                });
+                module.modules.iter().for_each(|name| {
+                    add(name, kind::COMPLETION_MODULE, None, None);
+                });
+            }
+
+            return json!(items);
+        }
+
+        find_functions(&tree).iter().for_each(|f| {
+            add(&f.name, kind::COMPLETION_FUNCTION, Some(&f.signature), None);
+        });
+
+        find_declarations(&tree).iter().for_each(|d| {
+            let kind = match d.kind {
+                DeclarationKind::Variable => kind::COMPLETION_VARIABLE,
+                DeclarationKind::Constant => kind::COMPLETION_CONSTANT,
+                DeclarationKind::Import => kind::COMPLETION_MODULE,
+            };
+            add(&d.name, kind, d.path.as_deref(), None);
+        });
+
+        self.metadata.functions.iter().for_each(|f| {
+            let doc = fn_markdown(&f.signature, &f.doc_comments);
+            add(
+                &f.name,
+                kind::COMPLETION_FUNCTION,
+                Some(&f.signature),
+                Some(doc),
+            );
+        });
+
+        self.signatures
+            .iter()
+            .filter(|signature| is_identifier(signature_name(signature)))
+            .for_each(|signature| {
+                let name = signature_name(signature);
+                add(name, kind::COMPLETION_FUNCTION, Some(signature), None);
+            });
+
+        KEYWORDS.iter().for_each(|keyword| {
+            add(keyword, kind::COMPLETION_KEYWORD, None, None);
+        });
+
+        json!(items)
+    }
+
+    /// Handle the `textDocument/hover` request.
+    fn hover(&self, params: &Value) -> Value {
+        let (text, pos) = match self.document(params).and_then(|(_, text)| {
+            from_lsp_position(text, &params["position"]).map(|pos| (text, pos))
+        }) {
+            Some(x) => x,
+            None => return Value::Null,
+        };
+
+        let tree = self.engine.parse_syntax_tree(text);
+
+        let reference = match find_reference(&tree, pos) {
+            Some(reference) => reference,
+            None => return Value::Null,
+        };
+
+        let name = reference.name.as_str();
+        let mut contents = Vec::new();
+
+        if !reference.namespace.is_empty() {
+            if let Some(module) = self.resolve_module(&tree, &reference.namespace) {
+                contents.extend(
+                    module
+                        .functions
+                        .iter()
+                        .filter(|f| f.name == name)
+                        .map(|f| fn_markdown(&f.signature, &f.doc_comments)),
+                );
+            }
+        } else {
+            contents.extend(
+                find_functions(&tree)
+                    .iter()
+                    .filter(|f| f.name == name)
+                    .map(|f| fn_markdown(&f.signature, &[])),
+            );
+            contents.extend(
+                self.metadata
+                    .functions
+                    .iter()
+                    .filter(|f| f.name == name)
+                    .map(|f| fn_markdown(&f.signature, &f.doc_comments)),
+            );
+            contents.extend(
+                self.signatures
+                    .iter()
+                    .filter(|signature| signature_name(signature) == name)
+                    .map(|signature| fn_markdown(signature, &[])),
+            );
+
+            if contents.is_empty() {
+                contents.extend(
+                    find_declarations(&tree)
+                        .iter()
+                        .filter(|d| d.name == name)
+                        .map(|d| match (d.kind, &d.path) {
+                            (DeclarationKind::Variable, _) => format!("```rhai\nlet {}\n```", name),
+                            (DeclarationKind::Constant, _) => {
+                                format!("```rhai\nconst {}\n```", name)
+                            }
+                            (DeclarationKind::Import, Some(path)) => {
+                                format!("```rhai\nimport \"{}\" as {}\n```", path, name)
+                            }
+                            (DeclarationKind::Import, None) => {
+                                format!("```rhai\nimport ... as {}\n```", name)
+                            }
+                        }),
+                );
+            }
+        }
+
+        if contents.is_empty() {
+            return Value::Null;
+        }
+
+        json!({
+            "contents": { "kind": "markdown", "value": contents.join("\n\n---\n\n") },
+            "range": to_lsp_range(text, reference.span)
+        })
+    }
+
+    /// Handle the `textDocument/definition` request.
+    fn definition(&self, params: &Value) -> Value {
+        let (uri, text, pos) = match self.document(params).and_then(|(uri, text)| {
+            from_lsp_position(text, &params["position"]).map(|pos| (uri, text, pos))
+        }) {
+            Some(x) => x,
+            None => return Value::Null,
+        };
+
+        let tree = self.engine.parse_syntax_tree(text);
+        let declarations = find_declarations(&tree);
+
+        // Go to the script file of an imported module
+        let import = declarations
+            .iter()
+            .filter(|d| d.kind == DeclarationKind::Import)
+            .find(|d| span_contains(d.span, pos) && find_token(&tree, pos).is_some());
+
+        if let Some(path) = import.and_then(|d| d.path.as_ref()) {
+            return self
+                .module_file(path)
+                .map_or(Value::Null, |file| file_location(&file));
+        }
+
+        let reference = match find_reference(&tree, pos) {
+            Some(reference) => reference,
+            None => return Value::Null,
+        };
+
+        let name = reference.name.as_str();
+
+        if !reference.namespace.is_empty() {
+            return self
+                .resolve_module(&tree, &reference.namespace)
+                .and_then(|module| {
+                    module
+                        .functions
+                        .into_iter()
+                        .filter(|f| f.name == name)
+                        .find_map(|f| f.location)
+                })
+                .unwrap_or(Value::Null);
+        }
+
+        if let Some(f) = find_functions(&tree).into_iter().find(|f| f.name == name) {
+            return json!({ "uri": uri, "range": to_lsp_range(text, f.name_span) });
+        }
+
+        match declarations.iter().find(|d| d.name == name) {
+            Some(d) if d.kind == DeclarationKind::Import => d
+                .path
+                .as_ref()
+                .and_then(|path| self.module_file(path))
+                .map_or(Value::Null, |file| file_location(&file)),
+            Some(d) => json!({ "uri": uri, "range": to_lsp_range(text, d.name_span) }),
+            None => Value::Null,
+        }
+    }
+
+    /// Handle the `textDocument/documentSymbol` request.
+    fn document_symbols(&self, params: &Value) -> Value {
+        let text = match self.document(params) {
+            Some((_, text)) => text,
+            None => return Value::Null,
+        };
+
+        let tree = self.engine.parse_syntax_tree(text);
+
+        let symbol = |name: &str, detail: Option<&str>, kind: u8, span: Span, name_span: Span| {
+            let start = span.start();
+            let symbol = json!({
+                "name": name,
+                "detail": detail.unwrap_or(""),
+                "kind": kind,
+                "range": to_lsp_range(text, span),
+                "selectionRange": to_lsp_range(text, name_span)
+            });
+            ((start.line(), start.position()), symbol)
+        };
+
+        let mut symbols: Vec<_> = find_functions(&tree)
+            .iter()
+            .map(|f| {
+                let kind = kind::SYMBOL_FUNCTION;
+                symbol(&f.name, Some(&f.signature), kind, f.span, f.name_span)
+            })
+            .chain(find_declarations(&tree).iter().map(|d| {
+                let kind = match d.kind {
+                    DeclarationKind::Variable => kind::SYMBOL_VARIABLE,
+                    DeclarationKind::Constant => kind::SYMBOL_CONSTANT,
+                    DeclarationKind::Import => kind::SYMBOL_MODULE,
+                };
+                symbol(&d.name, d.path.as_deref(), kind, d.span, d.name_span)
+            }))
+            .collect();
+
+        symbols.sort_by_key(|(pos, _)| *pos);
+
+        symbols.into_iter().map(|(_, symbol)| symbol).collect()
+    }
+}
+
+/// Create a notification message.
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn main() {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--metadata" | "--modules" => match args.next() {
+                Some(path) if arg == "--metadata" => options.metadata = Some(path.into()),
+                Some(path) => options.modules = Some(path.into()),
+                None => {
+                    eprintln!("Option {} requires a path\n\n{}", arg, USAGE);
+                    exit(2);
+                }
+            },
+            "--strict" => options.strict = true,
+            "--stdio" => (),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => {
+                eprintln!("Unknown option: {}\n\n{}", arg, USAGE);
+                exit(2);
+            }
+        }
+    }
+
+    let metadata = match options.metadata {
+        Some(ref path) => Metadata::load(path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        }),
+        None => Default::default(),
+    };
+
+    let mut server = Server::new(options, metadata);
+
+    let input = stdin();
+    let mut input = input.lock();
+    let output = stdout();
+    let mut output = output.lock();
+
+    loop {
+        let body = match read_message(&mut input) {
+            Ok(Some(body)) => body,
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("Cannot read message: {}", err);
+                exit(1);
+            }
+        };
+
+        let message: Value = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(err) => {
+                eprintln!("Invalid message: {}", err);
+                continue;
+            }
+        };
+
+        if let Err(err) = server.handle(&message, &mut output) {
+            eprintln!("Cannot write message: {}", err);
+            exit(1);
+        }
+    }
+
+    exit(if server.shutdown { 0 } else { 1 });
+}
@@ -0,0 +1,161 @@
+//! Loading the functions and modules of an `Engine` from a metadata file.
+
+use rhai::{Dynamic, Module};
+use serde_json::Value;
+
+use std::{any::TypeId, collections::BTreeMap, fs, path::Path};
+
+/// A function described in a metadata file.
+#[derive(Debug, Clone)]
+pub struct FnMetadata {
+    /// Name of the function.
+    pub name: String,
+    /// Number of parameters.
+    pub num_params: usize,
+    /// Signature of the function, e.g. `add(x: i64, y: i64) -> i64`.
+    pub signature: String,
+    /// Documentation of the function, one line per entry.
+    pub doc_comments: Vec<String>,
+}
+
+impl FnMetadata {
+    /// Read a function from its JSON description, which must at least have a `name`.
+    ///
+    /// Operators, property getters/setters and indexers (by `kind`) are skipped, as they are
+    /// not called by name.
+    fn from_json(json: &Value) -> Option<Self> {
+        match json["kind"].as_str() {
+            None | Some("function") => (),
+            Some(_) => return None,
+        }
+
+        let name = json["name"].as_str()?.to_string();
+
+        let params: Vec<_> = json["params"].as_array().map_or(Vec::new(), |params| {
+            params
+                .iter()
+                .map(|p| match (p["name"].as_str(), p["type"].as_str()) {
+                    (Some(name), Some(typ)) => format!("{}: {}", name, typ),
+                    (Some(name), None) => name.to_string(),
+                    (None, Some(typ)) => format!("_: {}", typ),
+                    (None, None) => "_".to_string(),
+                })
+                .collect()
+        });
+
+        let num_params = json["numParams"]
+            .as_u64()
+            .map_or(params.len(), |n| n as usize);
+
+        let signature = match json["signature"].as_str() {
+            Some(signature) => signature.to_string(),
+            None => {
+                let params = if params.is_empty() {
+                    vec!["_"; num_params].join(", ")
+                } else {
+                    params.join(", ")
+                };
+
+                match json["returnType"].as_str() {
+                    Some(typ) => format!("{}({}) -> {}", name, params, typ),
+                    None => format!("{}({})", name, params),
+                }
+            }
+        };
+
+        let doc_comments = json["docComments"].as_array().map_or(Vec::new(), |lines| {
+            lines
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        });
+
+        Some(Self {
+            name,
+            num_params,
+            signature,
+            doc_comments,
+        })
+    }
+}
+
+/// Functions and sub-modules described in a metadata file, such as one generated by
+/// `Engine::gen_metadata_to_json`.
+///
+/// The file is a JSON object with a `functions` array and a `modules` object holding
+/// sub-modules (in the same format) by name:
+///
+/// ```json
+/// {
+///     "functions": [
+///         { "name": "add", "numParams": 2, "signature": "add(x: i64, y: i64) -> i64" }
+///     ],
+///     "modules": {
+///         "math": { "functions": [ { "name": "sqrt", "params": [ { "name": "x" } ] } ] }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    /// Functions.
+    pub functions: Vec<FnMetadata>,
+    /// Sub-modules by name.
+    pub modules: BTreeMap<String, Metadata>,
+}
+
+impl Metadata {
+    /// Load a metadata file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Cannot read '{}': {}", path.display(), err))?;
+
+        let json: Value = serde_json::from_str(&text)
+            .map_err(|err| format!("Cannot parse '{}': {}", path.display(), err))?;
+
+        Ok(Self::from_json(&json))
+    }
+
+    /// Read functions and sub-modules from their JSON description.
+    fn from_json(json: &Value) -> Self {
+        let functions = json["functions"]
+            .as_array()
+            .map_or(Vec::new(), |functions| {
+                functions.iter().filter_map(FnMetadata::from_json).collect()
+            });
+
+        let modules = json["modules"]
+            .as_object()
+            .map_or(BTreeMap::new(), |modules| {
+                modules
+                    .iter()
+                    .map(|(name, module)| (name.clone(), Self::from_json(module)))
+                    .collect()
+            });
+
+        Self { functions, modules }
+    }
+
+    /// Find a sub-module by path (e.g. `["foo", "bar"]` for `foo::bar`).
+    pub fn find_module(&self, path: &[&str]) -> Option<&Self> {
+        path.iter()
+            .try_fold(self, |module, name| module.modules.get(*name))
+    }
+
+    /// Create a `Module` holding a function that does nothing in place of each function, so that
+    /// calls to the functions are accepted in strict mode.
+    pub fn to_module(&self) -> Module {
+        let mut module = Module::new();
+
+        self.functions.iter().for_each(|f| {
+            let arg_types = vec![TypeId::of::<Dynamic>(); f.num_params];
+            module.set_raw_fn(f.name.as_str(), &arg_types, |_, _| Ok(()));
+        });
+
+        self.modules.iter().for_each(|(name, sub_module)| {
+            module.set_sub_module(name.as_str(), sub_module.to_module());
+        });
+
+        module
+    }
+}
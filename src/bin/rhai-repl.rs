@@ -0,0 +1,417 @@
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+
+#[cfg(not(feature = "no_optimize"))]
+use rhai::OptimizationLevel;
+
+use rustyline::{error::ReadlineError, Editor};
+
+use std::{env, fs, path::PathBuf};
+
+const HISTORY_FILE: &str = ".rhai-repl-history";
+
+fn print_error(input: &str, err: EvalAltResult) {
+    let lines: Vec<_> = input.trim_end().split('\n').collect();
+    let pos = err.position();
+
+    let line_no = if lines.len() > 1 {
+        if pos.is_none() {
+            "".to_string()
+        } else {
+            format!("{}: ", pos.line().unwrap())
+        }
+    } else {
+        "".to_string()
+    };
+
+    // Print error
+    let pos_text = format!(" ({})", pos);
+
+    if pos.is_none() {
+        // No position
+        println!("{}", err);
+    } else {
+        // Specific position
+        println!(
+            "{}{}",
+            line_no,
+            lines.get(pos.line().unwrap() - 1).unwrap_or(&"")
+        );
+
+        println!(
+            "{0:>1$} {2}",
+            "^",
+            line_no.len() + pos.position().unwrap(),
+            err.to_string().replace(&pos_text, "")
+        );
+    }
+}
+
+fn print_help() {
+    println!("help          => print this help");
+    println!("quit, exit    => quit");
+    println!("history       => print the history of inputs");
+    println!("scope         => print all variables in the scope");
+    #[cfg(not(feature = "no_function"))]
+    println!("functions     => print all functions defined so far");
+    println!("ast           => print the last AST (optimized)");
+    println!("astu          => print the last raw, un-optimized AST");
+    #[cfg(not(feature = "no_optimize"))]
+    println!("optimize [L]  => print or set the optimization level (none, simple, full)");
+    println!("load FILE     => load and run a script file, keeping its functions");
+    #[cfg(not(feature = "unchecked"))]
+    println!("limits        => print all limits");
+    #[cfg(not(feature = "unchecked"))]
+    println!("limit NAME N  => set a limit (0 for unlimited)");
+    println!();
+    println!("Input continues on the next line while braces, brackets or parentheses are open,");
+    println!(r"or when a line ends with '\'.");
+    println!();
+}
+
+/// Is the input incomplete, i.e. does it have unbalanced braces, brackets or parentheses,
+/// or an unterminated block comment?
+fn is_incomplete(input: &str) -> bool {
+    let mut depth = 0_i32;
+    let mut comment_level = 0_usize;
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if comment_level > 0 {
+            match (ch, chars.peek()) {
+                ('*', Some('/')) => {
+                    chars.next();
+                    comment_level -= 1;
+                }
+                ('/', Some('*')) => {
+                    chars.next();
+                    comment_level += 1;
+                }
+                _ => (),
+            }
+            continue;
+        }
+
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            '"' | '\'' => {
+                // Skip string and character literals
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '\n' => break,
+                        c if c == ch => break,
+                        _ => (),
+                    }
+                }
+            }
+            '/' => match chars.peek() {
+                Some('/') => {
+                    // Skip line comment
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                Some('*') => {
+                    chars.next();
+                    comment_level += 1;
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    depth > 0 || comment_level > 0
+}
+
+fn history_path() -> PathBuf {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(HISTORY_FILE)
+}
+
+#[cfg(not(feature = "no_optimize"))]
+fn parse_optimization_level(level: &str) -> Option<OptimizationLevel> {
+    match level {
+        "none" => Some(OptimizationLevel::None),
+        "simple" => Some(OptimizationLevel::Simple),
+        "full" => Some(OptimizationLevel::Full),
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "unchecked"))]
+fn print_limits(engine: &Engine) {
+    println!("operations     = {}", engine.max_operations());
+    println!("call-levels    = {}", engine.max_call_levels());
+    println!("expr-depth     = {}", engine.max_expr_depth());
+    #[cfg(not(feature = "no_function"))]
+    println!("fn-expr-depth  = {}", engine.max_function_expr_depth());
+    println!("string-size    = {}", engine.max_string_size());
+    #[cfg(not(feature = "no_index"))]
+    println!("array-size     = {}", engine.max_array_size());
+    #[cfg(not(feature = "no_object"))]
+    println!("map-size       = {}", engine.max_map_size());
+    #[cfg(not(feature = "no_module"))]
+    println!("modules        = {}", engine.max_modules());
+    println!("stack-size     = {}", engine.max_stack_size());
+    println!();
+}
+
+#[cfg(not(feature = "unchecked"))]
+fn set_limit(engine: &mut Engine, name: &str, value: &str) -> Result<(), String> {
+    let value: usize = value
+        .parse()
+        .map_err(|_| format!("Invalid limit value: {}", value))?;
+
+    match name {
+        "operations" => {
+            engine.set_max_operations(value as u64);
+        }
+        "call-levels" => {
+            engine.set_max_call_levels(value);
+        }
+        "expr-depth" => {
+            engine.set_max_expr_depths(
+                value,
+                #[cfg(not(feature = "no_function"))]
+                engine.max_function_expr_depth(),
+            );
+        }
+        #[cfg(not(feature = "no_function"))]
+        "fn-expr-depth" => {
+            engine.set_max_expr_depths(engine.max_expr_depth(), value);
+        }
+        "string-size" => {
+            engine.set_max_string_size(value);
+        }
+        #[cfg(not(feature = "no_index"))]
+        "array-size" => {
+            engine.set_max_array_size(value);
+        }
+        #[cfg(not(feature = "no_object"))]
+        "map-size" => {
+            engine.set_max_map_size(value);
+        }
+        #[cfg(not(feature = "no_module"))]
+        "modules" => {
+            engine.set_max_modules(value);
+        }
+        "stack-size" => {
+            engine.set_max_stack_size(value);
+        }
+        _ => return Err(format!("Unknown limit: {}", name)),
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let mut engine = Engine::new();
+
+    // Compile without optimization in order to keep the raw AST;
+    // each entry is then optimized separately
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    #[cfg(not(feature = "no_optimize"))]
+    let mut optimization_level = OptimizationLevel::Simple;
+
+    let mut scope = Scope::new();
+
+    let mut main_ast: AST = Default::default();
+    let mut ast_u: AST = Default::default();
+    let mut ast: AST = Default::default();
+
+    let mut rl = Editor::<()>::new();
+    let history_path = history_path();
+
+    // A missing history file is not an error
+    let _ = rl.load_history(&history_path);
+
+    println!("Rhai REPL tool");
+    println!("==============");
+    print_help();
+
+    'main_loop: loop {
+        let mut input = String::new();
+        let mut prompt = "rhai> ";
+
+        loop {
+            let line = match rl.readline(prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) if !input.is_empty() => {
+                    // Ctrl-C cancels the current entry
+                    continue 'main_loop;
+                }
+                Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break 'main_loop,
+                Err(err) => {
+                    eprintln!("Input error: {}", err);
+                    break 'main_loop;
+                }
+            };
+
+            let line = line.trim_end();
+
+            // Allow line continuation
+            if let Some(line) = line.strip_suffix('\\') {
+                input.push_str(line);
+                input.push('\n');
+            } else {
+                input.push_str(line);
+                input.push('\n');
+
+                if !is_incomplete(&input) {
+                    break;
+                }
+            }
+
+            prompt = "    > ";
+        }
+
+        let script = input.trim();
+
+        if script.is_empty() {
+            continue;
+        }
+
+        rl.add_history_entry(script);
+
+        let mut words = script.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let args: Vec<_> = words.collect();
+
+        // Implement standard commands
+        match (command, args.as_slice()) {
+            ("help", []) => {
+                print_help();
+                continue;
+            }
+            ("exit", []) | ("quit", []) => break, // quit
+            ("history", []) => {
+                rl.history()
+                    .iter()
+                    .enumerate()
+                    .for_each(|(i, entry)| println!("[{}] {}", i + 1, entry));
+                println!();
+                continue;
+            }
+            ("scope", []) => {
+                scope
+                    .iter_raw()
+                    .enumerate()
+                    .for_each(|(i, (name, constant, value))| {
+                        println!(
+                            "[{}] {}{}{} = {:?}",
+                            i + 1,
+                            if constant { "const " } else { "" },
+                            name,
+                            if value.is_shared() { " (shared)" } else { "" },
+                            *value.read_lock::<Dynamic>().unwrap(),
+                        )
+                    });
+                println!();
+                continue;
+            }
+            #[cfg(not(feature = "no_function"))]
+            ("functions", []) => {
+                main_ast
+                    .iter_functions()
+                    .for_each(|(_, _, _, f)| println!("{}", f));
+                println!();
+                continue;
+            }
+            ("astu", []) => {
+                // print the last un-optimized AST
+                println!("{:#?}\n", &ast_u);
+                continue;
+            }
+            ("ast", []) => {
+                // print the last AST
+                println!("{:#?}\n", &ast);
+                continue;
+            }
+            #[cfg(not(feature = "no_optimize"))]
+            ("optimize", []) => {
+                println!("Optimization level: {:?}\n", optimization_level);
+                continue;
+            }
+            #[cfg(not(feature = "no_optimize"))]
+            ("optimize", [level]) => {
+                match parse_optimization_level(level) {
+                    Some(level) => optimization_level = level,
+                    None => println!("Unknown optimization level: {}\n", level),
+                }
+                continue;
+            }
+            #[cfg(not(feature = "unchecked"))]
+            ("limits", []) => {
+                print_limits(&engine);
+                continue;
+            }
+            #[cfg(not(feature = "unchecked"))]
+            ("limit", [name, value]) => {
+                if let Err(err) = set_limit(&mut engine, name, value) {
+                    println!("{}\n", err);
+                }
+                continue;
+            }
+            ("load", [filename]) => match fs::read_to_string(filename) {
+                Ok(contents) => input = contents,
+                Err(err) => {
+                    println!("Error reading script file: {}\n{}\n", filename, err);
+                    continue;
+                }
+            },
+            _ => (),
+        }
+
+        match engine
+            .compile_with_scope(&scope, &input)
+            .map_err(Into::into)
+            .and_then(|r| {
+                ast_u = r.clone();
+
+                #[cfg(not(feature = "no_optimize"))]
+                {
+                    ast = engine.optimize_ast(&scope, r, optimization_level);
+                }
+
+                #[cfg(feature = "no_optimize")]
+                {
+                    ast = r;
+                }
+
+                // Merge the AST into the main, keeping all functions defined so far
+                main_ast += ast.clone();
+
+                // Evaluate
+                engine.eval_ast_with_scope::<Dynamic>(&mut scope, &main_ast)
+            }) {
+            Ok(result) if !result.is::<()>() => {
+                println!("=> {:?}", result);
+                println!();
+            }
+            Ok(_) => (),
+            Err(err) => {
+                println!();
+                print_error(&input, *err);
+                println!();
+            }
+        }
+
+        // Throw away all the statements, leaving only the functions
+        main_ast.clear_statements();
+    }
+
+    if let Err(err) = rl.save_history(&history_path) {
+        eprintln!("Error saving history: {}", err);
+    }
+}
@@ -0,0 +1,317 @@
+use rhai::{Engine, EvalAltResult, Position, RegisterResultFn, Scope, INT, NO_POS};
+
+#[cfg(not(feature = "no_optimize"))]
+use rhai::OptimizationLevel;
+
+#[cfg(not(feature = "no_module"))]
+use rhai::module_resolvers::{FileModuleResolver, ModuleResolversCollection};
+
+use rhai::packages::{CorePackage, Package, StandardPackage};
+
+use std::{env, fs, process::exit};
+
+const USAGE: &str = "\
+Usage: rhai-run [OPTIONS] SCRIPT [ARGS...]
+
+Run a Rhai script.
+The arguments after the script are available to it as an array of strings in the constant ARGS.
+The script can call quit(code) to stop with an exit code.
+
+Options:
+    --optimize LEVEL                Optimization level: none, simple or full (default: full)
+    --packages SET                  Packages to load: standard (default), core or none
+    -M, --modules DIR               Add DIR to the search path of imported modules
+                                    (default: the current directory); may be repeated
+    --max-operations N              Maximum number of operations
+    --max-call-levels N             Maximum levels of function calls
+    --max-expr-depth N              Maximum depth of expressions at global level
+    --max-function-expr-depth N     Maximum depth of expressions in functions
+    --max-string-size N             Maximum length of strings
+    --max-array-size N              Maximum size of arrays
+    --max-map-size N                Maximum size of object maps
+    --max-modules N                 Maximum number of imported modules
+    --max-stack-size N              Maximum bytes of native stack
+    -h, --help                      Print this help message
+
+All limits are unlimited when set to 0.
+
+Exit codes:
+    0    The script ran to completion, or called quit() or quit(0)
+    1    Syntax or runtime error
+    2    Invalid options or script file not found
+    N    The script called quit(N), or threw the integer N, with N between 1 and 255
+         (quit() or throw with any other integer exits with 1)";
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    exit(2);
+}
+
+#[cfg(not(feature = "unchecked"))]
+fn parse_number(option: &str, value: Option<String>) -> usize {
+    match value.as_deref().map(str::parse) {
+        Some(Ok(n)) => n,
+        _ => usage_error(&format!("Option {} requires a number", option)),
+    }
+}
+
+fn eprint_error(filename: &str, input: &str, err: &str, pos: Position, source: Option<&str>) {
+    // Positions in other sources (e.g. imported modules) cannot be shown in this script
+    if pos.is_none() || source != Some(filename) {
+        eprintln!("{}: {}", filename, err);
+        return;
+    }
+
+    let line = pos.line().unwrap();
+    let line_no = format!("{}:{}: ", filename, line);
+    let pos_text = format!(" ({} in '{}')", pos, filename);
+
+    eprintln!(
+        "{}{}",
+        line_no,
+        input.split('\n').nth(line - 1).unwrap_or("")
+    );
+    eprintln!(
+        "{:>1$} {2}",
+        "^",
+        line_no.len() + pos.position().unwrap(),
+        err.replace(&pos_text, "")
+    );
+}
+
+/// Get the exit code for an error, and whether the error should be reported.
+///
+/// Exit codes outside of 1 to 255 cannot be reported on all platforms, so they map to 1
+/// (except for `quit(0)`, which is not a failure).
+fn exit_code(err: &EvalAltResult) -> (i32, bool) {
+    match err {
+        EvalAltResult::ErrorInFunctionCall(_, _, _, err, _)
+        | EvalAltResult::ErrorInModule(_, err, _) => exit_code(err),
+        EvalAltResult::ErrorTerminated(value, _) if value.is::<()>() => (0, false),
+        EvalAltResult::ErrorTerminated(value, _) => match value.as_int() {
+            Ok(0) => (0, false),
+            Ok(code) => (failure_code(code), false),
+            Err(_) => (1, true),
+        },
+        EvalAltResult::ErrorRuntime(value, _) => match value.as_int() {
+            Ok(code) => (failure_code(code), true),
+            _ => (1, true),
+        },
+        _ => (1, true),
+    }
+}
+
+/// Map an integer into a failure exit code, which is never zero.
+fn failure_code(code: INT) -> i32 {
+    if (1..=255).contains(&code) {
+        code as i32
+    } else {
+        1
+    }
+}
+
+/// Blank out a shebang (`#!`) line, keeping line numbers intact.
+fn strip_shebang(contents: &mut String) {
+    if contents.starts_with("#!") {
+        let end = contents.find('\n').unwrap_or(contents.len());
+        contents.replace_range(..end, "");
+    }
+}
+
+fn main() {
+    #[cfg(not(feature = "no_optimize"))]
+    let mut optimization_level = OptimizationLevel::Full;
+    let mut packages = "standard".to_string();
+    #[cfg(not(feature = "no_module"))]
+    let mut module_paths = Vec::new();
+    #[cfg(not(feature = "unchecked"))]
+    let mut limits = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    let filename = loop {
+        let arg = match args.next() {
+            Some(arg) => arg,
+            None => usage_error("No script file"),
+        };
+
+        match arg.as_str() {
+            #[cfg(not(feature = "no_optimize"))]
+            "--optimize" => {
+                optimization_level = match args.next().as_deref() {
+                    Some("none") => OptimizationLevel::None,
+                    Some("simple") => OptimizationLevel::Simple,
+                    Some("full") => OptimizationLevel::Full,
+                    _ => usage_error("Option --optimize requires none, simple or full"),
+                }
+            }
+            "--packages" => match args.next() {
+                Some(set) if set == "standard" || set == "core" || set == "none" => packages = set,
+                _ => usage_error("Option --packages requires standard, core or none"),
+            },
+            #[cfg(not(feature = "no_module"))]
+            "-M" | "--modules" => match args.next() {
+                Some(path) => module_paths.push(path),
+                None => usage_error(&format!("Option {} requires a directory", arg)),
+            },
+            #[cfg(not(feature = "unchecked"))]
+            _ if arg.starts_with("--max-") => {
+                let value = parse_number(&arg, args.next());
+                limits.push((arg, value));
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--" => match args.next() {
+                Some(filename) => break filename,
+                None => usage_error("No script file"),
+            },
+            _ if arg.starts_with('-') => usage_error(&format!("Unknown option: {}", arg)),
+            _ => break arg,
+        }
+    };
+
+    let mut engine = Engine::new_raw();
+
+    match packages.as_str() {
+        "standard" => engine.load_package(StandardPackage::new().get()),
+        "core" => engine.load_package(CorePackage::new().get()),
+        _ => &mut engine,
+    };
+
+    engine
+        .on_print(|s| println!("{}", s))
+        .on_debug(|s| println!("{}", s));
+
+    engine.register_result_fn("quit", || {
+        EvalAltResult::ErrorTerminated(().into(), NO_POS.into()).into()
+    });
+    engine.register_result_fn("quit", |code: INT| {
+        EvalAltResult::ErrorTerminated(code.into(), NO_POS.into()).into()
+    });
+
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(optimization_level);
+
+    #[cfg(not(feature = "no_module"))]
+    if module_paths.is_empty() {
+        engine.set_module_resolver(Some(FileModuleResolver::new()));
+    } else {
+        let mut resolvers = ModuleResolversCollection::new();
+
+        for path in module_paths {
+            resolvers.push(FileModuleResolver::new_with_path(path));
+        }
+
+        engine.set_module_resolver(Some(resolvers));
+    }
+
+    #[cfg(not(feature = "unchecked"))]
+    for (option, value) in limits {
+        match option.as_str() {
+            "--max-operations" => {
+                engine.set_max_operations(value as u64);
+            }
+            "--max-call-levels" => {
+                engine.set_max_call_levels(value);
+            }
+            "--max-expr-depth" => {
+                engine.set_max_expr_depths(
+                    value,
+                    #[cfg(not(feature = "no_function"))]
+                    engine.max_function_expr_depth(),
+                );
+            }
+            #[cfg(not(feature = "no_function"))]
+            "--max-function-expr-depth" => {
+                engine.set_max_expr_depths(engine.max_expr_depth(), value);
+            }
+            "--max-string-size" => {
+                engine.set_max_string_size(value);
+            }
+            #[cfg(not(feature = "no_index"))]
+            "--max-array-size" => {
+                engine.set_max_array_size(value);
+            }
+            #[cfg(not(feature = "no_object"))]
+            "--max-map-size" => {
+                engine.set_max_map_size(value);
+            }
+            #[cfg(not(feature = "no_module"))]
+            "--max-modules" => {
+                engine.set_max_modules(value);
+            }
+            "--max-stack-size" => {
+                engine.set_max_stack_size(value);
+            }
+            _ => usage_error(&format!("Unknown option: {}", option)),
+        }
+    }
+
+    let mut contents = match fs::read_to_string(&filename) {
+        Ok(contents) => contents,
+        Err(err) => {
+            eprintln!("Error reading script file: {}\n{}", filename, err);
+            exit(2);
+        }
+    };
+
+    strip_shebang(&mut contents);
+
+    let mut scope = Scope::new();
+
+    #[cfg(not(feature = "no_index"))]
+    scope.push_constant("ARGS", args.map(Into::into).collect::<rhai::Array>());
+
+    let ast = match engine.compile_sources_with_scope(&scope, &[(&filename, &contents)]) {
+        Ok(ast) => ast,
+        Err(err) => {
+            let (pos, source) = (err.position(), err.source());
+            eprint_error(&filename, &contents, &err.to_string(), pos, source);
+            exit(1);
+        }
+    };
+
+    if let Err(err) = engine.consume_ast_with_scope(&mut scope, &ast) {
+        let (code, report) = exit_code(&err);
+
+        if report {
+            eprint_error(
+                &filename,
+                &contents,
+                &err.to_string(),
+                err.position(),
+                err.source(),
+            );
+        }
+
+        exit(code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rhai::Dynamic;
+
+    #[test]
+    fn test_exit_code() {
+        let quit = |value: Dynamic| EvalAltResult::ErrorTerminated(value, NO_POS.into());
+        let throw = |value: Dynamic| EvalAltResult::ErrorRuntime(value, NO_POS.into());
+
+        assert_eq!(exit_code(&quit(().into())), (0, false));
+        assert_eq!(exit_code(&quit((0 as INT).into())), (0, false));
+        assert_eq!(exit_code(&quit((42 as INT).into())), (42, false));
+        assert_eq!(exit_code(&quit((255 as INT).into())), (255, false));
+        assert_eq!(exit_code(&quit((256 as INT).into())), (1, false));
+        assert_eq!(exit_code(&quit((-1 as INT).into())), (1, false));
+        assert_eq!(exit_code(&quit("x".into())), (1, true));
+
+        assert_eq!(exit_code(&throw((0 as INT).into())), (1, true));
+        assert_eq!(exit_code(&throw((3 as INT).into())), (3, true));
+        assert_eq!(exit_code(&throw((512 as INT).into())), (1, true));
+        assert_eq!(exit_code(&throw(INT::MIN.into())), (1, true));
+        assert_eq!(exit_code(&throw("x".into())), (1, true));
+    }
+}
@@ -0,0 +1,141 @@
+use rhai::packages::{Package, TestingPackage};
+use rhai::{Engine, TestOptions, TestReport, TestResult};
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+const USAGE: &str = "\
+Usage: rhai-test [OPTIONS] [PATHS...]
+
+Run the test functions in Rhai scripts, i.e. functions without parameters with names starting
+with test_, each in isolation.  Directories are searched for .rhai script files.
+With no paths, the current directory is searched.
+
+The results are written to standard output in the Test Anything Protocol (TAP) format.
+Output of print and debug goes to standard error.
+
+Options:
+    --filter TEXT       Only run tests with names containing TEXT
+    --prefix PREFIX     Prefix of the names of test functions (default: test_)
+    --max-operations N  Maximum number of operations of each test (default: unlimited)
+    -h, --help          Print this help message
+
+Exit codes:
+    0    All tests passed
+    1    A test failed, or a script cannot be read or compiled
+    2    Invalid options";
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    exit(2);
+}
+
+/// Collect all `.rhai` script files in a path, in sorted order.
+fn collect_scripts(path: &Path, scripts: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        scripts.push(path.to_path_buf());
+        return;
+    }
+
+    let mut entries: Vec<_> = match fs::read_dir(path) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+        Err(_) => return,
+    };
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            collect_scripts(&entry, scripts);
+        } else if entry.extension() == Some("rhai".as_ref()) {
+            scripts.push(entry);
+        }
+    }
+}
+
+fn main() {
+    let mut options = TestOptions::default();
+    #[cfg(not(feature = "unchecked"))]
+    let mut max_operations = None;
+    let mut paths = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--filter" => match args.next() {
+                Some(filter) => options.filter = Some(filter),
+                None => usage_error("Option --filter requires a text"),
+            },
+            "--prefix" => match args.next() {
+                Some(prefix) => options.prefix = prefix,
+                None => usage_error("Option --prefix requires a prefix"),
+            },
+            #[cfg(not(feature = "unchecked"))]
+            "--max-operations" => match args.next().as_deref().map(str::parse) {
+                Some(Ok(n)) => max_operations = Some(n),
+                _ => usage_error("Option --max-operations requires a number"),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => usage_error(&format!("Unknown option: {}", arg)),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+
+    let mut scripts = Vec::new();
+
+    for path in &paths {
+        collect_scripts(path, &mut scripts);
+    }
+
+    let mut engine = Engine::new();
+
+    engine.load_package(TestingPackage::new().get());
+
+    // Keep standard output for the test report
+    engine
+        .on_print(|s| eprintln!("{}", s))
+        .on_debug(|s| eprintln!("{}", s));
+
+    #[cfg(not(feature = "unchecked"))]
+    if let Some(n) = max_operations {
+        engine.set_max_operations(n);
+    }
+
+    let mut report = TestReport::default();
+
+    for script in scripts {
+        let source = script.to_string_lossy().into_owned();
+
+        // Scripts that cannot be compiled are reported as failed tests
+        match engine.compile_file(script) {
+            Ok(ast) => report.append(engine.run_tests(&ast, &options)),
+            Err(err) => report.results.push(TestResult {
+                source: Some(source),
+                name: "<compile>".into(),
+                error: Some(err),
+            }),
+        }
+    }
+
+    print!("{}", report.to_tap());
+
+    eprintln!(
+        "{} passed, {} failed",
+        report.num_passed(),
+        report.num_failed()
+    );
+
+    if !report.is_success() {
+        exit(1);
+    }
+}
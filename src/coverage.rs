@@ -0,0 +1,389 @@
+//! Module defining the script coverage collector.
+
+use crate::ast::{Expr, Stmt, AST};
+use crate::engine::{Engine, State};
+use crate::fn_native::{Locked, Shared};
+use crate::token::Position;
+use crate::utils::ImmutableString;
+
+use crate::stdlib::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    string::String,
+    vec::Vec,
+};
+
+#[cfg(not(feature = "sync"))]
+use crate::stdlib::cell::RefMut;
+
+#[cfg(feature = "sync")]
+use crate::stdlib::sync::RwLockWriteGuard;
+
+/// Name of the source for scripts without one in exported coverage data.
+const NO_SOURCE: &str = "<script>";
+
+/// Type of a branching construct.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum BranchKind {
+    /// `if` statement.  The branches are the `if` block and the (possibly empty) `else` block.
+    If,
+    /// `&&` operator.  The branches are evaluating the right-hand side and short-circuiting.
+    And,
+    /// `||` operator.  The branches are evaluating the right-hand side and short-circuiting.
+    Or,
+    /// `try` ... `catch` statement.  The branches are completing the `try` block without
+    /// catching an error and running the `catch` block.
+    TryCatch,
+}
+
+/// Coverage of a single statement.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct StatementCoverage {
+    /// Position of the statement.
+    pub pos: Position,
+    /// Name of the source (e.g. the script file) of the statement, if any.
+    pub source: Option<ImmutableString>,
+    /// Number of times the statement was executed.
+    pub count: u64,
+}
+
+/// Coverage of a single branching construct.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BranchCoverage {
+    /// Position of the `if` or `try` keyword, or of the `&&` or `||` operator.
+    pub pos: Position,
+    /// Name of the source (e.g. the script file) of the branching construct, if any.
+    pub source: Option<ImmutableString>,
+    /// Type of the branching construct.
+    pub kind: BranchKind,
+    /// Number of times each of the two branches was taken, in the order listed in `BranchKind`.
+    pub counts: [u64; 2],
+}
+
+/// A structured report of a `Coverage` collector.
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReport {
+    /// Coverage of statements, sorted by position.
+    statements: Vec<StatementCoverage>,
+    /// Coverage of branches, sorted by position.
+    branches: Vec<BranchCoverage>,
+}
+
+impl CoverageReport {
+    /// Get the coverage of all statements, sorted by position.
+    #[inline(always)]
+    pub fn statements(&self) -> &[StatementCoverage] {
+        &self.statements
+    }
+    /// Get the coverage of all branching constructs, sorted by position.
+    #[inline(always)]
+    pub fn branches(&self) -> &[BranchCoverage] {
+        &self.branches
+    }
+    /// Get the execution counts of all lines with statements in a particular source
+    /// (`None` for scripts without a source), sorted by line number.
+    ///
+    /// The count of a line is the highest count of the statements on it.
+    pub fn lines(&self, source: Option<&str>) -> Vec<(usize, u64)> {
+        let mut lines: BTreeMap<usize, u64> = Default::default();
+
+        self.statements
+            .iter()
+            .filter(|s| s.source.as_deref().map(String::as_str) == source)
+            .filter_map(|s| s.pos.line().map(|line| (line, s.count)))
+            .for_each(|(line, count)| {
+                let n = lines.entry(line).or_default();
+                *n = (*n).max(count);
+            });
+
+        lines.into_iter().collect()
+    }
+    /// Export the report in the `lcov` tracefile format, with one record per source.
+    ///
+    /// Scripts without a source are recorded under the name `<script>`.
+    pub fn to_lcov(&self) -> String {
+        let mut sources: BTreeMap<&str, (Vec<&StatementCoverage>, Vec<&BranchCoverage>)> =
+            Default::default();
+
+        for s in &self.statements {
+            let source = s.source.as_deref().map_or(NO_SOURCE, String::as_str);
+            sources.entry(source).or_default().0.push(s);
+        }
+        for b in &self.branches {
+            let source = b.source.as_deref().map_or(NO_SOURCE, String::as_str);
+            sources.entry(source).or_default().1.push(b);
+        }
+
+        let mut output = String::new();
+
+        for (source, (statements, branches)) in sources {
+            output.push_str("TN:\n");
+            let _ = writeln!(output, "SF:{}", source);
+
+            let mut branches_hit = 0;
+
+            for (block, b) in branches.iter().enumerate() {
+                let line = b.pos.line().unwrap_or(0);
+                let reached = b.counts.iter().sum::<u64>() > 0;
+
+                for (branch, &count) in b.counts.iter().enumerate() {
+                    if !reached {
+                        let _ = writeln!(output, "BRDA:{},{},{},-", line, block, branch);
+                    } else {
+                        let _ = writeln!(output, "BRDA:{},{},{},{}", line, block, branch, count);
+                    }
+                    if count > 0 {
+                        branches_hit += 1;
+                    }
+                }
+            }
+
+            let _ = writeln!(output, "BRF:{}", branches.len() * 2);
+            let _ = writeln!(output, "BRH:{}", branches_hit);
+
+            let mut lines: BTreeMap<usize, u64> = Default::default();
+
+            for s in statements {
+                if let Some(line) = s.pos.line() {
+                    let n = lines.entry(line).or_default();
+                    *n = (*n).max(s.count);
+                }
+            }
+
+            for (line, count) in &lines {
+                let _ = writeln!(output, "DA:{},{}", line, count);
+            }
+
+            let _ = writeln!(output, "LF:{}", lines.len());
+            let _ = writeln!(
+                output,
+                "LH:{}",
+                lines.values().filter(|&&count| count > 0).count()
+            );
+            output.push_str("end_of_record\n");
+        }
+
+        output
+    }
+}
+
+/// Execution counts collected by a `Coverage` collector.
+#[derive(Debug, Clone, Default)]
+struct CoverageData {
+    /// Execution counts of statements, by source and position.
+    statements: HashMap<(Option<ImmutableString>, Position), u64>,
+    /// Counts of branches taken, by source and position.
+    branches: HashMap<(Option<ImmutableString>, Position), (BranchKind, [u64; 2])>,
+}
+
+impl CoverageData {
+    /// Register the statements and branches in a statement from a source, with zero counts.
+    fn add_stmt(&mut self, source: &Option<ImmutableString>, stmt: &Stmt) {
+        if is_covered(stmt) {
+            self.statements
+                .entry((source.clone(), stmt.position()))
+                .or_default();
+        }
+
+        match stmt {
+            Stmt::IfThenElse(expr, x, pos) => {
+                self.add_branch(source, *pos, BranchKind::If);
+                self.add_expr(source, expr);
+                self.add_stmt(source, &x.0);
+                if let Some(ref stmt) = x.1 {
+                    self.add_stmt(source, stmt);
+                }
+            }
+            Stmt::While(expr, body, _) => {
+                self.add_expr(source, expr);
+                self.add_stmt(source, body);
+            }
+            Stmt::Loop(body, _) => self.add_stmt(source, body),
+            Stmt::For(expr, x, _) => {
+                self.add_expr(source, expr);
+                self.add_stmt(source, &x.1);
+            }
+            Stmt::Let(_, Some(expr), _) | Stmt::Const(_, Some(expr), _) => {
+                self.add_expr(source, expr)
+            }
+            Stmt::Assignment(x, _) => {
+                self.add_expr(source, &x.0);
+                self.add_expr(source, &x.2);
+            }
+            Stmt::Block(statements, _) => statements.iter().for_each(|s| self.add_stmt(source, s)),
+            Stmt::TryCatch(x, pos, _) => {
+                self.add_branch(source, *pos, BranchKind::TryCatch);
+                self.add_stmt(source, &x.0);
+                self.add_stmt(source, &x.3);
+                if let Some(ref stmt) = x.4 {
+                    self.add_stmt(source, stmt);
+                }
+            }
+            Stmt::Expr(expr) | Stmt::ReturnWithVal(_, Some(expr), _) => self.add_expr(source, expr),
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Import(expr, _, _) => self.add_expr(source, expr),
+            _ => (),
+        }
+    }
+    /// Register the statements and branches in an expression from a source, with zero counts.
+    fn add_expr(&mut self, source: &Option<ImmutableString>, expr: &Expr) {
+        match expr {
+            Expr::Expr(x) => self.add_expr(source, x),
+            Expr::Stmt(x, _, _) => x.iter().for_each(|s| self.add_stmt(source, s)),
+            Expr::FnCall(x, _, _) => x.args.iter().for_each(|e| self.add_expr(source, e)),
+            Expr::Dot(x, _) | Expr::Index(x, _, _) | Expr::In(x, _) => {
+                self.add_expr(source, &x.lhs);
+                self.add_expr(source, &x.rhs);
+            }
+            Expr::And(x, pos) | Expr::Or(x, pos) => {
+                let kind = match expr {
+                    Expr::And(_, _) => BranchKind::And,
+                    _ => BranchKind::Or,
+                };
+                self.add_branch(source, *pos, kind);
+                self.add_expr(source, &x.lhs);
+                self.add_expr(source, &x.rhs);
+            }
+            Expr::Array(x, _, _) => x.iter().for_each(|e| self.add_expr(source, e)),
+            Expr::Map(x, _, _) => x.iter().for_each(|(_, e)| self.add_expr(source, e)),
+            Expr::Custom(x, _, _) => x.keywords.iter().for_each(|e| self.add_expr(source, e)),
+            _ => (),
+        }
+    }
+    /// Register a branching construct from a source, with zero counts.
+    fn add_branch(&mut self, source: &Option<ImmutableString>, pos: Position, kind: BranchKind) {
+        self.branches
+            .entry((source.clone(), pos))
+            .or_insert((kind, [0, 0]));
+    }
+}
+
+/// Is a statement recorded by coverage?
+///
+/// Blocks are not recorded - only the statements inside.
+#[inline(always)]
+pub(crate) fn is_covered(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Block(_, _) | Stmt::Noop(_) | Stmt::Source(_) => false,
+        #[cfg(not(feature = "no_closure"))]
+        Stmt::Share(_) => false,
+        _ => true,
+    }
+}
+
+/// A coverage collector that records execution counts of statements and branches.
+///
+/// A `Coverage` collector is a cheap handle to shared execution counts: clone it, pass one copy
+/// to `Engine::set_coverage`, then call `report` on the other after evaluation.
+/// Execution counts accumulate over all evaluation runs until `reset` is called.
+///
+/// Only statements and branches that are executed at least once are recorded, unless the
+/// `AST` is registered beforehand via `add_ast`.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage(Shared<Locked<CoverageData>>);
+
+impl Coverage {
+    /// Create a new `Coverage` collector.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Lock the execution counts for update.
+    #[cfg(not(feature = "sync"))]
+    #[inline(always)]
+    fn data(&self) -> RefMut<'_, CoverageData> {
+        self.0.borrow_mut()
+    }
+    /// Lock the execution counts for update.
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    fn data(&self) -> RwLockWriteGuard<'_, CoverageData> {
+        self.0.write().unwrap()
+    }
+    /// Register all statements and branches in an `AST` (including script-defined functions)
+    /// with zero counts, so that code never executed shows up in the report.
+    pub fn add_ast(&self, ast: &AST) {
+        let data = &mut self.data();
+        let mut source = None;
+
+        for stmt in ast.stmts() {
+            match stmt {
+                Stmt::Source(s) => source = s.clone(),
+                _ => data.add_stmt(&source, stmt),
+            }
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        ast.iter_functions()
+            .for_each(|(_, _, _, fn_def)| data.add_stmt(&fn_def.source, &fn_def.body));
+    }
+    /// Clear all execution counts and registered statements and branches.
+    #[inline(always)]
+    pub fn reset(&self) {
+        *self.data() = Default::default();
+    }
+    /// Get a report of all execution counts so far.
+    pub fn report(&self) -> CoverageReport {
+        let data = self.data();
+
+        let mut statements: Vec<_> = data
+            .statements
+            .iter()
+            .map(|((source, pos), &count)| StatementCoverage {
+                pos: *pos,
+                source: source.clone(),
+                count,
+            })
+            .collect();
+        statements.sort_by_key(|s| s.pos);
+
+        let mut branches: Vec<_> = data
+            .branches
+            .iter()
+            .map(|((source, pos), &(kind, counts))| BranchCoverage {
+                pos: *pos,
+                source: source.clone(),
+                kind,
+                counts,
+            })
+            .collect();
+        branches.sort_by_key(|b| b.pos);
+
+        CoverageReport {
+            statements,
+            branches,
+        }
+    }
+}
+
+impl Engine {
+    /// Record the execution of a statement, if coverage is on.
+    #[inline]
+    pub(crate) fn cover_statement(&self, state: &State, stmt: &Stmt) {
+        if let Some(ref coverage) = self.coverage {
+            if is_covered(stmt) {
+                *coverage
+                    .data()
+                    .statements
+                    .entry((state.source.clone(), stmt.position()))
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    /// Record a branch taken, if coverage is on.
+    ///
+    /// `first` is `true` when the first of the two branches (in the order listed in
+    /// `BranchKind`) is taken.
+    #[inline]
+    pub(crate) fn cover_branch(&self, state: &State, pos: Position, kind: BranchKind, first: bool) {
+        if let Some(ref coverage) = self.coverage {
+            let mut data = coverage.data();
+            let (_, counts) = data
+                .branches
+                .entry((state.source.clone(), pos))
+                .or_insert((kind, [0, 0]));
+            counts[if first { 0 } else { 1 }] += 1;
+        }
+    }
+}
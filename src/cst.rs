@@ -0,0 +1,691 @@
+//! Module defining the lossless concrete syntax tree.
+
+use crate::ast::AST;
+use crate::engine::Engine;
+use crate::parse_error::ParseError;
+use crate::token::{Position, Span, Token, TokenStream};
+
+use crate::stdlib::{
+    collections::HashMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Type of a token in a `SyntaxTree`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TokenKind {
+    /// Whitespace (trivia), including new-lines.
+    Whitespace,
+    /// Comment (trivia), including the `//` or `/*` ... `*/` delimiters.
+    Comment,
+    /// Keyword, including custom keywords.
+    Keyword,
+    /// Identifier.
+    Identifier,
+    /// Integer literal.
+    Integer,
+    /// Floating-point literal.
+    Float,
+    /// String literal, including the quotes.
+    String,
+    /// Character literal, including the quotes.
+    Char,
+    /// Operator or punctuation.
+    Symbol,
+    /// Invalid input, such as a malformed literal or a reserved symbol.
+    Error,
+}
+
+/// Role of an identifier in a `SyntaxTree`, as determined by the parser.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum IdentRole {
+    /// Declaration of a variable, constant, parameter or catch variable, or an export alias.
+    Declaration,
+    /// Reference to a variable or constant.
+    Variable,
+    /// Property of an object map, either accessed via `.` or a key in a map literal.
+    Property,
+    /// Name of a function, in a function definition or a function or method call.
+    Function,
+    /// Name of a module, in a module path or an import alias.
+    Module,
+}
+
+/// Type of a node in a `SyntaxTree`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum NodeKind {
+    /// The entire script.
+    Script,
+    /// A statement, including the terminating `;` if any.
+    Statement,
+    /// A function definition, from the `fn` (or `private`) keyword to the closing `}`.
+    Function,
+    /// A statement block, within `{` ... `}`.
+    Block,
+    /// Anything within `(` ... `)`, e.g. function call arguments or parameters.
+    Parens,
+    /// Anything within `[` ... `]`, e.g. an array literal or an index.
+    Brackets,
+    /// An object map literal, within `#{` ... `}`.
+    Map,
+}
+
+/// A token or piece of trivia (whitespace or comment) in a `SyntaxTree`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxToken {
+    /// Type of the token.
+    kind: TokenKind,
+    /// Text of the token in the source.
+    text: String,
+    /// Location of the token in the source, from its first to its last character.
+    span: Span,
+    /// Byte offset of the token in the source.
+    offset: usize,
+    /// The parsed token, `None` for whitespace.
+    token: Option<Token>,
+    /// Role of the identifier, if the token is an identifier parsed successfully.
+    role: Option<IdentRole>,
+}
+
+impl SyntaxToken {
+    /// Get the type of this token.
+    #[inline(always)]
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+    /// Is this token whitespace or a comment?
+    #[inline(always)]
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Whitespace | TokenKind::Comment)
+    }
+    /// Get the text of this token in the source.
+    #[inline(always)]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Get the location of this token in the source, from its first to its last character.
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+    /// Get the byte offset of this token in the source.
+    #[inline(always)]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    /// Get the role of this identifier as determined by the parser, or `None` if this token is not
+    /// an identifier or it was skipped because of a syntax error.
+    #[inline(always)]
+    pub fn role(&self) -> Option<IdentRole> {
+        self.role
+    }
+    /// _[INTERNALS]_ Get the parsed token, or `None` for whitespace.
+    /// Exported under the `internals` feature only.
+    #[cfg(feature = "internals")]
+    #[inline(always)]
+    pub fn token(&self) -> Option<&Token> {
+        self.token.as_ref()
+    }
+    /// Get the parsed token, or `None` for whitespace.
+    #[cfg(not(feature = "internals"))]
+    #[inline(always)]
+    pub(crate) fn token(&self) -> Option<&Token> {
+        self.token.as_ref()
+    }
+}
+
+/// An element of a `SyntaxTree`: either a node or a token.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement {
+    /// A node containing other elements.
+    Node(SyntaxNode),
+    /// A token or piece of trivia.
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    /// Get the location of this element in the source.
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Node(node) => node.span(),
+            Self::Token(token) => token.span(),
+        }
+    }
+    /// Get the byte offset of this element in the source.
+    #[inline(always)]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::Node(node) => node.offset(),
+            Self::Token(token) => token.offset(),
+        }
+    }
+    /// Get the text of this element in the source.
+    #[inline(always)]
+    pub fn text(&self) -> String {
+        match self {
+            Self::Node(node) => node.text(),
+            Self::Token(token) => token.text().to_string(),
+        }
+    }
+    /// Get the node, if this element is a node.
+    #[inline(always)]
+    pub fn as_node(&self) -> Option<&SyntaxNode> {
+        match self {
+            Self::Node(node) => Some(node),
+            Self::Token(_) => None,
+        }
+    }
+    /// Get the token, if this element is a token.
+    #[inline(always)]
+    pub fn as_token(&self) -> Option<&SyntaxToken> {
+        match self {
+            Self::Node(_) => None,
+            Self::Token(token) => Some(token),
+        }
+    }
+    /// Is this element whitespace or a comment?
+    #[inline(always)]
+    fn is_trivia(&self) -> bool {
+        match self {
+            Self::Node(_) => false,
+            Self::Token(token) => token.is_trivia(),
+        }
+    }
+}
+
+/// A node in a `SyntaxTree`, containing other nodes and tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    /// Type of the node.
+    kind: NodeKind,
+    /// Child elements, in source order.
+    children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    /// Create a new `SyntaxNode`.
+    #[inline(always)]
+    fn new(kind: NodeKind, children: Vec<SyntaxElement>) -> Self {
+        Self { kind, children }
+    }
+    /// Get the type of this node.
+    #[inline(always)]
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+    /// Get the child elements of this node, in source order.
+    #[inline(always)]
+    pub fn children(&self) -> &[SyntaxElement] {
+        &self.children
+    }
+    /// Get the location of this node in the source, from its first to its last character.
+    ///
+    /// Returns `Span::NONE` for an empty script.
+    pub fn span(&self) -> Span {
+        match (self.children.first(), self.children.last()) {
+            (Some(first), Some(last)) => Span::new(first.span().start(), last.span().end()),
+            _ => Span::NONE,
+        }
+    }
+    /// Get the byte offset of this node in the source.
+    #[inline(always)]
+    pub fn offset(&self) -> usize {
+        self.children.first().map_or(0, SyntaxElement::offset)
+    }
+    /// Get the text of this node in the source.
+    pub fn text(&self) -> String {
+        self.tokens().map(SyntaxToken::text).collect()
+    }
+    /// Iterate through all the tokens (including trivia) within this node, in source order.
+    pub fn tokens(&self) -> impl Iterator<Item = &SyntaxToken> {
+        let mut tokens = Vec::new();
+        collect_tokens(&self.children, &mut tokens);
+        tokens.into_iter()
+    }
+    /// Iterate through this node and all the nodes within it, in source order
+    /// (i.e. each node before its children).
+    pub fn descendants(&self) -> impl Iterator<Item = &SyntaxNode> {
+        let mut nodes = Vec::new();
+        collect_nodes(self, &mut nodes);
+        nodes.into_iter()
+    }
+    /// Get the first token within this node that is not trivia.
+    pub fn first_token(&self) -> Option<&SyntaxToken> {
+        self.tokens().find(|token| !token.is_trivia())
+    }
+}
+
+/// Collect all the tokens within a list of elements, in source order.
+fn collect_tokens<'a>(elements: &'a [SyntaxElement], tokens: &mut Vec<&'a SyntaxToken>) {
+    elements.iter().for_each(|element| match element {
+        SyntaxElement::Node(node) => collect_tokens(&node.children, tokens),
+        SyntaxElement::Token(token) => tokens.push(token),
+    });
+}
+
+/// Collect a node and all the nodes within it, in source order.
+fn collect_nodes<'a>(node: &'a SyntaxNode, nodes: &mut Vec<&'a SyntaxNode>) {
+    nodes.push(node);
+    node.children
+        .iter()
+        .filter_map(SyntaxElement::as_node)
+        .for_each(|node| collect_nodes(node, nodes));
+}
+
+/// A lossless concrete syntax tree of a script.
+///
+/// Unlike an `AST`, a `SyntaxTree` keeps every token of the source, including all whitespace,
+/// comments and parentheses, each with its location.  The text of the tree is always identical to
+/// the source, so tools can make source-to-source edits (e.g. renaming a function) by replacing the
+/// text of individual tokens.
+///
+/// The nodes of the tree only group tokens into statements and brackets, not expressions.
+/// Instead, the source is parsed once into an `AST` alongside the tree, and each identifier token
+/// carries its `IdentRole` from the parser (e.g. whether it is a variable or a function name).
+/// Use `to_ast` to get the `AST`, whose positions can be mapped back to tokens via `token_at`.
+#[derive(Debug, Clone)]
+pub struct SyntaxTree {
+    /// Root node, of type `NodeKind::Script`.
+    root: SyntaxNode,
+    /// `AST` of all the statements and functions parsed successfully.
+    ast: AST,
+    /// Syntax errors found.
+    errors: Vec<ParseError>,
+}
+
+impl SyntaxTree {
+    /// Get the root node, of type `NodeKind::Script`.
+    #[inline(always)]
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+    /// Get the text of the tree, which is identical to the source.
+    #[inline(always)]
+    pub fn text(&self) -> String {
+        self.root.text()
+    }
+    /// Iterate through all the tokens (including trivia), in source order.
+    #[inline(always)]
+    pub fn tokens(&self) -> impl Iterator<Item = &SyntaxToken> {
+        self.root.tokens()
+    }
+    /// Get all the syntax errors found, in order of position.
+    #[inline(always)]
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+    /// Find the token at a particular position.
+    pub fn token_at(&self, pos: Position) -> Option<&SyntaxToken> {
+        let line = pos.line()?;
+        let col = pos.position().unwrap_or(0);
+
+        self.tokens().find(|token| {
+            let (start, end) = (token.span.start(), token.span.end());
+            let start = (start.line().unwrap_or(0), start.position().unwrap_or(0));
+            let end = (end.line().unwrap_or(0), end.position().unwrap_or(0));
+            start <= (line, col) && (line, col) <= end
+        })
+    }
+    /// Get the `AST` of the tree, or the first syntax error found.
+    ///
+    /// Positions in the `AST` point to the tokens in the tree.
+    #[inline]
+    pub fn to_ast(&self) -> Result<AST, ParseError> {
+        match self.errors.first() {
+            Some(err) => Err(err.clone()),
+            None => Ok(self.ast.clone()),
+        }
+    }
+}
+
+/// Get the type of a token.
+fn token_kind(token: &Token) -> TokenKind {
+    match token {
+        Token::IntegerConstant(_) => TokenKind::Integer,
+        #[cfg(not(feature = "no_float"))]
+        Token::FloatConstant(_) => TokenKind::Float,
+        Token::StringConstant(_) => TokenKind::String,
+        Token::CharConstant(_) => TokenKind::Char,
+        Token::Identifier(_) => TokenKind::Identifier,
+        Token::Custom(_) => TokenKind::Keyword,
+        Token::Comment(_) => TokenKind::Comment,
+        Token::Reserved(_) | Token::LexError(_) => TokenKind::Error,
+        token if token.is_keyword() => TokenKind::Keyword,
+        _ => TokenKind::Symbol,
+    }
+}
+
+/// Location of the next character while splitting a script into tokens.
+struct Cursor {
+    /// Byte offset.
+    offset: usize,
+    /// Line number.
+    line: u32,
+    /// Character position.
+    col: u32,
+}
+
+impl Cursor {
+    /// Move past a piece of text, returning it as a token.
+    fn token(&mut self, kind: TokenKind, text: &str, token: Option<Token>) -> SyntaxToken {
+        let offset = self.offset;
+        // Text beyond the maximum line number or character position, which the lexer reports as
+        // an error, has no position
+        let start = Position::try_new(self.line, self.col).unwrap_or(Position::NONE);
+        let mut end = start;
+
+        for ch in text.chars() {
+            end = Position::try_new(self.line, self.col).unwrap_or(Position::NONE);
+
+            if ch == '\n' {
+                self.line += 1;
+                self.col = 1;
+            } else {
+                self.col += 1;
+            }
+        }
+
+        self.offset += text.len();
+
+        SyntaxToken {
+            kind,
+            text: text.into(),
+            span: Span::new(start, end),
+            offset,
+            token,
+            role: None,
+        }
+    }
+    /// Find the byte offset of a position at or after this cursor.
+    fn find(&self, script: &str, pos: Position) -> usize {
+        let target = (
+            pos.line().unwrap_or(0) as u32,
+            pos.position().unwrap_or(0) as u32,
+        );
+        let (mut line, mut col) = (self.line, self.col);
+        let mut offset = self.offset;
+
+        for ch in script[self.offset..].chars() {
+            if (line, col) >= target {
+                break;
+            }
+            if ch == '\n' {
+                line += 1;
+                col = 1;
+            } else {
+                col += 1;
+            }
+            offset += ch.len_utf8();
+        }
+
+        offset
+    }
+}
+
+/// Split a script into tokens and trivia, keeping all text.
+fn tokenize(engine: &Engine, script: &str) -> Vec<SyntaxToken> {
+    let mut tokens = Vec::new();
+    let mut cursor = Cursor {
+        offset: 0,
+        line: 1,
+        col: 1,
+    };
+
+    let scripts = [script];
+    let mut stream = engine.lex_with_comments(&scripts).peekable();
+
+    while let Some((token, _)) = stream.next() {
+        if token == Token::EOF {
+            break;
+        }
+
+        // The text of the token runs up to the next token
+        let end = match stream.peek() {
+            Some(&(Token::EOF, _)) | None => script.len(),
+            Some(&(_, pos)) => cursor.find(script, pos),
+        };
+        let rest = &script[cursor.offset..end];
+
+        // Leading whitespace
+        let text = rest.trim_start();
+        let leading = &rest[..rest.len() - text.len()];
+
+        if !leading.is_empty() {
+            tokens.push(cursor.token(TokenKind::Whitespace, leading, None));
+        }
+
+        // Token text, with all trailing whitespace going to trivia
+        let len = match token {
+            Token::Comment(ref comment) if text.starts_with(comment.as_str()) => comment.len(),
+            _ => text.trim_end().len(),
+        };
+        let (text, trailing) = text.split_at(len);
+
+        if !text.is_empty() {
+            tokens.push(cursor.token(token_kind(&token), text, Some(token)));
+        }
+        if !trailing.is_empty() {
+            tokens.push(cursor.token(TokenKind::Whitespace, trailing, None));
+        }
+    }
+
+    // Anything left over is trivia
+    if cursor.offset < script.len() {
+        let rest = &script[cursor.offset..];
+        tokens.push(cursor.token(TokenKind::Whitespace, rest, None));
+    }
+
+    tokens
+}
+
+/// Builder of a `SyntaxTree` from a list of tokens.
+struct Builder {
+    /// Tokens (including trivia) in reverse order.
+    tokens: Vec<SyntaxToken>,
+}
+
+impl Builder {
+    /// Peek at the next token.
+    fn peek(&self) -> Option<&SyntaxToken> {
+        self.tokens.last()
+    }
+    /// Peek at the next token that is not trivia.
+    fn peek_significant(&self) -> Option<&Token> {
+        self.tokens
+            .iter()
+            .rev()
+            .find(|token| !token.is_trivia())
+            .and_then(SyntaxToken::token)
+    }
+    /// Take the next token.
+    fn next(&mut self) -> SyntaxElement {
+        SyntaxElement::Token(self.tokens.pop().unwrap())
+    }
+    /// Build a list of statements, up to a closing `}` if `in_block` is `true`,
+    /// or the end of the script otherwise.
+    fn statements(&mut self, in_block: bool) -> Vec<SyntaxElement> {
+        let mut elements = Vec::new();
+
+        while let Some(token) = self.peek() {
+            match token.token() {
+                None | Some(Token::Comment(_)) => elements.push(self.next()),
+                Some(Token::RightBrace) if in_block => break,
+                Some(Token::RightBrace) => elements.push(self.next()),
+                Some(_) => elements.push(SyntaxElement::Node(self.statement(in_block))),
+            }
+        }
+
+        elements
+    }
+    /// Build a statement.
+    fn statement(&mut self, in_block: bool) -> SyntaxNode {
+        let mut significant = self.tokens.iter().rev().filter(|t| !t.is_trivia());
+
+        let (kind, ends_with_block) = match significant.next().and_then(SyntaxToken::token) {
+            #[cfg(not(feature = "no_function"))]
+            Some(Token::Fn) => (NodeKind::Function, true),
+            #[cfg(not(feature = "no_function"))]
+            Some(Token::Private) => match significant.next().and_then(SyntaxToken::token) {
+                Some(Token::Fn) => (NodeKind::Function, true),
+                _ => (NodeKind::Statement, false),
+            },
+            Some(Token::If)
+            | Some(Token::While)
+            | Some(Token::Loop)
+            | Some(Token::For)
+            | Some(Token::Try)
+            | Some(Token::LeftBrace) => (NodeKind::Statement, true),
+            _ => (NodeKind::Statement, false),
+        };
+
+        let mut children = Vec::new();
+
+        while let Some(token) = self.peek() {
+            match token.token() {
+                Some(Token::SemiColon) => {
+                    children.push(self.next());
+                    break;
+                }
+                Some(Token::RightBrace) if in_block => break,
+                Some(Token::LeftBrace) => {
+                    children.push(SyntaxElement::Node(self.block()));
+
+                    // Statements such as `if` end with a block, unless continued
+                    match self.peek_significant() {
+                        Some(Token::Else)
+                        | Some(Token::Catch)
+                        | Some(Token::Finally)
+                        | Some(Token::SemiColon) => (),
+                        _ if ends_with_block => break,
+                        _ => (),
+                    }
+                }
+                Some(_) => children.push(self.element()),
+                None => children.push(self.next()),
+            }
+        }
+
+        // Trailing trivia belongs to the enclosing node
+        while matches!(children.last(), Some(element) if element.is_trivia()) {
+            if let Some(SyntaxElement::Token(token)) = children.pop() {
+                self.tokens.push(token);
+            }
+        }
+
+        SyntaxNode::new(kind, children)
+    }
+    /// Build a statement block.
+    fn block(&mut self) -> SyntaxNode {
+        let mut children = vec![self.next()];
+        children.extend(self.statements(true));
+
+        if let Some(Token::RightBrace) = self.peek().and_then(SyntaxToken::token) {
+            children.push(self.next());
+        }
+
+        SyntaxNode::new(NodeKind::Block, children)
+    }
+    /// Build a group of elements within a pair of brackets.
+    fn group(&mut self, kind: NodeKind, close: Token) -> SyntaxNode {
+        let mut children = vec![self.next()];
+
+        while let Some(token) = self.peek() {
+            match token.token() {
+                Some(token) if *token == close => {
+                    children.push(self.next());
+                    break;
+                }
+                // Mismatched closing bracket
+                Some(Token::RightBrace) | Some(Token::RightParen) | Some(Token::RightBracket) => {
+                    break
+                }
+                _ => children.push(self.element()),
+            }
+        }
+
+        SyntaxNode::new(kind, children)
+    }
+    /// Build the next element within a statement.
+    fn element(&mut self) -> SyntaxElement {
+        match self.peek().and_then(SyntaxToken::token) {
+            Some(Token::LeftBrace) => SyntaxElement::Node(self.block()),
+            Some(Token::MapStart) => {
+                SyntaxElement::Node(self.group(NodeKind::Map, Token::RightBrace))
+            }
+            Some(Token::LeftParen) => {
+                SyntaxElement::Node(self.group(NodeKind::Parens, Token::RightParen))
+            }
+            Some(Token::LeftBracket) => {
+                SyntaxElement::Node(self.group(NodeKind::Brackets, Token::RightBracket))
+            }
+            _ => self.next(),
+        }
+    }
+}
+
+impl Engine {
+    /// Parse a script into a lossless `SyntaxTree`, keeping all tokens, whitespace and comments.
+    ///
+    /// Parsing into a `SyntaxTree` never fails - invalid input is kept as tokens of type
+    /// `TokenKind::Error`, and the syntax errors are available via `SyntaxTree::errors`.
+    /// Use `SyntaxTree::to_ast` to get the `AST`, which is parsed together with the tree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), rhai::ParseError> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, IdentRole};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let script = "fn foo(x) { x + 1 }   // add one\nlet foo = 41; foo(foo)";
+    ///
+    /// let tree = engine.parse_syntax_tree(script);
+    ///
+    /// // The tree keeps all text
+    /// assert_eq!(tree.text(), script);
+    ///
+    /// // Rename the function 'foo' to 'bar', but not the variable 'foo'
+    /// let code: String = tree
+    ///     .tokens()
+    ///     .map(|t| match (t.role(), t.text()) {
+    ///         (Some(IdentRole::Function), "foo") => "bar",
+    ///         (_, text) => text,
+    ///     })
+    ///     .collect();
+    ///
+    /// assert_eq!(code, "fn bar(x) { x + 1 }   // add one\nlet foo = 41; bar(foo)");
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_syntax_tree(&self, script: &str) -> SyntaxTree {
+        let scripts = [script];
+        let mut stream = TokenStream::new(self.lex(&scripts, None));
+        let mut idents = Vec::new();
+        let (ast, errors) = self.parse_with_errors(
+            &mut stream,
+            &Default::default(),
+            self.optimization_level,
+            Some(&mut idents),
+        );
+
+        // Later roles take precedence, e.g. a function name first parsed as a variable
+        let roles: HashMap<_, _> = idents.into_iter().collect();
+
+        let mut tokens = tokenize(self, script);
+        tokens
+            .iter_mut()
+            .filter(|token| token.kind == TokenKind::Identifier)
+            .for_each(|token| token.role = roles.get(&token.span.start()).copied());
+        tokens.reverse();
+
+        let mut builder = Builder { tokens };
+        let root = SyntaxNode::new(NodeKind::Script, builder.statements(false));
+
+        SyntaxTree { root, ast, errors }
+    }
+}
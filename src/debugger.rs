@@ -0,0 +1,483 @@
+//! Module defining the debugging interface.
+
+use crate::ast::ScriptFnDef;
+use crate::dynamic::Dynamic;
+use crate::engine::{Engine, EvalContext, Imports, State};
+use crate::fn_native::Locked;
+use crate::module::Module;
+use crate::result::{CallFrame, EvalAltResult};
+use crate::scope::Scope;
+use crate::token::{Position, NO_POS};
+use crate::utils::ImmutableString;
+
+use crate::stdlib::{boxed::Box, fmt, string::String, vec::Vec};
+
+/// A break-point for the debugger.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum BreakPoint {
+    /// Break at the statements starting on a particular line.
+    ///
+    /// If `source` is `None`, the break-point applies to scripts from any source.
+    AtLine {
+        /// Source of the script (e.g. the script file path), if any.
+        source: Option<String>,
+        /// Line number (starting from 1).
+        line: usize,
+    },
+    /// Break on entering a script-defined function with a particular name.
+    AtFunction {
+        /// Name of the function.
+        name: String,
+    },
+}
+
+impl fmt::Display for BreakPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AtLine { source: None, line } => write!(f, "line {}", line),
+            Self::AtLine {
+                source: Some(source),
+                line,
+            } => write!(f, "line {} in '{}'", line, source),
+            Self::AtFunction { name } => write!(f, "function {}", name),
+        }
+    }
+}
+
+/// A command returned by the debugger callback to continue evaluation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DebuggerCommand {
+    /// Continue evaluation until the next break-point.
+    Continue,
+    /// Break at the next statement, stepping into function calls.
+    StepInto,
+    /// Break at the next expression or statement, stepping into function calls.
+    StepExpression,
+    /// Break at the next statement, stepping over function calls.
+    StepOver,
+    /// Break at the next statement after returning from the current function.
+    StepOut,
+}
+
+/// The reason why the debugger callback is called.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DebuggerEvent {
+    /// Stepping has stopped at a statement, or at an expression via `StepExpression`.
+    Step,
+    /// A break-point is hit.  The index of the break-point is provided.
+    BreakPoint(usize),
+}
+
+/// Where evaluation stops next.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+enum StepMode {
+    /// Only stop at break-points.
+    Run,
+    /// Stop at the next statement.
+    Step,
+    /// Stop at the next expression or statement.
+    Expression,
+    /// Stop at the next statement at this call level or below.
+    Over(usize),
+    /// Stop at the next statement below this call level.
+    Out(usize),
+}
+
+/// State of the debugger during an evaluation run.
+///
+/// A new `Debugger` is created (via the initialization function passed to
+/// `Engine::register_debugger`) for each evaluation run.  Evaluation runs nested inside native
+/// functions (e.g. calling a closure passed to `map`) continue with the same `Debugger`.
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    /// Break-points.
+    break_points: Vec<BreakPoint>,
+    /// Where evaluation stops next.
+    mode: StepMode,
+    /// Stack of script-defined function calls, outer-most call first.
+    call_stack: Vec<CallFrame>,
+    /// Position of the current statement.
+    pos: Position,
+    /// Name of the source of the current statement, if any.
+    source: Option<ImmutableString>,
+    /// Call level of the native function call that the current evaluation run is nested in,
+    /// added to all call levels of the run.
+    level_base: usize,
+}
+
+/// Container for lending the `Debugger` to evaluation runs nested inside a native function call.
+///
+/// Holds `None` while the outer evaluation run is paused.
+pub(crate) type DebuggerCell = Locked<Option<Box<Debugger>>>;
+
+/// `Debugger` lent to evaluation runs nested inside a native function call.
+#[derive(Debug)]
+pub(crate) struct LentDebugger {
+    /// The lent `Debugger`.
+    cell: DebuggerCell,
+    /// Position of the current statement of the outer evaluation run.
+    pos: Position,
+    /// Name of the source of the current statement of the outer evaluation run, if any.
+    source: Option<ImmutableString>,
+    /// Call level of the outer evaluation run.
+    level: usize,
+}
+
+impl LentDebugger {
+    /// Get the container to pass to the native function call.
+    #[inline(always)]
+    pub(crate) fn cell(&self) -> &DebuggerCell {
+        &self.cell
+    }
+}
+
+/// Take the `Debugger`, if any, out of a `DebuggerCell`.
+#[inline(always)]
+fn take_debugger(cell: &DebuggerCell) -> Option<Box<Debugger>> {
+    #[cfg(not(feature = "sync"))]
+    return cell.borrow_mut().take();
+    #[cfg(feature = "sync")]
+    return cell.write().unwrap().take();
+}
+
+impl Debugger {
+    /// Create a new `Debugger` with a list of break-points.
+    ///
+    /// `command` specifies how evaluation starts: use `DebuggerCommand::StepInto` to stop at the
+    /// very first statement, or `DebuggerCommand::Continue` to stop at the first break-point.
+    #[inline(always)]
+    pub fn new(break_points: Vec<BreakPoint>, command: DebuggerCommand) -> Self {
+        let mut debugger = Self {
+            break_points,
+            mode: StepMode::Run,
+            call_stack: Default::default(),
+            pos: NO_POS,
+            source: None,
+            level_base: 0,
+        };
+        debugger.set_command(command, 0);
+        debugger
+    }
+    /// Get all the break-points.
+    #[inline(always)]
+    pub fn break_points(&self) -> &[BreakPoint] {
+        &self.break_points
+    }
+    /// Get a mutable reference to the list of break-points, for adding or removing break-points.
+    #[inline(always)]
+    pub fn break_points_mut(&mut self) -> &mut Vec<BreakPoint> {
+        &mut self.break_points
+    }
+    /// Get the name of the source (e.g. the script file path) of the current statement, if any.
+    #[inline(always)]
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_ref().map(|s| s.as_str())
+    }
+    /// Get the stack of script-defined function calls, inner-most call first.
+    ///
+    /// The position of each call is the position of the statement making the call.
+    #[inline(always)]
+    pub fn call_stack(&self) -> impl Iterator<Item = &CallFrame> {
+        self.call_stack.iter().rev()
+    }
+    /// Set where evaluation stops next, based on the command and the current call level.
+    fn set_command(&mut self, command: DebuggerCommand, level: usize) {
+        let level = self.level_base + level;
+
+        self.mode = match command {
+            DebuggerCommand::Continue => StepMode::Run,
+            DebuggerCommand::StepInto => StepMode::Step,
+            DebuggerCommand::StepExpression => StepMode::Expression,
+            DebuggerCommand::StepOver => StepMode::Over(level),
+            DebuggerCommand::StepOut => StepMode::Out(level),
+        };
+    }
+    /// Should evaluation stop at a statement?
+    ///
+    /// `source` is the name of the source of the statement, if any.
+    fn stop_at_statement(
+        &self,
+        source: Option<&str>,
+        pos: Position,
+        level: usize,
+    ) -> Option<DebuggerEvent> {
+        let level = self.level_base + level;
+
+        let step = match self.mode {
+            StepMode::Run => false,
+            StepMode::Step | StepMode::Expression => true,
+            StepMode::Over(n) => level <= n,
+            StepMode::Out(n) => level < n,
+        };
+
+        if step {
+            return Some(DebuggerEvent::Step);
+        }
+
+        // Line break-points do not stop again at statements further along the same line
+        // (which may be nested inside the first one), unless evaluation has looped back
+        let line = pos.line()?;
+
+        if self.pos.line() == Some(line)
+            && self.source() == source
+            && self.pos.position() < pos.position()
+        {
+            return None;
+        }
+
+        self.break_points
+            .iter()
+            .position(|bp| match bp {
+                BreakPoint::AtLine {
+                    source: Some(bp_source),
+                    line: bp_line,
+                } => *bp_line == line && Some(bp_source.as_str()) == source,
+                BreakPoint::AtLine {
+                    source: None,
+                    line: bp_line,
+                } => *bp_line == line,
+                BreakPoint::AtFunction { .. } => false,
+            })
+            .map(DebuggerEvent::BreakPoint)
+    }
+    /// Should evaluation stop at an expression?
+    ///
+    /// An expression starting at the current position (e.g. the expression making up the statement
+    /// evaluation has just stopped at) is skipped.
+    #[inline(always)]
+    fn stop_at_expression(&self, pos: Position) -> Option<DebuggerEvent> {
+        if self.mode == StepMode::Expression && pos != self.pos {
+            Some(DebuggerEvent::Step)
+        } else {
+            None
+        }
+    }
+}
+
+impl Engine {
+    /// Get the debugger state for the current evaluation run, creating it if necessary.
+    ///
+    /// Returns `None` while the debugger is paused.
+    fn get_debugger<'s>(&self, state: &'s mut State) -> Option<&'s mut Debugger> {
+        let (init, _) = self.debugger.as_ref()?;
+
+        if state.debugger_paused {
+            return None;
+        }
+
+        Some(state.debugger.get_or_insert_with(|| Box::new(init())))
+    }
+
+    /// Call the debugger callback, then act on the returned command.
+    fn call_debugger(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        event: DebuggerEvent,
+        pos: Position,
+        level: usize,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let (_, callback) = self.debugger.as_ref().unwrap();
+
+        // Take the debugger out of the state while the callback holds the evaluation context,
+        // pausing it for anything evaluated with that context
+        let mut debugger = state.debugger.take().unwrap();
+        state.debugger_paused = true;
+
+        let mut context = EvalContext {
+            engine: self,
+            scope,
+            mods,
+            state,
+            lib,
+            this_ptr,
+            level,
+        };
+
+        let result = callback(&mut context, &mut debugger, event, pos);
+
+        if let Ok(command) = result {
+            debugger.set_command(command, level);
+        }
+
+        state.debugger = Some(debugger);
+        state.debugger_paused = false;
+
+        result.map(|_| ()).map_err(|err| err.fill_position(pos))
+    }
+
+    /// Run the debugger, if any, before evaluating a statement.
+    #[inline]
+    pub(crate) fn debug_statement(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        pos: Position,
+        level: usize,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let source = state.source.clone();
+
+        let debugger = match self.get_debugger(state) {
+            Some(debugger) => debugger,
+            None => return Ok(()),
+        };
+
+        let event = debugger.stop_at_statement(source.as_ref().map(|s| s.as_str()), pos, level);
+        debugger.pos = pos;
+        debugger.source = source;
+
+        match event {
+            Some(event) => self.call_debugger(scope, mods, state, lib, this_ptr, event, pos, level),
+            None => Ok(()),
+        }
+    }
+
+    /// Run the debugger, if any, before evaluating an expression.
+    #[inline]
+    pub(crate) fn debug_expression(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        pos: Position,
+        level: usize,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let debugger = match self.get_debugger(state) {
+            Some(debugger) => debugger,
+            None => return Ok(()),
+        };
+
+        match debugger.stop_at_expression(pos) {
+            Some(event) => {
+                debugger.pos = pos;
+                self.call_debugger(scope, mods, state, lib, this_ptr, event, pos, level)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Run the debugger, if any, on entering a script-defined function.
+    ///
+    /// The arguments are already in the scope.
+    #[inline]
+    pub(crate) fn debug_enter_function(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        fn_def: &ScriptFnDef,
+        num_args: usize,
+        level: usize,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let debugger = match self.get_debugger(state) {
+            Some(debugger) => debugger,
+            None => return Ok(()),
+        };
+
+        let pos = fn_def.span.start();
+
+        debugger.call_stack.push(CallFrame {
+            fn_name: fn_def.name.to_string(),
+            num_args,
+            pos: debugger.pos,
+            source: fn_def.source.clone(),
+        });
+
+        let index = debugger.break_points.iter().position(|bp| match bp {
+            BreakPoint::AtFunction { name } => *name == fn_def.name,
+            BreakPoint::AtLine { .. } => false,
+        });
+
+        match index {
+            Some(index) => {
+                let event = DebuggerEvent::BreakPoint(index);
+                self.call_debugger(scope, mods, state, lib, this_ptr, event, pos, level)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Update the debugger, if any, on leaving a script-defined function.
+    ///
+    /// The source of the caller is already restored.
+    #[inline]
+    pub(crate) fn debug_leave_function(&self, state: &mut State) {
+        let source = state.source.clone();
+
+        if let Some(debugger) = self.get_debugger(state) {
+            // The caller continues at the statement making the call
+            if let Some(frame) = debugger.call_stack.pop() {
+                debugger.pos = frame.pos;
+                debugger.source = source;
+            }
+        }
+    }
+
+    /// Lend the debugger, if any, to evaluation runs nested inside a native function call
+    /// made at a particular call level.
+    ///
+    /// Nothing is lent if the debugger has not yet been created for the current evaluation run.
+    pub(crate) fn lend_debugger(&self, state: &mut State, level: usize) -> Option<LentDebugger> {
+        if state.debugger.is_none() && !state.debugger_paused {
+            return None;
+        }
+
+        let (pos, source) = state.debugger.as_ref().map_or((NO_POS, None), |debugger| {
+            (debugger.pos, debugger.source.clone())
+        });
+
+        let debugger = state.debugger.take().map(|mut debugger| {
+            debugger.level_base += level;
+            debugger
+        });
+
+        Some(LentDebugger {
+            cell: Locked::new(debugger),
+            pos,
+            source,
+            level,
+        })
+    }
+
+    /// Get back the debugger lent via `lend_debugger` after the native function call returns.
+    pub(crate) fn return_debugger(&self, state: &mut State, lent: Option<LentDebugger>) {
+        if let Some(lent) = lent {
+            if let Some(mut debugger) = take_debugger(&lent.cell) {
+                debugger.level_base -= lent.level;
+                debugger.pos = lent.pos;
+                debugger.source = lent.source;
+                state.debugger = Some(debugger);
+            }
+        }
+    }
+
+    /// Start an evaluation run nested inside a native function call with the lent debugger, if any.
+    pub(crate) fn borrow_debugger(&self, state: &mut State, cell: Option<&DebuggerCell>) {
+        if let Some(cell) = cell {
+            state.debugger = take_debugger(cell);
+            state.debugger_paused = state.debugger.is_none();
+        }
+    }
+
+    /// Give back the debugger borrowed via `borrow_debugger` at the end of a nested evaluation run.
+    pub(crate) fn give_back_debugger(&self, state: &mut State, cell: Option<&DebuggerCell>) {
+        if let Some(cell) = cell {
+            if let Some(debugger) = state.debugger.take() {
+                #[cfg(not(feature = "sync"))]
+                cell.borrow_mut().replace(debugger);
+                #[cfg(feature = "sync")]
+                cell.write().unwrap().replace(debugger);
+            }
+        }
+    }
+}
@@ -0,0 +1,546 @@
+//! Module implementing the generator of reference documentation for the API of an `Engine`.
+
+use crate::engine::Engine;
+use crate::metadata::{FnKind, FnMetadata, FnParamMetadata, ModuleMetadata, TypeMetadata};
+use crate::module::Module;
+
+use crate::stdlib::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Output format of documentation generated by `Engine::gen_docs`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DocsFormat {
+    /// Markdown pages, with file names ending in `.md`.
+    Markdown,
+    /// HTML pages, with file names ending in `.html`.
+    Html,
+}
+
+/// Options controlling documentation generated by `Engine::gen_docs`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DocsOptions {
+    /// Output format.  Default is `DocsFormat::Markdown`.
+    pub format: DocsFormat,
+    /// Title of the page documenting the global namespace.  Default is `API Reference`.
+    pub title: String,
+    /// Include functions and type iterators in loaded packages?  Default is `true`.
+    pub include_packages: bool,
+}
+
+impl Default for DocsOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            format: DocsFormat::Markdown,
+            title: "API Reference".into(),
+            include_packages: true,
+        }
+    }
+}
+
+/// A page of documentation generated by `Engine::gen_docs`, documenting one namespace.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DocsPage {
+    /// Path of the namespace (e.g. `foo::bar`), empty for the global namespace.
+    pub namespace: String,
+    /// File name of the page (e.g. `foo-bar.md`), which other pages link to.
+    /// The page documenting the global namespace is named `index`.
+    pub file_name: String,
+    /// Content of the page.
+    pub content: String,
+}
+
+/// Get the file name of the page documenting a namespace.
+fn page_file_name(namespace: &str, format: DocsFormat) -> String {
+    let name = if namespace.is_empty() {
+        "index".to_string()
+    } else {
+        namespace
+            .replace("::", "-")
+            .chars()
+            .map(|ch| match ch {
+                ch if ch.is_alphanumeric() || ch == '_' || ch == '-' => ch,
+                _ => '-',
+            })
+            .collect()
+    };
+
+    match format {
+        DocsFormat::Markdown => format!("{}.md", name),
+        DocsFormat::Html => format!("{}.html", name),
+    }
+}
+
+/// Get the text of doc comments, with the `///` or `/** */` markers removed.
+fn doc_text(comments: &[String]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for comment in comments {
+        if let Some(line) = comment.strip_prefix("///") {
+            lines.push(line.strip_prefix(' ').unwrap_or(line).to_string());
+        } else if let Some(block) = comment.strip_prefix("/**") {
+            let block = block.strip_suffix("*/").unwrap_or(block);
+
+            block.trim().lines().for_each(|line| {
+                let line = line.trim();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                lines.push(line.strip_prefix(' ').unwrap_or(line).to_string());
+            });
+        }
+    }
+
+    lines
+}
+
+/// Escape text for HTML.
+fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    text.chars().for_each(|ch| match ch {
+        '&' => result.push_str("&amp;"),
+        '<' => result.push_str("&lt;"),
+        '>' => result.push_str("&gt;"),
+        '"' => result.push_str("&quot;"),
+        ch => result.push(ch),
+    });
+
+    result
+}
+
+/// Get the type of a parameter, without any `&mut`.
+fn param_type(param: Option<&FnParamMetadata>) -> &str {
+    match param.and_then(|p| p.typ.as_deref()) {
+        Some(typ) => typ.strip_prefix("&mut ").unwrap_or(typ),
+        None => "_",
+    }
+}
+
+/// Get the return type of a function, if it returns any value.
+fn return_type(f: &FnMetadata) -> Option<&str> {
+    match f.return_type.as_deref() {
+        Some("()") => None,
+        typ => typ,
+    }
+}
+
+/// Format the usage of an operator, e.g. `i64 + i64 -> i64`.
+fn operator_usage(f: &FnMetadata) -> String {
+    let usage = match f.num_params {
+        1 => format!("{}{}", f.name, param_type(f.params.first())),
+        2 => format!(
+            "{} {} {}",
+            param_type(f.params.first()),
+            f.name,
+            param_type(f.params.get(1))
+        ),
+        _ => return format!("fn {}", f.signature),
+    };
+
+    match return_type(f) {
+        Some(typ) => format!("{} -> {}", usage, typ),
+        None => usage,
+    }
+}
+
+/// Format the usage of a property getter or setter, e.g. `TestStruct.x -> i64`.
+fn property_usage(f: &FnMetadata, property: &str) -> String {
+    let object = param_type(f.params.first());
+
+    match f.kind {
+        FnKind::Setter => format!("{}.{} = {}", object, property, param_type(f.params.get(1))),
+        _ => format!(
+            "{}.{} -> {}",
+            object,
+            property,
+            return_type(f).unwrap_or("_")
+        ),
+    }
+}
+
+/// Format the usage of an indexer getter or setter, e.g. `TestStruct[i64] -> i64`.
+fn indexer_usage(f: &FnMetadata) -> String {
+    let object = param_type(f.params.first());
+    let index = param_type(f.params.get(1));
+
+    match f.kind {
+        FnKind::IndexSetter => format!("{}[{}] = {}", object, index, param_type(f.params.get(2))),
+        _ => format!("{}[{}] -> {}", object, index, return_type(f).unwrap_or("_")),
+    }
+}
+
+/// Writer of the content of a page.
+struct PageWriter {
+    /// Output format.
+    format: DocsFormat,
+    /// Content written so far.
+    content: String,
+}
+
+impl PageWriter {
+    /// Write a heading, optionally as code.
+    fn heading(&mut self, level: usize, text: &str, code: bool) {
+        match self.format {
+            DocsFormat::Markdown if code => {
+                self.content += &format!("{} `{}`\n\n", "#".repeat(level), text)
+            }
+            DocsFormat::Markdown => self.content += &format!("{} {}\n\n", "#".repeat(level), text),
+            DocsFormat::Html if code => {
+                self.content +=
+                    &format!("<h{0}><code>{1}</code></h{0}>\n", level, escape_html(text))
+            }
+            DocsFormat::Html => {
+                self.content += &format!("<h{0}>{1}</h{0}>\n", level, escape_html(text))
+            }
+        }
+    }
+    /// Write a block of code.
+    fn code(&mut self, code: &str) {
+        match self.format {
+            DocsFormat::Markdown => self.content += &format!("```rust\n{}\n```\n\n", code),
+            DocsFormat::Html => {
+                self.content += &format!("<pre><code>{}</code></pre>\n", escape_html(code))
+            }
+        }
+    }
+    /// Write the text of doc comments.  In HTML, blank lines separate paragraphs.
+    fn text(&mut self, lines: &[String]) {
+        if lines.is_empty() {
+            return;
+        }
+
+        match self.format {
+            DocsFormat::Markdown => {
+                lines.iter().for_each(|line| {
+                    self.content += line;
+                    self.content.push('\n');
+                });
+                self.content.push('\n');
+            }
+            DocsFormat::Html => {
+                lines
+                    .split(|line| line.trim().is_empty())
+                    .filter(|paragraph| !paragraph.is_empty())
+                    .for_each(|paragraph| {
+                        let text: Vec<_> = paragraph.iter().map(|line| escape_html(line)).collect();
+                        self.content += &format!("<p>{}</p>\n", text.join("\n"));
+                    });
+            }
+        }
+    }
+    /// Write a list of items, each optionally linking to a page.
+    fn list(&mut self, items: &[(String, Option<String>)]) {
+        match self.format {
+            DocsFormat::Markdown => {
+                items.iter().for_each(|(text, link)| match link {
+                    Some(link) => self.content += &format!("* [`{}`]({})\n", text, link),
+                    None => self.content += &format!("* `{}`\n", text),
+                });
+                self.content.push('\n');
+            }
+            DocsFormat::Html => {
+                self.content += "<ul>\n";
+                items.iter().for_each(|(text, link)| match link {
+                    Some(link) => {
+                        self.content += &format!(
+                            "<li><a href=\"{}\"><code>{}</code></a></li>\n",
+                            escape_html(link),
+                            escape_html(text)
+                        )
+                    }
+                    None => {
+                        self.content += &format!("<li><code>{}</code></li>\n", escape_html(text))
+                    }
+                });
+                self.content += "</ul>\n";
+            }
+        }
+    }
+    /// Write a link to another page.
+    fn link(&mut self, text: &str, link: &str) {
+        match self.format {
+            DocsFormat::Markdown => self.content += &format!("[{}]({})\n\n", text, link),
+            DocsFormat::Html => {
+                self.content += &format!(
+                    "<p><a href=\"{}\">{}</a></p>\n",
+                    escape_html(link),
+                    escape_html(text)
+                )
+            }
+        }
+    }
+    /// Write the documentation of overloaded functions, as their usages followed by their doc
+    /// comments.  Consecutive usages without doc comments are kept in the same block of code.
+    fn functions<'a>(&mut self, functions: impl Iterator<Item = (String, &'a FnMetadata)>) {
+        let mut usages = Vec::new();
+
+        for (usage, f) in functions {
+            let text = doc_text(&f.doc_comments);
+
+            if text.is_empty() {
+                usages.push(usage);
+                continue;
+            }
+
+            if !usages.is_empty() {
+                self.code(&usages.join("\n"));
+                usages.clear();
+            }
+
+            self.code(&usage);
+            self.text(&text);
+        }
+
+        if !usages.is_empty() {
+            self.code(&usages.join("\n"));
+        }
+    }
+    /// Finish the page, returning its content.
+    fn finish(self, title: &str) -> String {
+        match self.format {
+            DocsFormat::Markdown => self.content,
+            DocsFormat::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+                escape_html(title),
+                self.content
+            ),
+        }
+    }
+}
+
+/// Group functions with the same key, keeping the order of their first appearances.
+fn group_by<'a>(
+    functions: impl Iterator<Item = &'a FnMetadata>,
+    key: impl Fn(&'a FnMetadata) -> &'a str,
+) -> Vec<(&'a str, Vec<&'a FnMetadata>)> {
+    let mut groups: Vec<(&str, Vec<_>)> = Vec::new();
+
+    for f in functions {
+        let key = key(f);
+
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, group)) => group.push(f),
+            None => groups.push((key, vec![f])),
+        }
+    }
+
+    groups
+}
+
+/// Generate the page documenting a namespace, then the pages documenting its sub-modules.
+fn gen_pages(
+    namespace: &str,
+    title: &str,
+    metadata: &ModuleMetadata,
+    types: &[TypeMetadata],
+    options: &DocsOptions,
+    pages: &mut Vec<DocsPage>,
+) {
+    let format = options.format;
+    let mut page = PageWriter {
+        format,
+        content: String::new(),
+    };
+
+    if namespace.is_empty() {
+        page.heading(1, title, false);
+    } else {
+        page.heading(1, namespace, true);
+    }
+
+    if !namespace.is_empty() {
+        page.link(&options.title, &page_file_name("", format));
+    }
+
+    if !types.is_empty() {
+        page.heading(2, "Types", false);
+        let items: Vec<_> = types
+            .iter()
+            .map(|t| (format!("{} ({})", t.name, t.rust_type), None))
+            .collect();
+        page.list(&items);
+    }
+
+    if !metadata.modules.is_empty() {
+        page.heading(2, "Modules", false);
+        let items: Vec<_> = metadata
+            .modules
+            .keys()
+            .map(|name| {
+                let path = if namespace.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}::{}", namespace, name)
+                };
+                let link = page_file_name(&path, format);
+                (path, Some(link))
+            })
+            .collect();
+        page.list(&items);
+    }
+
+    let functions = group_by(
+        metadata
+            .functions
+            .iter()
+            .filter(|f| f.kind == FnKind::Function),
+        |f| &f.name,
+    );
+
+    if !functions.is_empty() {
+        page.heading(2, "Functions", false);
+
+        for (name, overloads) in functions {
+            page.heading(3, name, true);
+            page.functions(
+                overloads
+                    .into_iter()
+                    .map(|f| (format!("fn {}", f.signature), f)),
+            );
+        }
+    }
+
+    let operators = group_by(
+        metadata
+            .functions
+            .iter()
+            .filter(|f| f.kind == FnKind::Operator),
+        |f| &f.name,
+    );
+
+    if !operators.is_empty() {
+        page.heading(2, "Operators", false);
+
+        for (name, overloads) in operators {
+            page.heading(3, name, true);
+            page.functions(overloads.into_iter().map(|f| (operator_usage(f), f)));
+        }
+    }
+
+    let properties = group_by(
+        metadata
+            .functions
+            .iter()
+            .filter(|f| f.kind == FnKind::Getter || f.kind == FnKind::Setter),
+        |f| f.name.find('$').map_or(&f.name[..], |i| &f.name[i + 1..]),
+    );
+
+    if !properties.is_empty() {
+        page.heading(2, "Properties", false);
+
+        for (name, accessors) in properties {
+            page.heading(3, name, true);
+            page.functions(accessors.into_iter().map(|f| (property_usage(f, name), f)));
+        }
+    }
+
+    let indexers: Vec<_> = metadata
+        .functions
+        .iter()
+        .filter(|f| f.kind == FnKind::IndexGetter || f.kind == FnKind::IndexSetter)
+        .collect();
+
+    if !indexers.is_empty() {
+        page.heading(2, "Indexers", false);
+        page.functions(indexers.into_iter().map(|f| (indexer_usage(f), f)));
+    }
+
+    if !metadata.iterators.is_empty() {
+        page.heading(2, "Iterable Types", false);
+        let items: Vec<_> = metadata
+            .iterators
+            .iter()
+            .map(|typ| (typ.clone(), None))
+            .collect();
+        page.list(&items);
+    }
+
+    pages.push(DocsPage {
+        namespace: namespace.into(),
+        file_name: page_file_name(namespace, format),
+        content: page.finish(title),
+    });
+
+    metadata.modules.iter().for_each(|(name, m)| {
+        let path = if namespace.is_empty() {
+            name.clone()
+        } else {
+            format!("{}::{}", namespace, name)
+        };
+        let title = format!("Module {}", path);
+
+        gen_pages(&path, &title, m, &[], options, pages);
+    });
+}
+
+impl Engine {
+    /// Generate reference documentation for all the functions, operators, properties, indexers,
+    /// type iterators and custom types registered in the `Engine` (optionally including those in
+    /// loaded packages), plus a list of modules (e.g. those in a `StaticModuleResolver`), each with
+    /// the path under which it is imported.
+    ///
+    /// One page is generated for the global namespace (named `index`), and one for each module
+    /// and sub-module.  Doc comments of functions are included.
+    ///
+    /// Available under the `metadata` feature only.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{DocsOptions, Engine, Module, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("add", |x: i64, y: i64| x + y);
+    ///
+    /// let mut module = Module::new();
+    /// module.set_fn_0("answer", || Ok(42_i64));
+    ///
+    /// let options = DocsOptions {
+    ///     include_packages: false,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let pages = engine.gen_docs(vec![("hello", &module)], &options);
+    ///
+    /// assert_eq!(pages.len(), 2);
+    /// assert_eq!(pages[0].file_name, "index.md");
+    /// assert!(pages[0].content.contains("fn add(_: i64, _: i64) -> i64"));
+    /// assert_eq!(pages[1].namespace, "hello");
+    /// assert!(pages[1].content.contains("fn answer() -> i64"));
+    /// ```
+    pub fn gen_docs<'a>(
+        &self,
+        modules: impl IntoIterator<Item = (&'a str, &'a Module)>,
+        options: &DocsOptions,
+    ) -> Vec<DocsPage> {
+        let metadata = self.gen_metadata(options.include_packages);
+
+        let mut root = ModuleMetadata {
+            functions: metadata.functions,
+            iterators: metadata.iterators,
+            modules: metadata.modules,
+        };
+
+        let static_modules: BTreeMap<_, _> = modules
+            .into_iter()
+            .map(|(path, module)| (path.to_string(), self.gen_module_metadata(path, module)))
+            .collect();
+
+        root.modules.extend(static_modules);
+
+        let mut pages = Vec::new();
+        gen_pages(
+            "",
+            &options.title,
+            &root,
+            &metadata.types,
+            options,
+            &mut pages,
+        );
+        pages
+    }
+}
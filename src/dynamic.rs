@@ -1,12 +1,19 @@
 //! Helper module which defines the `Any` trait to to allow dynamic value handling.
 
 use crate::fn_native::{FnPtr, SendSync};
+
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+use crate::fn_native::{shared_make_mut, shared_take_or_clone, Shared};
 use crate::r#unsafe::{unsafe_cast_box, unsafe_try_cast};
 use crate::utils::ImmutableString;
 use crate::INT;
 
 #[cfg(not(feature = "no_closure"))]
-use crate::fn_native::{shared_try_take, Locked, Shared};
+use crate::fn_native::{shared_try_take, Locked};
+
+#[cfg(not(feature = "no_closure"))]
+#[cfg(all(feature = "no_index", feature = "no_object"))]
+use crate::fn_native::Shared;
 
 #[cfg(not(feature = "no_float"))]
 use crate::FLOAT;
@@ -20,7 +27,7 @@ use crate::engine::Map;
 use crate::stdlib::{
     any::{type_name, Any, TypeId},
     boxed::Box,
-    fmt,
+    fmt, mem,
     ops::{Deref, DerefMut},
     string::{String, ToString},
 };
@@ -144,6 +151,13 @@ pub struct Dynamic(pub(crate) Union);
 /// Internal `Dynamic` representation.
 ///
 /// Most variants are boxed to reduce the size.
+/// Every variant holds at most a single word (a pointer or a 64-bit primitive) so that,
+/// together with the tag, a `Dynamic` is no larger than 16 bytes.
+/// Rarer and larger types must be boxed to keep it that way.
+///
+/// Arrays and object maps are held in reference-counted storage so that cloning them is cheap.
+/// They are copied on write, only when mutated while other references are still outstanding,
+/// so the value semantics seen by scripts are unchanged.
 pub enum Union {
     Unit(()),
     Bool(bool),
@@ -153,9 +167,9 @@ pub enum Union {
     #[cfg(not(feature = "no_float"))]
     Float(FLOAT),
     #[cfg(not(feature = "no_index"))]
-    Array(Box<Array>),
+    Array(Shared<Array>),
     #[cfg(not(feature = "no_object"))]
-    Map(Box<Map>),
+    Map(Shared<Map>),
     FnPtr(Box<FnPtr>),
     #[cfg(not(feature = "no_std"))]
     TimeStamp(Box<Instant>),
@@ -166,6 +180,9 @@ pub enum Union {
     Shared(Shared<Locked<Dynamic>>),
 }
 
+// Fail to compile if `Dynamic` ever grows larger than 16 bytes.
+const _: [(); 0] = [(); (mem::size_of::<Dynamic>() > 16) as usize];
+
 /// Underlying `Variant` read guard for `Dynamic`.
 ///
 /// This data structure provides transparent interoperability between
@@ -731,7 +748,11 @@ impl Dynamic {
         #[cfg(not(feature = "no_index"))]
         if TypeId::of::<T>() == TypeId::of::<Array>() {
             return match self.0 {
-                Union::Array(value) => unsafe_cast_box::<_, T>(value).ok().map(|v| *v),
+                Union::Array(value) => {
+                    unsafe_cast_box::<_, T>(Box::new(shared_take_or_clone(value)))
+                        .ok()
+                        .map(|v| *v)
+                }
                 _ => None,
             };
         }
@@ -739,7 +760,9 @@ impl Dynamic {
         #[cfg(not(feature = "no_object"))]
         if TypeId::of::<T>() == TypeId::of::<Map>() {
             return match self.0 {
-                Union::Map(value) => unsafe_cast_box::<_, T>(value).ok().map(|v| *v),
+                Union::Map(value) => unsafe_cast_box::<_, T>(Box::new(shared_take_or_clone(value)))
+                    .ok()
+                    .map(|v| *v),
                 _ => None,
             };
         }
@@ -1078,14 +1101,16 @@ impl Dynamic {
         #[cfg(not(feature = "no_index"))]
         if TypeId::of::<T>() == TypeId::of::<Array>() {
             return match &mut self.0 {
-                Union::Array(value) => <dyn Any>::downcast_mut::<T>(value.as_mut()),
+                Union::Array(value) => {
+                    <dyn Any>::downcast_mut::<T>(shared_make_mut::<Array>(value))
+                }
                 _ => None,
             };
         }
         #[cfg(not(feature = "no_object"))]
         if TypeId::of::<T>() == TypeId::of::<Map>() {
             return match &mut self.0 {
-                Union::Map(value) => <dyn Any>::downcast_mut::<T>(value.as_mut()),
+                Union::Map(value) => <dyn Any>::downcast_mut::<T>(shared_make_mut::<Map>(value)),
                 _ => None,
             };
         }
@@ -1258,7 +1283,7 @@ impl<S: Into<ImmutableString>> From<S> for Dynamic {
 impl<T: Variant + Clone> From<Vec<T>> for Dynamic {
     #[inline(always)]
     fn from(value: Vec<T>) -> Self {
-        Self(Union::Array(Box::new(
+        Self(Union::Array(Shared::new(
             value.into_iter().map(Dynamic::from).collect(),
         )))
     }
@@ -1267,7 +1292,7 @@ impl<T: Variant + Clone> From<Vec<T>> for Dynamic {
 impl<T: Variant + Clone> From<&[T]> for Dynamic {
     #[inline(always)]
     fn from(value: &[T]) -> Self {
-        Self(Union::Array(Box::new(
+        Self(Union::Array(Shared::new(
             value.iter().cloned().map(Dynamic::from).collect(),
         )))
     }
@@ -1276,7 +1301,7 @@ impl<T: Variant + Clone> From<&[T]> for Dynamic {
 impl<K: Into<ImmutableString>, T: Variant + Clone> From<HashMap<K, T>> for Dynamic {
     #[inline(always)]
     fn from(value: HashMap<K, T>) -> Self {
-        Self(Union::Map(Box::new(
+        Self(Union::Map(Shared::new(
             value
                 .into_iter()
                 .map(|(k, v)| (k.into(), Dynamic::from(v)))
@@ -1,9 +1,16 @@
 //! Main module defining the script evaluation `Engine`.
 
 use crate::ast::{BinaryExpr, Expr, FnCallInfo, Ident, IdentX, ReturnType, Stmt};
+use crate::coverage::{BranchKind, Coverage};
+use crate::debugger::{Debugger, LentDebugger};
 use crate::dynamic::{map_std_type_name, Dynamic, Union, Variant};
 use crate::fn_call::run_builtin_op_assignment;
-use crate::fn_native::{Callback, FnPtr, OnVarCallback};
+use crate::fn_native::{
+    Callback, FnPtr, NativeCallContext, OnDebuggerCallback, OnDebuggerInit, OnVarCallback, Shared,
+};
+
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+use crate::fn_native::shared_make_mut;
 use crate::module::{Module, ModuleRef};
 use crate::optimize::OptimizationLevel;
 use crate::packages::{Package, PackagesCollection, StandardPackage};
@@ -24,6 +31,9 @@ use crate::module::ModuleResolver;
 #[cfg(not(feature = "no_module"))]
 use crate::module::resolvers;
 
+#[cfg(not(feature = "no_std"))]
+use crate::profiler::{ProfileState, Profiler};
+
 #[cfg(any(not(feature = "no_object"), not(feature = "no_module")))]
 use crate::utils::ImmutableString;
 
@@ -140,6 +150,10 @@ pub const MAX_EXPR_DEPTH: usize = 128;
 #[cfg(not(debug_assertions))]
 pub const MAX_FUNCTION_EXPR_DEPTH: usize = 32;
 
+/// Default native stack budget, leaving headroom within the 2MB stack of threads spawned by Rust.
+#[cfg(not(feature = "unchecked"))]
+pub const MAX_STACK_SIZE: usize = 1536 * 1024;
+
 pub const KEYWORD_PRINT: &str = "print";
 pub const KEYWORD_DEBUG: &str = "debug";
 pub const KEYWORD_TYPE_OF: &str = "type_of";
@@ -176,6 +190,24 @@ pub enum ChainType {
     Dot,
 }
 
+/// Is a dot/index chain applied via `chain_type` free of method calls?
+///
+/// Methods take the object by mutable reference, so they may modify it.
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+fn is_read_only_chain(expr: &Expr, chain_type: ChainType) -> bool {
+    let (lhs, rest) = match expr {
+        Expr::Index(x, _, _) => (&x.lhs, Some((&x.rhs, ChainType::Index))),
+        Expr::Dot(x, _) => (&x.lhs, Some((&x.rhs, ChainType::Dot))),
+        expr => (expr, None),
+    };
+
+    match (lhs, rest) {
+        (Expr::FnCall(_, _, _), _) if chain_type == ChainType::Dot => false,
+        (_, Some((rhs, next_chain))) => is_read_only_chain(rhs, next_chain),
+        (_, None) => true,
+    }
+}
+
 #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
 #[derive(Debug, Clone)]
 pub enum IndexChainValue {
@@ -369,7 +401,7 @@ impl<'a> Target<'a> {
                     Box::new(EvalAltResult::ErrorMismatchDataType(
                         err.to_string(),
                         "char".to_string(),
-                        new_val.1,
+                        new_val.1.into(),
                     ))
                 })?;
 
@@ -417,12 +449,17 @@ impl<T: Into<Dynamic>> From<T> for Target<'_> {
 /// ## WARNING
 ///
 /// This type is volatile and may change.
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct State {
-    /// Normally, access to variables are parsed with a relative offset into the scope to avoid a lookup.
-    /// In some situation, e.g. after running an `eval` statement, subsequent offsets become mis-aligned.
-    /// When that happens, this flag is turned on to force a scope lookup by name.
+    /// Normally, access to imported modules are parsed with a relative offset to avoid a lookup.
+    /// After running an `eval` statement that imports modules, subsequent offsets become mis-aligned.
+    /// When that happens, this flag is turned on to force a module lookup by name.
     pub always_search: bool,
+    /// Ranges of the scope (start and length, lowest first) holding variables defined by `eval`
+    /// statements in the current function call.  Variable offsets parsed before the `eval` skip
+    /// over these entries, and only these entries are searched by name (because they may shadow
+    /// the variable at the offset).
+    pub eval_vars: StaticVec<(usize, usize)>,
     /// Level of the current scope.  The global (root) level is zero, a new block (or function call)
     /// is one level higher, and so on.
     pub scope_level: usize,
@@ -430,9 +467,49 @@ pub struct State {
     pub operations: u64,
     /// Number of modules loaded.
     pub modules: usize,
+    /// Arguments of a pending self-recursive call in tail position, if any.
+    /// The currently-running script-defined function restarts itself with these arguments
+    /// instead of nesting a new function call.
+    #[cfg(not(feature = "no_function"))]
+    pub tail_call: Option<StaticVec<Dynamic>>,
+    /// State of the debugger, if any, created on first use.
+    pub debugger: Option<Box<Debugger>>,
+    /// Is the debugger paused (i.e. the debugger callback is running, or the evaluation run is
+    /// nested inside a native function called while the callback is running)?
+    pub debugger_paused: bool,
+    /// State of the profiler, if any, created on first use.
+    #[cfg(not(feature = "no_std"))]
+    pub profile: Option<Box<ProfileState>>,
+    /// Name of the source (e.g. the script file path) of the statements being evaluated, if any.
+    pub source: Option<ImmutableString>,
 }
 
 impl State {
+    /// Get the index of a variable in the scope from its offset (parsed with a relative offset
+    /// from the end of the scope), skipping over variables defined by `eval` statements.
+    #[inline]
+    pub(crate) fn scope_index(&self, scope: &Scope, name: &str, offset: usize) -> usize {
+        let mut index = scope.len() - offset;
+
+        if self.eval_vars.is_empty() {
+            return index;
+        }
+
+        for &(start, len) in self.eval_vars.iter().rev() {
+            if index >= start + len {
+                break;
+            }
+            index -= len;
+        }
+
+        // A variable of the same name defined by an `eval` after the variable shadows it
+        self.eval_vars
+            .iter()
+            .rev()
+            .take_while(|&&(start, _)| start > index)
+            .find_map(|&(start, len)| scope.get_index_in(name, start..start + len))
+            .unwrap_or(index)
+    }
     /// Is the state currently at global (root) level?
     #[inline(always)]
     pub fn is_global(&self) -> bool {
@@ -440,6 +517,76 @@ impl State {
     }
 }
 
+#[cfg(not(feature = "unchecked"))]
+#[cfg(not(feature = "no_std"))]
+thread_local! {
+    /// Position of the native stack at the start of the outer-most evaluation or parsing
+    /// on the current thread, zero if none is running.
+    static STACK_BASE: crate::stdlib::cell::Cell<usize> = const { crate::stdlib::cell::Cell::new(0) };
+}
+
+/// Approximate position of the native stack at the point of call.
+#[cfg(not(feature = "unchecked"))]
+#[cfg(not(feature = "no_std"))]
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0_u8;
+    &marker as *const u8 as usize
+}
+
+/// A guard marking the start of an evaluation or parsing on the current thread,
+/// from which the amount of native stack used is measured.
+///
+/// Guards can be nested (e.g. a native function re-entering the `Engine`), in which case the
+/// start position of the outer-most guard is kept, so that the whole stack usage is counted.
+///
+/// Stack usage is not tracked under `no_std`.
+#[cfg(not(feature = "unchecked"))]
+pub(crate) struct StackGuard(bool);
+
+#[cfg(not(feature = "unchecked"))]
+impl StackGuard {
+    /// Create a new `StackGuard`, marking the current position of the native stack
+    /// if no evaluation or parsing is already running on the current thread.
+    #[inline(always)]
+    pub fn new() -> Self {
+        #[cfg(not(feature = "no_std"))]
+        return STACK_BASE.with(|base| {
+            if base.get() == 0 {
+                base.set(stack_position());
+                Self(true)
+            } else {
+                Self(false)
+            }
+        });
+
+        #[cfg(feature = "no_std")]
+        return Self(false);
+    }
+    /// Number of bytes of native stack used since the outer-most `StackGuard` was created.
+    #[inline(always)]
+    pub fn stack_used() -> usize {
+        #[cfg(not(feature = "no_std"))]
+        return STACK_BASE.with(|base| match base.get() {
+            0 => 0,
+            base => base.abs_diff(stack_position()),
+        });
+
+        #[cfg(feature = "no_std")]
+        return 0;
+    }
+}
+
+#[cfg(not(feature = "unchecked"))]
+impl Drop for StackGuard {
+    fn drop(&mut self) {
+        if self.0 {
+            #[cfg(not(feature = "no_std"))]
+            STACK_BASE.with(|base| base.set(0));
+        }
+    }
+}
+
 /// _[INTERNALS]_ A type containing all the limits imposed by the `Engine`.
 /// Exported under the `internals` feature only.
 ///
@@ -461,6 +608,11 @@ pub struct Limits {
     pub max_function_expr_depth: usize,
     /// Maximum number of operations allowed to run (0 = unlimited).
     pub max_operations: u64,
+    /// Maximum number of bytes of native stack that evaluation or parsing may use (0 = unlimited).
+    /// Stack usage is not tracked under `no_std`.
+    ///
+    /// Defaults to 1.5MB.
+    pub max_stack_size: usize,
     /// Maximum number of modules allowed to load.
     /// Not available under `no_module`.
     #[cfg(not(feature = "no_module"))]
@@ -513,6 +665,11 @@ impl<'e, 'x, 'px, 'a, 's, 'm, 'pm, 't, 'pt> EvalContext<'e, 'x, 'px, 'a, 's, 'm,
     pub fn this_ptr(&self) -> Option<&Dynamic> {
         self.this_ptr.as_ref().map(|v| &**v)
     }
+    /// Get a mutable reference to the current bound `this` pointer, if any.
+    #[inline(always)]
+    pub fn this_ptr_mut(&mut self) -> Option<&mut Dynamic> {
+        self.this_ptr.as_mut().map(|v| &mut **v)
+    }
     /// The current nesting level of function calls.
     #[inline(always)]
     pub fn call_level(&self) -> usize {
@@ -566,6 +723,8 @@ pub struct Engine {
     pub(crate) custom_syntax: HashMap<ImmutableString, CustomSyntax>,
     /// Callback closure for resolving variable access.
     pub(crate) resolve_var: Option<OnVarCallback>,
+    /// Names of variables provided by the variable resolver.
+    pub(crate) declared_vars: HashSet<String>,
 
     /// Callback closure for implementing the `print` command.
     pub(crate) print: Callback<str, ()>,
@@ -573,9 +732,18 @@ pub struct Engine {
     pub(crate) debug: Callback<str, ()>,
     /// Callback closure for progress reporting.
     pub(crate) progress: Option<Callback<u64, Option<Dynamic>>>,
+    /// Initialization function and callback closure for debugging.
+    pub(crate) debugger: Option<(OnDebuggerInit, OnDebuggerCallback)>,
+    /// Profiler collecting measurements.
+    #[cfg(not(feature = "no_std"))]
+    pub(crate) profiler: Option<Profiler>,
+    /// Coverage collector recording execution counts.
+    pub(crate) coverage: Option<Coverage>,
 
     /// Optimize the AST after compilation.
     pub(crate) optimization_level: OptimizationLevel,
+    /// Check variables and functions at compile time.
+    pub(crate) strict_mode: bool,
 
     /// Max limits.
     #[cfg(not(feature = "unchecked"))]
@@ -650,7 +818,7 @@ pub fn search_imports<'s>(
     } else {
         mods.find(root)
             .map(|n| mods.get(n).expect("invalid index in Imports"))
-            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(root.to_string(), *pos))?
+            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(root.to_string(), (*pos).into()))?
     })
 }
 
@@ -680,6 +848,7 @@ impl Engine {
 
             // variable resolver
             resolve_var: None,
+            declared_vars: Default::default(),
 
             // default print/debug implementations
             print: Box::new(default_print),
@@ -688,12 +857,23 @@ impl Engine {
             // progress callback
             progress: None,
 
+            // debugger
+            debugger: None,
+
+            // profiler
+            #[cfg(not(feature = "no_std"))]
+            profiler: None,
+
+            // coverage
+            coverage: None,
+
             // optimization level
             optimization_level: if cfg!(feature = "no_optimize") {
                 OptimizationLevel::None
             } else {
                 OptimizationLevel::Simple
             },
+            strict_mode: false,
 
             #[cfg(not(feature = "unchecked"))]
             limits_set: Limits {
@@ -702,6 +882,7 @@ impl Engine {
                 #[cfg(not(feature = "no_function"))]
                 max_function_expr_depth: MAX_FUNCTION_EXPR_DEPTH,
                 max_operations: 0,
+                max_stack_size: MAX_STACK_SIZE,
                 #[cfg(not(feature = "no_module"))]
                 max_modules: usize::MAX,
                 max_string_size: 0,
@@ -736,16 +917,22 @@ impl Engine {
             custom_syntax: Default::default(),
 
             resolve_var: None,
+            declared_vars: Default::default(),
 
             print: Box::new(|_| {}),
             debug: Box::new(|_| {}),
             progress: None,
+            debugger: None,
+            #[cfg(not(feature = "no_std"))]
+            profiler: None,
+            coverage: None,
 
             optimization_level: if cfg!(feature = "no_optimize") {
                 OptimizationLevel::None
             } else {
                 OptimizationLevel::Simple
             },
+            strict_mode: false,
 
             #[cfg(not(feature = "unchecked"))]
             limits_set: Limits {
@@ -754,6 +941,7 @@ impl Engine {
                 #[cfg(not(feature = "no_function"))]
                 max_function_expr_depth: MAX_FUNCTION_EXPR_DEPTH,
                 max_operations: 0,
+                max_stack_size: MAX_STACK_SIZE,
                 #[cfg(not(feature = "no_module"))]
                 max_modules: usize::MAX,
                 max_string_size: 0,
@@ -767,6 +955,8 @@ impl Engine {
 
     /// Search for a variable within the scope or within imports,
     /// depending on whether the variable name is qualified.
+    ///
+    /// Also returns the type annotation of a variable in the scope, if any.
     pub(crate) fn search_namespace<'s, 'a>(
         &self,
         scope: &'s mut Scope,
@@ -775,7 +965,16 @@ impl Engine {
         lib: &[&Module],
         this_ptr: &'s mut Option<&mut Dynamic>,
         expr: &'a Expr,
-    ) -> Result<(Target<'s>, &'a str, ScopeEntryType, Position), Box<EvalAltResult>> {
+    ) -> Result<
+        (
+            Target<'s>,
+            &'a str,
+            ScopeEntryType,
+            Option<&'s ImmutableString>,
+            Position,
+        ),
+        Box<EvalAltResult>,
+    > {
         match expr {
             Expr::Variable(v) => match v.as_ref() {
                 // Qualified variable
@@ -792,7 +991,13 @@ impl Engine {
                     })?;
 
                     // Module variables are constant
-                    Ok((target.clone().into(), name, ScopeEntryType::Constant, *pos))
+                    Ok((
+                        target.clone().into(),
+                        name,
+                        ScopeEntryType::Constant,
+                        None,
+                        *pos,
+                    ))
                 }
                 // Normal variable access
                 _ => self.search_scope_only(scope, mods, state, lib, this_ptr, expr),
@@ -801,7 +1006,9 @@ impl Engine {
         }
     }
 
-    /// Search for a variable within the scope
+    /// Search for a variable within the scope.
+    ///
+    /// Also returns the type annotation of the variable, if any.
     pub(crate) fn search_scope_only<'s, 'a>(
         &self,
         scope: &'s mut Scope,
@@ -810,7 +1017,16 @@ impl Engine {
         lib: &[&Module],
         this_ptr: &'s mut Option<&mut Dynamic>,
         expr: &'a Expr,
-    ) -> Result<(Target<'s>, &'a str, ScopeEntryType, Position), Box<EvalAltResult>> {
+    ) -> Result<
+        (
+            Target<'s>,
+            &'a str,
+            ScopeEntryType,
+            Option<&'s ImmutableString>,
+            Position,
+        ),
+        Box<EvalAltResult>,
+    > {
         let (index, _, _, Ident { name, pos }) = match expr {
             Expr::Variable(v) => v.as_ref(),
             _ => unreachable!(),
@@ -819,21 +1035,26 @@ impl Engine {
         // Check if the variable is `this`
         if name == KEYWORD_THIS {
             if let Some(val) = this_ptr {
-                return Ok(((*val).into(), KEYWORD_THIS, ScopeEntryType::Normal, *pos));
+                return Ok((
+                    (*val).into(),
+                    KEYWORD_THIS,
+                    ScopeEntryType::Normal,
+                    None,
+                    *pos,
+                ));
             } else {
-                return EvalAltResult::ErrorUnboundThis(*pos).into();
+                return EvalAltResult::ErrorUnboundThis((*pos).into()).into();
             }
         }
 
         // Check if it is directly indexed
-        let index = if state.always_search {
-            0
-        } else {
-            index.map_or(0, NonZeroUsize::get)
-        };
+        let index = index.map_or(0, NonZeroUsize::get);
 
         // Check the variable resolver, if any
         if let Some(ref resolve_var) = self.resolve_var {
+            // Offsets are only passed on while they point directly into the scope
+            let index = if state.eval_vars.is_empty() { index } else { 0 };
+
             let context = EvalContext {
                 engine: self,
                 scope,
@@ -846,21 +1067,21 @@ impl Engine {
             if let Some(result) =
                 resolve_var(name, index, &context).map_err(|err| err.fill_position(*pos))?
             {
-                return Ok((result.into(), name, ScopeEntryType::Constant, *pos));
+                return Ok((result.into(), name, ScopeEntryType::Constant, None, *pos));
             }
         }
 
         let index = if index > 0 {
-            scope.len() - index
+            state.scope_index(scope, name, index)
         } else {
             // Find the variable in the scope
             scope
                 .get_index(name)
-                .ok_or_else(|| EvalAltResult::ErrorVariableNotFound(name.into(), *pos))?
+                .ok_or_else(|| EvalAltResult::ErrorVariableNotFound(name.into(), (*pos).into()))?
                 .0
         };
 
-        let (val, typ) = scope.get_mut(index);
+        let (val, typ, var_type) = scope.get_mut(index);
 
         // Check for data race - probably not necessary because the only place it should conflict is in a method call
         //                       when the object variable is also used as a parameter.
@@ -868,7 +1089,7 @@ impl Engine {
         //     return EvalAltResult::ErrorDataRace(name.into(), *pos).into();
         // }
 
-        Ok((val.into(), name, typ, *pos))
+        Ok((val.into(), name, typ, var_type, *pos))
     }
 
     /// Chain-evaluate a dot/index chain.
@@ -891,10 +1112,14 @@ impl Engine {
             panic!();
         }
 
+        #[cfg(not(feature = "unchecked"))]
+        self.check_stack_size()
+            .map_err(|err| err.fill_position(rhs.position()))?;
+
         let is_ref = target.is_ref();
 
         let next_chain = match rhs {
-            Expr::Index(_, _) => ChainType::Index,
+            Expr::Index(_, _, _) => ChainType::Index,
             Expr::Dot(_, _) => ChainType::Dot,
             _ => ChainType::None,
         };
@@ -909,12 +1134,23 @@ impl Engine {
 
                 match rhs {
                     // xxx[idx].expr... | xxx[idx][expr]...
-                    Expr::Dot(x, x_pos) | Expr::Index(x, x_pos) => {
+                    Expr::Dot(x, x_pos) | Expr::Index(x, x_pos, _) => {
                         let idx_pos = x.lhs.position();
                         let idx_val = idx_val.as_value();
-                        let obj_ptr = &mut self.get_indexed_mut(
-                            mods, state, lib, target, idx_val, idx_pos, false, true, level,
-                        )?;
+
+                        // Read a nested array or map from a copy, keeping it shared
+                        let value = if new_val.is_none() && is_read_only_chain(&x.rhs, next_chain) {
+                            self.get_indexed_value(state, target, &idx_val, true)?
+                        } else {
+                            None
+                        };
+
+                        let obj_ptr = &mut match value {
+                            Some(value) => value.into(),
+                            None => self.get_indexed_mut(
+                                mods, state, lib, target, idx_val, idx_pos, false, true, level,
+                            )?,
+                        };
 
                         self.eval_dot_index_chain_helper(
                             mods, state, lib, this_ptr, obj_ptr, &x.rhs, idx_values, next_chain,
@@ -961,7 +1197,7 @@ impl Engine {
                                 {
                                     EvalAltResult::ErrorIndexingType(
                                         self.map_type_name(val_type_name).into(),
-                                        NO_POS,
+                                        NO_POS.into(),
                                     )
                                 }
                                 err => err,
@@ -973,6 +1209,13 @@ impl Engine {
                     // xxx[rhs]
                     _ => {
                         let idx_val = idx_val.as_value();
+
+                        if let Some(value) =
+                            self.get_indexed_value(state, target, &idx_val, false)?
+                        {
+                            return Ok((value, false));
+                        }
+
                         self.get_indexed_mut(
                             mods, state, lib, target, idx_val, pos, false, true, level,
                         )
@@ -985,7 +1228,7 @@ impl Engine {
             ChainType::Dot => {
                 match rhs {
                     // xxx.fn_name(arg_expr_list)
-                    Expr::FnCall(x, pos) if x.namespace.is_none() => {
+                    Expr::FnCall(x, pos, _) if x.namespace.is_none() => {
                         let FnCallInfo {
                             name,
                             native_only: native,
@@ -1002,7 +1245,7 @@ impl Engine {
                         .map_err(|err| err.fill_position(*pos))
                     }
                     // xxx.module::fn_name(...) - syntax error
-                    Expr::FnCall(_, _) => unreachable!(),
+                    Expr::FnCall(_, _, _) => unreachable!(),
                     // {xxx:map}.id = ???
                     Expr::Property(x) if target.is::<Map>() && new_val.is_some() => {
                         let IdentX { name, pos } = &x.1;
@@ -1018,6 +1261,11 @@ impl Engine {
                     Expr::Property(x) if target.is::<Map>() => {
                         let IdentX { name, pos } = &x.1;
                         let index = name.clone().into();
+
+                        if let Some(value) = self.get_indexed_value(state, target, &index, false)? {
+                            return Ok((value, false));
+                        }
+
                         let val = self.get_indexed_mut(
                             mods, state, lib, target, index, *pos, false, false, level,
                         )?;
@@ -1048,17 +1296,30 @@ impl Engine {
                         .map_err(|err| err.fill_position(*pos))
                     }
                     // {xxx:map}.sub_lhs[expr] | {xxx:map}.sub_lhs.expr
-                    Expr::Index(x, x_pos) | Expr::Dot(x, x_pos) if target.is::<Map>() => {
+                    Expr::Index(x, x_pos, _) | Expr::Dot(x, x_pos) if target.is::<Map>() => {
                         let mut val = match &x.lhs {
                             Expr::Property(p) => {
                                 let IdentX { name, pos } = &p.1;
                                 let index = name.clone().into();
-                                self.get_indexed_mut(
-                                    mods, state, lib, target, index, *pos, false, true, level,
-                                )?
+
+                                // Read a nested array or map from a copy, keeping it shared
+                                let value = if new_val.is_none()
+                                    && is_read_only_chain(&x.rhs, next_chain)
+                                {
+                                    self.get_indexed_value(state, target, &index, true)?
+                                } else {
+                                    None
+                                };
+
+                                match value {
+                                    Some(value) => value.into(),
+                                    None => self.get_indexed_mut(
+                                        mods, state, lib, target, index, *pos, false, true, level,
+                                    )?,
+                                }
                             }
                             // {xxx:map}.fn_name(arg_expr_list)[expr] | {xxx:map}.fn_name(arg_expr_list).expr
-                            Expr::FnCall(x, pos) if x.namespace.is_none() => {
+                            Expr::FnCall(x, pos, _) if x.namespace.is_none() => {
                                 let FnCallInfo {
                                     name,
                                     native_only: native,
@@ -1077,7 +1338,7 @@ impl Engine {
                                 val.into()
                             }
                             // {xxx:map}.module::fn_name(...) - syntax error
-                            Expr::FnCall(_, _) => unreachable!(),
+                            Expr::FnCall(_, _, _) => unreachable!(),
                             // Others - syntax error
                             _ => unreachable!(),
                         };
@@ -1089,7 +1350,7 @@ impl Engine {
                         .map_err(|err| err.fill_position(*x_pos))
                     }
                     // xxx.sub_lhs[expr] | xxx.sub_lhs.expr
-                    Expr::Index(x, x_pos) | Expr::Dot(x, x_pos) => {
+                    Expr::Index(x, x_pos, _) | Expr::Dot(x, x_pos) => {
                         match &x.lhs {
                             // xxx.prop[expr] | xxx.prop.expr
                             Expr::Property(p) => {
@@ -1142,7 +1403,7 @@ impl Engine {
                                 Ok((result, may_be_changed))
                             }
                             // xxx.fn_name(arg_expr_list)[expr] | xxx.fn_name(arg_expr_list).expr
-                            Expr::FnCall(f, pos) if f.namespace.is_none() => {
+                            Expr::FnCall(f, pos, _) if f.namespace.is_none() => {
                                 let FnCallInfo {
                                     name,
                                     native_only: native,
@@ -1168,13 +1429,13 @@ impl Engine {
                                 .map_err(|err| err.fill_position(*pos))
                             }
                             // xxx.module::fn_name(...) - syntax error
-                            Expr::FnCall(_, _) => unreachable!(),
+                            Expr::FnCall(_, _, _) => unreachable!(),
                             // Others - syntax error
                             _ => unreachable!(),
                         }
                     }
                     // Syntax error
-                    _ => EvalAltResult::ErrorDotExpr("".into(), rhs.position()).into(),
+                    _ => EvalAltResult::ErrorDotExpr("".into(), rhs.position().into()).into(),
                 }
             }
 
@@ -1203,7 +1464,7 @@ impl Engine {
             chain_type,
             op_pos,
         ) = match expr {
-            Expr::Index(x, pos) => (x.as_ref(), ChainType::Index, *pos),
+            Expr::Index(x, pos, _) => (x.as_ref(), ChainType::Index, *pos),
             Expr::Dot(x, pos) => (x.as_ref(), ChainType::Dot, *pos),
             _ => unreachable!(),
         };
@@ -1234,14 +1495,17 @@ impl Engine {
                 self.inc_operations(state)
                     .map_err(|err| err.fill_position(*var_pos))?;
 
-                let (target, _, typ, pos) =
+                let (target, _, typ, _, pos) =
                     self.search_namespace(scope, mods, state, lib, this_ptr, dot_lhs)?;
 
                 // Constants cannot be modified
                 match typ {
                     ScopeEntryType::Constant if new_val.is_some() => {
-                        return EvalAltResult::ErrorAssignmentToConstant(var_name.to_string(), pos)
-                            .into();
+                        return EvalAltResult::ErrorAssignmentToConstant(
+                            var_name.to_string(),
+                            pos.into(),
+                        )
+                        .into();
                     }
                     ScopeEntryType::Constant | ScopeEntryType::Normal => (),
                 }
@@ -1289,8 +1553,12 @@ impl Engine {
         self.inc_operations(state)
             .map_err(|err| err.fill_position(expr.position()))?;
 
+        #[cfg(not(feature = "unchecked"))]
+        self.check_stack_size()
+            .map_err(|err| err.fill_position(expr.position()))?;
+
         match expr {
-            Expr::FnCall(x, _) if x.namespace.is_none() => {
+            Expr::FnCall(x, _, _) if x.namespace.is_none() => {
                 let arg_values = x
                     .args
                     .iter()
@@ -1301,15 +1569,17 @@ impl Engine {
 
                 idx_values.push(arg_values.into());
             }
-            Expr::FnCall(_, _) => unreachable!(),
+            Expr::FnCall(_, _, _) => unreachable!(),
             Expr::Property(_) => idx_values.push(IndexChainValue::None),
-            Expr::Index(x, _) | Expr::Dot(x, _) => {
+            Expr::Index(x, _, _) | Expr::Dot(x, _) => {
                 let BinaryExpr { lhs, rhs, .. } = x.as_ref();
 
                 // Evaluate in left-to-right order
                 let lhs_val = match lhs {
                     Expr::Property(_) => IndexChainValue::None,
-                    Expr::FnCall(x, _) if chain_type == ChainType::Dot && x.namespace.is_none() => {
+                    Expr::FnCall(x, _, _)
+                        if chain_type == ChainType::Dot && x.namespace.is_none() =>
+                    {
                         x.args
                             .iter()
                             .map(|arg_expr| {
@@ -1318,7 +1588,7 @@ impl Engine {
                             .collect::<Result<StaticVec<Dynamic>, _>>()?
                             .into()
                     }
-                    Expr::FnCall(_, _) => unreachable!(),
+                    Expr::FnCall(_, _, _) => unreachable!(),
                     _ => self
                         .eval_expr(scope, mods, state, lib, this_ptr, lhs, level)?
                         .into(),
@@ -1326,7 +1596,7 @@ impl Engine {
 
                 // Push in reverse order
                 let chain_type = match expr {
-                    Expr::Index(_, _) => ChainType::Index,
+                    Expr::Index(_, _, _) => ChainType::Index,
                     Expr::Dot(_, _) => ChainType::Dot,
                     _ => unreachable!(),
                 };
@@ -1345,6 +1615,55 @@ impl Engine {
         Ok(())
     }
 
+    /// Get a copy of the value at the indexed position of an array or object map
+    /// without un-sharing its storage.
+    ///
+    /// If `containers_only` is `true`, only copy the value if it is itself an array or object map,
+    /// which is cheap because its storage is shared.
+    ///
+    /// Returns `None` if the target is of any other type, if the index is not valid,
+    /// or if the value is not copied, in which case `get_indexed_mut` should be used instead.
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    fn get_indexed_value(
+        &self,
+        state: &mut State,
+        target: &mut Target,
+        idx: &Dynamic,
+        containers_only: bool,
+    ) -> Result<Option<Dynamic>, Box<EvalAltResult>> {
+        let is_copied = |value: &&Dynamic| match value.0 {
+            #[cfg(not(feature = "no_index"))]
+            Union::Array(_) => true,
+            #[cfg(not(feature = "no_object"))]
+            Union::Map(_) => true,
+            _ => !containers_only,
+        };
+
+        let value = match target.as_mut() {
+            #[cfg(not(feature = "no_index"))]
+            Dynamic(Union::Array(arr)) => match idx.as_int() {
+                Ok(index) if index >= 0 => arr.get(index as usize).filter(is_copied).cloned(),
+                _ => None,
+            },
+            #[cfg(not(feature = "no_object"))]
+            Dynamic(Union::Map(map)) => match idx.read_lock::<ImmutableString>() {
+                Some(index) => match map.get(&*index) {
+                    Some(value) => Some(value).filter(is_copied).cloned(),
+                    None if containers_only => None,
+                    None => Some(Default::default()),
+                },
+                None => None,
+            },
+            _ => None,
+        };
+
+        if value.is_some() {
+            self.inc_operations(state)?;
+        }
+
+        Ok(value)
+    }
+
     /// Get the value at the indexed position of a base type
     /// Position in `EvalAltResult` may be None and should be set afterwards.
     #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
@@ -1378,13 +1697,14 @@ impl Engine {
                 let arr_len = arr.len();
 
                 if index >= 0 {
-                    arr.get_mut(index as usize)
+                    shared_make_mut(arr)
+                        .get_mut(index as usize)
                         .map(Target::from)
                         .ok_or_else(|| {
-                            EvalAltResult::ErrorArrayBounds(arr_len, index, idx_pos).into()
+                            EvalAltResult::ErrorArrayBounds(arr_len, index, idx_pos.into()).into()
                         })
                 } else {
-                    EvalAltResult::ErrorArrayBounds(arr_len, index, idx_pos).into()
+                    EvalAltResult::ErrorArrayBounds(arr_len, index, idx_pos.into()).into()
                 }
             }
 
@@ -1396,13 +1716,17 @@ impl Engine {
                         self.make_type_mismatch_err::<ImmutableString>(err, idx_pos)
                     })?;
 
-                    map.entry(index).or_insert_with(Default::default).into()
+                    shared_make_mut(map)
+                        .entry(index)
+                        .or_insert_with(Default::default)
+                        .into()
                 } else {
                     let index = idx.read_lock::<ImmutableString>().ok_or_else(|| {
                         self.make_type_mismatch_err::<ImmutableString>("", idx_pos)
                     })?;
 
-                    map.get_mut(&*index)
+                    shared_make_mut(map)
+                        .get_mut(&*index)
                         .map(Target::from)
                         .unwrap_or_else(|| Target::from(()))
                 })
@@ -1419,11 +1743,11 @@ impl Engine {
                 if index >= 0 {
                     let offset = index as usize;
                     let ch = s.chars().nth(offset).ok_or_else(|| {
-                        EvalAltResult::ErrorStringBounds(chars_len, index, idx_pos)
+                        EvalAltResult::ErrorStringBounds(chars_len, index, idx_pos.into())
                     })?;
                     Ok(Target::StringChar(val, offset, ch.into()))
                 } else {
-                    EvalAltResult::ErrorStringBounds(chars_len, index, idx_pos).into()
+                    EvalAltResult::ErrorStringBounds(chars_len, index, idx_pos.into()).into()
                 }
             }
 
@@ -1439,16 +1763,20 @@ impl Engine {
                 .map(|(v, _)| v.into())
                 .map_err(|err| match *err {
                     EvalAltResult::ErrorFunctionNotFound(fn_sig, _) if fn_sig.ends_with(']') => {
-                        Box::new(EvalAltResult::ErrorIndexingType(type_name.into(), NO_POS))
+                        Box::new(EvalAltResult::ErrorIndexingType(
+                            type_name.into(),
+                            NO_POS.into(),
+                        ))
                     }
                     _ => err,
                 })
             }
 
-            _ => {
-                EvalAltResult::ErrorIndexingType(self.map_type_name(val.type_name()).into(), NO_POS)
-                    .into()
-            }
+            _ => EvalAltResult::ErrorIndexingType(
+                self.map_type_name(val.type_name()).into(),
+                NO_POS.into(),
+            )
+            .into(),
         }
     }
 
@@ -1472,14 +1800,15 @@ impl Engine {
 
         match rhs_value {
             #[cfg(not(feature = "no_index"))]
-            Dynamic(Union::Array(mut rhs_value)) => {
+            Dynamic(Union::Array(rhs_value)) => {
                 const OP_FUNC: &str = "==";
 
                 // Call the `==` operator to compare each value
                 let def_value = Some(false.into());
 
-                for value in rhs_value.iter_mut() {
-                    let args = &mut [&mut lhs_value.clone(), value];
+                // Compare copies of the values, so the array need not be un-shared
+                for value in rhs_value.iter() {
+                    let args = &mut [&mut lhs_value.clone(), &mut value.clone()];
                     let def_value = def_value.clone();
 
                     // Qualifiers (none) + function name + number of arguments + argument `TypeId`'s.
@@ -1487,7 +1816,9 @@ impl Engine {
                         calc_native_fn_hash(empty(), OP_FUNC, args.iter().map(|a| a.type_id()));
 
                     if self
-                        .call_native_fn(state, lib, OP_FUNC, hash, args, false, false, def_value)
+                        .call_native_fn(
+                            state, lib, OP_FUNC, hash, args, false, false, def_value, level,
+                        )
                         .map_err(|err| err.fill_position(rhs.position()))?
                         .0
                         .as_bool()
@@ -1504,15 +1835,15 @@ impl Engine {
                 // Only allows string or char
                 Dynamic(Union::Str(s)) => Ok(rhs_value.contains_key(&s).into()),
                 Dynamic(Union::Char(c)) => Ok(rhs_value.contains_key(&c.to_string()).into()),
-                _ => EvalAltResult::ErrorInExpr(lhs.position()).into(),
+                _ => EvalAltResult::ErrorInExpr(lhs.position().into()).into(),
             },
             Dynamic(Union::Str(rhs_value)) => match lhs_value {
                 // Only allows string or char
                 Dynamic(Union::Str(s)) => Ok(rhs_value.contains(s.as_str()).into()),
                 Dynamic(Union::Char(c)) => Ok(rhs_value.contains(c).into()),
-                _ => EvalAltResult::ErrorInExpr(lhs.position()).into(),
+                _ => EvalAltResult::ErrorInExpr(lhs.position().into()).into(),
             },
-            _ => EvalAltResult::ErrorInExpr(rhs.position()).into(),
+            _ => EvalAltResult::ErrorInExpr(rhs.position().into()).into(),
         }
     }
 
@@ -1526,43 +1857,68 @@ impl Engine {
         this_ptr: &mut Option<&mut Dynamic>,
         expr: &Expr,
         level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        self.eval_expr_raw(scope, mods, state, lib, this_ptr, expr, level)
+            .map_err(|err| err.fill_span(expr.span()))
+    }
+
+    /// Evaluate an expression, without recording its `Span` in any error.
+    fn eval_expr_raw(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        expr: &Expr,
+        level: usize,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         self.inc_operations(state)
             .map_err(|err| err.fill_position(expr.position()))?;
 
+        #[cfg(not(feature = "unchecked"))]
+        self.check_stack_size()
+            .map_err(|err| err.fill_position(expr.position()))?;
+
+        // Parenthesized expressions and statement blocks are not expressions that the debugger
+        // stops at - only the expressions and statements inside
+        if self.debugger.is_some() && !matches!(expr, Expr::Expr(_) | Expr::Stmt(_, _, _)) {
+            self.debug_expression(scope, mods, state, lib, this_ptr, expr.position(), level)?;
+        }
+
         let result = match expr {
             Expr::Expr(x) => self.eval_expr(scope, mods, state, lib, this_ptr, x, level),
 
-            Expr::IntegerConstant(x, _) => Ok((*x).into()),
+            Expr::IntegerConstant(x, _, _) => Ok((*x).into()),
             #[cfg(not(feature = "no_float"))]
-            Expr::FloatConstant(x, _) => Ok(x.0.into()),
-            Expr::StringConstant(x) => Ok(x.name.clone().into()),
-            Expr::CharConstant(x, _) => Ok((*x).into()),
-            Expr::FnPointer(x) => {
+            Expr::FloatConstant(x, _, _) => Ok(x.0.into()),
+            Expr::StringConstant(x, _) => Ok(x.name.clone().into()),
+            Expr::CharConstant(x, _, _) => Ok((*x).into()),
+            Expr::FnPointer(x, _) => {
                 Ok(FnPtr::new_unchecked(x.name.clone(), Default::default()).into())
             }
             Expr::Variable(x) if (x.3).name == KEYWORD_THIS => {
                 if let Some(val) = this_ptr {
                     Ok(val.clone())
                 } else {
-                    EvalAltResult::ErrorUnboundThis((x.3).pos).into()
+                    EvalAltResult::ErrorUnboundThis(x.3.pos.into()).into()
                 }
             }
             Expr::Variable(_) => {
-                let (val, _, _, _) =
+                let (val, _, _, _, _) =
                     self.search_namespace(scope, mods, state, lib, this_ptr, expr)?;
                 Ok(val.take_or_clone())
             }
             Expr::Property(_) => unreachable!(),
 
             // Statement block
-            Expr::Stmt(x, _) => {
+            Expr::Stmt(x, _, _) => {
                 self.eval_statements(scope, mods, state, lib, this_ptr, x.as_ref(), level)
             }
 
             // lhs[idx_expr]
             #[cfg(not(feature = "no_index"))]
-            Expr::Index(_, _) => {
+            Expr::Index(_, _, _) => {
                 self.eval_dot_index_chain(scope, mods, state, lib, this_ptr, expr, level, None)
             }
 
@@ -1573,14 +1929,14 @@ impl Engine {
             }
 
             #[cfg(not(feature = "no_index"))]
-            Expr::Array(x, _) => Ok(Dynamic(Union::Array(Box::new(
+            Expr::Array(x, _, _) => Ok(Dynamic(Union::Array(Shared::new(
                 x.iter()
                     .map(|item| self.eval_expr(scope, mods, state, lib, this_ptr, item, level))
                     .collect::<Result<Vec<_>, _>>()?,
             )))),
 
             #[cfg(not(feature = "no_object"))]
-            Expr::Map(x, _) => Ok(Dynamic(Union::Map(Box::new(
+            Expr::Map(x, _, _) => Ok(Dynamic(Union::Map(Shared::new(
                 x.iter()
                     .map(|(key, expr)| {
                         self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)
@@ -1589,8 +1945,23 @@ impl Engine {
                     .collect::<Result<HashMap<_, _>, _>>()?,
             )))),
 
+            // Self-recursive function call in tail position
+            #[cfg(not(feature = "no_function"))]
+            Expr::FnCall(x, pos, _) if x.tail_call => {
+                // Evaluate the arguments, then unwind back to the running function,
+                // which restarts itself with the new arguments in the same call frame
+                let args = x
+                    .args
+                    .iter()
+                    .map(|expr| self.eval_expr(scope, mods, state, lib, this_ptr, expr, level))
+                    .collect::<Result<_, _>>()?;
+
+                state.tail_call = Some(args);
+                EvalAltResult::Return(Default::default(), (*pos).into()).into()
+            }
+
             // Normal function call
-            Expr::FnCall(x, pos) if x.namespace.is_none() => {
+            Expr::FnCall(x, pos, _) if x.namespace.is_none() => {
                 let FnCallInfo {
                     name,
                     native_only: native,
@@ -1609,7 +1980,7 @@ impl Engine {
             }
 
             // Module-qualified function call
-            Expr::FnCall(x, pos) if x.namespace.is_some() => {
+            Expr::FnCall(x, pos, _) if x.namespace.is_some() => {
                 let FnCallInfo {
                     name,
                     namespace,
@@ -1630,11 +2001,17 @@ impl Engine {
                 self.eval_in_expr(scope, mods, state, lib, this_ptr, &x.lhs, &x.rhs, level)
             }
 
-            Expr::And(x, _) => {
-                Ok((self
+            Expr::And(x, pos) => {
+                let lhs = self
                     .eval_expr(scope, mods, state, lib, this_ptr, &x.lhs, level)?
                     .as_bool()
-                    .map_err(|err| self.make_type_mismatch_err::<bool>(err, x.lhs.position()))?
+                    .map_err(|err| self.make_type_mismatch_err::<bool>(err, x.lhs.position()))?;
+
+                if self.coverage.is_some() {
+                    self.cover_branch(state, *pos, BranchKind::And, lhs);
+                }
+
+                Ok((lhs
                     && // Short-circuit using &&
                 self
                     .eval_expr(scope, mods, state, lib, this_ptr, &x.rhs, level)?
@@ -1643,11 +2020,17 @@ impl Engine {
                 .into())
             }
 
-            Expr::Or(x, _) => {
-                Ok((self
+            Expr::Or(x, pos) => {
+                let lhs = self
                     .eval_expr(scope, mods, state, lib, this_ptr, &x.lhs, level)?
                     .as_bool()
-                    .map_err(|err| self.make_type_mismatch_err::<bool>(err, x.lhs.position()))?
+                    .map_err(|err| self.make_type_mismatch_err::<bool>(err, x.lhs.position()))?;
+
+                if self.coverage.is_some() {
+                    self.cover_branch(state, *pos, BranchKind::Or, !lhs);
+                }
+
+                Ok((lhs
                     || // Short-circuit using ||
                 self
                     .eval_expr(scope, mods, state, lib, this_ptr, &x.rhs, level)?
@@ -1658,9 +2041,9 @@ impl Engine {
 
             Expr::True(_) => Ok(true.into()),
             Expr::False(_) => Ok(false.into()),
-            Expr::Unit(_) => Ok(().into()),
+            Expr::Unit(_, _) => Ok(().into()),
 
-            Expr::Custom(custom, _) => {
+            Expr::Custom(custom, _, _) => {
                 let expressions = custom
                     .keywords()
                     .iter()
@@ -1697,6 +2080,8 @@ impl Engine {
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         let prev_scope_len = scope.len();
         let prev_mods_len = mods.len();
+        let prev_always_search = state.always_search;
+        let prev_eval_vars_len = state.eval_vars.len();
         state.scope_level += 1;
 
         let result = statements
@@ -1710,20 +2095,37 @@ impl Engine {
         state.scope_level -= 1;
 
         // The impact of an eval statement goes away at the end of a block
-        // because any new variables introduced will go out of scope
-        state.always_search = false;
+        // because any new variables introduced will go out of scope.
+        // An eval in an enclosing block still affects the rest of that block.
+        state.always_search = prev_always_search;
+        state.eval_vars.truncate(prev_eval_vars_len);
 
         result
     }
 
     /// Evaluate a statement
+    pub(crate) fn eval_stmt(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        stmt: &Stmt,
+        level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        self.eval_stmt_raw(scope, mods, state, lib, this_ptr, stmt, level)
+            .map_err(|err| err.fill_span(stmt.span()))
+    }
+
+    /// Evaluate a statement, without recording its `Span` in any error.
     ///
     ///
     /// # Safety
     ///
     /// This method uses some unsafe code, mainly for avoiding cloning of local variable names via
     /// direct lifetime casting.
-    pub(crate) fn eval_stmt(
+    fn eval_stmt_raw(
         &self,
         scope: &mut Scope,
         mods: &mut Imports,
@@ -1733,27 +2135,57 @@ impl Engine {
         stmt: &Stmt,
         level: usize,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
+        // Source names only mark where the statements of a script start - they are not counted
+        // as operations nor seen by the profiler, coverage and debugger
+        if let Stmt::Source(source) = stmt {
+            state.source = source.clone();
+            return Ok(Default::default());
+        }
+
         self.inc_operations(state)
             .map_err(|err| err.fill_position(stmt.position()))?;
 
+        #[cfg(not(feature = "unchecked"))]
+        self.check_stack_size()
+            .map_err(|err| err.fill_position(stmt.position()))?;
+
+        // Blocks are not statements that the profiler and debugger stop at - only the statements inside
+        #[cfg(not(feature = "no_std"))]
+        if self.profiler.is_some() && !matches!(stmt, Stmt::Block(_, _) | Stmt::Noop(_)) {
+            self.profile_statement(state, stmt.position());
+        }
+
+        if self.coverage.is_some() {
+            self.cover_statement(state, stmt);
+        }
+
+        if self.debugger.is_some() && !matches!(stmt, Stmt::Block(_, _) | Stmt::Noop(_)) {
+            self.debug_statement(scope, mods, state, lib, this_ptr, stmt.position(), level)?;
+        }
+
         let result = match stmt {
-            // No-op
-            Stmt::Noop(_) => Ok(Default::default()),
+            // No-op, or source names handled above
+            Stmt::Noop(_) | Stmt::Source(_) => Ok(Default::default()),
 
             // Expression as statement
             Stmt::Expr(expr) => self.eval_expr(scope, mods, state, lib, this_ptr, expr, level),
 
             // var op= rhs
             Stmt::Assignment(x, op_pos) if x.0.get_variable_access(false).is_some() => {
-                let (lhs_expr, op, rhs_expr) = x.as_ref();
+                let (lhs_expr, op, rhs_expr, var_type) = x.as_ref();
                 let mut rhs_val = self
                     .eval_expr(scope, mods, state, lib, this_ptr, rhs_expr, level)?
                     .flatten();
-                let (mut lhs_ptr, name, typ, pos) =
+                let (mut lhs_ptr, name, typ, scope_var_type, pos) =
                     self.search_namespace(scope, mods, state, lib, this_ptr, lhs_expr)?;
 
+                // Variables declared outside of this script (e.g. before an `eval`)
+                // keep their type annotations in the scope
+                let var_type = var_type.as_ref().or(scope_var_type);
+
                 if !lhs_ptr.is_ref() {
-                    return EvalAltResult::ErrorAssignmentToConstant(name.to_string(), pos).into();
+                    return EvalAltResult::ErrorAssignmentToConstant(name.to_string(), pos.into())
+                        .into();
                 }
 
                 self.inc_operations(state)
@@ -1762,10 +2194,13 @@ impl Engine {
                 match typ {
                     // Assignment to constant variable
                     ScopeEntryType::Constant => Err(Box::new(
-                        EvalAltResult::ErrorAssignmentToConstant(name.to_string(), pos),
+                        EvalAltResult::ErrorAssignmentToConstant(name.to_string(), pos.into()),
                     )),
                     // Normal assignment
                     ScopeEntryType::Normal if op.is_empty() => {
+                        if let Some(var_type) = var_type {
+                            self.check_type_annotation(&rhs_val, var_type, rhs_expr.position())?;
+                        }
                         if cfg!(not(feature = "no_closure")) && lhs_ptr.is_shared() {
                             *lhs_ptr.as_mut().write_lock::<Dynamic>().unwrap() = rhs_val;
                         } else {
@@ -1803,12 +2238,23 @@ impl Engine {
 
                                 let args = &mut [lhs_ptr_inner, &mut rhs_val];
 
+                                let debugger = self.lend_debugger(state, level);
+                                let context = NativeCallContext::new_with_debugger(
+                                    self,
+                                    lib,
+                                    debugger.as_ref().map(LentDebugger::cell),
+                                );
+
                                 // Overriding exact implementation
-                                if func.is_plugin_fn() {
-                                    func.get_plugin_fn().call((self, lib).into(), args)?;
+                                let result = if func.is_plugin_fn() {
+                                    func.get_plugin_fn().call(context, args)
                                 } else {
-                                    func.get_native_fn()((self, lib).into(), args)?;
-                                }
+                                    func.get_native_fn()(context, args)
+                                };
+
+                                self.return_debugger(state, debugger);
+
+                                result?;
                             }
                             // Built-in op-assignment function
                             _ if run_builtin_op_assignment(op, lhs_ptr.as_mut(), &rhs_val)?
@@ -1837,6 +2283,11 @@ impl Engine {
                                 }
                             }
                         }
+
+                        // The type of the variable may have changed
+                        if let Some(var_type) = var_type {
+                            self.check_type_annotation(lhs_ptr.as_mut(), var_type, *op_pos)?;
+                        }
                         Ok(Default::default())
                     }
                 }
@@ -1844,7 +2295,7 @@ impl Engine {
 
             // lhs op= rhs
             Stmt::Assignment(x, op_pos) => {
-                let (lhs_expr, op, rhs_expr) = x.as_ref();
+                let (lhs_expr, op, rhs_expr, _) = x.as_ref();
                 let mut rhs_val =
                     self.eval_expr(scope, mods, state, lib, this_ptr, rhs_expr, level)?;
 
@@ -1875,7 +2326,7 @@ impl Engine {
                     Expr::Variable(_) => unreachable!(),
                     // idx_lhs[idx_expr] op= rhs
                     #[cfg(not(feature = "no_index"))]
-                    Expr::Index(_, _) => {
+                    Expr::Index(_, _, _) => {
                         self.eval_dot_index_chain(
                             scope, mods, state, lib, this_ptr, lhs_expr, level, _new_val,
                         )?;
@@ -1900,12 +2351,16 @@ impl Engine {
             }
 
             // If-else statement
-            Stmt::IfThenElse(expr, x, _) => {
+            Stmt::IfThenElse(expr, x, pos) => {
                 let (if_block, else_block) = x.as_ref();
                 self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
                     .as_bool()
                     .map_err(|err| self.make_type_mismatch_err::<bool>(err, expr.position()))
                     .and_then(|guard_val| {
+                        if self.coverage.is_some() {
+                            self.cover_branch(state, *pos, BranchKind::If, guard_val);
+                        }
+
                         if guard_val {
                             self.eval_stmt(scope, mods, state, lib, this_ptr, if_block, level)
                         } else if let Some(stmt) = else_block {
@@ -1953,7 +2408,7 @@ impl Engine {
 
             // For loop
             Stmt::For(expr, x, _) => {
-                let (name, stmt) = x.as_ref();
+                let (Ident { name, .. }, stmt) = x.as_ref();
                 let iter_obj = self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?;
                 let iter_type = iter_obj.type_id();
 
@@ -1974,7 +2429,7 @@ impl Engine {
                     state.scope_level += 1;
 
                     for iter_value in func(iter_obj) {
-                        let (loop_var, _) = scope.get_mut(index);
+                        let (loop_var, _, _) = scope.get_mut(index);
 
                         let value = iter_value.flatten();
                         if cfg!(not(feature = "no_closure")) && loop_var.is_shared() {
@@ -2000,97 +2455,129 @@ impl Engine {
                     scope.rewind(scope.len() - 1);
                     Ok(Default::default())
                 } else {
-                    EvalAltResult::ErrorFor(expr.position()).into()
+                    EvalAltResult::ErrorFor(expr.position().into()).into()
                 }
             }
 
             // Continue statement
-            Stmt::Continue(pos) => EvalAltResult::LoopBreak(false, *pos).into(),
+            Stmt::Continue(pos) => EvalAltResult::LoopBreak(false, (*pos).into()).into(),
 
             // Break statement
-            Stmt::Break(pos) => EvalAltResult::LoopBreak(true, *pos).into(),
+            Stmt::Break(pos) => EvalAltResult::LoopBreak(true, (*pos).into()).into(),
 
             // Try/Catch statement
-            Stmt::TryCatch(x, _, _) => {
-                let (try_body, var_def, catch_body) = x.as_ref();
+            Stmt::TryCatch(x, pos, _) => {
+                let (try_body, var_def, _stack_var_def, catch_body, finally_body) = x.as_ref();
 
                 let result = self
                     .eval_stmt(scope, mods, state, lib, this_ptr, try_body, level)
                     .map(|_| ().into());
 
-                match result {
+                if self.coverage.is_some() {
+                    let caught = matches!(result, Err(ref err) if err.is_catchable());
+                    self.cover_branch(state, *pos, BranchKind::TryCatch, !caught);
+                }
+
+                let result = match result {
                     Ok(_) => result,
-                    Err(err) => match *err {
-                        mut err @ EvalAltResult::ErrorRuntime(_, _) | mut err
-                            if err.is_catchable() =>
-                        {
-                            let value = if let EvalAltResult::ErrorRuntime(ref x, _) = err {
-                                x.clone()
+                    // `return`, `break`, `continue` and errors that cannot be caught
+                    Err(err) if !err.is_catchable() => Err(err),
+                    Err(err) => {
+                        #[cfg(not(feature = "no_index"))]
+                        let call_stack: Option<Array> = _stack_var_def
+                            .as_ref()
+                            .map(|_| err.call_stack().into_iter().map(Into::into).collect());
+
+                        // The error is caught in the same source it occurs in
+                        let err = err.fill_source(&state.source);
+                        let value = err.catch_value();
+
+                        let orig_scope_len = scope.len();
+                        state.scope_level += 1;
+
+                        if let Some(Ident { name, .. }) = var_def {
+                            let var_name: Cow<'_, str> = if state.is_global() {
+                                name.clone().into()
                             } else {
-                                err.set_position(NO_POS);
-                                err.to_string().into()
+                                unsafe_cast_var_name_to_lifetime(name).into()
                             };
+                            scope.push(var_name, value);
+                        }
 
-                            let orig_scope_len = scope.len();
-                            state.scope_level += 1;
+                        #[cfg(not(feature = "no_index"))]
+                        if let (Some(Ident { name, .. }), Some(call_stack)) =
+                            (_stack_var_def, call_stack)
+                        {
+                            let var_name: Cow<'_, str> = if state.is_global() {
+                                name.clone().into()
+                            } else {
+                                unsafe_cast_var_name_to_lifetime(name).into()
+                            };
+                            scope.push(var_name, call_stack);
+                        }
 
-                            if let Some(Ident { name, .. }) = var_def {
-                                let var_name: Cow<'_, str> = if state.is_global() {
-                                    name.clone().into()
-                                } else {
-                                    unsafe_cast_var_name_to_lifetime(name).into()
-                                };
-                                scope.push(var_name, value);
-                            }
+                        let result = self
+                            .eval_stmt(scope, mods, state, lib, this_ptr, catch_body, level)
+                            .map(|_| ().into());
 
-                            let mut result = self
-                                .eval_stmt(scope, mods, state, lib, this_ptr, catch_body, level)
-                                .map(|_| ().into());
+                        state.scope_level -= 1;
+                        scope.rewind(orig_scope_len);
 
-                            if let Some(result_err) = result.as_ref().err() {
-                                if let EvalAltResult::ErrorRuntime(Dynamic(Union::Unit(_)), pos) =
-                                    result_err.as_ref()
-                                {
-                                    err.set_position(*pos);
-                                    result = Err(Box::new(err));
-                                }
+                        match result {
+                            // `throw;` re-throws the original error unchanged
+                            Err(result_err) if matches!(*result_err, EvalAltResult::Rethrow(_)) => {
+                                Err(err)
                             }
-
-                            state.scope_level -= 1;
-                            scope.rewind(orig_scope_len);
-
-                            result
+                            result => result,
                         }
-                        _ => Err(err),
-                    },
+                    }
+                };
+
+                match finally_body {
+                    // System exceptions abort the script without running the `finally` block
+                    Some(_) if matches!(result, Err(ref err) if err.is_system_exception()) => {
+                        result
+                    }
+                    // Errors, `return`, `break` and `continue` in the `finally` block
+                    // override the result of the `try` and `catch` blocks
+                    Some(finally_body) => self
+                        .eval_stmt(scope, mods, state, lib, this_ptr, finally_body, level)
+                        .and(result),
+                    None => result,
                 }
             }
 
             // Return value
             Stmt::ReturnWithVal((ReturnType::Return, pos), Some(expr), _) => EvalAltResult::Return(
                 self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?,
-                *pos,
+                (*pos).into(),
             )
             .into(),
 
             // Empty return
             Stmt::ReturnWithVal((ReturnType::Return, pos), None, _) => {
-                EvalAltResult::Return(Default::default(), *pos).into()
+                EvalAltResult::Return(Default::default(), (*pos).into()).into()
             }
 
             // Throw value
             Stmt::ReturnWithVal((ReturnType::Exception, pos), Some(expr), _) => {
                 let val = self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?;
-                EvalAltResult::ErrorRuntime(val, *pos).into()
+                EvalAltResult::ErrorRuntime(val, (*pos).into()).into()
             }
 
             // Empty throw
             Stmt::ReturnWithVal((ReturnType::Exception, pos), None, _) => {
-                EvalAltResult::ErrorRuntime(().into(), *pos).into()
+                EvalAltResult::ErrorRuntime(().into(), (*pos).into()).into()
+            }
+
+            // Re-throw the error caught by the enclosing catch block
+            Stmt::ReturnWithVal((ReturnType::Rethrow, pos), _, _) => {
+                EvalAltResult::Rethrow((*pos).into()).into()
             }
 
             // Let/const statement
-            Stmt::Let(var_def, expr, _) | Stmt::Const(var_def, expr, _) => {
+            Stmt::Let(x, expr, _) | Stmt::Const(x, expr, _) => {
+                let (var_def, typ) = x.as_ref();
                 let entry_type = match stmt {
                     Stmt::Let(_, _, _) => ScopeEntryType::Normal,
                     Stmt::Const(_, _, _) => ScopeEntryType::Constant,
@@ -2098,8 +2585,15 @@ impl Engine {
                 };
 
                 let val = if let Some(expr) = expr {
-                    self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
-                        .flatten()
+                    let val = self
+                        .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+                        .flatten();
+
+                    if let Some(typ) = typ {
+                        self.check_type_annotation(&val, &typ.name, expr.position())?;
+                    }
+
+                    val
                 } else {
                     ().into()
                 };
@@ -2109,6 +2603,9 @@ impl Engine {
                     unsafe_cast_var_name_to_lifetime(&var_def.name).into()
                 };
                 scope.push_dynamic_value(var_name, entry_type, val);
+                if let Some(typ) = typ {
+                    scope.set_entry_annotation(scope.len() - 1, typ.name.clone());
+                }
                 Ok(Default::default())
             }
 
@@ -2118,7 +2615,7 @@ impl Engine {
                 // Guard against too many modules
                 #[cfg(not(feature = "unchecked"))]
                 if state.modules >= self.max_modules() {
-                    return EvalAltResult::ErrorTooManyModules(*_pos).into();
+                    return EvalAltResult::ErrorTooManyModules((*_pos).into()).into();
                 }
 
                 if let Some(path) = self
@@ -2137,10 +2634,11 @@ impl Engine {
 
                         Ok(Default::default())
                     } else {
-                        Err(
-                            EvalAltResult::ErrorModuleNotFound(path.to_string(), expr.position())
-                                .into(),
+                        Err(EvalAltResult::ErrorModuleNotFound(
+                            path.to_string(),
+                            expr.position().into(),
                         )
+                        .into())
                     }
                 } else {
                     Err(self.make_type_mismatch_err::<ImmutableString>("", expr.position()))
@@ -2156,7 +2654,8 @@ impl Engine {
                         let alias = rename.as_ref().map(|x| &x.name).unwrap_or_else(|| name);
                         scope.set_entry_alias(index, alias.clone());
                     } else {
-                        return EvalAltResult::ErrorVariableNotFound(name.into(), *id_pos).into();
+                        return EvalAltResult::ErrorVariableNotFound(name.into(), (*id_pos).into())
+                            .into();
                     }
                 }
                 Ok(Default::default())
@@ -2165,16 +2664,20 @@ impl Engine {
             // Share statement
             #[cfg(not(feature = "no_closure"))]
             Stmt::Share(x) => {
-                match scope.get_index(&x.name) {
-                    Some((index, ScopeEntryType::Normal)) => {
-                        let (val, _) = scope.get_mut(index);
+                let (Ident { name, .. }, index) = x.as_ref();
 
-                        if !val.is_shared() {
-                            // Replace the variable with a shared value.
-                            *val = mem::take(val).into_shared();
-                        }
+                let index = match index {
+                    Some(offset) => Some(state.scope_index(scope, name, offset.get())),
+                    None => scope.get_index(name).map(|(index, _)| index),
+                };
+
+                if let Some(index) = index {
+                    let (val, typ, _) = scope.get_mut(index);
+
+                    if typ == ScopeEntryType::Normal && !val.is_shared() {
+                        // Replace the variable with a shared value.
+                        *val = mem::take(val).into_shared();
                     }
-                    _ => (),
                 }
                 Ok(Default::default())
             }
@@ -2289,18 +2792,23 @@ impl Engine {
         let (_arr, _map, s) = calc_size(result.as_ref().unwrap());
 
         if s > self.max_string_size() {
-            return EvalAltResult::ErrorDataTooLarge("Length of string".to_string(), NO_POS).into();
+            return EvalAltResult::ErrorDataTooLarge("Length of string".to_string(), NO_POS.into())
+                .into();
         }
 
         #[cfg(not(feature = "no_index"))]
         if _arr > self.max_array_size() {
-            return EvalAltResult::ErrorDataTooLarge("Size of array".to_string(), NO_POS).into();
+            return EvalAltResult::ErrorDataTooLarge("Size of array".to_string(), NO_POS.into())
+                .into();
         }
 
         #[cfg(not(feature = "no_object"))]
         if _map > self.max_map_size() {
-            return EvalAltResult::ErrorDataTooLarge("Size of object map".to_string(), NO_POS)
-                .into();
+            return EvalAltResult::ErrorDataTooLarge(
+                "Size of object map".to_string(),
+                NO_POS.into(),
+            )
+            .into();
         }
 
         result
@@ -2314,20 +2822,32 @@ impl Engine {
         #[cfg(not(feature = "unchecked"))]
         // Guard against too many operations
         if self.max_operations() > 0 && state.operations > self.max_operations() {
-            return EvalAltResult::ErrorTooManyOperations(NO_POS).into();
+            return EvalAltResult::ErrorTooManyOperations(NO_POS.into()).into();
         }
 
         // Report progress - only in steps
         if let Some(progress) = &self.progress {
             if let Some(token) = progress(&state.operations) {
                 // Terminate script if progress returns a termination token
-                return EvalAltResult::ErrorTerminated(token, NO_POS).into();
+                return EvalAltResult::ErrorTerminated(token, NO_POS.into()).into();
             }
         }
 
         Ok(())
     }
 
+    /// Check if the native stack used stays within limit.
+    /// Position in `EvalAltResult` is `None` and must be set afterwards.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub(crate) fn check_stack_size(&self) -> Result<(), Box<EvalAltResult>> {
+        if self.max_stack_size() > 0 && StackGuard::stack_used() > self.max_stack_size() {
+            EvalAltResult::ErrorStackOverflow(NO_POS.into()).into()
+        } else {
+            Ok(())
+        }
+    }
+
     /// Map a type_name into a pretty-print name
     #[inline(always)]
     pub(crate) fn map_type_name<'a>(&'a self, name: &'a str) -> &'a str {
@@ -2337,14 +2857,190 @@ impl Engine {
             .unwrap_or_else(|| map_std_type_name(name))
     }
 
+    /// Map a type name in a type annotation into a pretty-print name.
+    ///
+    /// `int` and `float` are aliases of the integer and floating-point types.
+    #[inline(always)]
+    pub(crate) fn map_annotated_type_name<'a>(&'a self, name: &'a str) -> &'a str {
+        match name {
+            "int" => self.map_type_name(type_name::<crate::INT>()),
+            #[cfg(not(feature = "no_float"))]
+            "float" => self.map_type_name(type_name::<crate::FLOAT>()),
+            _ => name,
+        }
+    }
+
+    /// Is a type name valid in a type annotation?
+    pub(crate) fn is_known_type_name(&self, name: &str) -> bool {
+        let name = self.map_annotated_type_name(name);
+
+        let std_types = [
+            "bool",
+            "char",
+            "string",
+            "Fn",
+            #[cfg(not(feature = "no_index"))]
+            "array",
+            #[cfg(not(feature = "no_object"))]
+            "map",
+            #[cfg(not(feature = "no_std"))]
+            "timestamp",
+        ];
+
+        #[cfg(not(feature = "no_float"))]
+        if name == self.map_type_name(type_name::<crate::FLOAT>()) {
+            return true;
+        }
+
+        std_types.contains(&name)
+            || name == self.map_type_name(type_name::<crate::INT>())
+            || self.type_names.values().any(|typ| typ == name)
+    }
+
+    /// Does a value match a type annotation?
+    #[inline(always)]
+    pub(crate) fn is_value_of_type(&self, value: &Dynamic, name: &str) -> bool {
+        self.map_type_name(value.type_name()) == self.map_annotated_type_name(name)
+    }
+
+    /// Check a value against a type annotation.
+    #[inline]
+    pub(crate) fn check_type_annotation(
+        &self,
+        value: &Dynamic,
+        name: &str,
+        pos: Position,
+    ) -> Result<(), Box<EvalAltResult>> {
+        if self.is_value_of_type(value, name) {
+            Ok(())
+        } else {
+            EvalAltResult::ErrorMismatchDataType(
+                self.map_type_name(value.type_name()).into(),
+                self.map_annotated_type_name(name).into(),
+                pos.into(),
+            )
+            .into()
+        }
+    }
+
     /// Make a Box<EvalAltResult<ErrorMismatchDataType>>.
     #[inline(always)]
     pub(crate) fn make_type_mismatch_err<T>(&self, typ: &str, pos: Position) -> Box<EvalAltResult> {
         EvalAltResult::ErrorMismatchDataType(
             typ.into(),
             self.map_type_name(type_name::<T>()).into(),
-            pos,
+            pos.into(),
         )
         .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::dynamic::{Dynamic, Union};
+    use crate::fn_native::Shared;
+    use crate::{Engine, Scope, INT};
+
+    /// Reading through a chain of nested arrays and maps must not un-share their storage.
+    #[test]
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_object"))]
+    fn check_read_only_chain_keeps_sharing() {
+        let engine = Engine::new();
+        let mut scope = Scope::new();
+
+        let is_shared =
+            |scope: &mut Scope, value: &Dynamic| match (&scope.get_mut(0).0 .0, &value.0) {
+                (Union::Array(a), Union::Array(b)) => Shared::ptr_eq(a, b),
+                _ => unreachable!(),
+            };
+
+        let value: Dynamic = engine.eval("[1, [2, #{a: [3]}]]").unwrap();
+        scope.push_dynamic("x", value.clone());
+
+        assert_eq!(
+            engine
+                .eval_with_scope::<INT>(&mut scope, "x[1][0]")
+                .unwrap(),
+            2
+        );
+        assert!(is_shared(&mut scope, &value));
+        assert_eq!(
+            engine
+                .eval_with_scope::<INT>(&mut scope, "x[1][1].a[0]")
+                .unwrap(),
+            3
+        );
+        assert!(is_shared(&mut scope, &value));
+        assert!(engine
+            .eval_with_scope::<bool>(&mut scope, "1 in x")
+            .unwrap());
+        assert!(is_shared(&mut scope, &value));
+
+        // Methods which only read their object do not copy it
+        assert_eq!(
+            engine
+                .eval_with_scope::<INT>(&mut scope, "x.len()")
+                .unwrap(),
+            2
+        );
+        assert!(is_shared(&mut scope, &value));
+        #[cfg(not(feature = "no_function"))]
+        assert_eq!(
+            engine
+                .eval_with_scope::<INT>(&mut scope, "fn count(a) { a.len() + len(a) } count(x)")
+                .unwrap(),
+            4
+        );
+        assert!(is_shared(&mut scope, &value));
+
+        // Other method calls may modify their object
+        engine
+            .eval_with_scope::<()>(&mut scope, "x[1][1].a.push(4)")
+            .unwrap();
+        assert!(!is_shared(&mut scope, &value));
+    }
+
+    /// Variables defined before an `eval` are still accessed via their offsets, so only the
+    /// variables defined by the `eval` itself are compared by name.
+    #[test]
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_std"))]
+    fn check_eval_keeps_offsets() {
+        use crate::scope::tests::take_name_comparisons;
+
+        let engine = Engine::new();
+
+        let script = |eval| {
+            format!(
+                r#"
+                    fn sum(n, a, b, c) {{
+                        let x1 = 0; let x2 = 0; let x3 = 0; let x4 = 0; let x5 = 0;
+                        let x6 = 0; let x7 = 0; let x8 = 0; let x9 = 0; let x10 = 0;
+                        let sum = 0;
+                        {}
+                        for i in range(0, n) {{
+                            sum += a + b + c;
+                        }}
+                        sum
+                    }}
+                    sum(100, 1, 2, 3)
+                "#,
+                eval
+            )
+        };
+
+        take_name_comparisons();
+        assert_eq!(engine.eval::<INT>(&script("")).unwrap(), 600);
+        assert_eq!(take_name_comparisons(), 0);
+
+        assert_eq!(
+            engine
+                .eval::<INT>(&script(r#"eval("let z = 0;");"#))
+                .unwrap(),
+            600
+        );
+        // Each of the 4 variables accessed in the loop is compared with `z` only
+        assert_eq!(take_name_comparisons(), 4 * 100 + 2);
+    }
+}
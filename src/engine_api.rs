@@ -1,20 +1,20 @@
 //! Module that defines the extern API of `Engine`.
 
-use crate::ast::AST;
+use crate::ast::{FnAccess, AST};
+use crate::debugger::{Debugger, DebuggerCommand, DebuggerEvent};
 use crate::dynamic::{Dynamic, Variant};
 use crate::engine::{Engine, EvalContext, Imports};
 use crate::fn_native::{FnCallArgs, NativeCallContext, SendSync};
+use crate::module::FuncInfo;
 use crate::optimize::OptimizationLevel;
 use crate::parse_error::ParseError;
 use crate::result::EvalAltResult;
 use crate::scope::Scope;
-use crate::token::{Position, NO_POS};
+use crate::token::{Position, TokenStream, NO_POS};
+use crate::utils::ImmutableString;
 
 #[cfg(not(feature = "no_index"))]
-use crate::{
-    engine::{Array, FN_IDX_GET, FN_IDX_SET},
-    utils::ImmutableString,
-};
+use crate::engine::{Array, FN_IDX_GET, FN_IDX_SET};
 
 #[cfg(not(feature = "no_object"))]
 use crate::{
@@ -27,7 +27,17 @@ use crate::{
 use crate::fn_register::{RegisterFn, RegisterResultFn};
 
 #[cfg(not(feature = "no_function"))]
-use crate::{fn_args::FuncArgs, fn_call::ensure_no_data_race, module::Module, StaticVec};
+use crate::{
+    calc_script_fn_hash, fn_args::FuncArgs, fn_call::ensure_no_data_race,
+    fn_native::CallableFunction, module::Module, StaticVec,
+};
+
+#[cfg(not(feature = "no_function"))]
+use crate::stdlib::iter::empty;
+
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "unchecked"))]
+use crate::engine::StackGuard;
 
 #[cfg(not(feature = "no_optimize"))]
 use crate::optimize::optimize_into_ast;
@@ -36,6 +46,7 @@ use crate::stdlib::{
     any::{type_name, TypeId},
     boxed::Box,
     string::String,
+    vec::Vec,
 };
 
 #[cfg(not(feature = "no_optimize"))]
@@ -896,19 +907,158 @@ impl Engine {
         scope: &Scope,
         scripts: &[&str],
     ) -> Result<AST, ParseError> {
-        self.compile_with_scope_and_optimization_level(scope, scripts, self.optimization_level)
+        self.compile_with_scope_and_optimization_level(
+            scope,
+            scripts,
+            self.optimization_level,
+            self.strict_mode,
+        )
     }
 
-    /// Join a list of strings and compile into an `AST` using own scope at a specific optimization level.
+    /// Join a list of strings and compile into an `AST` using own scope at a specific optimization level,
+    /// optionally checking variables and functions as in strict mode.
     #[inline(always)]
     pub(crate) fn compile_with_scope_and_optimization_level(
         &self,
         scope: &Scope,
         scripts: &[&str],
         optimization_level: OptimizationLevel,
+        strict: bool,
     ) -> Result<AST, ParseError> {
         let stream = self.lex(scripts, None);
-        self.parse(&mut stream.peekable(), scope, optimization_level)
+        self.parse(
+            &mut TokenStream::new(stream),
+            scope,
+            optimization_level,
+            strict,
+        )
+    }
+
+    /// Compile a string into an `AST`, giving it the name of its source
+    /// (e.g. the path of the script file).
+    ///
+    /// Errors in the script (including errors in functions defined in it) carry the source name,
+    /// which is available via `ParseError::source` and `EvalAltResult::source`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let err = engine.compile_with_source("greet.rhai", "let x = ;").expect_err("should error");
+    ///
+    /// assert_eq!(err.source(), Some("greet.rhai"));
+    /// assert_eq!(err.to_string(), "Unexpected ';' (line 1, position 9 in 'greet.rhai')");
+    /// ```
+    #[inline(always)]
+    pub fn compile_with_source(&self, source: &str, script: &str) -> Result<AST, ParseError> {
+        self.compile_sources_with_scope(&Default::default(), &[(source, script)])
+    }
+
+    /// Compile a list of named scripts into one `AST` using own scope, giving the statements and
+    /// functions of each script the name of its source.
+    ///
+    /// Each script is a tuple of (source name, script text).
+    ///
+    /// Unlike `compile_scripts_with_scope`, each script is parsed separately and must be a
+    /// complete script on its own. Line numbers restart from one for each script.
+    /// The resulting `AST`'s are combined in order, so functions in later scripts overwrite
+    /// functions with the same name and number of parameters in earlier scripts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, EvalAltResult, Scope};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile_sources_with_scope(&Scope::new(), &[
+    ///                 ("init.rhai", "let x = 40;"),
+    ///                 ("main.rhai", "x += 2;\nx + y")
+    ///           ])?;
+    ///
+    /// let err = engine.eval_ast::<i64>(&ast).expect_err("should error");
+    ///
+    /// assert!(matches!(*err, EvalAltResult::ErrorVariableNotFound(_, _)));
+    /// assert_eq!(err.source(), Some("main.rhai"));
+    /// assert_eq!(err.position().line(), Some(2));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compile_sources_with_scope(
+        &self,
+        scope: &Scope,
+        sources: &[(&str, &str)],
+    ) -> Result<AST, ParseError> {
+        let mut ast = AST::default();
+
+        for (source, script) in sources {
+            let scripts = [*script];
+            let stream = self.lex_with_source(&scripts, Some(source), None);
+            ast.combine(self.parse(
+                &mut TokenStream::new(stream),
+                scope,
+                self.optimization_level,
+                self.strict_mode,
+            )?);
+        }
+
+        Ok(ast)
+    }
+
+    /// Compile a string into an `AST`, recovering from syntax errors in order to report
+    /// all of them at once instead of stopping at the first.
+    ///
+    /// After each syntax error, parsing resumes at the next statement boundary.
+    ///
+    /// Returns an `AST` containing all the statements and functions that are parsed successfully,
+    /// together with a list of all syntax errors found, in order of occurrence.
+    /// The script is valid only if this list is empty; otherwise, the `AST` is partial
+    /// and mostly useful for tooling.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let (ast, errors) = engine.compile_with_errors(r"
+    ///     let x = 1 +;
+    ///     let y = 42;
+    ///     let z = (;
+    /// ");
+    ///
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[0].position().line(), Some(2));
+    /// assert_eq!(errors[1].position().line(), Some(4));
+    ///
+    /// // The AST contains the statement parsed successfully
+    /// let mut scope = rhai::Scope::new();
+    /// engine.consume_ast_with_scope(&mut scope, &ast).unwrap();
+    /// assert_eq!(scope.get_value::<i64>("y"), Some(42));
+    /// ```
+    #[inline(always)]
+    pub fn compile_with_errors(&self, script: &str) -> (AST, Vec<ParseError>) {
+        self.compile_with_scope_and_errors(&Default::default(), script)
+    }
+
+    /// Compile a string into an `AST` using own scope, recovering from syntax errors in order to
+    /// report all of them at once instead of stopping at the first.
+    ///
+    /// See [`compile_with_errors`][Engine::compile_with_errors] for details.
+    #[inline(always)]
+    pub fn compile_with_scope_and_errors(
+        &self,
+        scope: &Scope,
+        script: &str,
+    ) -> (AST, Vec<ParseError>) {
+        let scripts = [script];
+        let mut stream = TokenStream::new(self.lex(&scripts, None));
+        self.parse_with_errors(&mut stream, scope, self.optimization_level, None)
     }
 
     /// Read the contents of a file into a string.
@@ -1001,7 +1151,11 @@ impl Engine {
         scope: &Scope,
         path: PathBuf,
     ) -> Result<AST, Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| Ok(self.compile_with_scope(scope, &contents)?))
+        let source = path.to_string_lossy().into_owned();
+
+        Self::read_file(path).and_then(|contents| {
+            Ok(self.compile_sources_with_scope(scope, &[(&source, &contents)])?)
+        })
     }
 
     /// Parse a JSON string into a map.
@@ -1057,7 +1211,10 @@ impl Engine {
                 Token::LeftBrace.syntax().into(),
                 "to start a JSON object hash".into(),
             )
-            .into_err(Position::new(1, (json.len() - json_text.len() + 1) as u16))
+            .into_err(
+                Position::try_new(1, (json.len() - json_text.len() + 1) as u32)
+                    .unwrap_or(Position::NONE),
+            )
             .into());
         };
 
@@ -1073,8 +1230,12 @@ impl Engine {
                 None
             },
         );
-        let ast =
-            self.parse_global_expr(&mut stream.peekable(), &scope, OptimizationLevel::None)?;
+        let ast = self.parse_global_expr(
+            &mut TokenStream::new(stream),
+            &scope,
+            OptimizationLevel::None,
+            false,
+        )?;
 
         // Handle null - map to ()
         if has_null {
@@ -1157,8 +1318,13 @@ impl Engine {
         let scripts = [script];
         let stream = self.lex(&scripts, None);
         {
-            let mut peekable = stream.peekable();
-            self.parse_global_expr(&mut peekable, scope, self.optimization_level)
+            let mut peekable = TokenStream::new(stream);
+            self.parse_global_expr(
+                &mut peekable,
+                scope,
+                self.optimization_level,
+                self.strict_mode,
+            )
         }
     }
 
@@ -1181,7 +1347,7 @@ impl Engine {
     #[cfg(not(target_arch = "wasm32"))]
     #[inline(always)]
     pub fn eval_file<T: Variant + Clone>(&self, path: PathBuf) -> Result<T, Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.eval::<T>(&contents))
+        self.eval_file_with_scope(&mut Default::default(), path)
     }
 
     /// Evaluate a script file with own scope.
@@ -1211,7 +1377,8 @@ impl Engine {
         scope: &mut Scope,
         path: PathBuf,
     ) -> Result<T, Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.eval_with_scope::<T>(scope, &contents))
+        let ast = self.compile_file_with_scope(scope, path)?;
+        self.eval_ast_with_scope(scope, &ast)
     }
 
     /// Evaluate a string.
@@ -1265,6 +1432,7 @@ impl Engine {
             scope,
             &[script],
             self.optimization_level,
+            self.strict_mode,
         )?;
         self.eval_ast_with_scope(scope, &ast)
     }
@@ -1319,7 +1487,12 @@ impl Engine {
         let stream = self.lex(&scripts, None);
 
         // No need to optimize a lone expression
-        let ast = self.parse_global_expr(&mut stream.peekable(), scope, OptimizationLevel::None)?;
+        let ast = self.parse_global_expr(
+            &mut TokenStream::new(stream),
+            scope,
+            OptimizationLevel::None,
+            self.strict_mode,
+        )?;
 
         self.eval_ast_with_scope(scope, &ast)
     }
@@ -1391,7 +1564,7 @@ impl Engine {
             EvalAltResult::ErrorMismatchOutputType(
                 self.map_type_name(type_name::<T>()).into(),
                 typ.into(),
-                NO_POS,
+                NO_POS.into(),
             )
             .into()
         });
@@ -1405,7 +1578,7 @@ impl Engine {
         mods: &mut Imports,
         ast: &'a AST,
     ) -> Result<(Dynamic, u64), Box<EvalAltResult>> {
-        self.eval_statements_raw(scope, mods, ast.statements(), &[ast.lib()])
+        self.eval_statements_raw(scope, mods, ast.statements(), &[ast.lib()], None)
     }
 
     /// Evaluate a file, but throw away the result and only return error (if any).
@@ -1414,7 +1587,7 @@ impl Engine {
     #[cfg(not(target_arch = "wasm32"))]
     #[inline(always)]
     pub fn consume_file(&self, path: PathBuf) -> Result<(), Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.consume(&contents))
+        self.consume_file_with_scope(&mut Default::default(), path)
     }
 
     /// Evaluate a file with own scope, but throw away the result and only return error (if any).
@@ -1427,7 +1600,8 @@ impl Engine {
         scope: &mut Scope,
         path: PathBuf,
     ) -> Result<(), Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.consume_with_scope(scope, &contents))
+        let ast = self.compile_file_with_scope(scope, path)?;
+        self.consume_ast_with_scope(scope, &ast)
     }
 
     /// Evaluate a string, but throw away the result and only return error (if any).
@@ -1447,7 +1621,12 @@ impl Engine {
     ) -> Result<(), Box<EvalAltResult>> {
         let scripts = [script];
         let stream = self.lex(&scripts, None);
-        let ast = self.parse(&mut stream.peekable(), scope, self.optimization_level)?;
+        let ast = self.parse(
+            &mut TokenStream::new(stream),
+            scope,
+            self.optimization_level,
+            self.strict_mode,
+        )?;
         self.consume_ast_with_scope(scope, &ast)
     }
 
@@ -1467,7 +1646,7 @@ impl Engine {
         ast: &AST,
     ) -> Result<(), Box<EvalAltResult>> {
         let mut mods = Default::default();
-        self.eval_statements_raw(scope, &mut mods, ast.statements(), &[ast.lib()])
+        self.eval_statements_raw(scope, &mut mods, ast.statements(), &[ast.lib()], None)
             .map(|_| ())
     }
 
@@ -1527,7 +1706,7 @@ impl Engine {
             EvalAltResult::ErrorMismatchOutputType(
                 self.map_type_name(type_name::<T>()).into(),
                 typ.into(),
-                NO_POS,
+                NO_POS.into(),
             )
             .into()
         });
@@ -1615,9 +1794,19 @@ impl Engine {
         this_ptr: &mut Option<&mut Dynamic>,
         args: &mut FnCallArgs,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
+        // Qualifiers (none) + function name + number of arguments.
+        let hash_script = calc_script_fn_hash(empty(), name, args.len());
+        let hash_script = self.resolve_script_fn_overload(&[lib], hash_script, args, true);
+
         let fn_def = lib
-            .get_script_fn(name, args.len(), true)
-            .ok_or_else(|| EvalAltResult::ErrorFunctionNotFound(name.into(), NO_POS))?;
+            .get_fn(hash_script, true)
+            .filter(|f| f.is_script())
+            .map(CallableFunction::get_fn_def)
+            .ok_or_else(|| EvalAltResult::ErrorFunctionNotFound(name.into(), NO_POS.into()))?;
+
+        // Measure native stack usage from here
+        #[cfg(not(feature = "unchecked"))]
+        let _guard = StackGuard::new();
 
         let mut state = Default::default();
         let mut mods = Default::default();
@@ -1715,6 +1904,41 @@ impl Engine {
         self
     }
 
+    /// Declare the name of a variable provided by the variable resolver (see
+    /// [`on_var`][Engine::on_var]), so that scripts using it compile in
+    /// [strict mode][Engine::set_strict_mode].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_strict_mode(true);
+    ///
+    /// engine.on_var(|name, _, _| {
+    ///     match name {
+    ///         "MYSTIC_NUMBER" => Ok(Some(42_i64.into())),
+    ///         _ => Ok(None)
+    ///     }
+    /// });
+    ///
+    /// assert!(engine.compile("MYSTIC_NUMBER").is_err());
+    ///
+    /// engine.declare_var("MYSTIC_NUMBER");
+    ///
+    /// assert_eq!(engine.eval::<i64>("MYSTIC_NUMBER")?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn declare_var(&mut self, name: impl Into<String>) -> &mut Self {
+        self.declared_vars.insert(name.into());
+        self
+    }
+
     /// Register a callback for script evaluation progress.
     ///
     /// # Example
@@ -1758,6 +1982,84 @@ impl Engine {
         self
     }
 
+    /// Register a debugger.
+    ///
+    /// `init` is called at the start of each evaluation run to create the `Debugger`, which holds
+    /// the break-points and keeps track of stepping.
+    ///
+    /// `callback` is called whenever evaluation stops - at a break-point, or at the next statement
+    /// (or expression) when stepping - with the current evaluation context (containing the `Scope` and the `this`
+    /// pointer), the `Debugger`, the reason why evaluation stopped and the current `Position`.
+    ///
+    /// Scripts called back from native functions (e.g. closures passed to `map`) and scripts run
+    /// via `eval` continue with the same `Debugger`.  Scripts evaluated while the debugger is
+    /// paused are not debugged.
+    ///
+    /// ## Return Value of Callback
+    ///
+    /// Return `Ok(DebuggerCommand)` to continue evaluation.
+    ///
+    /// ## Errors in Callback
+    ///
+    /// Return `Err(...)` to abort evaluation with the error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::{BreakPoint, Debugger, DebuggerCommand, Engine};
+    ///
+    /// let result = Arc::new(RwLock::new(Vec::new()));
+    /// let logger = result.clone();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_debugger(
+    ///     // Stop at line 3
+    ///     || Debugger::new(vec![BreakPoint::AtLine { source: None, line: 3 }], DebuggerCommand::Continue),
+    ///     move |context, _, _, pos| {
+    ///         let x = context.scope.get_value::<i64>("x").unwrap();
+    ///         logger.write().unwrap().push((pos.line().unwrap(), x));
+    ///
+    ///         // Modify a variable while paused
+    ///         context.scope.set_value("x", 40_i64);
+    ///
+    ///         // Run until the next break-point
+    ///         Ok(DebuggerCommand::Continue)
+    ///     },
+    /// );
+    ///
+    /// let result2 = engine.eval::<i64>("
+    ///     let x = 1;
+    ///     x += 1;
+    ///     x + 1
+    /// ")?;
+    ///
+    /// assert_eq!(*result.read().unwrap(), vec![(3, 1)]);
+    /// assert_eq!(result2, 42);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn register_debugger(
+        &mut self,
+        init: impl Fn() -> Debugger + SendSync + 'static,
+        callback: impl Fn(
+                &mut EvalContext,
+                &mut Debugger,
+                DebuggerEvent,
+                Position,
+            ) -> Result<DebuggerCommand, Box<EvalAltResult>>
+            + SendSync
+            + 'static,
+    ) -> &mut Self {
+        self.debugger = Some((Box::new(init), Box::new(callback)));
+        self
+    }
+
     /// Override default action of `print` (print to stdout using `println!`)
     ///
     /// # Example
@@ -1817,4 +2119,38 @@ impl Engine {
         self.debug = Box::new(callback);
         self
     }
+
+    /// Generate signatures for all the functions registered in the `Engine`,
+    /// optionally including those in loaded packages, sorted by name.
+    ///
+    /// Parameters of native Rust functions are shown as `_`, e.g. `foo(_, _)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("add", |x: i64, y: i64| x + y);
+    ///
+    /// assert_eq!(engine.gen_fn_signatures(false), vec!["add(_, _)"]);
+    /// assert!(engine.gen_fn_signatures(true).len() > 1);
+    /// ```
+    pub fn gen_fn_signatures(&self, include_packages: bool) -> Vec<String> {
+        let mut signatures: Vec<_> = self.global_module.gen_fn_signatures().collect();
+
+        if include_packages {
+            signatures.extend(
+                self.packages
+                    .iter_fn()
+                    .filter(|f| f.access == FnAccess::Public)
+                    .map(FuncInfo::gen_signature),
+            );
+        }
+
+        signatures.sort();
+        signatures.dedup();
+        signatures
+    }
 }
@@ -1,5 +1,6 @@
 //! Configuration settings for `Engine`.
 
+use crate::coverage::Coverage;
 use crate::engine::Engine;
 use crate::packages::PackageLibrary;
 use crate::token::{is_valid_identifier, Token};
@@ -10,6 +11,9 @@ use crate::module::ModuleResolver;
 #[cfg(not(feature = "no_optimize"))]
 use crate::optimize::OptimizationLevel;
 
+#[cfg(not(feature = "no_std"))]
+use crate::profiler::Profiler;
+
 use crate::stdlib::{format, string::String};
 
 #[cfg(not(feature = "no_module"))]
@@ -48,6 +52,40 @@ impl Engine {
         self.optimization_level
     }
 
+    /// Turn strict mode on or off (default off).
+    ///
+    /// In strict mode, compiling a script fails if it uses a variable or calls a function that
+    /// is not defined. Variables must be declared in the script, or be found in the `Scope`
+    /// passed to the compiler or among the names declared via [`declare_var`][Engine::declare_var].
+    /// Functions must be defined in the script, registered into the `Engine`, found in a loaded
+    /// package, or found in a module imported under a constant path.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, ParseErrorType};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_strict_mode(true);
+    ///
+    /// let err = engine.compile("let x = 42; if x > 100 { print(y); }").expect_err("should error");
+    ///
+    /// assert_eq!(*err.0, ParseErrorType::VariableNotDefined("y".into()));
+    /// ```
+    #[inline(always)]
+    pub fn set_strict_mode(&mut self, enable: bool) -> &mut Self {
+        self.strict_mode = enable;
+        self
+    }
+
+    /// Is strict mode turned on?
+    /// In strict mode, variables and functions are checked to be defined when compiling a script.
+    #[inline(always)]
+    pub fn strict_mode(&self) -> bool {
+        self.strict_mode
+    }
+
     /// Set the maximum levels of function calls allowed for a script in order to avoid
     /// infinite recursion and stack overflows.
     #[cfg(not(feature = "unchecked"))]
@@ -84,6 +122,29 @@ impl Engine {
         self.limits_set.max_operations
     }
 
+    /// Set the maximum number of bytes of native stack that evaluating or parsing a script may use
+    /// (0 for unlimited), in order to avoid stack overflows.
+    ///
+    /// Stack usage is measured from the start of the outer-most evaluation or parsing on the
+    /// current thread, including any re-entrant calls into the `Engine` made by native functions.
+    ///
+    /// Not available under `no_std`.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_std"))]
+    #[inline(always)]
+    pub fn set_max_stack_size(&mut self, bytes: usize) -> &mut Self {
+        self.limits_set.max_stack_size = bytes;
+        self
+    }
+
+    /// The maximum number of bytes of native stack that evaluating or parsing a script may use
+    /// (0 for unlimited).
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub fn max_stack_size(&self) -> usize {
+        self.limits_set.max_stack_size
+    }
+
     /// Set the maximum number of imported modules allowed for a script.
     #[cfg(not(feature = "unchecked"))]
     #[cfg(not(feature = "no_module"))]
@@ -202,6 +263,76 @@ impl Engine {
         self
     }
 
+    /// Set the `Profiler` collecting operation counts and wall time per function and source line.
+    /// Profiling is off when set to `None` (the default).
+    ///
+    /// Not available under the `no_std` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, Profiler};
+    ///
+    /// let profiler = Profiler::new();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_profiler(Some(profiler.clone()));
+    ///
+    /// engine.consume("fn foo(x) { x + 1 } foo(1); foo(2);")?;
+    ///
+    /// let report = profiler.report();
+    ///
+    /// assert_eq!(report.function("foo").unwrap().calls, 2);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_std"))]
+    #[inline(always)]
+    pub fn set_profiler(&mut self, profiler: Option<Profiler>) -> &mut Self {
+        self.profiler = profiler;
+        self
+    }
+
+    /// Set the `Coverage` collector recording execution counts of statements and branches.
+    /// Coverage is off when set to `None` (the default).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Coverage, Engine};
+    ///
+    /// let coverage = Coverage::new();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_coverage(Some(coverage.clone()));
+    ///
+    /// let ast = engine.compile("let x = 1;\nif x > 0 { x = 0; }")?;
+    ///
+    /// // Register all statements and branches, so that code never executed is also reported
+    /// coverage.add_ast(&ast);
+    ///
+    /// engine.consume_ast(&ast)?;
+    ///
+    /// let report = coverage.report();
+    ///
+    /// assert_eq!(report.lines(None), vec![(1, 1), (2, 1)]);
+    /// assert_eq!(report.branches()[0].counts, [1, 0]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn set_coverage(&mut self, coverage: Option<Coverage>) -> &mut Self {
+        self.coverage = coverage;
+        self
+    }
+
     /// Disable a particular keyword or operator in the language.
     ///
     /// # Examples
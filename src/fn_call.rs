@@ -1,13 +1,14 @@
 //! Implement function-calling mechanism for `Engine`.
 
 use crate::ast::{Expr, Stmt};
+use crate::debugger::{DebuggerCell, LentDebugger};
 use crate::dynamic::Dynamic;
 use crate::engine::{
     search_imports, Engine, Imports, State, KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR,
     KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY, KEYWORD_IS_DEF_FN, KEYWORD_IS_DEF_VAR,
     KEYWORD_PRINT, KEYWORD_TYPE_OF,
 };
-use crate::fn_native::{FnCallArgs, FnPtr};
+use crate::fn_native::{FnCallArgs, FnPtr, NativeCallContext};
 use crate::module::{Module, ModuleRef};
 use crate::optimize::OptimizationLevel;
 use crate::parse_error::ParseErrorType;
@@ -20,7 +21,7 @@ use crate::{calc_native_fn_hash, calc_script_fn_hash, StaticVec, INT};
 
 #[cfg(not(feature = "no_function"))]
 use crate::{
-    ast::ScriptFnDef, r#unsafe::unsafe_cast_var_name_to_lifetime,
+    ast::ScriptFnDef, fn_native::CallableFunction, r#unsafe::unsafe_cast_var_name_to_lifetime,
     scope::EntryType as ScopeEntryType,
 };
 
@@ -36,6 +37,9 @@ use crate::engine::{Map, Target, FN_GET, FN_SET};
 #[cfg(not(feature = "no_closure"))]
 use crate::engine::KEYWORD_IS_SHARED;
 
+#[cfg(not(feature = "unchecked"))]
+use crate::engine::StackGuard;
+
 use crate::stdlib::{
     any::{type_name, TypeId},
     boxed::Box,
@@ -158,7 +162,7 @@ pub fn ensure_no_data_race(
         {
             return EvalAltResult::ErrorDataRace(
                 format!("argument #{} of function '{}'", n + 1 + skip, fn_name),
-                NO_POS,
+                NO_POS.into(),
             )
             .into();
         }
@@ -167,6 +171,18 @@ pub fn ensure_no_data_race(
     Ok(())
 }
 
+/// Record the type annotations of the parameters of a script-defined function
+/// on their entries in the `Scope`, which start at `offset`.
+#[cfg(not(feature = "no_function"))]
+#[inline(always)]
+fn annotate_params(scope: &mut Scope, offset: usize, fn_def: &ScriptFnDef) {
+    fn_def.param_types.iter().enumerate().for_each(|(i, typ)| {
+        if let Some(typ) = typ {
+            scope.set_entry_annotation(offset + i, typ.clone());
+        }
+    });
+}
+
 impl Engine {
     /// Call a native Rust function registered with the `Engine`.
     /// Position in `EvalAltResult` is `None` and must be set afterwards.
@@ -186,6 +202,7 @@ impl Engine {
         is_ref: bool,
         pub_only: bool,
         def_val: Option<Dynamic>,
+        level: usize,
     ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
         self.inc_operations(state)?;
 
@@ -203,13 +220,28 @@ impl Engine {
             let mut backup: ArgBackup = Default::default();
             backup.change_first_arg_to_copy(is_ref && func.is_pure(), args);
 
+            #[cfg(not(feature = "no_std"))]
+            self.profile_enter_function(state, fn_name, true);
+
             // Run external function
+            let debugger = self.lend_debugger(state, level);
+            let context = NativeCallContext::new_with_debugger(
+                self,
+                lib,
+                debugger.as_ref().map(LentDebugger::cell),
+            );
+
             let result = if func.is_plugin_fn() {
-                func.get_plugin_fn().call((self, lib).into(), args)
+                func.get_plugin_fn().call(context, args)
             } else {
-                func.get_native_fn()((self, lib).into(), args)
+                func.get_native_fn()(context, args)
             };
 
+            self.return_debugger(state, debugger);
+
+            #[cfg(not(feature = "no_std"))]
+            self.profile_leave_function(state);
+
             // Restore the original reference
             backup.restore_first_arg(args);
 
@@ -222,7 +254,7 @@ impl Engine {
                         EvalAltResult::ErrorMismatchOutputType(
                             self.map_type_name(type_name::<ImmutableString>()).into(),
                             typ.into(),
-                            NO_POS,
+                            NO_POS.into(),
                         )
                     })?)
                     .into(),
@@ -233,7 +265,7 @@ impl Engine {
                         EvalAltResult::ErrorMismatchOutputType(
                             self.map_type_name(type_name::<ImmutableString>()).into(),
                             typ.into(),
-                            NO_POS,
+                            NO_POS.into(),
                         )
                     })?)
                     .into(),
@@ -264,7 +296,7 @@ impl Engine {
                     prop,
                     self.map_type_name(args[0].type_name())
                 ),
-                NO_POS,
+                NO_POS.into(),
             )
             .into();
         }
@@ -278,7 +310,7 @@ impl Engine {
                     self.map_type_name(args[0].type_name()),
                     self.map_type_name(args[1].type_name()),
                 ),
-                NO_POS,
+                NO_POS.into(),
             )
             .into();
         }
@@ -292,7 +324,7 @@ impl Engine {
                     self.map_type_name(args[0].type_name()),
                     self.map_type_name(args[1].type_name()),
                 ),
-                NO_POS,
+                NO_POS.into(),
             )
             .into();
         }
@@ -306,7 +338,7 @@ impl Engine {
                     self.map_type_name(args[0].type_name()),
                     self.map_type_name(args[1].type_name()),
                 ),
-                NO_POS,
+                NO_POS.into(),
             )
             .into();
         }
@@ -325,7 +357,7 @@ impl Engine {
                     .collect::<Vec<_>>()
                     .join(", ")
             ),
-            NO_POS,
+            NO_POS.into(),
         )
         .into()
     }
@@ -356,12 +388,20 @@ impl Engine {
         #[cfg(not(feature = "no_function"))]
         #[cfg(not(feature = "unchecked"))]
         if level > self.max_call_levels() {
-            return Err(Box::new(EvalAltResult::ErrorStackOverflow(NO_POS)));
+            return Err(Box::new(EvalAltResult::ErrorStackOverflow(NO_POS.into())));
         }
 
         let orig_scope_level = state.scope_level;
         state.scope_level += 1;
 
+        // A function gets a fresh frame, so variable offsets are always valid inside it
+        let orig_always_search = state.always_search;
+        state.always_search = false;
+        let orig_eval_vars = mem::take(&mut state.eval_vars);
+
+        // The function body comes from the source the function is defined in
+        let orig_source = mem::replace(&mut state.source, fn_def.source.clone());
+
         let prev_scope_len = scope.len();
         let prev_mods_len = mods.len();
 
@@ -377,6 +417,7 @@ impl Engine {
                     (var_name, ScopeEntryType::Normal, value)
                 }),
         );
+        annotate_params(scope, prev_scope_len, fn_def);
 
         // Merge in encapsulated environment, if any
         let mut lib_merged: StaticVec<_>;
@@ -392,31 +433,115 @@ impl Engine {
 
         // Evaluate the function at one higher level of call depth
         let stmt = &fn_def.body;
+        let mut this_ptr = this_ptr;
+        let mut no_this_ptr = None;
+
+        #[cfg(not(feature = "no_std"))]
+        self.profile_enter_function(state, &fn_def.name, false);
+
+        let entered = if self.debugger.is_some() {
+            let num_args = args.len();
+            self.debug_enter_function(
+                scope,
+                mods,
+                state,
+                unified_lib,
+                this_ptr,
+                fn_def,
+                num_args,
+                level + 1,
+            )
+        } else {
+            Ok(())
+        };
 
-        let result =
-            self.eval_stmt(scope, mods, state, unified_lib, this_ptr, stmt, level + 1)
-                .or_else(|err| match *err {
-                    // Convert return statement to return value
-                    EvalAltResult::Return(x, _) => Ok(x),
-                    EvalAltResult::ErrorInFunctionCall(name, err, _) => {
-                        EvalAltResult::ErrorInFunctionCall(
-                            format!("{} > {}", fn_def.name, name),
-                            err,
-                            NO_POS,
+        // Errors leave the function body at the source the function is defined in
+        let result = if let Err(err) = entered {
+            Err(err.fill_source(&fn_def.source))
+        } else {
+            loop {
+                let result = self
+                    .eval_stmt(scope, mods, state, unified_lib, this_ptr, stmt, level + 1)
+                    .or_else(|err| match *err {
+                        // Convert return statement to return value
+                        EvalAltResult::Return(x, _) => Ok(x),
+                        // System errors are passed straight-through
+                        err if err.is_system_exception() => {
+                            Err(Box::new(err).fill_source(&fn_def.source))
+                        }
+                        // Other errors are wrapped in `ErrorInFunctionCall`, one level per call
+                        _ => EvalAltResult::ErrorInFunctionCall(
+                            fn_def.name.to_string(),
+                            args.len(),
+                            fn_def.source.clone(),
+                            err.fill_source(&fn_def.source),
+                            NO_POS.into(),
                         )
-                        .into()
-                    }
-                    // System errors are passed straight-through
-                    err if err.is_system_exception() => Err(Box::new(err)),
-                    // Other errors are wrapped in `ErrorInFunctionCall`
-                    _ => EvalAltResult::ErrorInFunctionCall(fn_def.name.to_string(), err, NO_POS)
                         .into(),
-                });
+                    });
+
+                // A self-recursive call in tail position leaves its arguments behind -
+                // restart the function body with them, re-using the current call frame
+                let args = match state.tail_call.take() {
+                    Some(args) if result.is_ok() => args,
+                    _ => break result,
+                };
+
+                if let Err(err) = self.inc_operations(state) {
+                    break Err(err);
+                }
+
+                scope.rewind(prev_scope_len);
+                mods.truncate(prev_mods_len);
+                state.always_search = false;
+                state.eval_vars.clear();
+
+                // The recursive call is a normal function call, so `this` is no longer bound
+                this_ptr = &mut no_this_ptr;
+
+                scope.extend(fn_def.params.iter().zip(args).map(|(name, value)| {
+                    let var_name: Cow<'_, str> = unsafe_cast_var_name_to_lifetime(name).into();
+                    (var_name, ScopeEntryType::Normal, value)
+                }));
+                annotate_params(scope, prev_scope_len, fn_def);
+            }
+        };
+
+        // Check the return value against the return type annotation, if any
+        let result = match (result, &fn_def.return_type) {
+            (Ok(value), Some(typ)) if !self.is_value_of_type(&value, typ) => {
+                EvalAltResult::ErrorInFunctionCall(
+                    fn_def.name.to_string(),
+                    args.len(),
+                    fn_def.source.clone(),
+                    Box::new(EvalAltResult::ErrorMismatchOutputType(
+                        self.map_type_name(value.type_name()).into(),
+                        self.map_annotated_type_name(typ).into(),
+                        NO_POS.into(),
+                    )),
+                    NO_POS.into(),
+                )
+                .into()
+            }
+            (result, _) => result,
+        };
+
+        // Back to the source of the caller
+        state.source = orig_source;
+
+        if self.debugger.is_some() {
+            self.debug_leave_function(state);
+        }
+
+        #[cfg(not(feature = "no_std"))]
+        self.profile_leave_function(state);
 
         // Remove all local variables
         scope.rewind(prev_scope_len);
         mods.truncate(prev_mods_len);
         state.scope_level = orig_scope_level;
+        state.always_search = orig_always_search;
+        state.eval_vars = orig_eval_vars;
 
         result
     }
@@ -459,6 +584,45 @@ impl Engine {
             || self.packages.contains_fn(hash_fn, pub_only)
     }
 
+    /// Resolve a call to a script-defined function into the overload whose parameter type
+    /// annotations match the arguments.
+    ///
+    /// Returns the hash of the matching overload, or the original hash if no overload matches.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) fn resolve_script_fn_overload(
+        &self,
+        lib: &[&Module],
+        hash_script: u64,
+        args: &[&mut Dynamic],
+        pub_only: bool,
+    ) -> u64 {
+        lib.iter()
+            .find_map(|&m| {
+                m.get_script_fn_overloads(hash_script)
+                    .iter()
+                    .find(|&&hash| self.is_matching_overload(m.get_fn(hash, pub_only), args))
+                    .cloned()
+            })
+            .unwrap_or(hash_script)
+    }
+
+    /// Is a function a script-defined function whose parameter type annotations match the arguments?
+    #[cfg(not(feature = "no_function"))]
+    fn is_matching_overload(&self, func: Option<&CallableFunction>, args: &[&mut Dynamic]) -> bool {
+        match func {
+            Some(func) if func.is_script() => func
+                .get_fn_def()
+                .param_types
+                .iter()
+                .zip(args.iter())
+                .all(|(typ, arg)| match typ {
+                    Some(typ) => self.is_value_of_type(arg, typ),
+                    None => true,
+                }),
+            _ => false,
+        }
+    }
+
     /// Perform an actual function call, native Rust or scripted, taking care of special functions.
     /// Position in `EvalAltResult` is `None` and must be set afterwards.
     ///
@@ -480,7 +644,7 @@ impl Engine {
         pub_only: bool,
         _capture_scope: Option<Scope>,
         def_val: Option<Dynamic>,
-        _level: usize,
+        level: usize,
     ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
         // Check for data race.
         if cfg!(not(feature = "no_closure")) {
@@ -491,6 +655,14 @@ impl Engine {
         let arg_types = args.iter().map(|a| a.type_id());
         let hash_fn = calc_native_fn_hash(empty(), fn_name, arg_types);
 
+        // Script-defined functions may be overloaded by parameter types
+        #[cfg(not(feature = "no_function"))]
+        let hash_script = if _is_method {
+            self.resolve_script_fn_overload(lib, hash_script, &args[1..], pub_only)
+        } else {
+            self.resolve_script_fn_overload(lib, hash_script, args, pub_only)
+        };
+
         match fn_name {
             // type_of
             KEYWORD_TYPE_OF
@@ -513,7 +685,7 @@ impl Engine {
                         fn_name, fn_name
                     )
                     .into(),
-                    NO_POS,
+                    NO_POS.into(),
                 )
                 .into()
             }
@@ -567,7 +739,7 @@ impl Engine {
                             &mut Some(*first),
                             func,
                             rest,
-                            _level,
+                            level,
                         )?
                     } else {
                         // Normal call of script function - map first argument to `this`
@@ -576,7 +748,7 @@ impl Engine {
                         backup.change_first_arg_to_copy(is_ref, args);
 
                         let result = self
-                            .call_script_fn(scope, mods, state, lib, &mut None, func, args, _level);
+                            .call_script_fn(scope, mods, state, lib, &mut None, func, args, level);
 
                         // Restore the original reference
                         backup.restore_first_arg(args);
@@ -596,19 +768,22 @@ impl Engine {
                         is_ref,
                         pub_only,
                         def_val,
+                        level,
                     )
                 }
             }
 
             // Normal native function call
             _ => self.call_native_fn(
-                state, lib, fn_name, hash_fn, args, is_ref, pub_only, def_val,
+                state, lib, fn_name, hash_fn, args, is_ref, pub_only, def_val, level,
             ),
         }
     }
 
     /// Evaluate a list of statements with an empty state and no `this` pointer.
     /// This is commonly used to evaluate a list of statements in an `AST` or a script function body.
+    ///
+    /// The debugger lent by an outer evaluation run, if any, is used instead of creating a new one.
     #[inline]
     pub(crate) fn eval_statements_raw<'a>(
         &self,
@@ -616,10 +791,17 @@ impl Engine {
         mods: &mut Imports,
         statements: impl IntoIterator<Item = &'a Stmt>,
         lib: &[&Module],
+        debugger: Option<&DebuggerCell>,
     ) -> Result<(Dynamic, u64), Box<EvalAltResult>> {
+        // Measure native stack usage from here
+        #[cfg(not(feature = "unchecked"))]
+        let _guard = StackGuard::new();
+
         let mut state = Default::default();
 
-        statements
+        self.borrow_debugger(&mut state, debugger);
+
+        let result = statements
             .into_iter()
             .try_fold(().into(), |_, stmt| {
                 self.eval_stmt(scope, mods, &mut state, lib, &mut None, stmt, 0)
@@ -627,9 +809,13 @@ impl Engine {
             .or_else(|err| match *err {
                 EvalAltResult::Return(out, _) => Ok(out),
                 EvalAltResult::LoopBreak(_, _) => unreachable!(),
-                _ => Err(err),
-            })
-            .map(|v| (v, state.operations))
+                // The error leaves the script at the source of the statement it occurs in
+                _ => Err(err.fill_source(&state.source)),
+            });
+
+        self.give_back_debugger(&mut state, debugger);
+
+        result.map(|v| (v, state.operations))
     }
 
     /// Evaluate a text string as a script - used primarily for 'eval'.
@@ -641,7 +827,7 @@ impl Engine {
         state: &mut State,
         lib: &[&Module],
         script: &str,
-        _level: usize,
+        level: usize,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         self.inc_operations(state)?;
 
@@ -653,8 +839,8 @@ impl Engine {
         // Check for stack overflow
         #[cfg(not(feature = "no_function"))]
         #[cfg(not(feature = "unchecked"))]
-        if _level > self.max_call_levels() {
-            return Err(Box::new(EvalAltResult::ErrorStackOverflow(NO_POS)));
+        if level > self.max_call_levels() {
+            return Err(Box::new(EvalAltResult::ErrorStackOverflow(NO_POS.into())));
         }
 
         // Compile the script text
@@ -663,6 +849,7 @@ impl Engine {
             &Default::default(),
             &[script],
             OptimizationLevel::None,
+            false,
         )?;
 
         // If new functions are defined within the eval string, it is an error
@@ -670,8 +857,20 @@ impl Engine {
             return Err(ParseErrorType::WrongFnDefinition.into());
         }
 
-        // Evaluate the AST
-        let (result, operations) = self.eval_statements_raw(scope, mods, ast.statements(), lib)?;
+        // Evaluate the AST, continuing with the debugger (if any) at the level of the caller
+        let debugger = self.lend_debugger(state, level - 1);
+
+        let result = self.eval_statements_raw(
+            scope,
+            mods,
+            ast.statements(),
+            lib,
+            debugger.as_ref().map(LentDebugger::cell),
+        );
+
+        self.return_debugger(state, debugger);
+
+        let (result, operations) = result?;
 
         state.operations += operations;
         self.inc_operations(state)?;
@@ -992,6 +1191,7 @@ impl Engine {
             if !self.has_override(lib, hash_fn, hash_script, pub_only) {
                 // eval - only in function call style
                 let prev_len = scope.len();
+                let prev_mods_len = mods.len();
                 let script =
                     self.eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?;
                 let script = script.as_str().map_err(|typ| {
@@ -1002,8 +1202,12 @@ impl Engine {
                     .map_err(|err| err.fill_position(args_expr[0].position()));
 
                 // IMPORTANT! If the eval defines new variables in the current scope,
-                //            all variable offsets from this point on will be mis-aligned.
-                if scope.len() != prev_len {
+                //            offsets of variables defined before it must skip over them.
+                if scope.len() > prev_len {
+                    state.eval_vars.push((prev_len, scope.len() - prev_len));
+                }
+                // The same goes for imported modules, which are then searched by name.
+                if mods.len() != prev_mods_len {
                     state.always_search = true;
                 }
 
@@ -1035,7 +1239,7 @@ impl Engine {
                     .map(|expr| self.eval_expr(scope, mods, state, lib, this_ptr, expr, level))
                     .collect::<Result<_, _>>()?;
 
-                let (target, _, _, pos) =
+                let (target, _, _, _, pos) =
                     self.search_namespace(scope, mods, state, lib, this_ptr, &args_expr[0])?;
 
                 self.inc_operations(state)
@@ -1117,7 +1321,7 @@ impl Engine {
                     .collect::<Result<_, _>>()?;
 
                 // Get target reference to first argument
-                let (target, _, _, pos) =
+                let (target, _, _, _, pos) =
                     self.search_scope_only(scope, mods, state, lib, this_ptr, &args_expr[0])?;
 
                 self.inc_operations(state)
@@ -1146,6 +1350,15 @@ impl Engine {
 
         let module = search_imports(mods, state, modules)?;
 
+        // Script-defined functions may be overloaded by parameter types
+        #[cfg(not(feature = "no_function"))]
+        let hash_script = module
+            .get_qualified_script_fn_overloads(hash_script)
+            .iter()
+            .find(|&&hash| self.is_matching_overload(module.get_qualified_fn(hash), &args))
+            .cloned()
+            .unwrap_or(hash_script);
+
         // First search in script-defined functions (can override built-in)
         let func = match module.get_qualified_fn(hash_script) {
             // Then search in Rust functions
@@ -1184,11 +1397,8 @@ impl Engine {
 
                 self.call_script_fn(new_scope, mods, state, lib, &mut None, &fn_def, args, level)
             }
-            Some(f) if f.is_plugin_fn() => {
-                f.get_plugin_fn().call((self, lib).into(), args.as_mut())
-            }
-            Some(f) if f.is_native() => {
-                if !f.is_method() {
+            Some(f) if f.is_plugin_fn() || f.is_native() => {
+                if f.is_native() && !f.is_method() {
                     // Clone first argument
                     if let Some(first) = first_arg_value {
                         let first_val = args[0].clone();
@@ -1197,7 +1407,22 @@ impl Engine {
                     }
                 }
 
-                f.get_native_fn()((self, lib).into(), args.as_mut())
+                let debugger = self.lend_debugger(state, level);
+                let context = NativeCallContext::new_with_debugger(
+                    self,
+                    lib,
+                    debugger.as_ref().map(LentDebugger::cell),
+                );
+
+                let result = if f.is_plugin_fn() {
+                    f.get_plugin_fn().call(context, args.as_mut())
+                } else {
+                    f.get_native_fn()(context, args.as_mut())
+                };
+
+                self.return_debugger(state, debugger);
+
+                result
             }
             Some(_) => unreachable!(),
             None if def_val.is_some() => Ok(def_val.unwrap().into()),
@@ -1215,7 +1440,7 @@ impl Engine {
                         .collect::<Vec<_>>()
                         .join(", ")
                 ),
-                NO_POS,
+                NO_POS.into(),
             )
             .into(),
         }
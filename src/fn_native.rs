@@ -1,18 +1,22 @@
 //! Module defining interfaces to native-Rust functions.
 
 use crate::ast::{FnAccess, ScriptFnDef};
+use crate::debugger::{Debugger, DebuggerCell, DebuggerCommand, DebuggerEvent};
 use crate::dynamic::Dynamic;
-use crate::engine::{Engine, EvalContext};
+use crate::engine::{Engine, EvalContext, State};
 use crate::module::Module;
 use crate::plugin::PluginFunction;
 use crate::result::EvalAltResult;
-use crate::token::{is_valid_identifier, NO_POS};
+use crate::token::{is_valid_identifier, Position, NO_POS};
 use crate::utils::ImmutableString;
 use crate::{calc_script_fn_hash, StaticVec};
 
 #[cfg(not(feature = "no_function"))]
 use crate::engine::FN_ANONYMOUS;
 
+#[cfg(not(feature = "unchecked"))]
+use crate::engine::StackGuard;
+
 use crate::stdlib::{boxed::Box, convert::TryFrom, fmt, iter::empty, mem, string::String};
 
 #[cfg(feature = "sync")]
@@ -53,6 +57,7 @@ pub type Locked<T> = RwLock<T>;
 pub struct NativeCallContext<'e, 'm, 'pm: 'm> {
     engine: &'e Engine,
     lib: &'m [&'pm Module],
+    debugger: Option<&'m DebuggerCell>,
 }
 
 impl<'e, 'm, 'pm: 'm, M: AsRef<[&'pm Module]> + ?Sized> From<(&'e Engine, &'m M)>
@@ -62,11 +67,26 @@ impl<'e, 'm, 'pm: 'm, M: AsRef<[&'pm Module]> + ?Sized> From<(&'e Engine, &'m M)
         Self {
             engine: value.0,
             lib: value.1.as_ref(),
+            debugger: None,
         }
     }
 }
 
 impl<'e, 'm, 'pm> NativeCallContext<'e, 'm, 'pm> {
+    /// Create a new `NativeCallContext`, lending the debugger (if any) to evaluation runs nested
+    /// inside the native function call.
+    #[inline(always)]
+    pub(crate) fn new_with_debugger(
+        engine: &'e Engine,
+        lib: &'m [&'pm Module],
+        debugger: Option<&'m DebuggerCell>,
+    ) -> Self {
+        Self {
+            engine,
+            lib,
+            debugger,
+        }
+    }
     /// The current `Engine`.
     #[inline(always)]
     pub fn engine(&self) -> &'e Engine {
@@ -88,6 +108,12 @@ pub fn shared_make_mut<T: Clone>(value: &mut Shared<T>) -> &mut T {
     return Arc::make_mut(value);
 }
 
+/// Consume a `Shared` resource and return the wrapped value.
+/// If the resource is shared (i.e. has other outstanding references), a cloned copy is returned.
+pub fn shared_take_or_clone<T: Clone>(value: Shared<T>) -> T {
+    shared_try_take(value).unwrap_or_else(|value| (*value).clone())
+}
+
 /// Consume a `Shared` resource if is unique (i.e. not shared).
 pub fn shared_try_take<T>(value: Shared<T>) -> Result<T, Shared<T>> {
     #[cfg(not(feature = "sync"))]
@@ -181,22 +207,34 @@ impl FnPtr {
             args.insert(0, obj);
         }
 
-        ctx.engine()
-            .exec_fn_call(
-                &mut Default::default(),
-                &mut Default::default(),
-                ctx.lib,
-                fn_name,
-                hash_script,
-                args.as_mut(),
-                has_this,
-                has_this,
-                true,
-                None,
-                None,
-                0,
-            )
-            .map(|(v, _)| v)
+        // Measure native stack usage from here
+        #[cfg(not(feature = "unchecked"))]
+        let _guard = StackGuard::new();
+
+        let engine = ctx.engine();
+        let state = &mut State::default();
+
+        // Continue debugging with the debugger of the evaluation run calling the native function
+        engine.borrow_debugger(state, ctx.debugger);
+
+        let result = engine.exec_fn_call(
+            &mut Default::default(),
+            state,
+            ctx.lib,
+            fn_name,
+            hash_script,
+            args.as_mut(),
+            has_this,
+            has_this,
+            true,
+            None,
+            None,
+            0,
+        );
+
+        engine.give_back_debugger(state, ctx.debugger);
+
+        result.map(|(v, _)| v)
     }
 }
 
@@ -215,7 +253,7 @@ impl TryFrom<ImmutableString> for FnPtr {
         if is_valid_identifier(value.chars()) {
             Ok(Self(value, Default::default()))
         } else {
-            EvalAltResult::ErrorFunctionNotFound(value.into(), NO_POS).into()
+            EvalAltResult::ErrorFunctionNotFound(value.into(), NO_POS.into()).into()
         }
     }
 }
@@ -276,6 +314,38 @@ pub type OnVarCallback = Box<
         + 'static,
 >;
 
+/// A standard function that creates the debugger state for an evaluation run.
+#[cfg(not(feature = "sync"))]
+pub type OnDebuggerInit = Box<dyn Fn() -> Debugger + 'static>;
+/// A standard function that creates the debugger state for an evaluation run.
+#[cfg(feature = "sync")]
+pub type OnDebuggerInit = Box<dyn Fn() -> Debugger + Send + Sync + 'static>;
+
+/// A standard debugger callback function.
+#[cfg(not(feature = "sync"))]
+pub type OnDebuggerCallback = Box<
+    dyn Fn(
+            &mut EvalContext,
+            &mut Debugger,
+            DebuggerEvent,
+            Position,
+        ) -> Result<DebuggerCommand, Box<EvalAltResult>>
+        + 'static,
+>;
+/// A standard debugger callback function.
+#[cfg(feature = "sync")]
+pub type OnDebuggerCallback = Box<
+    dyn Fn(
+            &mut EvalContext,
+            &mut Debugger,
+            DebuggerEvent,
+            Position,
+        ) -> Result<DebuggerCommand, Box<EvalAltResult>>
+        + Send
+        + Sync
+        + 'static,
+>;
+
 /// A type encapsulating a function callable by Rhai.
 #[derive(Clone)]
 pub enum CallableFunction {
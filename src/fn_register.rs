@@ -10,7 +10,12 @@ use crate::r#unsafe::unsafe_cast_box;
 use crate::result::EvalAltResult;
 use crate::utils::ImmutableString;
 
-use crate::stdlib::{any::TypeId, boxed::Box, mem, string::String};
+use crate::stdlib::{
+    any::{type_name, TypeId},
+    boxed::Box,
+    mem,
+    string::String,
+};
 
 /// Trait to register custom functions with the `Engine`.
 pub trait RegisterFn<FN, ARGS, RET> {
@@ -189,10 +194,11 @@ macro_rules! def_register {
         {
             #[inline]
             fn register_fn(&mut self, name: &str, f: FN) -> &mut Self {
-                self.global_module.set_fn(name, FnAccess::Public,
+                let hash_fn = self.global_module.set_fn(name, FnAccess::Public,
                     &[$(map_type_id::<$par>()),*],
                     CallableFunction::$abi(make_func!(f : map_dynamic ; $($par => $let => $clone => $arg),*))
                 );
+                self.global_module.set_fn_type_names(hash_fn, &[$(type_name::<$param>()),*], type_name::<RET>());
                 self
             }
         }
@@ -204,10 +210,11 @@ macro_rules! def_register {
         {
             #[inline]
             fn register_result_fn(&mut self, name: &str, f: FN) -> &mut Self {
-                self.global_module.set_fn(name, FnAccess::Public,
+                let hash_fn = self.global_module.set_fn(name, FnAccess::Public,
                     &[$(map_type_id::<$par>()),*],
                     CallableFunction::$abi(make_func!(f : map_result ; $($par => $let => $clone => $arg),*))
                 );
+                self.global_module.set_fn_type_names(hash_fn, &[$(type_name::<$param>()),*], type_name::<Dynamic>());
                 self
             }
         }
@@ -0,0 +1,1123 @@
+//! Module implementing the source formatter and `AST` pretty-printer.
+
+use crate::ast::{Expr, IdentX, ReturnType, Stmt, AST};
+use crate::engine::Engine;
+use crate::optimize::OptimizationLevel;
+use crate::parse_error::{LexError, ParseError, ParseErrorType};
+use crate::token::{is_valid_identifier, Position, Token};
+
+#[cfg(not(feature = "no_function"))]
+use crate::ast::{FnAccess, ScriptFnDef};
+
+#[cfg(not(feature = "no_function"))]
+use crate::engine::is_anonymous_fn;
+
+#[cfg(not(feature = "no_function"))]
+use crate::fn_native::Shared;
+
+#[cfg(not(feature = "no_closure"))]
+use crate::engine::KEYWORD_FN_PTR_CURRY;
+
+use crate::stdlib::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    slice,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Options controlling the output of the source formatter.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FormatOptions {
+    /// Text of one level of indentation.  Default is four spaces.
+    pub indent: String,
+    /// Maximum line width that lists (e.g. function call arguments, array and object map
+    /// literals) are kept within before being broken into one item per line.  Default is 100.
+    pub max_width: usize,
+}
+
+impl Default for FormatOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            indent: "    ".into(),
+            max_width: 100,
+        }
+    }
+}
+
+/// A comment in the source.
+#[derive(Debug, Clone)]
+struct Comment {
+    /// Position of the start of the comment.
+    pos: Position,
+    /// Text of the comment, including the `//` or `/*` ... `*/` delimiters.
+    text: String,
+    /// Does the comment follow other tokens on the same line?
+    trailing: bool,
+}
+
+/// Information about the source, other than the `AST`, retained by the formatter.
+#[derive(Debug, Clone, Default)]
+struct Trivia {
+    /// All comments, in order of position.
+    comments: Vec<Comment>,
+    /// Line numbers of all lines that are not blank.
+    lines: HashSet<usize>,
+    /// Positions of the closing `}` of all blocks, by position of the opening `{`.
+    braces: HashMap<Position, Position>,
+    /// Positions of all `}` immediately preceded by `;`.
+    semicolons: HashSet<Position>,
+    /// Does the script end with `;`?
+    ends_with_semicolon: bool,
+}
+
+impl Trivia {
+    /// Scan a script for comments, blank lines and braces.
+    fn scan(engine: &Engine, script: &str) -> Self {
+        let mut trivia: Self = Default::default();
+        let mut open_braces = Vec::new();
+        let mut last: Option<(Token, Position)> = None;
+
+        for (token, pos) in engine.lex_with_comments(&[script]) {
+            let line = pos.line().unwrap_or(0);
+
+            match token {
+                Token::EOF => {
+                    trivia.ends_with_semicolon = matches!(last, Some((Token::SemiColon, _)));
+                    break;
+                }
+                Token::Comment(text) => {
+                    let lines = text.matches('\n').count();
+                    trivia.lines.extend(line..=line + lines);
+                    trivia.comments.push(Comment {
+                        pos,
+                        trailing: last.as_ref().and_then(|(_, p)| p.line()) == Some(line),
+                        text: text.trim_end().into(),
+                    });
+                    continue;
+                }
+                Token::LeftBrace | Token::MapStart => open_braces.push(pos),
+                Token::RightBrace => {
+                    if let Some(open) = open_braces.pop() {
+                        trivia.braces.insert(open, pos);
+                    }
+                    if let Some((Token::SemiColon, _)) = last {
+                        trivia.semicolons.insert(pos);
+                    }
+                }
+                _ => (),
+            }
+
+            trivia.lines.insert(line);
+            last = Some((token, pos));
+        }
+
+        trivia
+    }
+}
+
+/// An item in a list of statements.
+#[derive(Debug, Clone, Copy)]
+enum Item<'a> {
+    /// A statement.
+    Stmt(&'a Stmt),
+    /// A script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    Fn(&'a ScriptFnDef),
+}
+
+/// An item in a comma-separated list.
+#[derive(Debug, Clone, Copy)]
+enum ListItem<'a> {
+    /// An expression.
+    Expr(&'a Expr),
+    /// A property of an object map literal.
+    Property(&'a IdentX, &'a Expr),
+}
+
+/// State of a running formatter.
+struct Formatter<'a> {
+    /// The `Engine` used for parsing, for custom operators.
+    engine: &'a Engine,
+    /// Formatting options.
+    options: &'a FormatOptions,
+    /// Source information, empty when pretty-printing an `AST` without source.
+    trivia: Trivia,
+    /// Has the source been scanned?
+    has_source: bool,
+    /// Index of the next comment to output.
+    next_comment: usize,
+    /// Width of one level of indentation.
+    indent_width: usize,
+    /// Anonymous functions (i.e. closures), by name.
+    #[cfg(not(feature = "no_function"))]
+    closures: HashMap<String, Shared<ScriptFnDef>>,
+    /// First error encountered, if any.
+    error: Option<ParseError>,
+}
+
+impl<'a> Formatter<'a> {
+    /// Create a new `Formatter`.
+    fn new(engine: &'a Engine, options: &'a FormatOptions, _ast: &AST) -> Self {
+        Self {
+            engine,
+            options,
+            trivia: Default::default(),
+            has_source: false,
+            next_comment: 0,
+            indent_width: options
+                .indent
+                .chars()
+                .map(|ch| if ch == '\t' { 4 } else { 1 })
+                .sum(),
+            #[cfg(not(feature = "no_function"))]
+            closures: _ast
+                .iter_functions()
+                .filter(|(_, name, _, _)| is_anonymous_fn(name))
+                .map(|(_, name, _, f)| (name.to_string(), f))
+                .collect(),
+            error: None,
+        }
+    }
+
+    /// Format an entire `AST`.
+    fn format(mut self, ast: &'a AST) -> Result<String, ParseError> {
+        #[allow(unused_mut)]
+        let mut items: Vec<_> = ast
+            .stmts()
+            .iter()
+            .map(|stmt| (stmt.start_position(), Item::Stmt(stmt)))
+            .collect();
+
+        #[cfg(not(feature = "no_function"))]
+        let functions: Vec<_> = ast
+            .iter_functions()
+            .filter(|(_, name, _, _)| !is_anonymous_fn(name))
+            .map(|(_, _, _, f)| f)
+            .collect();
+
+        #[cfg(not(feature = "no_function"))]
+        items.extend(functions.iter().map(|f| (f.span.start(), Item::Fn(f))));
+
+        items.sort_by_key(|(pos, _)| *pos);
+
+        let items: Vec<_> = items.into_iter().map(|(_, item)| item).collect();
+        let keep_semicolon = self.trivia.ends_with_semicolon;
+
+        let mut output = String::new();
+        self.items(&mut output, &items, None, 0, keep_semicolon);
+
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        // Remove the leading new-line
+        if output.starts_with('\n') {
+            output.remove(0);
+        }
+        if !output.is_empty() {
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
+
+    /// Indentation text for a level.
+    fn indent(&self, level: usize) -> String {
+        self.options.indent.repeat(level)
+    }
+
+    /// Does a piece of text fit on one line starting at a particular column?
+    fn fits(&self, col: usize, text: &str) -> bool {
+        !text.contains('\n') && col + text.chars().count() <= self.options.max_width
+    }
+
+    /// Is there any comment to output before a particular position
+    /// (or the end of the script if `None`)?
+    fn has_comments_before(&self, pos: Option<Position>) -> bool {
+        match (self.trivia.comments.get(self.next_comment), pos) {
+            (Some(comment), Some(pos)) => comment.pos < pos,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Start a new line at an indentation level, keeping one blank line before it
+    /// if there is one in the source.
+    fn new_line(&self, output: &mut String, line: Option<usize>, level: usize, first: &mut bool) {
+        if !*first && self.has_source {
+            if let Some(line) = line {
+                if line > 1 && !self.trivia.lines.contains(&(line - 1)) {
+                    output.push('\n');
+                }
+            }
+        }
+        output.push('\n');
+        output.push_str(&self.indent(level));
+        *first = false;
+    }
+
+    /// Output all comments before a particular position (or the end of the script if `None`).
+    fn comments(
+        &mut self,
+        output: &mut String,
+        pos: Option<Position>,
+        level: usize,
+        first: &mut bool,
+    ) {
+        while self.has_comments_before(pos) {
+            let comment = self.trivia.comments[self.next_comment].clone();
+            self.next_comment += 1;
+
+            if comment.trailing && !*first {
+                output.push(' ');
+            } else {
+                self.new_line(output, comment.pos.line(), level, first);
+            }
+            output.push_str(&comment.text);
+        }
+    }
+
+    /// Output a list of items, each on its own line, at an indentation level.
+    ///
+    /// `end` is the position of the end of the list (e.g. the closing `}` of a block),
+    /// or `None` for the end of the script.
+    fn items(
+        &mut self,
+        output: &mut String,
+        items: &[Item],
+        end: Option<Position>,
+        level: usize,
+        keep_semicolon: bool,
+    ) {
+        let items: Vec<_> = items
+            .iter()
+            .filter(|item| match item {
+                Item::Stmt(Stmt::Noop(_)) | Item::Stmt(Stmt::Source(_)) => false,
+                #[cfg(not(feature = "no_closure"))]
+                Item::Stmt(Stmt::Share(_)) => false,
+                _ => true,
+            })
+            .collect();
+
+        let mut first = true;
+        let mut after_fn = false;
+
+        for (index, item) in items.iter().enumerate() {
+            let is_last = index == items.len() - 1;
+
+            let (start, text) = match item {
+                Item::Stmt(stmt) => {
+                    let start = stmt.start_position();
+                    self.comments(output, Some(start), level, &mut first);
+                    let mut text = self.stmt(stmt, level, level * self.indent_width);
+                    if needs_semicolon(stmt) && (!is_last || keep_semicolon || !is_expr(stmt)) {
+                        text.push(';');
+                    }
+                    if after_fn && !self.has_source {
+                        output.push('\n');
+                    }
+                    after_fn = false;
+                    (start, text)
+                }
+                #[cfg(not(feature = "no_function"))]
+                Item::Fn(f) => {
+                    let start = f.span.start();
+                    self.comments(output, Some(start), level, &mut first);
+                    // Without the source, separate functions by blank lines
+                    if !self.has_source && !first {
+                        output.push('\n');
+                    }
+                    after_fn = true;
+                    (start, self.fn_def(f, level))
+                }
+            };
+
+            self.new_line(output, start.line(), level, &mut first);
+            output.push_str(&text);
+        }
+
+        self.comments(output, end, level, &mut first);
+    }
+
+    /// Format a block of statements.
+    ///
+    /// `start` is the position of the opening `{`, if any.
+    fn block(
+        &mut self,
+        statements: &[Stmt],
+        start: Option<Position>,
+        end: Option<Position>,
+        level: usize,
+    ) -> String {
+        let end = end.or_else(|| start.and_then(|pos| self.trivia.braces.get(&pos).copied()));
+        let keep_semicolon = matches!(end, Some(pos) if self.trivia.semicolons.contains(&pos));
+        let items: Vec<_> = statements.iter().map(Item::Stmt).collect();
+
+        let mut body = String::new();
+        self.items(&mut body, &items, end, level + 1, keep_semicolon);
+
+        if body.is_empty() {
+            "{}".into()
+        } else {
+            format!("{{{}\n{}}}", body, self.indent(level))
+        }
+    }
+
+    /// Format a block of statements in an expression, on a single line if possible.
+    fn inline_block(
+        &mut self,
+        statements: &[Stmt],
+        start: Option<Position>,
+        end: Option<Position>,
+        level: usize,
+        col: usize,
+    ) -> String {
+        let end = end.or_else(|| start.and_then(|pos| self.trivia.braces.get(&pos).copied()));
+
+        let statements: Vec<_> = statements.iter().filter(|s| !s.is_noop()).collect();
+
+        match statements.as_slice() {
+            [Stmt::Expr(expr)]
+                if (end.is_some() || !self.has_source)
+                    && !self.has_comments_before(end)
+                    && !matches!(end, Some(pos) if self.trivia.semicolons.contains(&pos)) =>
+            {
+                let text = format!("{{ {} }}", self.expr(expr, level, col + 2));
+
+                if self.fits(col, &text) {
+                    return text;
+                }
+            }
+            _ => (),
+        }
+
+        let statements: Vec<_> = statements.into_iter().cloned().collect();
+        self.block(&statements, None, end, level)
+    }
+
+    /// Format the body of a compound statement as a block.
+    fn body(&mut self, stmt: &Stmt, level: usize, col: usize, inline: bool) -> String {
+        match stmt {
+            Stmt::Block(statements, span) if inline => {
+                self.inline_block(statements, None, Some(span.end()), level, col)
+            }
+            Stmt::Block(statements, span) => self.block(statements, None, Some(span.end()), level),
+            stmt if inline => self.inline_block(slice::from_ref(stmt), None, None, level, col),
+            stmt => self.block(slice::from_ref(stmt), None, None, level),
+        }
+    }
+
+    /// Format an `if` statement.
+    ///
+    /// If `inline` is `true`, the blocks are kept on a single line if possible.
+    fn if_stmt(
+        &mut self,
+        guard: &Expr,
+        x: &(Stmt, Option<Stmt>),
+        level: usize,
+        col: usize,
+        inline: bool,
+    ) -> String {
+        let mut text = format!("if {}", self.expr(guard, level, col + 3));
+        text.push(' ');
+        let body = self.body(&x.0, level, col + text.len(), inline);
+        text.push_str(&body);
+
+        match x.1 {
+            Some(Stmt::IfThenElse(ref guard, ref x, _)) => {
+                text.push_str(" else ");
+                let col = last_line_width(&text, col);
+                text.push_str(&self.if_stmt(guard, x, level, col, inline));
+            }
+            Some(ref stmt) => {
+                text.push_str(" else ");
+                let col = last_line_width(&text, col);
+                text.push_str(&self.body(stmt, level, col, inline));
+            }
+            None => (),
+        }
+
+        text
+    }
+
+    /// Format a statement, without the terminating semicolon.
+    fn stmt(&mut self, stmt: &Stmt, level: usize, col: usize) -> String {
+        match stmt {
+            Stmt::Noop(_) => String::new(),
+            Stmt::IfThenElse(guard, x, _) => self.if_stmt(guard, x, level, col, false),
+            Stmt::While(guard, body, _) => {
+                let guard = self.expr(guard, level, col + 6);
+                format!("while {} {}", guard, self.body(body, level, col, false))
+            }
+            Stmt::Loop(body, _) => format!("loop {}", self.body(body, level, col, false)),
+            Stmt::For(expr, x, _) => {
+                let prefix = format!("for {} in ", x.0.name);
+                let expr = self.expr(expr, level, col + prefix.len());
+                format!("{}{} {}", prefix, expr, self.body(&x.1, level, col, false))
+            }
+            Stmt::Let(x, expr, _) | Stmt::Const(x, expr, _) => {
+                let (var, typ) = x.as_ref();
+                let keyword = match stmt {
+                    Stmt::Let(_, _, _) => "let",
+                    _ => "const",
+                };
+                let mut text = format!("{} {}", keyword, var.name);
+                if let Some(typ) = typ {
+                    let _ = write!(text, ": {}", typ.name);
+                }
+                if let Some(expr) = expr {
+                    text.push_str(" = ");
+                    let expr = self.expr(expr, level, col + text.len());
+                    text.push_str(&expr);
+                }
+                text
+            }
+            Stmt::Assignment(x, _) => {
+                let lhs = self.expr(&x.0, level, col);
+                let op = if x.1.is_empty() { "=" } else { x.1.as_ref() };
+                let prefix = format!("{} {} ", lhs, op);
+                let rhs = self.expr(&x.2, level, col + prefix.len());
+                prefix + &rhs
+            }
+            Stmt::Block(statements, span) => self.block(statements, None, Some(span.end()), level),
+            Stmt::TryCatch(x, _, _) => {
+                let mut text = format!("try {} catch", self.body(&x.0, level, col, false));
+                match (&x.1, &x.2) {
+                    (Some(var), Some(stack)) => {
+                        let _ = write!(text, " ({}, {})", var.name, stack.name);
+                    }
+                    (Some(var), None) => {
+                        let _ = write!(text, " ({})", var.name);
+                    }
+                    _ => (),
+                }
+                text.push(' ');
+                text.push_str(&self.body(&x.3, level, col, false));
+                if let Some(ref finally) = x.4 {
+                    text.push_str(" finally ");
+                    text.push_str(&self.body(finally, level, col, false));
+                }
+                text
+            }
+            Stmt::Expr(expr) => self.expr(expr, level, col),
+            Stmt::Continue(_) => Token::Continue.syntax().into(),
+            Stmt::Break(_) => Token::Break.syntax().into(),
+            Stmt::ReturnWithVal((return_type, _), expr, _) => {
+                let keyword = match return_type {
+                    ReturnType::Return => Token::Return,
+                    ReturnType::Exception | ReturnType::Rethrow => Token::Throw,
+                };
+                let mut text = keyword.syntax().to_string();
+                if let Some(expr) = expr {
+                    text.push(' ');
+                    let expr = self.expr(expr, level, col + text.len());
+                    text.push_str(&expr);
+                }
+                text
+            }
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Import(expr, alias, _) => {
+                let mut text = format!("import {}", self.expr(expr, level, col + 7));
+                if let Some(alias) = alias {
+                    let _ = write!(text, " as {}", alias.name);
+                }
+                text
+            }
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Export(list, _) => {
+                let list: Vec<_> = list
+                    .iter()
+                    .map(|(name, alias)| match alias {
+                        Some(alias) => format!("{} as {}", name.name, alias.name),
+                        None => name.name.clone(),
+                    })
+                    .collect();
+                format!("export {}", list.join(", "))
+            }
+            #[cfg(not(feature = "no_closure"))]
+            Stmt::Share(_) => String::new(),
+            Stmt::Source(_) => String::new(),
+        }
+    }
+
+    /// Format a script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    fn fn_def(&mut self, f: &ScriptFnDef, level: usize) -> String {
+        let access = match f.access {
+            FnAccess::Private => "private ",
+            FnAccess::Public => "",
+        };
+        let params: Vec<_> = f
+            .params
+            .iter()
+            .zip(f.param_types.iter())
+            .map(|(name, typ)| match typ {
+                Some(typ) => format!("{}: {}", name, typ),
+                None => name.clone(),
+            })
+            .collect();
+        let mut header = format!("{}fn {}({})", access, f.name, params.join(", "));
+        if let Some(ref typ) = f.return_type {
+            let _ = write!(header, " -> {}", typ);
+        }
+
+        match f.body {
+            Stmt::Block(ref statements, ref span) => format!(
+                "{} {}",
+                header,
+                self.block(statements, None, Some(span.end()), level)
+            ),
+            ref stmt => format!("{} {}", header, self.body(stmt, level, 0, false)),
+        }
+    }
+
+    /// Format a closure.
+    ///
+    /// The first `num_externals` parameters of the function are captured variables.
+    #[cfg(not(feature = "no_function"))]
+    fn closure(
+        &mut self,
+        f: &ScriptFnDef,
+        num_externals: usize,
+        level: usize,
+        col: usize,
+    ) -> String {
+        let params: Vec<_> = f.params.iter().skip(num_externals).cloned().collect();
+        let prefix = format!("|{}| ", params.join(", "));
+        let col = col + prefix.len();
+
+        let body = match f.body {
+            Stmt::Block(ref statements, ref span) => {
+                self.inline_block(statements, None, Some(span.end()), level, col)
+            }
+            Stmt::Expr(ref expr) => self.expr(expr, level, col),
+            ref stmt => self.stmt(stmt, level, col),
+        };
+
+        prefix + &body
+    }
+
+    /// Format a comma-separated list of items between delimiters.
+    ///
+    /// The list is kept on a single line if it fits, or if only the last item spans multiple lines
+    /// (e.g. a closure with a block body).  Otherwise, each item goes on its own line.
+    fn list(
+        &mut self,
+        open: &str,
+        items: &[ListItem],
+        close: &str,
+        level: usize,
+        col: usize,
+    ) -> String {
+        if items.is_empty() {
+            return format!("{}{}", open, close);
+        }
+
+        let start = self.next_comment;
+        let mut texts = Vec::with_capacity(items.len());
+        let mut item_col = col + open.len();
+
+        for item in items {
+            let text = self.list_item(item, level, item_col);
+            item_col = last_line_width(&text, item_col) + 2;
+            texts.push(text);
+        }
+
+        let text = format!("{}{}{}", open, texts.join(", "), close);
+
+        if self.fits(col, &text) {
+            return text;
+        }
+
+        // Only the last item spans multiple lines?
+        let (last, rest) = texts.split_last().unwrap();
+
+        if rest.iter().all(|t| !t.contains('\n'))
+            && last.contains('\n')
+            && text
+                .lines()
+                .all(|line| line.len() <= self.options.max_width)
+            && match items.last().unwrap() {
+                ListItem::Expr(expr) => is_hugging(expr),
+                ListItem::Property(_, _) => false,
+            }
+        {
+            return text;
+        }
+
+        // One item per line
+        self.next_comment = start;
+
+        let indent = self.indent(level + 1);
+        let col = (level + 1) * self.indent_width;
+        let mut text = open.to_string();
+
+        for item in items {
+            text.push('\n');
+            text.push_str(&indent);
+            text.push_str(&self.list_item(item, level + 1, col));
+            text.push(',');
+        }
+
+        text.push('\n');
+        text.push_str(&self.indent(level));
+        text.push_str(close);
+        text
+    }
+
+    /// Format an item in a comma-separated list.
+    fn list_item(&mut self, item: &ListItem, level: usize, col: usize) -> String {
+        match item {
+            ListItem::Expr(expr) => self.expr(expr, level, col),
+            ListItem::Property(name, expr) => {
+                let name = if is_valid_identifier(name.name.chars())
+                    && Token::lookup_from_syntax(&name.name).is_none()
+                {
+                    name.name.to_string()
+                } else {
+                    quote_string(&name.name)
+                };
+                let expr = self.expr(expr, level, col + name.len() + 2);
+                format!("{}: {}", name, expr)
+            }
+        }
+    }
+
+    /// Format a function call (not an operator).
+    fn fn_call(&mut self, expr: &Expr, level: usize, col: usize) -> String {
+        match expr {
+            Expr::FnCall(x, _, _) => {
+                let mut name = String::new();
+                if let Some(ref namespace) = x.namespace {
+                    let _ = write!(name, "{}", namespace);
+                }
+                name.push_str(&x.name);
+                if x.capture {
+                    name.push('!');
+                }
+                name.push('(');
+                let args: Vec<_> = x.args.iter().map(ListItem::Expr).collect();
+                let col = col + name.len() - 1;
+                self.list(&name, &args, ")", level, col)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Format an operand of a unary operator, or the left-hand side of `.` or `[`.
+    fn operand(&mut self, expr: &Expr, level: usize, col: usize) -> String {
+        if is_primary(expr) {
+            self.expr(expr, level, col)
+        } else {
+            format!("({})", self.expr(expr, level, col + 1))
+        }
+    }
+
+    /// Format a binary operator expression.
+    fn binary(
+        &mut self,
+        op: &str,
+        precedence: u8,
+        bind_right: bool,
+        lhs: &Expr,
+        rhs: &Expr,
+        level: usize,
+        col: usize,
+    ) -> String {
+        let lhs = match self.precedence(lhs) {
+            Some(p) if p < precedence || (p == precedence && bind_right) => {
+                format!("({})", self.expr(lhs, level, col + 1))
+            }
+            _ if is_closure_or_stmt(lhs) => format!("({})", self.expr(lhs, level, col + 1)),
+            _ => self.expr(lhs, level, col),
+        };
+        let prefix = format!("{} {} ", lhs, op);
+        let col = last_line_width(&prefix, col);
+        let rhs = match self.precedence(rhs) {
+            Some(p) if p < precedence || (p == precedence && !bind_right) => {
+                format!("({})", self.expr(rhs, level, col + 1))
+            }
+            _ if is_closure_or_stmt(rhs) => format!("({})", self.expr(rhs, level, col + 1)),
+            _ => self.expr(rhs, level, col),
+        };
+        prefix + &rhs
+    }
+
+    /// Get the operator, precedence and associativity of a binary operator function call.
+    fn binary_op(&self, expr: &Expr) -> Option<(String, u8, bool)> {
+        match expr {
+            Expr::FnCall(x, pos, _) if x.args.len() == 2 && x.namespace.is_none() => {
+                if x.native_only {
+                    let token = Token::lookup_from_syntax(&x.name)?;
+                    match token.precedence() {
+                        0 => None,
+                        p => Some((x.name.to_string(), p, token.is_bind_right())),
+                    }
+                } else if x.args[0].start_position() < *pos {
+                    match self.engine.custom_keywords.get(x.name.as_ref()) {
+                        Some(Some(p)) if *p > 0 => Some((x.name.to_string(), *p, false)),
+                        _ => None,
+                    }
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the precedence of an expression as an operand of a binary operator,
+    /// or `None` if it never needs parentheses.
+    fn precedence(&self, expr: &Expr) -> Option<u8> {
+        match expr {
+            Expr::FnCall(_, _, _) => self.binary_op(expr).map(|(_, p, _)| p),
+            Expr::And(_, _) => Some(Token::And.precedence()),
+            Expr::Or(_, _) => Some(Token::Or.precedence()),
+            Expr::In(_, _) => Some(Token::In.precedence()),
+            Expr::Expr(x) => self.precedence(x),
+            _ => None,
+        }
+    }
+
+    /// Format the right-hand side of a `.` property access or method call.
+    fn dot_rhs(&mut self, expr: &Expr, level: usize, col: usize) -> String {
+        match expr {
+            Expr::Property(x) => x.1.name.to_string(),
+            Expr::FnCall(_, _, _) => self.fn_call(expr, level, col),
+            Expr::Dot(x, _) => {
+                let lhs = self.dot_rhs(&x.lhs, level, col);
+                let col = last_line_width(&lhs, col) + 1;
+                format!("{}.{}", lhs, self.dot_rhs(&x.rhs, level, col))
+            }
+            Expr::Index(x, _, _) => {
+                let lhs = self.dot_rhs(&x.lhs, level, col);
+                let col = last_line_width(&lhs, col) + 1;
+                format!("{}[{}", lhs, self.index_rhs(&x.rhs, level, col))
+            }
+            Expr::Expr(x) => self.dot_rhs(x, level, col),
+            expr => self.expr(expr, level, col),
+        }
+    }
+
+    /// Format the index of a `[` ... `]` indexing operation, followed by `]` and any further
+    /// indexing or property access in the chain.
+    fn index_rhs(&mut self, expr: &Expr, level: usize, col: usize) -> String {
+        match expr {
+            Expr::Index(x, _, _) => {
+                let index = self.expr(&x.lhs, level, col);
+                let col = last_line_width(&index, col) + 2;
+                format!("{}][{}", index, self.index_rhs(&x.rhs, level, col))
+            }
+            Expr::Dot(x, _) => {
+                let index = self.expr(&x.lhs, level, col);
+                let col = last_line_width(&index, col) + 2;
+                format!("{}].{}", index, self.dot_rhs(&x.rhs, level, col))
+            }
+            Expr::Expr(x) => format!("{}]", self.expr(x, level, col)),
+            expr => format!("{}]", self.expr(expr, level, col)),
+        }
+    }
+
+    /// Format an expression.
+    fn expr(&mut self, expr: &Expr, level: usize, col: usize) -> String {
+        match expr {
+            Expr::IntegerConstant(x, _, _) => x.to_string(),
+            #[cfg(not(feature = "no_float"))]
+            Expr::FloatConstant(x, _, _) => {
+                let mut text = x.0.to_string();
+                if text.chars().all(|ch| ch == '-' || ch.is_ascii_digit()) {
+                    text.push_str(".0");
+                }
+                text
+            }
+            Expr::CharConstant(ch, _, _) => {
+                let mut text = String::from('\'');
+                escape_char(&mut text, *ch, '\'');
+                text.push('\'');
+                text
+            }
+            Expr::StringConstant(x, _) => quote_string(&x.name),
+            Expr::FnPointer(x, _) => {
+                #[cfg(not(feature = "no_function"))]
+                if let Some(f) = self.closures.get(x.name.as_str()).cloned() {
+                    return self.closure(&f, 0, level, col);
+                }
+                format!(
+                    "{}({})",
+                    crate::engine::KEYWORD_FN_PTR,
+                    quote_string(&x.name)
+                )
+            }
+            Expr::Variable(x) => match x.1 {
+                Some(ref namespace) => format!("{}{}", namespace, x.3.name),
+                None => x.3.name.clone(),
+            },
+            Expr::Property(x) => x.1.name.to_string(),
+            Expr::Stmt(statements, pos, _) => self.stmt_expr(statements, *pos, level, col),
+            Expr::Expr(x) => self.expr(x, level, col),
+            Expr::FnCall(x, _, _) => {
+                if let Some((op, precedence, bind_right)) = self.binary_op(expr) {
+                    self.binary(
+                        &op, precedence, bind_right, &x.args[0], &x.args[1], level, col,
+                    )
+                } else if x.native_only && x.args.len() == 1 && (x.name == "-" || x.name == "!") {
+                    format!("{}{}", x.name, self.operand(&x.args[0], level, col + 1))
+                } else {
+                    self.fn_call(expr, level, col)
+                }
+            }
+            Expr::Dot(x, _) => {
+                let lhs = self.operand(&x.lhs, level, col);
+                let lhs = match x.lhs {
+                    Expr::IntegerConstant(_, _, _) => format!("({})", lhs),
+                    #[cfg(not(feature = "no_float"))]
+                    Expr::FloatConstant(_, _, _) => format!("({})", lhs),
+                    _ => lhs,
+                };
+                let col = last_line_width(&lhs, col) + 1;
+                format!("{}.{}", lhs, self.dot_rhs(&x.rhs, level, col))
+            }
+            Expr::Index(x, _, _) => {
+                let lhs = self.operand(&x.lhs, level, col);
+                let col = last_line_width(&lhs, col) + 1;
+                format!("{}[{}", lhs, self.index_rhs(&x.rhs, level, col))
+            }
+            Expr::Array(x, _, _) => {
+                let items: Vec<_> = x.iter().map(ListItem::Expr).collect();
+                self.list("[", &items, "]", level, col)
+            }
+            Expr::Map(x, _, _) => {
+                let items: Vec<_> = x
+                    .iter()
+                    .map(|(name, expr)| ListItem::Property(name, expr))
+                    .collect();
+                self.list("#{", &items, "}", level, col)
+            }
+            Expr::In(x, _) | Expr::And(x, _) | Expr::Or(x, _) => {
+                let token = match expr {
+                    Expr::In(_, _) => Token::In,
+                    Expr::And(_, _) => Token::And,
+                    _ => Token::Or,
+                };
+                self.binary(
+                    &token.syntax(),
+                    token.precedence(),
+                    token.is_bind_right(),
+                    &x.lhs,
+                    &x.rhs,
+                    level,
+                    col,
+                )
+            }
+            Expr::True(_) => Token::True.syntax().into(),
+            Expr::False(_) => Token::False.syntax().into(),
+            Expr::Unit(_, _) => "()".into(),
+            Expr::Custom(_, pos, _) => {
+                if self.error.is_none() {
+                    self.error = Some(
+                        ParseErrorType::BadInput(LexError::ImproperSymbol(
+                            "custom syntax cannot be formatted".into(),
+                        ))
+                        .into_err(*pos),
+                    );
+                }
+                String::new()
+            }
+        }
+    }
+
+    /// Format a statement block used as an expression: an `if` expression, a block,
+    /// or a closure capturing external variables.
+    fn stmt_expr(
+        &mut self,
+        statements: &[Stmt],
+        pos: Position,
+        level: usize,
+        col: usize,
+    ) -> String {
+        match statements {
+            // if expression
+            [Stmt::IfThenElse(guard, x, if_pos)] if *if_pos == pos => {
+                let start = self.next_comment;
+                let text = self.if_stmt(guard, x, level, col, true);
+
+                if !text.contains('\n') {
+                    return text;
+                }
+
+                // Do not mix single-line and multi-line blocks
+                self.next_comment = start;
+                self.if_stmt(guard, x, level, col, false)
+            }
+            // Closure capturing external variables
+            #[cfg(not(feature = "no_closure"))]
+            [.., Stmt::Expr(Expr::FnCall(x, _, _))]
+                if x.name == KEYWORD_FN_PTR_CURRY
+                    && statements[..statements.len() - 1]
+                        .iter()
+                        .all(|s| matches!(s, Stmt::Share(_))) =>
+            {
+                match x.args[0] {
+                    Expr::FnPointer(ref f, _) if self.closures.contains_key(f.name.as_str()) => {
+                        let f = self.closures[f.name.as_str()].clone();
+                        self.closure(&f, x.args.len() - 1, level, col)
+                    }
+                    _ => self.inline_block(statements, Some(pos), None, level, col),
+                }
+            }
+            // Block
+            _ => self.inline_block(statements, Some(pos), None, level, col),
+        }
+    }
+}
+
+/// Does a statement need to be terminated by a semicolon?
+fn needs_semicolon(stmt: &Stmt) -> bool {
+    !matches!(
+        stmt,
+        Stmt::IfThenElse(_, _, _)
+            | Stmt::While(_, _, _)
+            | Stmt::Loop(_, _)
+            | Stmt::For(_, _, _)
+            | Stmt::Block(_, _)
+            | Stmt::TryCatch(_, _, _)
+    )
+}
+
+/// Is a statement an expression?
+fn is_expr(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Expr(_))
+}
+
+/// Can an expression be the operand of a unary operator, or the left-hand side of
+/// `.` or `[`, without parentheses?
+fn is_primary(expr: &Expr) -> bool {
+    match expr {
+        Expr::Expr(x) => is_primary(x),
+        Expr::IntegerConstant(x, _, _) => *x >= 0,
+        #[cfg(not(feature = "no_float"))]
+        Expr::FloatConstant(x, _, _) => x.0 >= 0.0,
+        // Operators are always native, except custom operators
+        Expr::FnCall(x, pos, _) => {
+            !x.native_only && (x.args.len() != 2 || x.args[0].start_position() > *pos)
+        }
+        Expr::FnPointer(_, _) => !is_closure_or_stmt(expr),
+        Expr::CharConstant(_, _, _)
+        | Expr::StringConstant(_, _)
+        | Expr::Variable(_)
+        | Expr::Property(_)
+        | Expr::Index(_, _, _)
+        | Expr::Array(_, _, _)
+        | Expr::Map(_, _, _)
+        | Expr::True(_)
+        | Expr::False(_)
+        | Expr::Unit(_, _) => true,
+        _ => false,
+    }
+}
+
+/// Is an expression a closure or a statement block?
+fn is_closure_or_stmt(expr: &Expr) -> bool {
+    match expr {
+        Expr::Expr(x) => is_closure_or_stmt(x),
+        #[cfg(not(feature = "no_function"))]
+        Expr::FnPointer(x, _) => is_anonymous_fn(&x.name),
+        Expr::Stmt(_, _, _) => true,
+        _ => false,
+    }
+}
+
+/// Can an expression spanning multiple lines be kept on the same line as the delimiters of the
+/// list containing it?
+fn is_hugging(expr: &Expr) -> bool {
+    match expr {
+        Expr::Expr(x) => is_hugging(x),
+        Expr::FnPointer(_, _) | Expr::Stmt(_, _, _) | Expr::Array(_, _, _) | Expr::Map(_, _, _) => {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Get the column at the end of a piece of text, starting at a particular column.
+fn last_line_width(text: &str, col: usize) -> usize {
+    match text.rfind('\n') {
+        Some(index) => text[index + 1..].chars().count(),
+        None => col + text.chars().count(),
+    }
+}
+
+/// Append a character to a literal, escaping it if necessary.
+fn escape_char(text: &mut String, ch: char, quote: char) {
+    match ch {
+        '\\' => text.push_str("\\\\"),
+        '\n' => text.push_str("\\n"),
+        '\r' => text.push_str("\\r"),
+        '\t' => text.push_str("\\t"),
+        ch if ch == quote => {
+            text.push('\\');
+            text.push(ch);
+        }
+        ch if ch.is_control() && (ch as u32) <= 0xff => {
+            let _ = write!(text, "\\x{:02x}", ch as u32);
+        }
+        ch if ch.is_control() => {
+            let _ = write!(text, "\\u{:04x}", ch as u32);
+        }
+        ch => text.push(ch),
+    }
+}
+
+/// Quote a string literal.
+fn quote_string(s: &str) -> String {
+    let mut text = String::with_capacity(s.len() + 2);
+    text.push('"');
+    s.chars().for_each(|ch| escape_char(&mut text, ch, '"'));
+    text.push('"');
+    text
+}
+
+impl Engine {
+    /// Format a script into canonical Rhai source code, keeping all comments.
+    ///
+    /// The script is first parsed (without optimization), so it must be free of syntax errors.
+    /// Scripts using custom syntax cannot be formatted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), rhai::ParseError> {
+    /// use rhai::{Engine, FormatOptions};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let code = engine.format("let x=40+2;x// answer", &FormatOptions::default())?;
+    ///
+    /// assert_eq!(code, "let x = 40 + 2;\nx // answer\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn format(&self, script: &str, options: &FormatOptions) -> Result<String, ParseError> {
+        let ast = self.compile_with_scope_and_optimization_level(
+            &Default::default(),
+            &[script],
+            OptimizationLevel::None,
+            false,
+        )?;
+
+        let mut formatter = Formatter::new(self, options, &ast);
+        formatter.trivia = Trivia::scan(self, script);
+        formatter.has_source = true;
+        formatter.format(&ast)
+    }
+
+    /// Pretty-print an `AST` into Rhai source code.
+    ///
+    /// Comments are not kept in an `AST`, so they do not appear in the output.
+    /// An `AST` containing custom syntax cannot be pretty-printed.
+    pub fn format_ast(&self, ast: &AST, options: &FormatOptions) -> Result<String, ParseError> {
+        Formatter::new(self, options, ast).format(ast)
+    }
+}
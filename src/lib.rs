@@ -58,6 +58,11 @@
 extern crate alloc;
 
 mod ast;
+mod coverage;
+mod cst;
+mod debugger;
+#[cfg(feature = "metadata")]
+mod docs;
 mod dynamic;
 mod engine;
 mod engine_api;
@@ -67,18 +72,26 @@ mod fn_call;
 mod fn_func;
 mod fn_native;
 mod fn_register;
+mod formatter;
+mod lint;
+#[cfg(feature = "metadata")]
+mod metadata;
 mod module;
 mod optimize;
 pub mod packages;
 mod parse_error;
 mod parser;
 pub mod plugin;
+#[cfg(not(feature = "no_std"))]
+mod profiler;
 mod result;
 mod scope;
 #[cfg(feature = "serde")]
 mod serde_impl;
 mod stdlib;
+mod strict;
 mod syntax;
+mod test_runner;
 mod token;
 mod r#unsafe;
 mod utils;
@@ -110,16 +123,28 @@ pub type FLOAT = f64;
 pub type FLOAT = f32;
 
 pub use ast::AST;
+pub use coverage::{BranchCoverage, BranchKind, Coverage, CoverageReport, StatementCoverage};
+pub use cst::{IdentRole, NodeKind, SyntaxElement, SyntaxNode, SyntaxToken, SyntaxTree, TokenKind};
+pub use debugger::{BreakPoint, Debugger, DebuggerCommand, DebuggerEvent};
+#[cfg(feature = "metadata")]
+pub use docs::{DocsFormat, DocsOptions, DocsPage};
 pub use dynamic::Dynamic;
 pub use engine::{Engine, EvalContext};
 pub use fn_native::{FnPtr, NativeCallContext};
 pub use fn_register::{RegisterFn, RegisterResultFn};
+pub use formatter::FormatOptions;
+pub use lint::{LintId, LintOptions, LintWarning, Severity};
+#[cfg(feature = "metadata")]
+pub use metadata::{FnKind, FnMetadata, FnParamMetadata, Metadata, ModuleMetadata, TypeMetadata};
 pub use module::Module;
 pub use parse_error::{LexError, ParseError, ParseErrorType};
-pub use result::EvalAltResult;
+#[cfg(not(feature = "no_std"))]
+pub use profiler::{FunctionProfile, LineProfile, ProfileMetric, ProfileReport, Profiler};
+pub use result::{CallFrame, EvalAltResult};
 pub use scope::Scope;
 pub use syntax::Expression;
-pub use token::{Position, NO_POS};
+pub use test_runner::{TestOptions, TestReport, TestResult};
+pub use token::{Location, Position, Span, NO_POS};
 pub use utils::ImmutableString;
 
 #[cfg(feature = "internals")]
@@ -0,0 +1,651 @@
+//! Module implementing the static linter.
+
+use crate::ast::{Expr, Stmt, AST};
+use crate::engine::{Engine, KEYWORD_EVAL};
+use crate::optimize::OptimizationLevel;
+use crate::parse_error::ParseError;
+use crate::token::{Position, Token};
+
+#[cfg(not(feature = "no_function"))]
+use crate::ast::ScriptFnDef;
+
+#[cfg(not(feature = "no_function"))]
+use crate::engine::is_anonymous_fn;
+
+#[cfg(not(feature = "no_function"))]
+use crate::fn_native::Shared;
+
+#[cfg(not(feature = "no_closure"))]
+use crate::engine::KEYWORD_FN_PTR_CURRY;
+
+use crate::stdlib::{collections::HashMap, fmt, string::String, vec::Vec};
+
+/// Identifier of a lint check.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum LintId {
+    /// A variable that is never used.
+    UnusedVariable,
+    /// A function parameter that is never used.
+    UnusedParameter,
+    /// A variable declaration that shadows another variable or parameter in the same function.
+    ShadowedVariable,
+    /// A statement that can never be run because it follows `return`, `throw`, `break` or
+    /// `continue`.
+    UnreachableCode,
+    /// A value assigned to a variable that is never read.
+    UnusedAssignment,
+    /// An `if` or `while` condition that is a constant.
+    ConstantCondition,
+}
+
+impl LintId {
+    /// All lint checks.
+    pub const ALL: [Self; 6] = [
+        Self::UnusedVariable,
+        Self::UnusedParameter,
+        Self::ShadowedVariable,
+        Self::UnreachableCode,
+        Self::UnusedAssignment,
+        Self::ConstantCondition,
+    ];
+
+    /// Get the name of this lint check, e.g. `unused_variable`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UnusedVariable => "unused_variable",
+            Self::UnusedParameter => "unused_parameter",
+            Self::ShadowedVariable => "shadowed_variable",
+            Self::UnreachableCode => "unreachable_code",
+            Self::UnusedAssignment => "unused_assignment",
+            Self::ConstantCondition => "constant_condition",
+        }
+    }
+    /// Find a lint check by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|id| id.name() == name)
+    }
+}
+
+impl fmt::Display for LintId {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Severity of a lint check.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    /// The check is turned off.
+    Off,
+    /// Results of the check are warnings.
+    Warning,
+    /// Results of the check are errors.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// Configuration of the severity of lint checks.
+///
+/// All checks default to `Severity::Warning`.
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions(HashMap<LintId, Severity>);
+
+impl LintOptions {
+    /// Create a new `LintOptions` with all checks at `Severity::Warning`.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Set the severity of a lint check.  Use `Severity::Off` to turn it off.
+    #[inline(always)]
+    pub fn set_severity(&mut self, id: LintId, severity: Severity) -> &mut Self {
+        self.0.insert(id, severity);
+        self
+    }
+    /// Get the severity of a lint check.
+    #[inline(always)]
+    pub fn severity(&self, id: LintId) -> Severity {
+        self.0.get(&id).copied().unwrap_or(Severity::Warning)
+    }
+}
+
+/// A result of a lint check.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LintWarning {
+    /// The lint check.
+    pub id: LintId,
+    /// Severity of the result.
+    pub severity: Severity,
+    /// Description of the problem.
+    pub message: String,
+    /// Location of the problem.
+    pub pos: Position,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.id, self.message)?;
+
+        if !self.pos.is_none() {
+            write!(f, " ({})", self.pos)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A variable in scope during linting.
+#[derive(Debug, Clone)]
+struct Var {
+    /// Name of the variable.
+    name: String,
+    /// Position of the declaration.
+    pos: Position,
+    /// Is this a function parameter?
+    is_param: bool,
+    /// Has the variable been read?
+    used: bool,
+    /// Is the variable captured by a closure (or a captured variable within a closure)?
+    /// Values assigned to it may then be read elsewhere.
+    shared: bool,
+    /// Position and region of the last value assigned that is not yet read.
+    assigned: Option<(Position, usize)>,
+    /// Loop nesting level of the declaration.
+    loop_level: usize,
+}
+
+/// State of a running linter.
+struct Linter<'a> {
+    /// Lint options.
+    options: &'a LintOptions,
+    /// Anonymous functions (i.e. closures), by name.
+    #[cfg(not(feature = "no_function"))]
+    closures: HashMap<String, Shared<ScriptFnDef>>,
+    /// Results.
+    warnings: Vec<LintWarning>,
+    /// Variables in scope.
+    vars: Vec<Var>,
+    /// Index of the first variable of the current function in `vars`.
+    fn_start: usize,
+    /// Current region of straight-line code.  A new region starts with each conditional branch,
+    /// loop body or closure body.
+    region: usize,
+    /// Total number of regions so far.
+    num_regions: usize,
+    /// Current loop nesting level.
+    loop_level: usize,
+}
+
+impl<'a> Linter<'a> {
+    /// Report a problem.
+    fn report(&mut self, id: LintId, message: String, pos: Position) {
+        match self.options.severity(id) {
+            Severity::Off => (),
+            severity => self.warnings.push(LintWarning {
+                id,
+                severity,
+                message,
+                pos,
+            }),
+        }
+    }
+
+    /// Find a variable in the current function.
+    fn find_var(&mut self, name: &str) -> Option<&mut Var> {
+        self.vars[self.fn_start..]
+            .iter_mut()
+            .rev()
+            .find(|v| v.name == name)
+    }
+
+    /// Declare a new variable (or parameter) in the current scope.
+    fn declare(&mut self, name: &str, pos: Position, is_param: bool, assigned: bool) {
+        let previous = self.find_var(name).map(|v| (v.is_param, v.pos));
+
+        if let Some((was_param, previous_pos)) = previous {
+            let kind = if was_param { "parameter" } else { "variable" };
+            let message = if previous_pos.is_none() {
+                format!("variable '{}' shadows an earlier {}", name, kind)
+            } else {
+                format!(
+                    "variable '{}' shadows an earlier {} ({})",
+                    name, kind, previous_pos
+                )
+            };
+            self.report(LintId::ShadowedVariable, message, pos);
+        }
+
+        self.vars.push(Var {
+            name: name.into(),
+            pos,
+            is_param,
+            used: false,
+            shared: false,
+            assigned: if assigned {
+                Some((pos, self.region))
+            } else {
+                None
+            },
+            loop_level: self.loop_level,
+        });
+    }
+
+    /// Read a variable.
+    fn read(&mut self, name: &str) {
+        if let Some(var) = self.find_var(name) {
+            var.used = true;
+            var.assigned = None;
+        }
+    }
+
+    /// Assign a value to a variable.
+    fn write(&mut self, name: &str, pos: Position) {
+        let (region, loop_level) = (self.region, self.loop_level);
+
+        let overwritten = match self.find_var(name) {
+            // Values assigned within a loop may be read in the next iteration
+            Some(var) if var.shared || var.loop_level < loop_level => {
+                var.assigned = None;
+                None
+            }
+            Some(var) => {
+                let overwritten = match var.assigned {
+                    Some((previous, r)) if r == region => Some(previous),
+                    _ => None,
+                };
+                var.assigned = Some((pos, region));
+                overwritten
+            }
+            None => None,
+        };
+
+        if let Some(previous) = overwritten {
+            self.report(
+                LintId::UnusedAssignment,
+                format!("value assigned to '{}' is never read", name),
+                previous,
+            );
+        }
+    }
+
+    /// Close all scopes down to a particular number of variables, reporting unused variables.
+    fn close_scope(&mut self, start: usize) {
+        for var in self.vars.split_off(start) {
+            if var.name.starts_with('_') {
+                continue;
+            }
+
+            match var {
+                Var { used: false, .. } => {
+                    let (id, kind) = if var.is_param {
+                        (LintId::UnusedParameter, "parameter")
+                    } else {
+                        (LintId::UnusedVariable, "variable")
+                    };
+                    self.report(id, format!("unused {} '{}'", kind, var.name), var.pos);
+                }
+                Var {
+                    shared: false,
+                    assigned: Some((pos, _)),
+                    ..
+                } => self.report(
+                    LintId::UnusedAssignment,
+                    format!("value assigned to '{}' is never read", var.name),
+                    pos,
+                ),
+                _ => (),
+            }
+        }
+    }
+
+    /// Run a closure within a new region of straight-line code.
+    fn in_region(&mut self, f: impl FnOnce(&mut Self)) {
+        let region = self.region;
+        self.num_regions += 1;
+        self.region = self.num_regions;
+        f(self);
+        self.region = region;
+    }
+
+    /// Run a closure within a loop body.
+    fn in_loop(&mut self, f: impl FnOnce(&mut Self)) {
+        self.loop_level += 1;
+        self.in_region(f);
+        self.loop_level -= 1;
+    }
+
+    /// Lint a script-defined function, with the first `num_externals` parameters being
+    /// variables captured from the enclosing scope.
+    #[cfg(not(feature = "no_function"))]
+    fn function(&mut self, f: &ScriptFnDef, num_externals: usize) {
+        let (fn_start, loop_level) = (self.fn_start, self.loop_level);
+        self.fn_start = self.vars.len();
+        self.loop_level = 0;
+
+        self.in_region(|this| {
+            let pos = f.span.start();
+
+            f.params.iter().enumerate().for_each(|(index, param)| {
+                this.declare(param, pos, true, false);
+
+                if index < num_externals {
+                    let var = this.vars.last_mut().unwrap();
+                    var.used = true;
+                    var.shared = true;
+                }
+            });
+
+            match f.body {
+                Stmt::Block(ref statements, _) => this.statements(statements),
+                ref stmt => this.stmt(stmt),
+            }
+
+            this.close_scope(this.fn_start);
+        });
+
+        self.fn_start = fn_start;
+        self.loop_level = loop_level;
+    }
+
+    /// Lint a block of statements, reporting unreachable code.
+    fn statements(&mut self, statements: &[Stmt]) {
+        let start = self.vars.len();
+        let mut diverged = false;
+
+        for stmt in statements.iter().filter(|s| !s.is_noop()) {
+            if diverged {
+                self.report(
+                    LintId::UnreachableCode,
+                    "unreachable code".into(),
+                    stmt.start_position(),
+                );
+                // Report only once per block
+                self.stmt(stmt);
+                break;
+            }
+
+            self.stmt(stmt);
+            diverged = always_exits(stmt);
+        }
+
+        self.close_scope(start);
+    }
+
+    /// Lint a statement.
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Noop(_) | Stmt::Continue(_) | Stmt::Break(_) => (),
+            Stmt::IfThenElse(guard, x, _) => {
+                self.condition(guard);
+                self.in_region(|this| this.stmt(&x.0));
+                if let Some(ref stmt) = x.1 {
+                    self.in_region(|this| this.stmt(stmt));
+                }
+            }
+            Stmt::While(guard, body, _) => {
+                self.in_loop(|this| {
+                    this.condition(guard);
+                    this.stmt(body);
+                });
+            }
+            Stmt::Loop(body, _) => self.in_loop(|this| this.stmt(body)),
+            Stmt::For(expr, x, _) => {
+                self.expr(expr);
+                self.in_loop(|this| {
+                    let start = this.vars.len();
+                    this.declare(&x.0.name, x.0.pos, false, false);
+                    this.stmt(&x.1);
+                    this.close_scope(start);
+                });
+            }
+            Stmt::Let(x, expr, _) | Stmt::Const(x, expr, _) => {
+                if let Some(expr) = expr {
+                    self.expr(expr);
+                }
+                self.declare(&x.0.name, x.0.pos, false, expr.is_some());
+            }
+            Stmt::Assignment(x, _) => {
+                self.expr(&x.2);
+
+                match x.0 {
+                    Expr::Variable(ref v) if v.1.is_none() => {
+                        // Compound assignments read the variable
+                        if !x.1.is_empty() {
+                            self.read(&v.3.name);
+                        }
+                        self.write(&v.3.name, v.3.pos);
+                    }
+                    ref lhs => self.expr(lhs),
+                }
+            }
+            Stmt::Block(statements, _) => self.statements(statements),
+            Stmt::TryCatch(x, _, _) => {
+                self.in_region(|this| this.stmt(&x.0));
+                self.in_region(|this| {
+                    let start = this.vars.len();
+                    if let Some(ref var) = x.1 {
+                        this.declare(&var.name, var.pos, false, false);
+                    }
+                    if let Some(ref var) = x.2 {
+                        this.declare(&var.name, var.pos, false, false);
+                    }
+                    this.stmt(&x.3);
+                    this.close_scope(start);
+                });
+                if let Some(ref stmt) = x.4 {
+                    self.stmt(stmt);
+                }
+            }
+            Stmt::Expr(expr) => self.expr(expr),
+            Stmt::ReturnWithVal(_, expr, _) => {
+                if let Some(expr) = expr {
+                    self.expr(expr);
+                }
+            }
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Import(expr, _, _) => self.expr(expr),
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Export(list, _) => list.iter().for_each(|(name, _)| self.read(&name.name)),
+            #[cfg(not(feature = "no_closure"))]
+            Stmt::Share(x) => {
+                if let Some(var) = self.find_var(&x.0.name) {
+                    var.shared = true;
+                    var.assigned = None;
+                }
+            }
+            Stmt::Source(_) => (),
+        }
+    }
+
+    /// Lint the condition of an `if` or `while` statement.
+    fn condition(&mut self, expr: &Expr) {
+        if is_constant(expr) {
+            self.report(
+                LintId::ConstantCondition,
+                "condition is always the same".into(),
+                expr.start_position(),
+            );
+        }
+        self.expr(expr);
+    }
+
+    /// Lint an expression.
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Variable(x) if x.1.is_none() => self.read(&x.3.name),
+            Expr::Stmt(statements, _, _) => self.statements(statements),
+            Expr::Expr(x) => self.expr(x),
+            #[cfg(not(feature = "no_function"))]
+            Expr::FnPointer(x, _) => {
+                if let Some(f) = self.closures.get(x.name.as_str()).cloned() {
+                    self.function(&f, 0);
+                }
+            }
+            // Closure capturing external variables
+            #[cfg(not(feature = "no_closure"))]
+            Expr::FnCall(x, _, _) if x.name == KEYWORD_FN_PTR_CURRY && x.namespace.is_none() => {
+                match x.args[0] {
+                    Expr::FnPointer(ref f, _) if self.closures.contains_key(f.name.as_str()) => {
+                        let f = self.closures[f.name.as_str()].clone();
+                        x.args[1..].iter().for_each(|e| self.expr(e));
+                        self.function(&f, x.args.len() - 1);
+                    }
+                    _ => x.args.iter().for_each(|e| self.expr(e)),
+                }
+            }
+            Expr::FnCall(x, _, _) => {
+                x.args.iter().for_each(|e| self.expr(e));
+
+                // 'eval' can access any variable
+                if x.name == KEYWORD_EVAL && x.namespace.is_none() {
+                    let fn_start = self.fn_start;
+                    self.vars[fn_start..].iter_mut().for_each(|v| {
+                        v.used = true;
+                        v.assigned = None;
+                    });
+                }
+            }
+            Expr::Dot(x, _) | Expr::Index(x, _, _) | Expr::In(x, _) => {
+                self.expr(&x.lhs);
+                self.expr(&x.rhs);
+            }
+            Expr::And(x, _) | Expr::Or(x, _) => {
+                self.expr(&x.lhs);
+                self.in_region(|this| this.expr(&x.rhs));
+            }
+            Expr::Array(x, _, _) => x.iter().for_each(|e| self.expr(e)),
+            Expr::Map(x, _, _) => x.iter().for_each(|(_, e)| self.expr(e)),
+            Expr::Custom(x, _, _) => {
+                self.in_region(|this| x.keywords.iter().for_each(|e| this.expr(e)))
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Does a statement always exit the enclosing block (via `return`, `throw`, `break` or `continue`)?
+fn always_exits(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::ReturnWithVal(_, _, _) | Stmt::Break(_) | Stmt::Continue(_) => true,
+        Stmt::Block(statements, _) => statements.iter().any(always_exits),
+        Stmt::IfThenElse(_, x, _) => match x.1 {
+            Some(ref stmt) => always_exits(&x.0) && always_exits(stmt),
+            None => false,
+        },
+        Stmt::TryCatch(x, _, _) => {
+            (always_exits(&x.0) && always_exits(&x.3)) || x.4.as_ref().is_some_and(always_exits)
+        }
+        Stmt::Expr(Expr::Stmt(statements, _, _)) => statements.iter().any(always_exits),
+        _ => false,
+    }
+}
+
+/// Is an expression made up of constants only?
+fn is_constant(expr: &Expr) -> bool {
+    match expr {
+        Expr::Expr(x) => is_constant(x),
+        Expr::IntegerConstant(_, _, _)
+        | Expr::CharConstant(_, _, _)
+        | Expr::StringConstant(_, _)
+        | Expr::True(_)
+        | Expr::False(_)
+        | Expr::Unit(_, _) => true,
+        #[cfg(not(feature = "no_float"))]
+        Expr::FloatConstant(_, _, _) => true,
+        // Operators are native
+        Expr::FnCall(x, _, _) => {
+            x.native_only
+                && x.namespace.is_none()
+                && Token::lookup_from_syntax(&x.name).is_some()
+                && x.args.iter().all(is_constant)
+        }
+        Expr::And(x, _) | Expr::Or(x, _) | Expr::In(x, _) => {
+            is_constant(&x.lhs) && is_constant(&x.rhs)
+        }
+        _ => false,
+    }
+}
+
+impl Engine {
+    /// Check a script for likely mistakes, returning all problems found, sorted by position.
+    ///
+    /// The script is parsed without optimization, which would otherwise remove code
+    /// (e.g. constant conditions).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), rhai::ParseError> {
+    /// use rhai::{Engine, LintId, LintOptions, Severity};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut options = LintOptions::new();
+    /// options.set_severity(LintId::UnusedAssignment, Severity::Off);
+    ///
+    /// let warnings = engine.lint("let x = 1; x = 2; print(x); let y = 42;", &options)?;
+    ///
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(warnings[0].id, LintId::UnusedVariable);
+    /// assert_eq!(warnings[0].message, "unused variable 'y'");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lint(
+        &self,
+        script: &str,
+        options: &LintOptions,
+    ) -> Result<Vec<LintWarning>, ParseError> {
+        let ast = self.compile_with_scope_and_optimization_level(
+            &Default::default(),
+            &[script],
+            OptimizationLevel::None,
+            false,
+        )?;
+
+        Ok(self.lint_ast(&ast, options))
+    }
+
+    /// Check an `AST` for likely mistakes, returning all problems found, sorted by position.
+    ///
+    /// The `AST` should be compiled with `OptimizationLevel::None`, as optimization removes code
+    /// (e.g. constant conditions) and may hide problems.
+    pub fn lint_ast(&self, ast: &AST, options: &LintOptions) -> Vec<LintWarning> {
+        let mut linter = Linter {
+            options,
+            #[cfg(not(feature = "no_function"))]
+            closures: ast
+                .iter_functions()
+                .filter(|(_, name, _, _)| is_anonymous_fn(name))
+                .map(|(_, name, _, f)| (name.into(), f))
+                .collect(),
+            warnings: Default::default(),
+            vars: Default::default(),
+            fn_start: 0,
+            region: 0,
+            num_regions: 0,
+            loop_level: 0,
+        };
+
+        linter.statements(ast.stmts());
+
+        #[cfg(not(feature = "no_function"))]
+        ast.iter_functions()
+            .filter(|(_, name, _, _)| !is_anonymous_fn(name))
+            .for_each(|(_, _, _, f)| linter.function(&f, 0));
+
+        let mut warnings = linter.warnings;
+        warnings.sort_by_key(|w| w.pos);
+        warnings
+    }
+}
@@ -0,0 +1,427 @@
+//! Module defining the metadata of functions, type iterators and custom types registered
+//! in an `Engine`.
+
+use crate::ast::FnAccess;
+use crate::engine::Engine;
+use crate::module::{FuncInfo, Module};
+use crate::utils::ImmutableString;
+use crate::INT;
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+#[cfg(not(feature = "no_index"))]
+use crate::engine::{FN_IDX_GET, FN_IDX_SET};
+
+#[cfg(not(feature = "no_object"))]
+use crate::engine::{FN_GET, FN_SET};
+
+#[cfg(not(feature = "no_function"))]
+use crate::engine::is_anonymous_fn;
+
+use crate::stdlib::{
+    any::{type_name, TypeId},
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde::Serialize;
+
+/// Type of a registered function.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FnKind {
+    /// Normal function.
+    Function,
+    /// Operator, e.g. `+` or `==`.
+    Operator,
+    /// Property getter, named `get$` followed by the property name.
+    Getter,
+    /// Property setter, named `set$` followed by the property name.
+    Setter,
+    /// Indexer getter.
+    IndexGetter,
+    /// Indexer setter.
+    IndexSetter,
+}
+
+impl FnKind {
+    /// Get the type of a function from its name.
+    fn from_name(name: &str) -> Self {
+        #[cfg(not(feature = "no_index"))]
+        if name == FN_IDX_GET {
+            return Self::IndexGetter;
+        } else if name == FN_IDX_SET {
+            return Self::IndexSetter;
+        }
+
+        #[cfg(not(feature = "no_object"))]
+        if name.starts_with(FN_GET) {
+            return Self::Getter;
+        } else if name.starts_with(FN_SET) {
+            return Self::Setter;
+        }
+
+        match name.chars().next() {
+            Some(ch) if ch.is_alphabetic() || ch == '_' => Self::Function,
+            _ => Self::Operator,
+        }
+    }
+}
+
+/// Metadata of a function parameter.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+pub struct FnParamMetadata {
+    /// Name of the parameter, if available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Type of the parameter, if available.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+}
+
+/// Metadata of a registered function.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FnMetadata {
+    /// Path of the module holding the function (e.g. `foo::bar`), empty at global level.
+    pub namespace: String,
+    /// Type of the function.
+    pub kind: FnKind,
+    /// Name of the function.
+    pub name: String,
+    /// Is the function script-defined?
+    pub is_script: bool,
+    /// Number of parameters.
+    pub num_params: usize,
+    /// Parameters, if available.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<FnParamMetadata>,
+    /// Return type, if available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<String>,
+    /// Signature of the function, e.g. `add(x: i64, y: i64) -> i64`.
+    pub signature: String,
+    /// Doc comments of the function (including the `///` or `/** */` markers), if any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub doc_comments: Vec<String>,
+}
+
+impl FnMetadata {
+    /// Create the metadata of a function.
+    fn new(engine: &Engine, namespace: &str, info: &FuncInfo) -> Self {
+        let (params, return_type) = fn_params(engine, info);
+
+        let signature = {
+            let params: Vec<_> = if params.is_empty() {
+                (0..info.params).map(|_| "_".to_string()).collect()
+            } else {
+                params
+                    .iter()
+                    .map(|p| match (&p.name, &p.typ) {
+                        (Some(name), Some(typ)) => format!("{}: {}", name, typ),
+                        (Some(name), None) => name.clone(),
+                        (None, Some(typ)) => format!("_: {}", typ),
+                        (None, None) => "_".to_string(),
+                    })
+                    .collect()
+            };
+
+            match return_type {
+                Some(ref typ) if typ != "()" => {
+                    format!("{}({}) -> {}", info.name, params.join(", "), typ)
+                }
+                _ => format!("{}({})", info.name, params.join(", ")),
+            }
+        };
+
+        Self {
+            namespace: namespace.into(),
+            kind: FnKind::from_name(&info.name),
+            name: info.name.clone(),
+            is_script: info.func.is_script(),
+            num_params: info.params,
+            params,
+            return_type,
+            signature,
+            doc_comments: info.comments().to_vec(),
+        }
+    }
+}
+
+/// Get the parameters and return type of a function, if available.
+fn fn_params(engine: &Engine, info: &FuncInfo) -> (Vec<FnParamMetadata>, Option<String>) {
+    #[cfg(not(feature = "no_function"))]
+    if info.func.is_script() {
+        let f = info.func.get_fn_def();
+
+        let params = f
+            .params
+            .iter()
+            .zip(f.param_types.iter())
+            .map(|(name, typ)| FnParamMetadata {
+                name: Some(name.clone()),
+                typ: typ.as_ref().map(ImmutableString::to_string),
+            })
+            .collect();
+
+        return (
+            params,
+            f.return_type.as_ref().map(ImmutableString::to_string),
+        );
+    }
+
+    match info.param_names.split_last() {
+        Some((return_type, params)) => {
+            let params = params
+                .iter()
+                .map(|param| {
+                    let mut parts = param.splitn(2, ": ");
+                    let name = parts.next().unwrap_or_default().trim();
+                    let typ = parts.next();
+
+                    FnParamMetadata {
+                        name: match name {
+                            "" | "_" => None,
+                            name => Some(name.into()),
+                        },
+                        typ: typ.map(|typ| format_type_name(engine, typ)),
+                    }
+                })
+                .collect();
+
+            (params, Some(format_type_name(engine, return_type)))
+        }
+        None => (Vec::new(), None),
+    }
+}
+
+/// Remove the module paths from a Rust type name, e.g. `core::ops::Range<i64>` becomes `Range<i64>`.
+fn strip_paths(name: &str) -> String {
+    let mut result = String::new();
+    let mut segment = String::new();
+
+    for ch in name.chars() {
+        if ch.is_alphanumeric() || ch == '_' || ch == ':' {
+            segment.push(ch);
+        } else {
+            result.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            result.push(ch);
+        }
+    }
+
+    result.push_str(segment.rsplit("::").next().unwrap_or_default());
+    result
+}
+
+/// Format a Rust type name (e.g. `alloc::string::String` or `&mut INT`) for display.
+fn format_type_name(engine: &Engine, name: &str) -> String {
+    let name = name.trim();
+
+    if let Some(name) = name.strip_prefix("&mut ") {
+        return format!("&mut {}", format_type_name(engine, name));
+    }
+
+    let mapped = engine.map_type_name(name);
+
+    if mapped != name {
+        return mapped.into();
+    }
+
+    let name = strip_paths(name);
+
+    // Custom types named in source code (e.g. by plugin modules) are not fully qualified
+    if let Some((_, display)) = engine
+        .type_names
+        .iter()
+        .find(|(typ, _)| strip_paths(typ) == name)
+    {
+        return display.clone();
+    }
+
+    match name.as_str() {
+        "ImmutableString" | "String" | "&str" | "str" => "string".into(),
+        "Array" => "array".into(),
+        "Map" => "map".into(),
+        "FnPtr" => "Fn".into(),
+        "Instant" => "timestamp".into(),
+        "INT" => type_name::<INT>().into(),
+        #[cfg(not(feature = "no_float"))]
+        "FLOAT" => type_name::<FLOAT>().into(),
+        _ => name,
+    }
+}
+
+/// Get the name of a type producing an iterator, if available.
+fn iterator_type_name(engine: &Engine, id: TypeId, name: Option<&str>) -> Option<String> {
+    match name {
+        Some(name) => Some(format_type_name(engine, name)),
+        None if id == TypeId::of::<ImmutableString>() => Some("string".into()),
+        None => None,
+    }
+}
+
+/// Metadata of the functions, type iterators and sub-modules in a module.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize)]
+pub struct ModuleMetadata {
+    /// Functions, sorted by name.
+    pub functions: Vec<FnMetadata>,
+    /// Types producing iterators (i.e. which can be iterated by `for` loops).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub iterators: Vec<String>,
+    /// Sub-modules by name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub modules: BTreeMap<String, ModuleMetadata>,
+}
+
+impl ModuleMetadata {
+    /// Add the public functions, type iterators and sub-modules in a module.
+    fn add_module(&mut self, engine: &Engine, namespace: &str, module: &Module) {
+        self.functions.extend(
+            module
+                .iter_fn()
+                .filter(|f| f.access == FnAccess::Public)
+                .filter(|_f| {
+                    #[cfg(not(feature = "no_function"))]
+                    return !is_anonymous_fn(&_f.name);
+                    #[cfg(feature = "no_function")]
+                    return true;
+                })
+                .map(|f| FnMetadata::new(engine, namespace, f)),
+        );
+
+        self.iterators.extend(
+            module
+                .iter_type_iterators()
+                .filter_map(|(id, name)| iterator_type_name(engine, id, name)),
+        );
+
+        module.iter_sub_modules().for_each(|(name, m)| {
+            let namespace = if namespace.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}::{}", namespace, name)
+            };
+
+            self.modules
+                .entry(name.into())
+                .or_default()
+                .add_module(engine, &namespace, m);
+        });
+    }
+
+    /// Sort the functions and type iterators, removing duplicates.
+    fn sort(&mut self) {
+        self.functions.sort_by(|a, b| {
+            (&a.name, a.num_params, &a.signature).cmp(&(&b.name, b.num_params, &b.signature))
+        });
+        self.functions
+            .dedup_by(|a, b| a.name == b.name && a.signature == b.signature);
+        self.iterators.sort();
+        self.iterators.dedup();
+        self.modules.values_mut().for_each(Self::sort);
+    }
+}
+
+/// Metadata of a custom type.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeMetadata {
+    /// Name of the type, as shown in scripts (e.g. by `type_of`).
+    pub name: String,
+    /// Full Rust type name.
+    pub rust_type: String,
+}
+
+/// Metadata of all the functions, type iterators, sub-modules and custom types registered
+/// in an `Engine`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize)]
+pub struct Metadata {
+    /// Functions, sorted by name.
+    pub functions: Vec<FnMetadata>,
+    /// Types producing iterators (i.e. which can be iterated by `for` loops).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub iterators: Vec<String>,
+    /// Sub-modules by name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub modules: BTreeMap<String, ModuleMetadata>,
+    /// Custom types registered with names, sorted by name.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<TypeMetadata>,
+}
+
+impl Engine {
+    /// Generate metadata of all the functions, type iterators and custom types registered
+    /// in the `Engine`, optionally including those in loaded packages.
+    ///
+    /// Available under the `metadata` feature only.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, FnKind, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("add", |x: i64, y: i64| x + y);
+    ///
+    /// let metadata = engine.gen_metadata(false);
+    ///
+    /// assert_eq!(metadata.functions.len(), 1);
+    /// assert_eq!(metadata.functions[0].kind, FnKind::Function);
+    /// assert_eq!(metadata.functions[0].signature, "add(_: i64, _: i64) -> i64");
+    /// ```
+    pub fn gen_metadata(&self, include_packages: bool) -> Metadata {
+        let mut root = ModuleMetadata::default();
+
+        root.add_module(self, "", &self.global_module);
+
+        if include_packages {
+            self.packages
+                .iter()
+                .for_each(|p| root.add_module(self, "", p));
+        }
+
+        root.sort();
+
+        let mut types: Vec<_> = self
+            .type_names
+            .iter()
+            .map(|(rust_type, name)| TypeMetadata {
+                name: name.clone(),
+                rust_type: rust_type.clone(),
+            })
+            .collect();
+
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Metadata {
+            functions: root.functions,
+            iterators: root.iterators,
+            modules: root.modules,
+            types,
+        }
+    }
+
+    /// Generate metadata of all the functions, type iterators and sub-modules in a module
+    /// (e.g. one loaded by a module resolver), with the path under which it is imported.
+    ///
+    /// Available under the `metadata` feature only.
+    pub fn gen_module_metadata(&self, path: &str, module: &Module) -> ModuleMetadata {
+        let mut metadata = ModuleMetadata::default();
+        metadata.add_module(self, path, module);
+        metadata.sort();
+        metadata
+    }
+
+    /// Generate metadata of all the functions, type iterators and custom types registered
+    /// in the `Engine` (see `Engine::gen_metadata`) in JSON format.
+    ///
+    /// Available under the `metadata` feature only.
+    pub fn gen_metadata_to_json(&self, include_packages: bool) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.gen_metadata(include_packages))
+    }
+}
@@ -12,6 +12,10 @@ use crate::{calc_native_fn_hash, calc_script_fn_hash, StaticVec};
 #[cfg(not(feature = "no_function"))]
 use crate::{ast::ScriptFnDef, fn_native::Shared};
 
+#[cfg(feature = "no_function")]
+#[cfg(not(feature = "no_module"))]
+use crate::ast::ScriptFnDef;
+
 #[cfg(not(feature = "no_module"))]
 use crate::{ast::AST, engine::Engine, scope::Scope};
 
@@ -26,7 +30,7 @@ use crate::engine::{make_getter, make_setter};
 use crate::engine::Map;
 
 use crate::stdlib::{
-    any::TypeId,
+    any::{type_name, TypeId},
     boxed::Box,
     collections::HashMap,
     fmt, format,
@@ -50,6 +54,61 @@ pub struct FuncInfo {
     pub params: usize,
     /// Parameter types (if applicable).
     pub types: Option<StaticVec<TypeId>>,
+    /// Parameter names and types (if available), in the format `name: type`, followed by the
+    /// return type.
+    ///
+    /// Only recorded under the `metadata` feature.
+    #[cfg(feature = "metadata")]
+    pub param_names: StaticVec<ImmutableString>,
+    /// Doc comments of a native Rust function (script-defined functions keep theirs in
+    /// `ScriptFnDef`).
+    ///
+    /// Only recorded under the `metadata` feature.
+    #[cfg(feature = "metadata")]
+    pub comments: StaticVec<String>,
+}
+
+impl FuncInfo {
+    /// Get the doc comments of the function.
+    #[inline]
+    pub(crate) fn comments(&self) -> &[String] {
+        #[cfg(not(feature = "no_function"))]
+        if self.func.is_script() {
+            return &self.func.get_fn_def().comments;
+        }
+
+        #[cfg(feature = "metadata")]
+        return &self.comments;
+        #[cfg(not(feature = "metadata"))]
+        return &[];
+    }
+    /// Generate a signature of the function, e.g. `foo(x, y: int) -> int`.
+    ///
+    /// Parameters of native Rust functions are shown as `_`.
+    pub(crate) fn gen_signature(&self) -> String {
+        #[cfg(not(feature = "no_function"))]
+        if self.func.is_script() {
+            let f = self.func.get_fn_def();
+
+            let params: Vec<_> = f
+                .params
+                .iter()
+                .zip(f.param_types.iter())
+                .map(|(name, typ)| match typ {
+                    Some(typ) => format!("{}: {}", name, typ),
+                    None => name.to_string(),
+                })
+                .collect();
+
+            return match f.return_type {
+                Some(ref typ) => format!("{}({}) -> {}", self.name, params.join(", "), typ),
+                None => format!("{}({})", self.name, params.join(", ")),
+            };
+        }
+
+        let params: Vec<_> = (0..self.params).map(|_| "_").collect();
+        format!("{}({})", self.name, params.join(", "))
+    }
 }
 
 /// An imported module, which may contain variables, sub-modules,
@@ -73,10 +132,25 @@ pub struct Module {
     /// Iterator functions, keyed by the type producing the iterator.
     type_iterators: HashMap<TypeId, IteratorFn>,
 
+    /// Names of the types producing iterators (if available).
+    #[cfg(feature = "metadata")]
+    type_iterator_names: HashMap<TypeId, &'static str>,
+
     /// Flattened collection of all external Rust functions, native or scripted,
     /// including those in sub-modules.
     all_functions: HashMap<u64, CallableFunction, StraightHasherBuilder>,
 
+    /// Script-defined functions overloaded by parameter types, keyed by the hash of
+    /// the function name and number of parameters.
+    #[cfg(not(feature = "no_function"))]
+    script_fn_overloads: HashMap<u64, StaticVec<u64>, StraightHasherBuilder>,
+
+    /// Flattened collection of all script-defined functions overloaded by parameter types,
+    /// including those in sub-modules, keyed by the hash of the qualified function name
+    /// and number of parameters.
+    #[cfg(not(feature = "no_function"))]
+    all_script_fn_overloads: HashMap<u64, StaticVec<u64>, StraightHasherBuilder>,
+
     /// Is the module indexed?
     indexed: bool,
 }
@@ -112,6 +186,8 @@ impl Clone for Module {
         Self {
             all_variables: self.all_variables.clone(),
             all_functions: self.all_functions.clone(),
+            #[cfg(not(feature = "no_function"))]
+            all_script_fn_overloads: self.all_script_fn_overloads.clone(),
             indexed: self.indexed,
             ..self.do_clone(false)
         }
@@ -195,7 +271,11 @@ impl Module {
             },
             variables: self.variables.clone(),
             functions: self.functions.clone(),
+            #[cfg(not(feature = "no_function"))]
+            script_fn_overloads: self.script_fn_overloads.clone(),
             type_iterators: self.type_iterators.clone(),
+            #[cfg(feature = "metadata")]
+            type_iterator_names: self.type_iterator_names.clone(),
             ..Default::default()
         }
     }
@@ -275,23 +355,24 @@ impl Module {
     #[inline(always)]
     pub(crate) fn get_qualified_var(&self, hash_var: u64) -> Result<&Dynamic, Box<EvalAltResult>> {
         if hash_var == 0 {
-            Err(EvalAltResult::ErrorVariableNotFound(String::new(), NO_POS).into())
+            Err(EvalAltResult::ErrorVariableNotFound(String::new(), NO_POS.into()).into())
         } else {
-            self.all_variables
-                .get(&hash_var)
-                .ok_or_else(|| EvalAltResult::ErrorVariableNotFound(String::new(), NO_POS).into())
+            self.all_variables.get(&hash_var).ok_or_else(|| {
+                EvalAltResult::ErrorVariableNotFound(String::new(), NO_POS.into()).into()
+            })
         }
     }
 
     /// Set a script-defined function into the module.
     ///
-    /// If there is an existing function of the same name and number of arguments, it is replaced.
+    /// If there is an existing function of the same name and number of arguments
+    /// (and the same parameter types, if any), it is replaced.
     #[cfg(not(feature = "no_function"))]
     #[inline]
     pub(crate) fn set_script_fn(&mut self, fn_def: Shared<ScriptFnDef>) -> u64 {
-        // None + function name + number of arguments.
         let num_params = fn_def.params.len();
-        let hash_script = calc_script_fn_hash(empty(), &fn_def.name, num_params);
+        let hash_script = fn_def.calc_hash();
+        let is_typed = fn_def.is_typed();
         self.functions.insert(
             hash_script,
             FuncInfo {
@@ -299,13 +380,66 @@ impl Module {
                 access: fn_def.access,
                 params: num_params,
                 types: None,
+                #[cfg(feature = "metadata")]
+                param_names: Default::default(),
+                #[cfg(feature = "metadata")]
+                comments: Default::default(),
                 func: fn_def.into(),
             },
         );
+        if is_typed {
+            self.index_script_fn_overloads();
+        }
         self.indexed = false;
         hash_script
     }
 
+    /// Get the hashes of the script-defined functions overloading a function by parameter types,
+    /// with the overloads having more parameter type annotations first.
+    ///
+    /// The `u64` hash is calculated by the function `crate::calc_script_fn_hash`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub(crate) fn get_script_fn_overloads(&self, hash_script: u64) -> &[u64] {
+        self.script_fn_overloads
+            .get(&hash_script)
+            .map_or(&[], |hashes| hashes.as_slice())
+    }
+
+    /// Get the hashes of the script-defined functions overloading a modules-qualified function
+    /// by parameter types, with the overloads having more parameter type annotations first.
+    ///
+    /// The `u64` hash must match the hash calculated by `index_all_sub_modules`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub(crate) fn get_qualified_script_fn_overloads(&self, hash_qualified_script: u64) -> &[u64] {
+        self.all_script_fn_overloads
+            .get(&hash_qualified_script)
+            .map_or(&[], |hashes| hashes.as_slice())
+    }
+
+    /// Rebuild the index of script-defined functions overloaded by parameter types.
+    #[cfg(not(feature = "no_function"))]
+    fn index_script_fn_overloads(&mut self) {
+        self.script_fn_overloads = make_overloads_index(
+            self.functions
+                .iter()
+                .filter(|(_, FuncInfo { func, .. })| func.is_script())
+                .map(
+                    |(
+                        &hash,
+                        FuncInfo {
+                            name, params, func, ..
+                        },
+                    )| {
+                        // None + function name + number of arguments.
+                        let hash_script = calc_script_fn_hash(empty(), name, *params);
+                        (hash_script, hash, func.get_fn_def())
+                    },
+                ),
+        );
+    }
+
     /// Get a script-defined function in the module based on name and number of parameters.
     #[cfg(not(feature = "no_function"))]
     #[inline(always)]
@@ -469,6 +603,10 @@ impl Module {
                 access,
                 params: params.len(),
                 types: Some(params),
+                #[cfg(feature = "metadata")]
+                param_names: Default::default(),
+                #[cfg(feature = "metadata")]
+                comments: Default::default(),
                 func: func.into(),
             },
         );
@@ -478,6 +616,112 @@ impl Module {
         hash_fn
     }
 
+    /// Update the metadata (parameter names and types, and return type) of a registered
+    /// function, by its hash key.
+    ///
+    /// Each parameter is given in the format `name: type` (`_` for an unknown name), followed by
+    /// the return type.
+    ///
+    /// The metadata is only recorded under the `metadata` feature. Otherwise this does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Module;
+    ///
+    /// let mut module = Module::new();
+    /// let hash = module.set_fn_2("add", |x: i64, y: i64| Ok(x + y));
+    /// module.update_fn_metadata(hash, &["x: i64", "y: i64", "i64"]);
+    /// ```
+    #[inline]
+    pub fn update_fn_metadata(&mut self, hash_fn: u64, arg_names: &[&str]) -> &mut Self {
+        #[cfg(feature = "metadata")]
+        if let Some(f) = self.functions.get_mut(&hash_fn) {
+            f.param_names = arg_names.iter().map(|&name| name.into()).collect();
+        }
+        #[cfg(not(feature = "metadata"))]
+        let _ = (hash_fn, arg_names);
+        self
+    }
+
+    /// Update the doc comments (e.g. `/// Add two numbers.`, including the markers) of a
+    /// registered native Rust function, by its hash key.
+    ///
+    /// The doc comments are only recorded under the `metadata` feature. Otherwise this does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Module;
+    ///
+    /// let mut module = Module::new();
+    /// let hash = module.set_fn_2("add", |x: i64, y: i64| Ok(x + y));
+    /// module.update_fn_comments(hash, &["/// Add two numbers."]);
+    /// ```
+    #[inline]
+    pub fn update_fn_comments(&mut self, hash_fn: u64, comments: &[&str]) -> &mut Self {
+        #[cfg(feature = "metadata")]
+        if let Some(f) = self.functions.get_mut(&hash_fn) {
+            f.comments = comments.iter().map(|&comment| comment.into()).collect();
+        }
+        #[cfg(not(feature = "metadata"))]
+        let _ = (hash_fn, comments);
+        self
+    }
+
+    /// Get the doc comments of a function in the module based on name and number of parameters.
+    ///
+    /// Doc comments are always kept for script-defined functions. For native Rust functions,
+    /// they are only recorded (by `update_fn_comments`) under the `metadata` feature.
+    ///
+    /// Returns `None` if the function does not exist. If there are overloaded versions of the
+    /// function, the first one found is used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Module;
+    ///
+    /// let mut module = Module::new();
+    /// let hash = module.set_fn_0("answer", || Ok(42_i64));
+    /// module.update_fn_comments(hash, &["/// The answer."]);
+    ///
+    /// # #[cfg(feature = "metadata")]
+    /// assert_eq!(module.get_fn_comments("answer", 0).unwrap(), ["/// The answer."]);
+    /// assert!(module.get_fn_comments("question", 0).is_none());
+    /// ```
+    #[inline]
+    pub fn get_fn_comments(&self, name: &str, num_params: usize) -> Option<&[String]> {
+        self.functions
+            .values()
+            .find(|f| f.params == num_params && f.name == name)
+            .map(FuncInfo::comments)
+    }
+
+    /// Record the parameter types and return type of a native Rust function, by its hash key,
+    /// returning the hash key.
+    ///
+    /// The type names are only recorded under the `metadata` feature.
+    #[inline(always)]
+    pub(crate) fn set_fn_type_names(
+        &mut self,
+        hash_fn: u64,
+        param_types: &[&str],
+        return_type: &str,
+    ) -> u64 {
+        #[cfg(feature = "metadata")]
+        if let Some(f) = self.functions.get_mut(&hash_fn) {
+            f.param_names = param_types
+                .iter()
+                .map(|typ| format!("_: {}", typ).into())
+                .chain(crate::stdlib::iter::once(return_type.into()))
+                .collect();
+        }
+        #[cfg(not(feature = "metadata"))]
+        let _ = (param_types, return_type);
+        hash_fn
+    }
+
     /// Set a Rust function taking a reference to the scripting `Engine`, the current set of functions,
     /// plus a list of mutable `Dynamic` references into the module, returning a hash key.
     ///
@@ -577,12 +821,13 @@ impl Module {
     ) -> u64 {
         let f = move |_: NativeCallContext, _: &mut FnCallArgs| func().map(Dynamic::from);
         let arg_types = [];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_pure(Box::new(f)),
-        )
+        );
+        self.set_fn_type_names(hash_fn, &[], type_name::<T>())
     }
 
     /// Set a Rust function taking one parameter into the module, returning a hash key.
@@ -608,12 +853,13 @@ impl Module {
             func(cast_arg::<A>(&mut args[0])).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>()];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_pure(Box::new(f)),
-        )
+        );
+        self.set_fn_type_names(hash_fn, &[type_name::<A>()], type_name::<T>())
     }
 
     /// Set a Rust function taking one mutable parameter into the module, returning a hash key.
@@ -639,12 +885,13 @@ impl Module {
             func(&mut args[0].write_lock::<A>().unwrap()).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>()];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_method(Box::new(f)),
-        )
+        );
+        self.set_fn_type_names(hash_fn, &[type_name::<&mut A>()], type_name::<T>())
     }
 
     /// Set a Rust getter function taking one mutable parameter, returning a hash key.
@@ -698,11 +945,16 @@ impl Module {
             func(a, b).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>()];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_pure(Box::new(f)),
+        );
+        self.set_fn_type_names(
+            hash_fn,
+            &[type_name::<A>(), type_name::<B>()],
+            type_name::<T>(),
         )
     }
 
@@ -735,11 +987,16 @@ impl Module {
             func(a, b).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>()];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_method(Box::new(f)),
+        );
+        self.set_fn_type_names(
+            hash_fn,
+            &[type_name::<&mut A>(), type_name::<B>()],
+            type_name::<T>(),
         )
     }
 
@@ -848,11 +1105,16 @@ impl Module {
             func(a, b, c).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>()];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_pure(Box::new(f)),
+        );
+        self.set_fn_type_names(
+            hash_fn,
+            &[type_name::<A>(), type_name::<B>(), type_name::<C>()],
+            type_name::<T>(),
         )
     }
 
@@ -891,11 +1153,16 @@ impl Module {
             func(a, b, c).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>()];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_method(Box::new(f)),
+        );
+        self.set_fn_type_names(
+            hash_fn,
+            &[type_name::<&mut A>(), type_name::<B>(), type_name::<C>()],
+            type_name::<T>(),
         )
     }
 
@@ -949,11 +1216,16 @@ impl Module {
             func(a, b, c).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>()];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             FN_IDX_SET,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_method(Box::new(f)),
+        );
+        self.set_fn_type_names(
+            hash_fn,
+            &[type_name::<&mut A>(), type_name::<B>(), type_name::<C>()],
+            type_name::<()>(),
         )
     }
 
@@ -1039,11 +1311,21 @@ impl Module {
             TypeId::of::<C>(),
             TypeId::of::<D>(),
         ];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_pure(Box::new(f)),
+        );
+        self.set_fn_type_names(
+            hash_fn,
+            &[
+                type_name::<A>(),
+                type_name::<B>(),
+                type_name::<C>(),
+                type_name::<D>(),
+            ],
+            type_name::<T>(),
         )
     }
 
@@ -1089,11 +1371,21 @@ impl Module {
             TypeId::of::<C>(),
             TypeId::of::<D>(),
         ];
-        self.set_fn(
+        let hash_fn = self.set_fn(
             name,
             FnAccess::Public,
             &arg_types,
             CallableFunction::from_method(Box::new(f)),
+        );
+        self.set_fn_type_names(
+            hash_fn,
+            &[
+                type_name::<&mut A>(),
+                type_name::<B>(),
+                type_name::<C>(),
+                type_name::<D>(),
+            ],
+            type_name::<T>(),
         )
     }
 
@@ -1133,7 +1425,12 @@ impl Module {
         self.modules.extend(other.modules.into_iter());
         self.variables.extend(other.variables.into_iter());
         self.functions.extend(other.functions.into_iter());
+        #[cfg(not(feature = "no_function"))]
+        self.index_script_fn_overloads();
         self.type_iterators.extend(other.type_iterators.into_iter());
+        #[cfg(feature = "metadata")]
+        self.type_iterator_names
+            .extend(other.type_iterator_names.into_iter());
         self.all_functions.clear();
         self.all_variables.clear();
         self.indexed = false;
@@ -1150,7 +1447,12 @@ impl Module {
         });
         self.variables.extend(other.variables.into_iter());
         self.functions.extend(other.functions.into_iter());
+        #[cfg(not(feature = "no_function"))]
+        self.index_script_fn_overloads();
         self.type_iterators.extend(other.type_iterators.into_iter());
+        #[cfg(feature = "metadata")]
+        self.type_iterator_names
+            .extend(other.type_iterator_names.into_iter());
         self.all_functions.clear();
         self.all_variables.clear();
         self.indexed = false;
@@ -1174,9 +1476,15 @@ impl Module {
         other.functions.iter().for_each(|(&k, v)| {
             self.functions.entry(k).or_insert_with(|| v.clone());
         });
+        #[cfg(not(feature = "no_function"))]
+        self.index_script_fn_overloads();
         other.type_iterators.iter().for_each(|(&k, &v)| {
             self.type_iterators.entry(k).or_insert(v);
         });
+        #[cfg(feature = "metadata")]
+        other.type_iterator_names.iter().for_each(|(&k, &v)| {
+            self.type_iterator_names.entry(k).or_insert(v);
+        });
         self.all_functions.clear();
         self.all_variables.clear();
         self.indexed = false;
@@ -1220,8 +1528,13 @@ impl Module {
                 })
                 .map(|(&k, v)| (k, v.clone())),
         );
+        #[cfg(not(feature = "no_function"))]
+        self.index_script_fn_overloads();
 
         self.type_iterators.extend(other.type_iterators.iter());
+        #[cfg(feature = "metadata")]
+        self.type_iterator_names
+            .extend(other.type_iterator_names.iter());
         self.all_functions.clear();
         self.all_variables.clear();
         self.indexed = false;
@@ -1240,6 +1553,7 @@ impl Module {
                 CallableFunction::Script(f) => filter(f.access, f.name.as_str(), f.params.len()),
                 _ => true,
             });
+        self.index_script_fn_overloads();
 
         self.all_functions.clear();
         self.all_variables.clear();
@@ -1263,13 +1577,41 @@ impl Module {
         self.variables.iter()
     }
 
+    /// Generate signatures for all the public functions in the module.
+    ///
+    /// Parameters of native Rust functions are shown as `_`, e.g. `foo(_, _)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Module;
+    ///
+    /// let mut module = Module::new();
+    /// module.set_fn_2("add", |x: i64, y: i64| Ok(x + y));
+    ///
+    /// assert_eq!(module.gen_fn_signatures().collect::<Vec<_>>(), vec!["add(_, _)"]);
+    /// ```
+    #[inline(always)]
+    pub fn gen_fn_signatures(&self) -> impl Iterator<Item = String> + '_ {
+        self.functions
+            .values()
+            .filter(|f| f.access == FnAccess::Public)
+            .map(FuncInfo::gen_signature)
+    }
+
     /// Get an iterator to the functions in the module.
-    #[cfg(not(feature = "no_optimize"))]
     #[inline(always)]
     pub(crate) fn iter_fn(&self) -> impl Iterator<Item = &FuncInfo> {
         self.functions.values()
     }
 
+    /// Get an iterator to the sub-modules in the module.
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    pub(crate) fn iter_sub_modules(&self) -> impl Iterator<Item = (&str, &Module)> {
+        self.modules.iter().map(|(name, m)| (name.as_str(), m))
+    }
+
     /// Get an iterator over all script-defined functions in the module.
     ///
     /// Function metadata includes:
@@ -1405,11 +1747,12 @@ impl Module {
             qualifiers: &mut Vec<&'a str>,
             variables: &mut Vec<(u64, Dynamic)>,
             functions: &mut Vec<(u64, CallableFunction)>,
+            _overloads: &mut Vec<(u64, u64, &'a ScriptFnDef)>,
         ) {
             module.modules.iter().for_each(|(name, m)| {
                 // Index all the sub-modules first.
                 qualifiers.push(name);
-                index_module(m, qualifiers, variables, functions);
+                index_module(m, qualifiers, variables, functions, _overloads);
                 qualifiers.pop();
             });
 
@@ -1458,6 +1801,24 @@ impl Module {
                                 // Qualifiers + function name + number of arguments.
                                 calc_script_fn_hash(qualifiers.iter().map(|&v| v), &name, *params)
                             };
+
+                            // Functions overloaded by parameter types are indexed with
+                            // the hash of their parameter types mixed in.
+                            #[cfg(not(feature = "no_function"))]
+                            if func.is_script() && func.get_fn_def().is_typed() {
+                                let hash_types =
+                                    _hash ^ calc_script_fn_hash(empty(), &name, *params);
+                                let hash_qualified_fn = hash_qualified_script ^ hash_types;
+
+                                _overloads.push((
+                                    hash_qualified_script,
+                                    hash_qualified_fn,
+                                    func.get_fn_def(),
+                                ));
+                                functions.push((hash_qualified_fn, func.clone()));
+                                return;
+                            }
+
                             functions.push((hash_qualified_script, func.clone()));
                         }
                     },
@@ -1468,13 +1829,27 @@ impl Module {
             let mut qualifiers: Vec<_> = Default::default();
             let mut variables: Vec<_> = Default::default();
             let mut functions: Vec<_> = Default::default();
+            let mut overloads: Vec<_> = Default::default();
 
             qualifiers.push("root");
 
-            index_module(self, &mut qualifiers, &mut variables, &mut functions);
+            index_module(
+                self,
+                &mut qualifiers,
+                &mut variables,
+                &mut functions,
+                &mut overloads,
+            );
+
+            #[cfg(not(feature = "no_function"))]
+            let all_script_fn_overloads = make_overloads_index(overloads.into_iter());
 
             self.all_variables = variables.into_iter().collect();
             self.all_functions = functions.into_iter().collect();
+            #[cfg(not(feature = "no_function"))]
+            {
+                self.all_script_fn_overloads = all_script_fn_overloads;
+            }
             self.indexed = true;
         }
     }
@@ -1497,6 +1872,10 @@ impl Module {
         T: Variant + Clone + IntoIterator,
         <T as IntoIterator>::Item: Variant + Clone,
     {
+        #[cfg(feature = "metadata")]
+        self.type_iterator_names
+            .insert(TypeId::of::<T>(), type_name::<T>());
+
         self.set_iter(TypeId::of::<T>(), |obj: Dynamic| {
             Box::new(obj.cast::<T>().into_iter().map(Dynamic::from))
         })
@@ -1508,6 +1887,10 @@ impl Module {
         T: Variant + Clone + Iterator,
         <T as Iterator>::Item: Variant + Clone,
     {
+        #[cfg(feature = "metadata")]
+        self.type_iterator_names
+            .insert(TypeId::of::<T>(), type_name::<T>());
+
         self.set_iter(TypeId::of::<T>(), |obj: Dynamic| {
             Box::new(obj.cast::<T>().map(Dynamic::from))
         })
@@ -1517,6 +1900,46 @@ impl Module {
     pub(crate) fn get_iter(&self, id: TypeId) -> Option<IteratorFn> {
         self.type_iterators.get(&id).cloned()
     }
+
+    /// Get an iterator over the types producing iterators in the module, together with their
+    /// names (if available).
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    pub(crate) fn iter_type_iterators(&self) -> impl Iterator<Item = (TypeId, Option<&str>)> {
+        self.type_iterators
+            .keys()
+            .map(move |id| (*id, self.type_iterator_names.get(id).cloned()))
+    }
+}
+
+/// Build an index of script-defined functions overloaded by parameter types.
+///
+/// Each item is made up of the hash of the function without parameter types, the hash of the
+/// overload, and the function definition.  Overloads with more parameter type annotations are
+/// placed first, so that the most specific overload matching a call is found first.
+#[cfg(not(feature = "no_function"))]
+fn make_overloads_index<'a>(
+    functions: impl Iterator<Item = (u64, u64, &'a ScriptFnDef)>,
+) -> HashMap<u64, StaticVec<u64>, StraightHasherBuilder> {
+    let mut overloads: HashMap<u64, Vec<(usize, u64)>> = Default::default();
+
+    functions
+        .filter(|(_, _, fn_def)| fn_def.is_typed())
+        .for_each(|(hash_script, hash, fn_def)| {
+            let num_types = fn_def.param_types.iter().filter(|t| t.is_some()).count();
+            overloads
+                .entry(hash_script)
+                .or_default()
+                .push((num_types, hash));
+        });
+
+    overloads
+        .into_iter()
+        .map(|(hash_script, mut hashes)| {
+            hashes.sort_by(|(n1, h1), (n2, h2)| n2.cmp(n1).then(h1.cmp(h2)));
+            (hash_script, hashes.into_iter().map(|(_, h)| h).collect())
+        })
+        .collect()
 }
 
 /// _[INTERNALS]_ A chain of module names to qualify a variable or function call.
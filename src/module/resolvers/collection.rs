@@ -102,7 +102,7 @@ impl ModuleResolver for ModuleResolversCollection {
             }
         }
 
-        EvalAltResult::ErrorModuleNotFound(path.into(), pos).into()
+        EvalAltResult::ErrorModuleNotFound(path.into(), pos.into()).into()
     }
 }
 
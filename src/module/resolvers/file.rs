@@ -120,6 +120,27 @@ impl FileModuleResolver {
         Default::default()
     }
 
+    /// Get the path of the script file that a module path resolves to.
+    ///
+    /// The module path is relative to the base path, and the file extension is forced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::module_resolvers::FileModuleResolver;
+    /// use std::path::Path;
+    ///
+    /// let resolver = FileModuleResolver::new_with_path("./scripts");
+    ///
+    /// assert_eq!(resolver.get_file_path("hello"), Path::new("./scripts/hello.rhai"));
+    /// ```
+    pub fn get_file_path(&self, path: &str) -> PathBuf {
+        let mut file_path = self.path.clone();
+        file_path.push(path);
+        file_path.set_extension(&self.extension); // Force extension
+        file_path
+    }
+
     /// Create a `Module` from a file path.
     #[inline(always)]
     pub fn create_module<P: Into<PathBuf>>(
@@ -139,9 +160,7 @@ impl ModuleResolver for FileModuleResolver {
         pos: Position,
     ) -> Result<Module, Box<EvalAltResult>> {
         // Construct the script file path
-        let mut file_path = self.path.clone();
-        file_path.push(path);
-        file_path.set_extension(&self.extension); // Force extension
+        let file_path = self.get_file_path(path);
 
         let scope = Default::default();
         let module;
@@ -155,17 +174,29 @@ impl ModuleResolver for FileModuleResolver {
 
             if let Some(ast) = c.get(&file_path) {
                 module = Module::eval_ast_as_new(scope, ast, engine).map_err(|err| {
-                    Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos))
+                    Box::new(EvalAltResult::ErrorInModule(
+                        path.to_string(),
+                        err,
+                        pos.into(),
+                    ))
                 })?;
                 None
             } else {
                 // Load the file and compile it if not found
                 let ast = engine.compile_file(file_path.clone()).map_err(|err| {
-                    Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos))
+                    Box::new(EvalAltResult::ErrorInModule(
+                        path.to_string(),
+                        err,
+                        pos.into(),
+                    ))
                 })?;
 
                 module = Module::eval_ast_as_new(scope, &ast, engine).map_err(|err| {
-                    Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos))
+                    Box::new(EvalAltResult::ErrorInModule(
+                        path.to_string(),
+                        err,
+                        pos.into(),
+                    ))
                 })?;
                 Some(ast)
             }
@@ -122,7 +122,7 @@ impl ModuleResolver for StaticModuleResolver {
         self.0
             .get(path)
             .cloned()
-            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(path.into(), pos).into())
+            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(path.into(), pos.into()).into())
     }
 }
 
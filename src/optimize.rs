@@ -10,7 +10,7 @@ use crate::fn_call::run_builtin_binary_op;
 use crate::module::Module;
 use crate::parser::map_dynamic_to_expr;
 use crate::scope::Scope;
-use crate::token::{is_valid_identifier, NO_POS};
+use crate::token::{is_valid_identifier, Span, NO_POS};
 use crate::{calc_native_fn_hash, StaticVec};
 
 #[cfg(not(feature = "no_function"))]
@@ -148,6 +148,7 @@ fn call_fn_with_constant_arguments(
             false,
             true,
             None,
+            0,
         )
         .ok()
         .map(|(v, _)| v)
@@ -159,10 +160,15 @@ fn optimize_stmt(stmt: Stmt, state: &mut State, preserve_result: bool) -> Stmt {
         // expr op= expr
         Stmt::Assignment(x, pos) => match x.0 {
             Expr::Variable(_) => {
-                Stmt::Assignment(Box::new((x.0, x.1, optimize_expr(x.2, state))), pos)
+                Stmt::Assignment(Box::new((x.0, x.1, optimize_expr(x.2, state), x.3)), pos)
             }
             _ => Stmt::Assignment(
-                Box::new((optimize_expr(x.0, state), x.1, optimize_expr(x.2, state))),
+                Box::new((
+                    optimize_expr(x.0, state),
+                    x.1,
+                    optimize_expr(x.2, state),
+                    x.3,
+                )),
                 pos,
             ),
         },
@@ -186,7 +192,7 @@ fn optimize_stmt(stmt: Stmt, state: &mut State, preserve_result: bool) -> Stmt {
                 statements.push(Stmt::Expr(expr));
                 statements.push(x.0);
 
-                Stmt::Block(statements, pos)
+                Stmt::Block(statements, Span::new(pos, pos))
             } else {
                 // -> expr
                 Stmt::Expr(expr)
@@ -238,7 +244,7 @@ fn optimize_stmt(stmt: Stmt, state: &mut State, preserve_result: bool) -> Stmt {
                     if preserve_result {
                         statements.push(Stmt::Noop(pos))
                     }
-                    Stmt::Block(statements, pos)
+                    Stmt::Block(statements, Span::new(pos, pos))
                 }
                 // while expr { block }
                 stmt => Stmt::While(optimize_expr(condition, state), Box::new(stmt), pos),
@@ -265,14 +271,17 @@ fn optimize_stmt(stmt: Stmt, state: &mut State, preserve_result: bool) -> Stmt {
             )
         }
         // let id = expr;
-        Stmt::Let(name, Some(expr), pos) => Stmt::Let(name, Some(optimize_expr(expr, state)), pos),
+        Stmt::Let(var_def, Some(expr), pos) => {
+            Stmt::Let(var_def, Some(optimize_expr(expr, state)), pos)
+        }
         // let id;
         stmt @ Stmt::Let(_, None, _) => stmt,
         // import expr as var;
         #[cfg(not(feature = "no_module"))]
         Stmt::Import(expr, alias, pos) => Stmt::Import(optimize_expr(expr, state), alias, pos),
         // { block }
-        Stmt::Block(statements, pos) => {
+        Stmt::Block(statements, span) => {
+            let pos = span.start();
             let orig_len = statements.len(); // Original number of statements in the block, for change detection
             let orig_constants_len = state.constants.len(); // Original number of constants in the state, for restore later
 
@@ -283,7 +292,7 @@ fn optimize_stmt(stmt: Stmt, state: &mut State, preserve_result: bool) -> Stmt {
                     // Add constant literals into the state
                     Stmt::Const(var_def, Some(expr), pos) if expr.is_literal() => {
                         state.set_dirty();
-                        state.push_constant(&var_def.name, expr);
+                        state.push_constant(&var_def.0.name, expr);
                         Stmt::Noop(pos) // No need to keep constants
                     }
                     Stmt::Const(var_def, Some(expr), pos) if expr.is_literal() => {
@@ -292,7 +301,7 @@ fn optimize_stmt(stmt: Stmt, state: &mut State, preserve_result: bool) -> Stmt {
                     }
                     Stmt::Const(var_def, None, pos) => {
                         state.set_dirty();
-                        state.push_constant(&var_def.name, Expr::Unit(var_def.pos));
+                        state.push_constant(&var_def.0.name, Expr::Unit(var_def.0.pos, NO_POS));
                         Stmt::Noop(pos) // No need to keep constants
                     }
                     // Optimize the statement
@@ -374,52 +383,59 @@ fn optimize_stmt(stmt: Stmt, state: &mut State, preserve_result: bool) -> Stmt {
                     Stmt::Noop(pos)
                 }
                 // Only one let statement - leave it alone
-                [x] if matches!(x, Stmt::Let(_, _, _)) => Stmt::Block(result, pos),
+                [x] if matches!(x, Stmt::Let(_, _, _)) => Stmt::Block(result, span),
                 // Only one import statement - leave it alone
                 #[cfg(not(feature = "no_module"))]
-                [x] if matches!(x, Stmt::Import(_, _, _)) => Stmt::Block(result, pos),
+                [x] if matches!(x, Stmt::Import(_, _, _)) => Stmt::Block(result, span),
                 // Only one statement - promote
                 [_] => {
                     state.set_dirty();
                     result.remove(0)
                 }
-                _ => Stmt::Block(result, pos),
+                _ => Stmt::Block(result, span),
             }
         }
-        // try { block } catch ( var ) { block }
+        // try { block } catch ( var ) { block } finally { block }
         Stmt::TryCatch(x, _, _) if x.0.is_pure() => {
             // If try block is pure, there will never be any exceptions
             state.set_dirty();
-            let pos = x.0.position();
-            let mut statements = match optimize_stmt(x.0, state, preserve_result) {
-                Stmt::Block(statements, _) => statements,
-                stmt => vec![stmt],
+            let (try_block, _, _, _, finally_block) = *x;
+            let pos = try_block.position();
+            let (mut statements, span) = match optimize_stmt(try_block, state, preserve_result) {
+                Stmt::Block(statements, span) => (statements, span),
+                stmt => (vec![stmt], Span::new(pos, pos)),
             };
+            // The finally block always runs after the try block
+            if let Some(finally_block) = finally_block {
+                statements.push(optimize_stmt(finally_block, state, false));
+            }
             statements.push(Stmt::Noop(pos));
-            Stmt::Block(statements, pos)
+            Stmt::Block(statements, span)
         }
-        // try { block } catch ( var ) { block }
+        // try { block } catch ( var ) { block } finally { block }
         Stmt::TryCatch(x, try_pos, catch_pos) => {
-            let (try_block, var_name, catch_block) = *x;
+            let (try_block, var_name, stack_var_name, catch_block, finally_block) = *x;
             Stmt::TryCatch(
                 Box::new((
                     optimize_stmt(try_block, state, false),
                     var_name,
+                    stack_var_name,
                     optimize_stmt(catch_block, state, false),
+                    finally_block.map(|stmt| optimize_stmt(stmt, state, false)),
                 )),
                 try_pos,
                 catch_pos,
             )
         }
         // {}
-        Stmt::Expr(Expr::Stmt(x, pos)) if x.is_empty() => {
+        Stmt::Expr(Expr::Stmt(x, pos, _)) if x.is_empty() => {
             state.set_dirty();
             Stmt::Noop(pos)
         }
         // {...};
-        Stmt::Expr(Expr::Stmt(x, pos)) => {
+        Stmt::Expr(Expr::Stmt(x, pos, end)) => {
             state.set_dirty();
-            Stmt::Block(x.into_vec(), pos)
+            Stmt::Block(x.into_vec(), Span::new(pos, end))
         }
         // expr;
         Stmt::Expr(expr) => Stmt::Expr(optimize_expr(expr, state)),
@@ -447,16 +463,16 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
         // expr - do not promote because there is a reason it is wrapped in an `Expr::Expr`
         Expr::Expr(x) => Expr::Expr(Box::new(optimize_expr(*x, state))),
         // {}
-        Expr::Stmt(x, pos) if x.is_empty() => {
+        Expr::Stmt(x, pos, end) if x.is_empty() => {
             state.set_dirty();
-            Expr::Unit(pos)
+            Expr::Unit(pos, end)
         }
         // { stmt }
-        Expr::Stmt(mut x, pos) if x.len() == 1 => match x.pop().unwrap() {
+        Expr::Stmt(mut x, pos, end) if x.len() == 1 => match x.pop().unwrap() {
             // {} -> ()
             Stmt::Noop(_) => {
                 state.set_dirty();
-                Expr::Unit(pos)
+                Expr::Unit(pos, end)
             }
             // { expr } -> expr
             Stmt::Expr(expr) => {
@@ -464,25 +480,26 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
                 optimize_expr(expr, state)
             }
             // { stmt }
-            stmt => Expr::Stmt(Box::new(vec![optimize_stmt(stmt, state, true)].into()), pos)
+            stmt => Expr::Stmt(Box::new(vec![optimize_stmt(stmt, state, true)].into()), pos, end)
         }
         // { stmt; ... }
-        Expr::Stmt(x, pos) => Expr::Stmt(Box::new(
+        Expr::Stmt(x, pos, end) => Expr::Stmt(Box::new(
             x.into_iter().map(|stmt| optimize_stmt(stmt, state, true)).collect(),
-        ), pos),
+        ), pos, end),
 
         // lhs.rhs
         #[cfg(not(feature = "no_object"))]
         Expr::Dot(x, dot_pos) => match (x.lhs, x.rhs) {
             // map.string
-            (Expr::Map(m, pos), Expr::Property(p)) if m.iter().all(|(_, x)| x.is_pure()) => {
+            (Expr::Map(m, pos, _), Expr::Property(p)) if m.iter().all(|(_, x)| x.is_pure()) => {
                 let prop = &p.1.name;
+                let span = Span::new(pos, p.1.end_position());
                 // Map literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
                 state.set_dirty();
                 m.into_iter().find(|(x, _)| &x.name == prop)
-                    .map(|(_, mut expr)| { expr.set_position(pos); expr })
-                    .unwrap_or_else(|| Expr::Unit(pos))
+                    .map(|(_, mut expr)| { expr.set_span(span); expr })
+                    .unwrap_or_else(|| Expr::Unit(span.start(), span.end()))
             }
             // var.rhs
             (lhs @ Expr::Variable(_), rhs) => Expr::Dot(Box::new(BinaryExpr {
@@ -498,68 +515,68 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
 
         // lhs[rhs]
         #[cfg(not(feature = "no_index"))]
-        Expr::Index(x, idx_pos) => match (x.lhs, x.rhs) {
+        Expr::Index(x, idx_pos, idx_end) => match (x.lhs, x.rhs) {
             // array[int]
-            (Expr::Array(mut a, pos), Expr::IntegerConstant(i, _))
+            (Expr::Array(mut a, pos, _), Expr::IntegerConstant(i, _, _))
                 if i >= 0 && (i as usize) < a.len() && a.iter().all(Expr::is_pure) =>
             {
                 // Array literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
                 state.set_dirty();
                 let mut expr = a.remove(i as usize);
-                expr.set_position(pos);
+                expr.set_span(Span::new(pos, idx_end));
                 expr
             }
             // map[string]
-            (Expr::Map(m, pos), Expr::StringConstant(s)) if m.iter().all(|(_, x)| x.is_pure()) => {
+            (Expr::Map(m, pos, _), Expr::StringConstant(s, _)) if m.iter().all(|(_, x)| x.is_pure()) => {
                 // Map literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
                 state.set_dirty();
                 m.into_iter().find(|(x, _)| x.name == s.name)
-                    .map(|(_, mut expr)| { expr.set_position(pos); expr })
-                    .unwrap_or_else(|| Expr::Unit(pos))
+                    .map(|(_, mut expr)| { expr.set_span(Span::new(pos, idx_end)); expr })
+                    .unwrap_or_else(|| Expr::Unit(pos, idx_end))
             }
             // string[int]
-            (Expr::StringConstant(s), Expr::IntegerConstant(i, _)) if i >= 0 && (i as usize) < s.name.chars().count() => {
+            (Expr::StringConstant(s, _), Expr::IntegerConstant(i, _, _)) if i >= 0 && (i as usize) < s.name.chars().count() => {
                 // String literal indexing - get the character
                 state.set_dirty();
-                Expr::CharConstant(s.name.chars().nth(i as usize).unwrap(), s.pos)
+                Expr::CharConstant(s.name.chars().nth(i as usize).unwrap(), s.pos, idx_end)
             }
             // var[rhs]
             (lhs @ Expr::Variable(_), rhs) => Expr::Index(Box::new(BinaryExpr {
                 lhs,
                 rhs: optimize_expr(rhs, state),
-            }), idx_pos),
+            }), idx_pos, idx_end),
             // lhs[rhs]
             (lhs, rhs) => Expr::Index(Box::new(BinaryExpr {
                 lhs: optimize_expr(lhs, state),
                 rhs: optimize_expr(rhs, state),
-            }), idx_pos),
+            }), idx_pos, idx_end),
         },
         // [ items .. ]
         #[cfg(not(feature = "no_index"))]
-        Expr::Array(a, pos) => Expr::Array(Box::new(a
+        Expr::Array(a, pos, end) => Expr::Array(Box::new(a
                                 .into_iter().map(|expr| optimize_expr(expr, state))
-                                .collect()), pos),
+                                .collect()), pos, end),
         // [ items .. ]
         #[cfg(not(feature = "no_object"))]
-        Expr::Map(m, pos) => Expr::Map(Box::new(m
+        Expr::Map(m, pos, end) => Expr::Map(Box::new(m
                                 .into_iter().map(|(key, expr)| (key, optimize_expr(expr, state)))
-                                .collect()), pos),
+                                .collect()), pos, end),
         // lhs in rhs
         Expr::In(x, in_pos) => match (x.lhs, x.rhs) {
             // "xxx" in "xxxxx"
-            (Expr::StringConstant(a), Expr::StringConstant(b)) => {
+            (Expr::StringConstant(a, _), Expr::StringConstant(b, _)) => {
                 state.set_dirty();
                 if b.name.contains(a.name.as_str()) { Expr::True(a.pos) } else { Expr::False(a.pos) }
             }
             // 'x' in "xxxxx"
-            (Expr::CharConstant(a, pos), Expr::StringConstant(b)) => {
+            (Expr::CharConstant(a, pos, _), Expr::StringConstant(b, _)) => {
                 state.set_dirty();
                 if b.name.contains(a) { Expr::True(pos) } else { Expr::False(pos) }
             }
             // "xxx" in #{...}
-            (Expr::StringConstant(a), Expr::Map(b, _)) => {
+            (Expr::StringConstant(a, _), Expr::Map(b, _, _)) => {
                 state.set_dirty();
                 if b.iter().find(|(x, _)| x.name == a.name).is_some() {
                     Expr::True(a.pos)
@@ -568,7 +585,7 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
                 }
             }
             // 'x' in #{...}
-            (Expr::CharConstant(a, pos), Expr::Map(b, _)) => {
+            (Expr::CharConstant(a, pos, _), Expr::Map(b, _, _)) => {
                 state.set_dirty();
                 let ch = a.to_string();
 
@@ -632,13 +649,13 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
         },
 
         // Do not call some special keywords
-        Expr::FnCall(mut x, pos) if DONT_EVAL_KEYWORDS.contains(&x.name.as_ref()) => {
+        Expr::FnCall(mut x, pos, end) if DONT_EVAL_KEYWORDS.contains(&x.name.as_ref()) => {
             x.args = x.args.into_iter().map(|a| optimize_expr(a, state)).collect();
-            Expr::FnCall(x, pos)
+            Expr::FnCall(x, pos, end)
         }
 
         // Call built-in operators
-        Expr::FnCall(mut x, pos)
+        Expr::FnCall(mut x, pos, end)
                 if x.namespace.is_none() // Non-qualified
                 && state.optimization_level == OptimizationLevel::Simple // simple optimizations
                 && x.args.len() == 2 // binary call
@@ -654,7 +671,7 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
             if !state.engine.has_override_by_name_and_arguments(state.lib, name, arg_types.as_ref(), false) {
                 if let Some(expr) = run_builtin_binary_op(name, &arg_values[0], &arg_values[1])
                                         .ok().flatten()
-                                        .and_then(|result| map_dynamic_to_expr(result, pos))
+                                        .and_then(|result| map_dynamic_to_expr(result, pos, end))
                 {
                     state.set_dirty();
                     return expr;
@@ -662,11 +679,11 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
             }
 
             x.args = x.args.into_iter().map(|a| optimize_expr(a, state)).collect();
-            Expr::FnCall(x, pos)
+            Expr::FnCall(x, pos, end)
         }
 
         // Eagerly call functions
-        Expr::FnCall(mut x, pos)
+        Expr::FnCall(mut x, pos, end)
                 if x.namespace.is_none() // Non-qualified
                 && state.optimization_level == OptimizationLevel::Full // full optimizations
                 && x.args.iter().all(Expr::is_constant) // all arguments are constants
@@ -700,7 +717,7 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
                                                 def_value.map(|v| v.into())
                                             }
                                         })
-                                        .and_then(|result| map_dynamic_to_expr(result, pos))
+                                        .and_then(|result| map_dynamic_to_expr(result, pos, end))
                 {
                     state.set_dirty();
                     return expr;
@@ -708,13 +725,13 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
             }
 
             x.args = x.args.into_iter().map(|a| optimize_expr(a, state)).collect();
-            Expr::FnCall(x, pos)
+            Expr::FnCall(x, pos, end)
         }
 
         // id(args ..) -> optimize function call arguments
-        Expr::FnCall(mut x, pos) => {
+        Expr::FnCall(mut x, pos, end) => {
             x.args = x.args.into_iter().map(|a| optimize_expr(a, state)).collect();
-            Expr::FnCall(x, pos)
+            Expr::FnCall(x, pos, end)
         }
 
         // constant-name
@@ -723,15 +740,15 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
 
             // Replace constant with value
             let mut expr = state.find_constant(&x.3.name).unwrap().clone();
-            expr.set_position(x.3.pos);
+            expr.set_span(Span::new(x.3.pos, x.3.end_position()));
             expr
         }
 
         // Custom syntax
-        Expr::Custom(x, pos) => Expr::Custom(Box::new(CustomExpr {
+        Expr::Custom(x, pos, end) => Expr::Custom(Box::new(CustomExpr {
             keywords: x.keywords.into_iter().map(|expr| optimize_expr(expr, state)).collect(),
             ..*x
-        }), pos),
+        }), pos, end),
 
         // All other expressions - skip
         expr => expr,
@@ -758,7 +775,7 @@ fn optimize(
         .iter()
         .filter(|(_, typ, _)| *typ)
         .for_each(|(name, _, value)| {
-            if let Some(val) = map_dynamic_to_expr(value, NO_POS) {
+            if let Some(val) = map_dynamic_to_expr(value, NO_POS, NO_POS) {
                 state.push_constant(name, val);
             }
         });
@@ -784,7 +801,7 @@ fn optimize(
                         let expr = optimize_expr(expr, &mut state);
 
                         if expr.is_literal() {
-                            state.push_constant(&var_def.name, expr.clone());
+                            state.push_constant(&var_def.0.name, expr.clone());
                         }
 
                         // Keep it in the global scope
@@ -796,7 +813,7 @@ fn optimize(
                         }
                     }
                     Stmt::Const(ref var_def, None, _) => {
-                        state.push_constant(&var_def.name, Expr::Unit(var_def.pos));
+                        state.push_constant(&var_def.0.name, Expr::Unit(var_def.0.pos, NO_POS));
 
                         // Keep it in the global scope
                         stmt
@@ -867,6 +884,11 @@ pub fn optimize_into_ast(
                         access: fn_def.access,
                         body: Default::default(),
                         params: fn_def.params.clone(),
+                        param_types: fn_def.param_types.clone(),
+                        return_type: fn_def.return_type.clone(),
+                        span: fn_def.span,
+                        source: fn_def.source.clone(),
+                        comments: Default::default(),
                         #[cfg(not(feature = "no_closure"))]
                         externals: fn_def.externals.clone(),
                         lib: None,
@@ -893,8 +915,9 @@ pub fn optimize_into_ast(
                             Stmt::Expr(expr)
                         }
                         // { return; } -> ()
-                        Stmt::ReturnWithVal((ReturnType::Return, pos), None, _) => {
-                            Stmt::Expr(Expr::Unit(pos))
+                        stmt @ Stmt::ReturnWithVal((ReturnType::Return, _), None, _) => {
+                            let span = stmt.span();
+                            Stmt::Expr(Expr::Unit(span.start(), span.end()))
                         }
                         // All others
                         stmt => stmt,
@@ -17,7 +17,7 @@ use crate::stdlib::{format, string::String};
 
 #[inline(always)]
 pub fn make_err(msg: impl Into<String>) -> Box<EvalAltResult> {
-    EvalAltResult::ErrorArithmetic(msg.into(), NO_POS).into()
+    EvalAltResult::ErrorArithmetic(msg.into(), NO_POS.into()).into()
 }
 
 macro_rules! gen_arithmetic_functions {
@@ -5,6 +5,7 @@ use crate::def_package;
 use crate::dynamic::Dynamic;
 use crate::engine::Array;
 use crate::fn_native::{FnPtr, NativeCallContext};
+use crate::packages::reg_reader;
 use crate::plugin::*;
 use crate::result::EvalAltResult;
 use crate::token::NO_POS;
@@ -12,7 +13,7 @@ use crate::utils::ImmutableString;
 use crate::INT;
 
 #[cfg(not(feature = "no_object"))]
-use crate::engine::Map;
+use crate::engine::{make_getter, Map};
 
 use crate::stdlib::{any::TypeId, boxed::Box, cmp::Ordering, string::ToString};
 
@@ -46,7 +47,7 @@ macro_rules! gen_array_functions {
                     #[cfg(not(feature = "unchecked"))]
                     if _ctx.engine().max_array_size() > 0 && len > 0 && (len as usize) > _ctx.engine().max_array_size() {
                         return EvalAltResult::ErrorDataTooLarge(
-                            "Size of array".to_string(), NO_POS
+                            "Size of array".to_string(), NO_POS.into()
                         ).into();
                     }
 
@@ -88,16 +89,19 @@ def_package!(crate:BasicArrayPackage:"Basic array utilities.", lib, {
     // Merge in the module at the end to override `+=` for arrays
     combine_with_exported_module!(lib, "array", array_functions);
 
+    // Functions which only read the array do not copy it when it is shared
+    let array = TypeId::of::<Array>();
+    let len = |list: &Array, _: &mut [&mut Dynamic]| list.len() as INT;
+    reg_reader(lib, "len", &[array], &["list: &mut Array", "INT"], len);
+    #[cfg(not(feature = "no_object"))]
+    reg_reader(lib, &make_getter("len"), &[array], &["list: &mut Array", "INT"], len);
+
     // Register array iterator
     lib.set_iterable::<Array>();
 });
 
 #[export_module]
 mod array_functions {
-    #[rhai_fn(name = "len", get = "len")]
-    pub fn len(list: &mut Array) -> INT {
-        list.len() as INT
-    }
     #[rhai_fn(name = "append", name = "+=")]
     pub fn append(x: &mut Array, y: Array) {
         x.extend(y);
@@ -218,8 +222,10 @@ mod array_functions {
                     .map_err(|err| {
                         Box::new(EvalAltResult::ErrorInFunctionCall(
                             "map".to_string(),
+                            2,
+                            None,
                             err,
-                            NO_POS,
+                            NO_POS.into(),
                         ))
                     })?,
             );
@@ -249,8 +255,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "filter".to_string(),
+                        2,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?
                 .as_bool()
@@ -282,8 +290,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "filter".to_string(),
+                        2,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?
                 .as_bool()
@@ -315,8 +325,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "filter".to_string(),
+                        2,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?
                 .as_bool()
@@ -350,8 +362,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "reduce".to_string(),
+                        2,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?;
         }
@@ -368,8 +382,10 @@ mod array_functions {
         let mut result = initial.call_dynamic(ctx, None, []).map_err(|err| {
             Box::new(EvalAltResult::ErrorInFunctionCall(
                 "reduce".to_string(),
+                3,
+                None,
                 err,
-                NO_POS,
+                NO_POS.into(),
             ))
         })?;
 
@@ -387,8 +403,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "reduce".to_string(),
+                        3,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?;
         }
@@ -417,8 +435,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "reduce".to_string(),
+                        2,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?;
         }
@@ -435,8 +455,10 @@ mod array_functions {
         let mut result = initial.call_dynamic(ctx, None, []).map_err(|err| {
             Box::new(EvalAltResult::ErrorInFunctionCall(
                 "reduce".to_string(),
+                3,
+                None,
                 err,
-                NO_POS,
+                NO_POS.into(),
             ))
         })?;
 
@@ -454,8 +476,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "reduce".to_string(),
+                        3,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?;
         }
@@ -524,8 +548,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "filter".to_string(),
+                        2,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?
                 .as_bool()
@@ -583,8 +609,10 @@ mod array_functions {
                 .map_err(|err| {
                     Box::new(EvalAltResult::ErrorInFunctionCall(
                         "filter".to_string(),
+                        2,
+                        None,
                         err,
-                        NO_POS,
+                        NO_POS.into(),
                     ))
                 })?
                 .as_bool()
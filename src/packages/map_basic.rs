@@ -3,6 +3,7 @@
 use crate::def_package;
 use crate::dynamic::Dynamic;
 use crate::engine::Map;
+use crate::packages::reg_reader;
 use crate::plugin::*;
 use crate::utils::ImmutableString;
 use crate::INT;
@@ -12,16 +13,28 @@ use crate::engine::Array;
 
 def_package!(crate:BasicMapPackage:"Basic object map utilities.", lib, {
     combine_with_exported_module!(lib, "map", map_functions);
+
+    // Functions which only read the object map do not copy it when it is shared
+    let map = TypeId::of::<Map>();
+    let string = TypeId::of::<ImmutableString>();
+
+    reg_reader(lib, "has", &[map, string], &["map: &mut Map", "prop: ImmutableString", "bool"],
+        |map: &Map, args| map.contains_key(args[0].read_lock::<ImmutableString>().unwrap().as_str()));
+    reg_reader(lib, "len", &[map], &["map: &mut Map", "INT"], |map: &Map, _| map.len() as INT);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        reg_reader(lib, "keys", &[map], &["map: &mut Map", "Array"], |map: &Map, _| {
+            map.iter().map(|(k, _)| k.clone().into()).collect::<Array>()
+        });
+        reg_reader(lib, "values", &[map], &["map: &mut Map", "Array"], |map: &Map, _| {
+            map.iter().map(|(_, v)| v.clone()).collect::<Array>()
+        });
+    }
 });
 
 #[export_module]
 mod map_functions {
-    pub fn has(map: &mut Map, prop: ImmutableString) -> bool {
-        map.contains_key(&prop)
-    }
-    pub fn len(map: &mut Map) -> INT {
-        map.len() as INT
-    }
     pub fn clear(map: &mut Map) {
         map.clear();
     }
@@ -46,14 +59,4 @@ mod map_functions {
             map1.entry(key).or_insert(value);
         });
     }
-
-    #[cfg(not(feature = "no_index"))]
-    pub mod indexing {
-        pub fn keys(map: &mut Map) -> Array {
-            map.iter().map(|(k, _)| k.clone().into()).collect()
-        }
-        pub fn values(map: &mut Map) -> Array {
-            map.iter().map(|(_, v)| v.clone()).collect()
-        }
-    }
 }
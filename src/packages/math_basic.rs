@@ -85,8 +85,11 @@ mod int_functions {
     #[rhai_fn(name = "parse_int", return_raw)]
     pub fn parse_int_radix(s: &str, radix: INT) -> Result<Dynamic, Box<EvalAltResult>> {
         if radix < 2 || radix > 36 {
-            return EvalAltResult::ErrorArithmetic(format!("Invalid radix: '{}'", radix), NO_POS)
-                .into();
+            return EvalAltResult::ErrorArithmetic(
+                format!("Invalid radix: '{}'", radix),
+                NO_POS.into(),
+            )
+            .into();
         }
 
         INT::from_str_radix(s.trim(), radix as u32)
@@ -94,7 +97,7 @@ mod int_functions {
             .map_err(|err| {
                 EvalAltResult::ErrorArithmetic(
                     format!("Error parsing integer number '{}': {}", s, err),
-                    NO_POS,
+                    NO_POS.into(),
                 )
                 .into()
             })
@@ -203,8 +206,11 @@ mod float_functions {
     #[rhai_fn(name = "to_int", return_raw)]
     pub fn f32_to_int(x: f32) -> Result<Dynamic, Box<EvalAltResult>> {
         if cfg!(not(feature = "unchecked")) && x > (MAX_INT as f32) {
-            EvalAltResult::ErrorArithmetic(format!("Integer overflow: to_int({})", x), NO_POS)
-                .into()
+            EvalAltResult::ErrorArithmetic(
+                format!("Integer overflow: to_int({})", x),
+                NO_POS.into(),
+            )
+            .into()
         } else {
             Ok((x.trunc() as INT).into())
         }
@@ -212,8 +218,11 @@ mod float_functions {
     #[rhai_fn(name = "to_int", return_raw)]
     pub fn f64_to_int(x: f64) -> Result<Dynamic, Box<EvalAltResult>> {
         if cfg!(not(feature = "unchecked")) && x > (MAX_INT as f64) {
-            EvalAltResult::ErrorArithmetic(format!("Integer overflow: to_int({})", x), NO_POS)
-                .into()
+            EvalAltResult::ErrorArithmetic(
+                format!("Integer overflow: to_int({})", x),
+                NO_POS.into(),
+            )
+            .into()
         } else {
             Ok((x.trunc() as INT).into())
         }
@@ -226,7 +235,7 @@ mod float_functions {
             .map_err(|err| {
                 EvalAltResult::ErrorArithmetic(
                     format!("Error parsing floating-point number '{}': {}", s, err),
-                    NO_POS,
+                    NO_POS.into(),
                 )
                 .into()
             })
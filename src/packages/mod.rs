@@ -1,10 +1,14 @@
 //! Module containing all built-in _packages_ available to Rhai, plus facilities to define custom packages.
 
-use crate::fn_native::{CallableFunction, IteratorFn, Shared};
-use crate::module::Module;
+use crate::ast::FnAccess;
+use crate::dynamic::{Dynamic, Variant};
+use crate::fn_native::{
+    CallableFunction, FnCallArgs, IteratorFn, NativeCallContext, SendSync, Shared,
+};
+use crate::module::{FuncInfo, Module};
 use crate::StaticVec;
 
-use crate::stdlib::any::TypeId;
+use crate::stdlib::{any::TypeId, boxed::Box};
 
 pub(crate) mod arithmetic;
 mod array_basic;
@@ -18,6 +22,7 @@ mod pkg_core;
 mod pkg_std;
 mod string_basic;
 mod string_more;
+mod testing;
 mod time_basic;
 
 pub use arithmetic::ArithmeticPackage;
@@ -34,6 +39,7 @@ pub use pkg_core::CorePackage;
 pub use pkg_std::StandardPackage;
 pub use string_basic::BasicStringPackage;
 pub use string_more::MoreStringPackage;
+pub use testing::TestingPackage;
 #[cfg(not(feature = "no_std"))]
 pub use time_basic::BasicTimePackage;
 
@@ -73,6 +79,15 @@ impl PackagesCollection {
             .find(|f| f.is_some())
             .flatten()
     }
+    /// Get an iterator to all packages in the `PackagesCollection`.
+    #[cfg(feature = "metadata")]
+    pub fn iter(&self) -> impl Iterator<Item = &Module> {
+        self.0.iter().map(|p| p.as_ref())
+    }
+    /// Get an iterator to the functions in all packages in the `PackagesCollection`.
+    pub fn iter_fn(&self) -> impl Iterator<Item = &FuncInfo> {
+        self.0.iter().flat_map(|p| p.iter_fn())
+    }
     /// Does the specified TypeId iterator exist in the `PackagesCollection`?
     #[allow(dead_code)]
     pub fn contains_iter(&self, id: TypeId) -> bool {
@@ -88,6 +103,34 @@ impl PackagesCollection {
     }
 }
 
+/// Register a function which only reads its first argument (e.g. an array or an object map).
+///
+/// The function is pure and reads the first argument via `read_lock`, so calling it does not
+/// copy storage which is shared with other values (e.g. an array passed into a function).
+/// The other arguments are passed on unchanged.
+#[allow(dead_code)]
+pub(crate) fn reg_reader<C: Variant + Clone, T: Variant + Clone>(
+    lib: &mut Module,
+    name: &str,
+    arg_types: &[TypeId],
+    param_names: &[&str],
+    func: impl Fn(&C, &mut [&mut Dynamic]) -> T + SendSync + 'static,
+) {
+    let f = move |_: NativeCallContext, args: &mut FnCallArgs| {
+        let (first, rest) = args.split_first_mut().unwrap();
+        let first = first.read_lock::<C>().unwrap();
+        Ok(Dynamic::from(func(&first, rest)))
+    };
+
+    let hash = lib.set_fn(
+        name,
+        FnAccess::Public,
+        arg_types,
+        CallableFunction::from_pure(Box::new(f)),
+    );
+    lib.update_fn_metadata(hash, param_names);
+}
+
 /// Macro that makes it easy to define a _package_ (which is basically a shared module)
 /// and register functions into it.
 ///
@@ -259,7 +259,8 @@ mod string_functions {
         // Check if string will be over max size limit
         #[cfg(not(feature = "unchecked"))]
         if _ctx.engine().max_string_size() > 0 && len as usize > _ctx.engine().max_string_size() {
-            return EvalAltResult::ErrorDataTooLarge("Length of string".to_string(), NO_POS).into();
+            return EvalAltResult::ErrorDataTooLarge("Length of string".to_string(), NO_POS.into())
+                .into();
         }
 
         if len > 0 {
@@ -277,7 +278,7 @@ mod string_functions {
                 {
                     return EvalAltResult::ErrorDataTooLarge(
                         "Length of string".to_string(),
-                        NO_POS,
+                        NO_POS.into(),
                     )
                     .into();
                 }
@@ -296,7 +297,8 @@ mod string_functions {
         // Check if string will be over max size limit
         #[cfg(not(feature = "unchecked"))]
         if _ctx.engine().max_string_size() > 0 && len as usize > _ctx.engine().max_string_size() {
-            return EvalAltResult::ErrorDataTooLarge("Length of string".to_string(), NO_POS).into();
+            return EvalAltResult::ErrorDataTooLarge("Length of string".to_string(), NO_POS.into())
+                .into();
         }
 
         if len > 0 {
@@ -321,7 +323,7 @@ mod string_functions {
                 {
                     return EvalAltResult::ErrorDataTooLarge(
                         "Length of string".to_string(),
-                        NO_POS,
+                        NO_POS.into(),
                     )
                     .into();
                 }
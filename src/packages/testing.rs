@@ -0,0 +1,247 @@
+use crate::def_package;
+use crate::dynamic::{Dynamic, Variant};
+use crate::fn_native::{FnCallArgs, FnPtr, NativeCallContext};
+use crate::plugin::*;
+use crate::result::EvalAltResult;
+use crate::token::NO_POS;
+use crate::utils::ImmutableString;
+use crate::INT;
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+#[cfg(not(feature = "no_index"))]
+use crate::engine::Array;
+
+#[cfg(not(feature = "no_object"))]
+use crate::engine::Map;
+
+use crate::stdlib::{any::TypeId, boxed::Box, format, string::ToString, vec};
+
+def_package!(crate:TestingPackage:"Assertions for testing scripts.", lib, {
+    combine_with_exported_module!(lib, "testing", assert_functions);
+
+    // Values of standard types can be compared with each other
+    let mut types = vec![
+        TypeId::of::<INT>(),
+        TypeId::of::<bool>(),
+        TypeId::of::<char>(),
+        TypeId::of::<ImmutableString>(),
+        TypeId::of::<FnPtr>(),
+        TypeId::of::<()>(),
+    ];
+
+    #[cfg(not(feature = "no_float"))]
+    types.push(TypeId::of::<FLOAT>());
+    #[cfg(not(feature = "no_index"))]
+    types.push(TypeId::of::<Array>());
+    #[cfg(not(feature = "no_object"))]
+    types.push(TypeId::of::<Map>());
+
+    for &x in &types {
+        for &y in &types {
+            reg_assert_cmp(lib, x, y);
+        }
+    }
+
+    // Other numeric types can only be compared with the same type
+    #[cfg(not(feature = "only_i32"))]
+    #[cfg(not(feature = "only_i64"))]
+    {
+        reg_assert_cmp(lib, TypeId::of::<i8>(), TypeId::of::<i8>());
+        reg_assert_cmp(lib, TypeId::of::<u8>(), TypeId::of::<u8>());
+        reg_assert_cmp(lib, TypeId::of::<i16>(), TypeId::of::<i16>());
+        reg_assert_cmp(lib, TypeId::of::<u16>(), TypeId::of::<u16>());
+        reg_assert_cmp(lib, TypeId::of::<i32>(), TypeId::of::<i32>());
+        reg_assert_cmp(lib, TypeId::of::<u32>(), TypeId::of::<u32>());
+        reg_assert_cmp(lib, TypeId::of::<u64>(), TypeId::of::<u64>());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            reg_assert_cmp(lib, TypeId::of::<i128>(), TypeId::of::<i128>());
+            reg_assert_cmp(lib, TypeId::of::<u128>(), TypeId::of::<u128>());
+        }
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        #[cfg(not(feature = "f32_float"))]
+        reg_assert_cmp(lib, TypeId::of::<f32>(), TypeId::of::<f32>());
+
+        #[cfg(feature = "f32_float")]
+        reg_assert_cmp(lib, TypeId::of::<f64>(), TypeId::of::<f64>());
+    }
+});
+
+#[export_module]
+mod assert_functions {
+    #[rhai_fn(return_raw)]
+    pub fn assert(condition: bool) -> Result<Dynamic, Box<EvalAltResult>> {
+        if condition {
+            Ok(().into())
+        } else {
+            Err("Assertion failed".into())
+        }
+    }
+    #[rhai_fn(name = "assert", return_raw)]
+    pub fn assert_with_message(
+        condition: bool,
+        message: ImmutableString,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if condition {
+            Ok(().into())
+        } else {
+            Err(format!("Assertion failed: {}", message).into())
+        }
+    }
+}
+
+/// Register `assert_eq` and `assert_ne` (with and without a message) for a pair of types.
+fn reg_assert_cmp(lib: &mut Module, x: TypeId, y: TypeId) {
+    let message = TypeId::of::<ImmutableString>();
+
+    lib.set_raw_fn("assert_eq", &[x, y], |ctx, args| {
+        assert_cmp(ctx, args, true)
+    });
+    lib.set_raw_fn("assert_eq", &[x, y, message], |ctx, args| {
+        assert_cmp(ctx, args, true)
+    });
+    lib.set_raw_fn("assert_ne", &[x, y], |ctx, args| {
+        assert_cmp(ctx, args, false)
+    });
+    lib.set_raw_fn("assert_ne", &[x, y, message], |ctx, args| {
+        assert_cmp(ctx, args, false)
+    });
+}
+
+impl Engine {
+    /// Register the `assert_eq` and `assert_ne` functions of the `TestingPackage` for a custom type.
+    ///
+    /// Values of the type are compared with the `==` operator, which must also be registered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, RegisterFn};
+    /// use rhai::packages::{Package, TestingPackage};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// struct TestStruct(i64);
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.load_package(TestingPackage::new().get());
+    ///
+    /// engine
+    ///     .register_fn("new_ts", TestStruct)
+    ///     .register_fn("==", |x: &mut TestStruct, y: TestStruct| *x == y)
+    ///     .register_assertions::<TestStruct>();
+    ///
+    /// engine.consume("assert_eq(new_ts(1), new_ts(1)); assert_ne(new_ts(1), new_ts(2));")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn register_assertions<T: Variant + Clone>(&mut self) -> &mut Self {
+        reg_assert_cmp(
+            &mut self.global_module,
+            TypeId::of::<T>(),
+            TypeId::of::<T>(),
+        );
+        self
+    }
+}
+
+/// Check that two values are equal (or not), failing with a message showing both values.
+fn assert_cmp(
+    ctx: NativeCallContext,
+    args: &mut FnCallArgs,
+    equal: bool,
+) -> Result<(), Box<EvalAltResult>> {
+    let (left, right) = (&*args[0], &*args[1]);
+
+    if values_equal(ctx, left, right)? == equal {
+        return Ok(());
+    }
+
+    let message = match args.get(2) {
+        Some(message) => message.to_string(),
+        None if equal => "left == right".to_string(),
+        None => "left != right".to_string(),
+    };
+
+    EvalAltResult::ErrorRuntime(
+        format!(
+            "Assertion failed: {} (left: {:?}, right: {:?})",
+            message, left, right
+        )
+        .into(),
+        NO_POS.into(),
+    )
+    .into()
+}
+
+/// Are two values equal?
+///
+/// Arrays and object maps are compared item by item.  Values of different types are never equal.
+fn values_equal(
+    ctx: NativeCallContext,
+    x: &Dynamic,
+    y: &Dynamic,
+) -> Result<bool, Box<EvalAltResult>> {
+    if x.type_id() != y.type_id() {
+        return Ok(false);
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    if x.is::<Array>() {
+        let x = x.read_lock::<Array>().unwrap();
+        let y = y.read_lock::<Array>().unwrap();
+
+        if x.len() != y.len() {
+            return Ok(false);
+        }
+        for (a, b) in x.iter().zip(y.iter()) {
+            if !values_equal(ctx, a, b)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    if x.is::<Map>() {
+        let x = x.read_lock::<Map>().unwrap();
+        let y = y.read_lock::<Map>().unwrap();
+
+        if x.len() != y.len() {
+            return Ok(false);
+        }
+        for (key, a) in x.iter() {
+            match y.get(key) {
+                Some(b) if values_equal(ctx, a, b)? => (),
+                _ => return Ok(false),
+            }
+        }
+        return Ok(true);
+    }
+
+    if x.is::<FnPtr>() {
+        let x = x.read_lock::<FnPtr>().unwrap();
+        let y = y.read_lock::<FnPtr>().unwrap();
+
+        if x.fn_name() != y.fn_name() || x.curry().len() != y.curry().len() {
+            return Ok(false);
+        }
+        for (a, b) in x.curry().iter().zip(y.curry().iter()) {
+            if !values_equal(ctx, a, b)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+
+    FnPtr::new_unchecked("==", Default::default())
+        .call_dynamic(ctx, None, [x.clone(), y.clone()])
+        .map(|r| r.as_bool().unwrap_or(false))
+}
@@ -1,7 +1,8 @@
 //! Module containing error definitions for the parsing process.
 
 use crate::result::EvalAltResult;
-use crate::token::{Position, NO_POS};
+use crate::token::{Location, Position, Span};
+use crate::utils::ImmutableString;
 
 use crate::stdlib::{
     boxed::Box,
@@ -35,6 +36,9 @@ pub enum LexError {
     MalformedIdentifier(String),
     /// Bad symbol encountered when tokenizing the script text.
     ImproperSymbol(String),
+    /// The script has more lines, or a line has more characters, than the maximum limit.
+    /// Wrapped value is the maximum limit.
+    PositionTooLarge(usize),
 }
 
 impl Error for LexError {}
@@ -49,6 +53,7 @@ impl fmt::Display for LexError {
             Self::MalformedIdentifier(s) => write!(f, "{}: '{}'", self.desc(), s),
             Self::UnterminatedString => f.write_str(self.desc()),
             Self::StringTooLong(max) => write!(f, "{} ({})", self.desc(), max),
+            Self::PositionTooLarge(max) => write!(f, "{} ({})", self.desc(), max),
             Self::ImproperSymbol(s) => f.write_str(s),
         }
     }
@@ -65,12 +70,15 @@ impl LexError {
             Self::MalformedChar(_) => "Invalid character",
             Self::MalformedIdentifier(_) => "Variable name is not proper",
             Self::ImproperSymbol(_) => "Invalid symbol encountered",
+            Self::PositionTooLarge(_) => {
+                "Number of lines or characters in a line exceeds the maximum limit"
+            }
         }
     }
     /// Convert a `&LexError` into a `ParseError`.
     #[inline(always)]
     pub fn into_err(&self, pos: Position) -> ParseError {
-        ParseError(Box::new(self.clone().into()), pos)
+        ParseError(Box::new(self.clone().into()), pos.into())
     }
 }
 
@@ -161,13 +169,25 @@ pub enum ParseErrorType {
     LiteralTooLarge(String, usize),
     /// Break statement not inside a loop.
     LoopBreak,
+    /// A variable that is not defined, found in strict mode. Wrapped value is the variable name.
+    VariableNotDefined(String),
+    /// A function that is not defined, found in strict mode.
+    /// Wrapped values are the function name and the number of arguments.
+    FnNotDefined(String, usize),
+    /// Missing a type name in a type annotation.
+    TypeExpected,
+    /// A type annotation names an unknown type. Wrapped value is the type name.
+    UnknownType(String),
+    /// A value does not match its type annotation.
+    /// Wrapped values are the actual type and the expected type.
+    MismatchedType(String, String),
 }
 
 impl ParseErrorType {
     /// Make a `ParseError` using the current type and position.
     #[inline(always)]
     pub(crate) fn into_err(self, pos: Position) -> ParseError {
-        ParseError(Box::new(self), pos)
+        ParseError(Box::new(self), pos.into())
     }
 
     pub(crate) fn desc(&self) -> &str {
@@ -196,7 +216,12 @@ impl ParseErrorType {
             Self::AssignmentToInvalidLHS(_) => "Expression cannot be assigned to",
             Self::ExprTooDeep => "Expression exceeds maximum complexity",
             Self::LiteralTooLarge(_, _) => "Literal exceeds maximum limit",
-            Self::LoopBreak => "Break statement should only be used inside a loop"
+            Self::LoopBreak => "Break statement should only be used inside a loop",
+            Self::VariableNotDefined(_) => "Variable is not defined",
+            Self::FnNotDefined(_, _) => "Function is not defined",
+            Self::TypeExpected => "Expecting name of a type",
+            Self::UnknownType(_) => "Unknown type",
+            Self::MismatchedType(_, _) => "Data type is incorrect",
         }
     }
 }
@@ -250,6 +275,19 @@ impl fmt::Display for ParseErrorType {
                 write!(f, "{} exceeds the maximum limit ({})", typ, max)
             }
             Self::Reserved(s) => write!(f, "'{}' is a reserved keyword", s),
+
+            Self::VariableNotDefined(s) => write!(f, "Variable '{}' is not defined", s),
+            Self::FnNotDefined(s, 1) => {
+                write!(f, "Function '{}' with 1 parameter is not defined", s)
+            }
+            Self::FnNotDefined(s, n) => {
+                write!(f, "Function '{}' with {} parameters is not defined", s, n)
+            }
+
+            Self::UnknownType(s) => write!(f, "Unknown type '{}'", s),
+            Self::MismatchedType(r, s) => {
+                write!(f, "Data type is incorrect: {} (expecting {})", r, s)
+            }
             _ => f.write_str(self.desc()),
         }
     }
@@ -269,7 +307,34 @@ impl From<LexError> for ParseErrorType {
 
 /// Error when parsing a script.
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
-pub struct ParseError(pub Box<ParseErrorType>, pub Position);
+pub struct ParseError(pub Box<ParseErrorType>, pub Location);
+
+impl ParseError {
+    /// Get the `Position` of this error.
+    #[inline(always)]
+    pub fn position(&self) -> Position {
+        self.1.position()
+    }
+    /// Get the `Span` of the token where this error occurs.
+    ///
+    /// If the `Span` is not known, it only covers the `Position` of this error.
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.1.span()
+    }
+    /// Get the name of the source (e.g. the script file path) where this error occurs, if any.
+    #[inline(always)]
+    pub fn source(&self) -> Option<&str> {
+        self.1.source()
+    }
+    /// Set the `Span` of the token where this error occurs and the name of its source,
+    /// unless already set.
+    #[inline(always)]
+    pub(crate) fn fill_location(&mut self, span: Span, source: &Option<ImmutableString>) {
+        self.1.fill_span(span);
+        self.1.fill_source(source);
+    }
+}
 
 impl Error for ParseError {}
 
@@ -290,7 +355,7 @@ impl fmt::Display for ParseError {
 impl From<ParseErrorType> for Box<EvalAltResult> {
     #[inline(always)]
     fn from(err: ParseErrorType) -> Self {
-        Box::new(EvalAltResult::ErrorParsing(err, NO_POS))
+        Box::new(EvalAltResult::ErrorParsing(err, Location::NONE))
     }
 }
 
@@ -3,6 +3,7 @@
 use crate::ast::{
     BinaryExpr, CustomExpr, Expr, FnCallInfo, Ident, IdentX, ReturnType, ScriptFnDef, Stmt, AST,
 };
+use crate::cst::IdentRole;
 use crate::dynamic::{Dynamic, Union};
 use crate::engine::{Engine, KEYWORD_THIS, MARKER_BLOCK, MARKER_EXPR, MARKER_IDENT};
 use crate::module::ModuleRef;
@@ -11,25 +12,31 @@ use crate::parse_error::{LexError, ParseError, ParseErrorType};
 use crate::scope::{EntryType as ScopeEntryType, Scope};
 use crate::syntax::CustomSyntax;
 use crate::token::{
-    is_keyword_function, is_valid_identifier, Position, Token, TokenStream, NO_POS,
+    is_keyword_function, is_valid_identifier, Position, Span, Token, TokenStream, NO_POS,
 };
-use crate::utils::StraightHasherBuilder;
-use crate::{calc_script_fn_hash, StaticVec};
+use crate::utils::{ImmutableString, StraightHasherBuilder};
+use crate::{calc_script_fn_hash, StaticVec, INT};
 
 #[cfg(not(feature = "no_float"))]
-use crate::ast::FloatWrapper;
+use crate::{ast::FloatWrapper, FLOAT};
+
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+use crate::fn_native::shared_take_or_clone;
 
 #[cfg(not(feature = "no_object"))]
 use crate::engine::{make_getter, make_setter, KEYWORD_EVAL, KEYWORD_FN_PTR};
 
 #[cfg(not(feature = "no_function"))]
-use crate::{
-    ast::FnAccess,
-    engine::{FN_ANONYMOUS, KEYWORD_FN_PTR_CURRY},
-    utils::ImmutableString,
-};
+use crate::{ast::FnAccess, engine::FN_ANONYMOUS};
+
+#[cfg(not(feature = "no_closure"))]
+use crate::engine::KEYWORD_FN_PTR_CURRY;
+
+#[cfg(not(feature = "unchecked"))]
+use crate::engine::StackGuard;
 
 use crate::stdlib::{
+    any::type_name,
     borrow::Cow,
     boxed::Box,
     collections::{HashMap, HashSet},
@@ -61,11 +68,23 @@ type FunctionsLib = HashMap<u64, ScriptFnDef, StraightHasherBuilder>;
 struct ParseState<'e> {
     /// Reference to the scripting `Engine`.
     engine: &'e Engine,
-    /// Encapsulates a local stack with variable names to simulate an actual runtime scope.
-    stack: Vec<(String, ScopeEntryType)>,
-    /// Tracks a list of external variables (variables that are not explicitly declared in the scope).
+    /// Encapsulates a local stack with variable names to simulate an actual runtime scope,
+    /// together with the type annotations of the variables (if any).
+    stack: Vec<(String, ScopeEntryType, Option<ImmutableString>)>,
+    /// Tracks a list of external variables (variables that are not explicitly declared in the scope),
+    /// in order of first access.
+    #[cfg(not(feature = "no_closure"))]
+    externals: Vec<(String, Position)>,
+    /// Are external variables captured into the function's frame (i.e. is this a closure)?
+    ///
+    /// If so, captured variables are curried in reverse order of first access,
+    /// just below the parameters, so they can also be accessed via offsets.
     #[cfg(not(feature = "no_closure"))]
-    externals: HashMap<String, Position>,
+    is_closure: bool,
+    /// Variables visible where a closure is defined, together with their type annotations (if any),
+    /// so that assignments to captured variables can be checked.
+    #[cfg(not(feature = "no_closure"))]
+    outer_vars: Vec<(String, Option<ImmutableString>)>,
     /// An indicator that disables variable capturing into externals one single time
     /// up until the nearest consumed Identifier token.
     /// If set to false the next call to `access_var` will not capture the variable.
@@ -75,6 +94,13 @@ struct ParseState<'e> {
     /// Encapsulates a local stack with imported module names.
     #[cfg(not(feature = "no_module"))]
     modules: Vec<String>,
+    /// Type annotation of the return value of the function being parsed, if any.
+    #[cfg(not(feature = "no_function"))]
+    fn_return_type: Option<ImmutableString>,
+    /// Syntax errors recovered from so far, if parsing in error-recovery mode (otherwise `None`).
+    errors: Option<Vec<ParseError>>,
+    /// Roles of the identifiers parsed so far by position, if recording them (otherwise `None`).
+    idents: Option<Vec<(Position, IdentRole)>>,
     /// Maximum levels of expression nesting.
     #[cfg(not(feature = "unchecked"))]
     max_expr_depth: usize,
@@ -104,11 +130,165 @@ impl<'e> ParseState<'e> {
             #[cfg(not(feature = "no_closure"))]
             externals: Default::default(),
             #[cfg(not(feature = "no_closure"))]
+            is_closure: false,
+            #[cfg(not(feature = "no_closure"))]
+            outer_vars: Default::default(),
+            #[cfg(not(feature = "no_closure"))]
             allow_capture: true,
             stack: Default::default(),
             #[cfg(not(feature = "no_module"))]
             modules: Default::default(),
+            #[cfg(not(feature = "no_function"))]
+            fn_return_type: None,
+            errors: None,
+            idents: None,
+        }
+    }
+
+    /// Create a new `ParseState` for parsing a function body, inheriting the error-recovery mode
+    /// and the recording of identifier roles.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn new_for_function(&self) -> Self {
+        let mut state = Self::new(
+            self.engine,
+            #[cfg(not(feature = "unchecked"))]
+            self.max_function_expr_depth,
+            #[cfg(not(feature = "unchecked"))]
+            self.max_function_expr_depth,
+        );
+        state.errors = self.errors.as_ref().map(|_| Default::default());
+        state.idents = self.idents.as_ref().map(|_| Default::default());
+        state
+    }
+
+    /// Move the syntax errors recovered from, and the identifier roles recorded, while parsing a
+    /// function body into this `ParseState`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn append_recorded(&mut self, other: &mut Self) {
+        if let (Some(errors), Some(other)) = (self.errors.as_mut(), other.errors.as_mut()) {
+            errors.append(other);
+        }
+        if let (Some(idents), Some(other)) = (self.idents.as_mut(), other.idents.as_mut()) {
+            idents.append(other);
+        }
+    }
+
+    /// Record the role of the identifier at a position, if recording identifier roles.
+    ///
+    /// A role recorded later for the same position takes precedence.
+    #[inline(always)]
+    pub fn record_ident(&mut self, pos: Position, role: IdentRole) {
+        if let Some(ref mut idents) = self.idents {
+            idents.push((pos, role));
+        }
+    }
+
+    /// Record a syntax error in error-recovery mode, so that parsing can continue.
+    /// Otherwise, the error is returned.
+    #[inline(always)]
+    pub fn record_error(&mut self, err: ParseError) -> Result<(), ParseError> {
+        match self.errors {
+            Some(ref mut errors) => {
+                errors.push(err);
+                Ok(())
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Recover from a syntax error in a statement starting at `start`.
+    ///
+    /// In error-recovery mode, the error is recorded and tokens are skipped up to the next
+    /// statement boundary, so that parsing can continue. Otherwise, the error is returned.
+    ///
+    /// Returns the `Position` of the token that caused the error if it is a `}` that has already
+    /// been consumed, meaning that the enclosing statement block has ended.
+    pub fn recover(
+        &mut self,
+        input: &mut TokenStream,
+        err: ParseError,
+        start: Position,
+    ) -> Result<Option<Position>, ParseError> {
+        // Has the parser already consumed the offending `;` or `}`?
+        let boundary = match err.0.as_ref() {
+            PERR::BadInput(LexError::UnexpectedInput(token))
+                if input.peek().unwrap().1 != err.position() =>
+            {
+                match token.as_str() {
+                    ";" => Some(None),
+                    "}" => Some(Some(err.position())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        self.record_error(err)?;
+
+        if let Some(block_end) = boundary {
+            return Ok(block_end);
+        }
+
+        let mut depth = 0_usize;
+
+        // Always skip at least one token to guarantee progress
+        if input.peek().unwrap().1 == start {
+            match input.next().unwrap().0 {
+                Token::LeftBrace => depth += 1,
+                Token::SemiColon | Token::RightBrace | Token::EOF => return Ok(None),
+                _ => (),
+            }
         }
+
+        loop {
+            match input.peek().unwrap() {
+                // Leave the end of the enclosing block to the block parser
+                (Token::EOF, _) | (Token::RightBrace, _) if depth == 0 => break,
+                // Start of a new statement
+                (Token::Let, _)
+                | (Token::Const, _)
+                | (Token::While, _)
+                | (Token::Loop, _)
+                | (Token::For, _)
+                | (Token::Return, _)
+                | (Token::Throw, _)
+                | (Token::Try, _)
+                    if depth == 0 =>
+                {
+                    break
+                }
+                #[cfg(not(feature = "no_function"))]
+                (Token::Comment(_), _) | (Token::Fn, _) | (Token::Private, _) if depth == 0 => {
+                    break
+                }
+                #[cfg(not(feature = "no_module"))]
+                (Token::Import, _) | (Token::Export, _) if depth == 0 => break,
+                (Token::SemiColon, _) if depth == 0 => {
+                    eat_token(input, Token::SemiColon);
+                    break;
+                }
+                (Token::LeftBrace, _) => {
+                    eat_token(input, Token::LeftBrace);
+                    depth += 1;
+                }
+                (Token::RightBrace, _) => {
+                    eat_token(input, Token::RightBrace);
+                    depth -= 1;
+
+                    // A block has ended
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {
+                    input.next().unwrap();
+                }
+            }
+        }
+
+        Ok(None)
     }
 
     /// Find explicitly declared variable by name in the `ParseState`, searching in reverse order.
@@ -117,6 +297,7 @@ impl<'e> ParseState<'e> {
     ///
     /// The return value is the offset to be deducted from `Stack::len`,
     /// i.e. the top element of the `ParseState` is offset 1.
+    /// Within a closure, captured variables sit just below the bottom of the `stack`.
     /// Return `None` when the variable name is not found in the `stack`.
     #[inline]
     fn access_var(&mut self, name: &str, _pos: Position) -> Option<NonZeroUsize> {
@@ -125,13 +306,23 @@ impl<'e> ParseState<'e> {
             .iter()
             .rev()
             .enumerate()
-            .find(|(_, (n, _))| *n == name)
+            .find(|(_, (n, _, _))| *n == name)
             .and_then(|(i, _)| NonZeroUsize::new(i + 1));
 
         #[cfg(not(feature = "no_closure"))]
         if self.allow_capture {
-            if index.is_none() && !self.externals.contains_key(name) {
-                self.externals.insert(name.to_string(), _pos);
+            if index.is_none() {
+                let offset = match self.externals.iter().position(|(n, _)| n == name) {
+                    Some(offset) => offset,
+                    None => {
+                        self.externals.push((name.to_string(), _pos));
+                        self.externals.len() - 1
+                    }
+                };
+
+                if self.is_closure {
+                    return NonZeroUsize::new(self.stack.len() + offset + 1);
+                }
             }
         } else {
             self.allow_capture = true
@@ -140,6 +331,39 @@ impl<'e> ParseState<'e> {
         index
     }
 
+    /// Get the type of the variable at an offset returned by `access_var`.
+    ///
+    /// Captured variables in closures lie outside the `stack` and are always `Normal`.
+    #[inline(always)]
+    fn entry_type(&self, index: NonZeroUsize) -> ScopeEntryType {
+        self.stack
+            .len()
+            .checked_sub(index.get())
+            .map_or(ScopeEntryType::Normal, |offset| self.stack[offset].1)
+    }
+
+    /// Get the type annotation of the variable at an offset returned by `access_var`, if any.
+    ///
+    /// The annotations of captured variables in closures are those where the closure is defined.
+    #[inline]
+    fn var_type(&self, index: NonZeroUsize) -> Option<&ImmutableString> {
+        match self.stack.len().checked_sub(index.get()) {
+            Some(offset) => self.stack[offset].2.as_ref(),
+            #[cfg(not(feature = "no_closure"))]
+            None => {
+                let (name, _) = &self.externals[index.get() - self.stack.len() - 1];
+
+                self.outer_vars
+                    .iter()
+                    .rev()
+                    .find(|(n, _)| n == name)
+                    .and_then(|(_, typ)| typ.as_ref())
+            }
+            #[cfg(feature = "no_closure")]
+            None => None,
+        }
+    }
+
     /// Find a module by name in the `ParseState`, searching in reverse.
     ///
     /// Returns the offset to be deducted from `Stack::len`,
@@ -173,6 +397,8 @@ struct ParseSettings {
     is_function_scope: bool,
     /// Is the current position inside a loop?
     is_breakable: bool,
+    /// Is the current position inside a `catch` block?
+    is_catch: bool,
     /// Is anonymous function allowed?
     allow_anonymous_fn: bool,
     /// Is if-expression allowed?
@@ -192,13 +418,16 @@ impl ParseSettings {
             ..*self
         }
     }
-    /// Make sure that the current level of expression nesting is within the maximum limit.
+    /// Make sure that the current level of expression nesting, as well as the native stack used,
+    /// are within the maximum limits.
     #[cfg(not(feature = "unchecked"))]
     #[inline]
-    pub fn ensure_level_within_max_limit(&self, limit: usize) -> Result<(), ParseError> {
-        if limit == 0 {
-            Ok(())
-        } else if self.level > limit {
+    fn ensure_level_within_max_limit(&self, state: &ParseState) -> Result<(), ParseError> {
+        let max_stack_size = state.engine.max_stack_size();
+
+        if state.max_expr_depth > 0 && self.level > state.max_expr_depth {
+            Err(PERR::ExprTooDeep.into_err(self.pos))
+        } else if max_stack_size > 0 && StackGuard::stack_used() > max_stack_size {
             Err(PERR::ExprTooDeep.into_err(self.pos))
         } else {
             Ok(())
@@ -238,10 +467,10 @@ fn parse_paren_expr(
     settings: ParseSettings,
 ) -> Result<Expr, ParseError> {
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
-    if match_token(input, Token::RightParen).0 {
-        return Ok(Expr::Unit(settings.pos));
+    if let (true, end) = match_token(input, Token::RightParen) {
+        return Ok(Expr::Unit(settings.pos, end));
     }
 
     let expr = parse_expr(input, state, lib, settings.level_up())?;
@@ -273,7 +502,7 @@ fn parse_fn_call(
     let (token, token_pos) = input.peek().unwrap();
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let mut args = StaticVec::new();
 
@@ -290,7 +519,7 @@ fn parse_fn_call(
         Token::LexError(err) => return Err(err.into_err(*token_pos)),
         // id()
         Token::RightParen => {
-            eat_token(input, Token::RightParen);
+            let end = eat_token(input, Token::RightParen);
 
             let hash_script = if let Some(modules) = namespace.as_mut() {
                 #[cfg(not(feature = "no_module"))]
@@ -319,6 +548,7 @@ fn parse_fn_call(
                     ..Default::default()
                 }),
                 settings.pos,
+                end,
             ));
         }
         // id...
@@ -337,7 +567,7 @@ fn parse_fn_call(
         match input.peek().unwrap() {
             // id(...args)
             (Token::RightParen, _) => {
-                eat_token(input, Token::RightParen);
+                let end = eat_token(input, Token::RightParen);
 
                 let hash_script = if let Some(modules) = namespace.as_mut() {
                     #[cfg(not(feature = "no_module"))]
@@ -366,6 +596,7 @@ fn parse_fn_call(
                         ..Default::default()
                     }),
                     settings.pos,
+                    end,
                 ));
             }
             // id(...args,
@@ -405,24 +636,24 @@ fn parse_index_chain(
     mut settings: ParseSettings,
 ) -> Result<Expr, ParseError> {
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let idx_expr = parse_expr(input, state, lib, settings.level_up())?;
 
     // Check type of indexing - must be integer or string
     match &idx_expr {
         // lhs[int]
-        Expr::IntegerConstant(x, pos) if *x < 0 => {
+        Expr::IntegerConstant(x, pos, _) if *x < 0 => {
             return Err(PERR::MalformedIndexExpr(format!(
                 "Array access expects non-negative index: {} < 0",
                 *x
             ))
             .into_err(*pos))
         }
-        Expr::IntegerConstant(_, pos) => match lhs {
-            Expr::Array(_, _) | Expr::StringConstant(_) => (),
+        Expr::IntegerConstant(_, pos, _) => match lhs {
+            Expr::Array(_, _, _) | Expr::StringConstant(_, _) => (),
 
-            Expr::Map(_, _) => {
+            Expr::Map(_, _, _) => {
                 return Err(PERR::MalformedIndexExpr(
                     "Object map access expects string index, not a number".into(),
                 )
@@ -430,20 +661,20 @@ fn parse_index_chain(
             }
 
             #[cfg(not(feature = "no_float"))]
-            Expr::FloatConstant(_, _) => {
+            Expr::FloatConstant(_, _, _) => {
                 return Err(PERR::MalformedIndexExpr(
                     "Only arrays, object maps and strings can be indexed".into(),
                 )
                 .into_err(lhs.position()))
             }
 
-            Expr::CharConstant(_, _)
+            Expr::CharConstant(_, _, _)
             | Expr::And(_, _)
             | Expr::Or(_, _)
             | Expr::In(_, _)
             | Expr::True(_)
             | Expr::False(_)
-            | Expr::Unit(_) => {
+            | Expr::Unit(_, _) => {
                 return Err(PERR::MalformedIndexExpr(
                     "Only arrays, object maps and strings can be indexed".into(),
                 )
@@ -454,10 +685,10 @@ fn parse_index_chain(
         },
 
         // lhs[string]
-        Expr::StringConstant(x) => match lhs {
-            Expr::Map(_, _) => (),
+        Expr::StringConstant(x, _) => match lhs {
+            Expr::Map(_, _, _) => (),
 
-            Expr::Array(_, _) | Expr::StringConstant(_) => {
+            Expr::Array(_, _, _) | Expr::StringConstant(_, _) => {
                 return Err(PERR::MalformedIndexExpr(
                     "Array or string expects numeric index, not a string".into(),
                 )
@@ -465,20 +696,20 @@ fn parse_index_chain(
             }
 
             #[cfg(not(feature = "no_float"))]
-            Expr::FloatConstant(_, _) => {
+            Expr::FloatConstant(_, _, _) => {
                 return Err(PERR::MalformedIndexExpr(
                     "Only arrays, object maps and strings can be indexed".into(),
                 )
                 .into_err(lhs.position()))
             }
 
-            Expr::CharConstant(_, _)
+            Expr::CharConstant(_, _, _)
             | Expr::And(_, _)
             | Expr::Or(_, _)
             | Expr::In(_, _)
             | Expr::True(_)
             | Expr::False(_)
-            | Expr::Unit(_) => {
+            | Expr::Unit(_, _) => {
                 return Err(PERR::MalformedIndexExpr(
                     "Only arrays, object maps and strings can be indexed".into(),
                 )
@@ -490,21 +721,21 @@ fn parse_index_chain(
 
         // lhs[float]
         #[cfg(not(feature = "no_float"))]
-        x @ Expr::FloatConstant(_, _) => {
+        x @ Expr::FloatConstant(_, _, _) => {
             return Err(PERR::MalformedIndexExpr(
                 "Array access expects integer index, not a float".into(),
             )
             .into_err(x.position()))
         }
         // lhs[char]
-        x @ Expr::CharConstant(_, _) => {
+        x @ Expr::CharConstant(_, _, _) => {
             return Err(PERR::MalformedIndexExpr(
                 "Array access expects integer index, not a character".into(),
             )
             .into_err(x.position()))
         }
         // lhs[()]
-        x @ Expr::Unit(_) => {
+        x @ Expr::Unit(_, _) => {
             return Err(PERR::MalformedIndexExpr(
                 "Array access expects integer index, not ()".into(),
             )
@@ -531,7 +762,7 @@ fn parse_index_chain(
     // Check if there is a closing bracket
     match input.peek().unwrap() {
         (Token::RightBracket, _) => {
-            eat_token(input, Token::RightBracket);
+            let end = eat_token(input, Token::RightBracket);
 
             // Any more indexing following?
             match input.peek().unwrap() {
@@ -543,9 +774,11 @@ fn parse_index_chain(
                     let idx_expr =
                         parse_index_chain(input, state, lib, idx_expr, settings.level_up())?;
                     // Indexing binds to right
+                    let end = idx_expr.end_position();
                     Ok(Expr::Index(
                         Box::new(BinaryExpr { lhs, rhs: idx_expr }),
                         prev_pos,
+                        end,
                     ))
                 }
                 // Otherwise terminate the indexing chain
@@ -554,18 +787,20 @@ fn parse_index_chain(
                         // Terminate with an `Expr::Expr` wrapper to prevent the last index expression
                         // inside brackets to be mis-parsed as another level of indexing, or a
                         // dot expression/function call to be mis-parsed as following the indexing chain.
-                        Expr::Index(_, _) | Expr::Dot(_, _) | Expr::FnCall(_, _) => {
+                        Expr::Index(_, _, _) | Expr::Dot(_, _) | Expr::FnCall(_, _, _) => {
                             Ok(Expr::Index(
                                 Box::new(BinaryExpr {
                                     lhs,
                                     rhs: Expr::Expr(Box::new(idx_expr)),
                                 }),
                                 settings.pos,
+                                end,
                             ))
                         }
                         _ => Ok(Expr::Index(
                             Box::new(BinaryExpr { lhs, rhs: idx_expr }),
                             settings.pos,
+                            end,
                         )),
                     }
                 }
@@ -589,7 +824,7 @@ fn parse_array_literal(
     settings: ParseSettings,
 ) -> Result<Expr, ParseError> {
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let mut arr = StaticVec::new();
 
@@ -637,7 +872,7 @@ fn parse_array_literal(
         };
     }
 
-    Ok(Expr::Array(Box::new(arr), settings.pos))
+    Ok(Expr::Array(Box::new(arr), settings.pos, input.last_end()))
 }
 
 /// Parse a map literal.
@@ -649,7 +884,7 @@ fn parse_map_literal(
     settings: ParseSettings,
 ) -> Result<Expr, ParseError> {
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let mut map = StaticVec::new();
 
@@ -665,7 +900,10 @@ fn parse_map_literal(
         }
 
         let (name, pos) = match input.next().unwrap() {
-            (Token::Identifier(s), pos) => (s, pos),
+            (Token::Identifier(s), pos) => {
+                state.record_ident(pos, IdentRole::Property);
+                (s, pos)
+            }
             (Token::StringConstant(s), pos) => (s, pos),
             (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
                 return Err(PERR::Reserved(s).into_err(pos));
@@ -746,7 +984,7 @@ fn parse_map_literal(
         })
         .map_err(|(key, pos)| PERR::DuplicatedProperty(key.to_string()).into_err(pos))?;
 
-    Ok(Expr::Map(Box::new(map), settings.pos))
+    Ok(Expr::Map(Box::new(map), settings.pos, input.last_end()))
 }
 
 /// Parse a primary expression.
@@ -760,13 +998,15 @@ fn parse_primary(
     settings.pos = *token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let (token, _) = match token {
         // { - block statement as expression
         Token::LeftBrace if settings.allow_stmt_expr => {
             return parse_block(input, state, lib, settings.level_up()).map(|block| match block {
-                Stmt::Block(statements, pos) => Expr::Stmt(Box::new(statements.into()), pos),
+                Stmt::Block(statements, span) => {
+                    Expr::Stmt(Box::new(statements.into()), span.start(), span.end())
+                }
                 _ => unreachable!(),
             })
         }
@@ -774,14 +1014,17 @@ fn parse_primary(
         _ => input.next().unwrap(),
     };
 
+    let end = input.last_end();
     let (next_token, _) = input.peek().unwrap();
 
     let mut root_expr = match token {
-        Token::IntegerConstant(x) => Expr::IntegerConstant(x, settings.pos),
+        Token::IntegerConstant(x) => Expr::IntegerConstant(x, settings.pos, end),
         #[cfg(not(feature = "no_float"))]
-        Token::FloatConstant(x) => Expr::FloatConstant(FloatWrapper(x), settings.pos),
-        Token::CharConstant(c) => Expr::CharConstant(c, settings.pos),
-        Token::StringConstant(s) => Expr::StringConstant(Box::new(IdentX::new(s, settings.pos))),
+        Token::FloatConstant(x) => Expr::FloatConstant(FloatWrapper(x), settings.pos, end),
+        Token::CharConstant(c) => Expr::CharConstant(c, settings.pos, end),
+        Token::StringConstant(s) => {
+            Expr::StringConstant(Box::new(IdentX::new(s, settings.pos)), end)
+        }
 
         // Function call
         Token::Identifier(s) if *next_token == Token::LeftParen || *next_token == Token::Bang => {
@@ -805,6 +1048,7 @@ fn parse_primary(
         // Normal variable access
         Token::Identifier(s) => {
             let index = state.access_var(&s, settings.pos);
+            state.record_ident(settings.pos, IdentRole::Variable);
             Expr::Variable(Box::new((index, None, 0, Ident::new(s, settings.pos))))
         }
 
@@ -887,12 +1131,14 @@ fn parse_primary(
 
                 let (_, modules, _, Ident { name, pos }) = *x;
                 settings.pos = pos;
+                state.record_ident(pos, IdentRole::Function);
                 parse_fn_call(input, state, lib, name, true, modules, settings.level_up())?
             }
             // Function call
             (Expr::Variable(x), Token::LeftParen) => {
                 let (_, modules, _, Ident { name, pos }) = *x;
                 settings.pos = pos;
+                state.record_ident(pos, IdentRole::Function);
                 parse_fn_call(input, state, lib, name, false, modules, settings.level_up())?
             }
             (Expr::Property(_), _) => unreachable!(),
@@ -901,6 +1147,9 @@ fn parse_primary(
                 (Token::Identifier(id2), pos2) => {
                     let (index, mut modules, _, var_name_def) = *x;
 
+                    state.record_ident(var_name_def.pos, IdentRole::Module);
+                    state.record_ident(pos2, IdentRole::Variable);
+
                     if let Some(ref mut modules) = modules {
                         modules.push(var_name_def);
                     } else {
@@ -960,14 +1209,14 @@ fn parse_unary(
     settings.pos = *token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     match token {
         // If statement is allowed to act as expressions
         Token::If if settings.allow_if_expr => {
             let mut block: StaticVec<_> = Default::default();
             block.push(parse_if(input, state, lib, settings.level_up())?);
-            Ok(Expr::Stmt(Box::new(block), settings.pos))
+            Ok(Expr::Stmt(Box::new(block), settings.pos, input.last_end()))
         }
         // -expr
         Token::UnaryMinus => {
@@ -975,12 +1224,16 @@ fn parse_unary(
 
             match parse_unary(input, state, lib, settings.level_up())? {
                 // Negative integer
-                Expr::IntegerConstant(num, pos) => num
+                Expr::IntegerConstant(num, pos, end) => num
                     .checked_neg()
-                    .map(|i| Expr::IntegerConstant(i, pos))
+                    .map(|i| Expr::IntegerConstant(i, pos, end))
                     .or_else(|| {
                         #[cfg(not(feature = "no_float"))]
-                        return Some(Expr::FloatConstant(-Into::<FloatWrapper>::into(num), pos));
+                        return Some(Expr::FloatConstant(
+                            -Into::<FloatWrapper>::into(num),
+                            pos,
+                            end,
+                        ));
                         #[cfg(feature = "no_float")]
                         return None;
                     })
@@ -988,12 +1241,13 @@ fn parse_unary(
 
                 // Negative float
                 #[cfg(not(feature = "no_float"))]
-                Expr::FloatConstant(x, pos) => Ok(Expr::FloatConstant(-x, pos)),
+                Expr::FloatConstant(x, pos, end) => Ok(Expr::FloatConstant(-x, pos, end)),
 
                 // Call negative function
                 expr => {
                     let op = "-";
                     let hash = calc_script_fn_hash(empty(), op, 1);
+                    let end = expr.end_position();
                     let mut args = StaticVec::new();
                     args.push(expr);
 
@@ -1007,6 +1261,7 @@ fn parse_unary(
                             ..Default::default()
                         }),
                         pos,
+                        end,
                     ))
                 }
             }
@@ -1021,6 +1276,7 @@ fn parse_unary(
             let pos = eat_token(input, Token::Bang);
             let mut args = StaticVec::new();
             let expr = parse_primary(input, state, lib, settings.level_up())?;
+            let end = expr.end_position();
             args.push(expr);
 
             let op = "!";
@@ -1036,18 +1292,13 @@ fn parse_unary(
                     ..Default::default()
                 }),
                 pos,
+                end,
             ))
         }
         // | ...
         #[cfg(not(feature = "no_function"))]
         Token::Pipe | Token::Or if settings.allow_anonymous_fn => {
-            let mut new_state = ParseState::new(
-                state.engine,
-                #[cfg(not(feature = "unchecked"))]
-                state.max_function_expr_depth,
-                #[cfg(not(feature = "unchecked"))]
-                state.max_function_expr_depth,
-            );
+            let mut new_state = state.new_for_function();
 
             let settings = ParseSettings {
                 allow_if_expr: true,
@@ -1056,16 +1307,42 @@ fn parse_unary(
                 is_global: false,
                 is_function_scope: true,
                 is_breakable: false,
+                is_catch: false,
                 level: 0,
                 pos: *token_pos,
             };
 
-            let (expr, func) = parse_anon_fn(input, &mut new_state, lib, settings)?;
+            #[cfg(not(feature = "no_closure"))]
+            {
+                new_state.is_closure = true;
+                new_state.outer_vars = state.outer_vars.clone();
+                new_state.outer_vars.extend(
+                    state
+                        .stack
+                        .iter()
+                        .map(|(name, _, typ)| (name.clone(), typ.clone())),
+                );
+            }
+
+            let result = parse_anon_fn(input, &mut new_state, lib, settings);
+            state.append_recorded(&mut new_state);
+            let (expr, func) = result?;
 
+            // Captured variables are curried in reverse order of first access
             #[cfg(not(feature = "no_closure"))]
-            new_state.externals.iter().for_each(|(closure, pos)| {
-                state.access_var(closure, *pos);
-            });
+            let expr = {
+                let externals = new_state
+                    .externals
+                    .iter()
+                    .rev()
+                    .map(|(name, pos)| {
+                        let index = state.access_var(name, *pos);
+                        (Ident::new(name.clone(), *pos), index)
+                    })
+                    .collect();
+
+                make_curry_from_externals(expr, externals, settings.pos)
+            };
 
             // Qualifiers (none) + function name + number of arguments.
             let hash = calc_script_fn_hash(empty(), &func.name, func.params.len());
@@ -1090,9 +1367,10 @@ fn make_assignment_stmt<'a>(
 ) -> Result<Stmt, ParseError> {
     match &lhs {
         // var (non-indexed) = rhs
-        Expr::Variable(x) if x.0.is_none() => {
-            Ok(Stmt::Assignment(Box::new((lhs, fn_name.into(), rhs)), pos))
-        }
+        Expr::Variable(x) if x.0.is_none() => Ok(Stmt::Assignment(
+            Box::new((lhs, fn_name.into(), rhs, None)),
+            pos,
+        )),
         // var (indexed) = rhs
         Expr::Variable(x) => {
             let (
@@ -1104,9 +1382,20 @@ fn make_assignment_stmt<'a>(
                     pos: name_pos,
                 },
             ) = x.as_ref();
-            match state.stack[(state.stack.len() - index.unwrap().get())].1 {
+            match state.entry_type(index.unwrap()) {
                 ScopeEntryType::Normal => {
-                    Ok(Stmt::Assignment(Box::new((lhs, fn_name.into(), rhs)), pos))
+                    let var_type = state.var_type(index.unwrap()).cloned();
+
+                    if let Some(ref typ) = var_type {
+                        if fn_name.is_empty() {
+                            check_static_type(state, &rhs, typ)?;
+                        }
+                    }
+
+                    Ok(Stmt::Assignment(
+                        Box::new((lhs, fn_name.into(), rhs, var_type)),
+                        pos,
+                    ))
                 }
                 // Constant values cannot be assigned to
                 ScopeEntryType::Constant => {
@@ -1115,11 +1404,12 @@ fn make_assignment_stmt<'a>(
             }
         }
         // xxx[???] = rhs, xxx.??? = rhs
-        Expr::Index(x, _) | Expr::Dot(x, _) => match &x.lhs {
+        Expr::Index(x, _, _) | Expr::Dot(x, _) => match &x.lhs {
             // var[???] (non-indexed) = rhs, var.??? (non-indexed) = rhs
-            Expr::Variable(x) if x.0.is_none() => {
-                Ok(Stmt::Assignment(Box::new((lhs, fn_name.into(), rhs)), pos))
-            }
+            Expr::Variable(x) if x.0.is_none() => Ok(Stmt::Assignment(
+                Box::new((lhs, fn_name.into(), rhs, None)),
+                pos,
+            )),
             // var[???] (indexed) = rhs, var.??? (indexed) = rhs
             Expr::Variable(x) => {
                 let (
@@ -1131,10 +1421,11 @@ fn make_assignment_stmt<'a>(
                         pos: name_pos,
                     },
                 ) = x.as_ref();
-                match state.stack[(state.stack.len() - index.unwrap().get())].1 {
-                    ScopeEntryType::Normal => {
-                        Ok(Stmt::Assignment(Box::new((lhs, fn_name.into(), rhs)), pos))
-                    }
+                match state.entry_type(index.unwrap()) {
+                    ScopeEntryType::Normal => Ok(Stmt::Assignment(
+                        Box::new((lhs, fn_name.into(), rhs, None)),
+                        pos,
+                    )),
                     // Constant values cannot be assigned to
                     ScopeEntryType::Constant => {
                         Err(PERR::AssignmentToConstant(name.clone()).into_err(*name_pos))
@@ -1170,7 +1461,7 @@ fn parse_op_assignment_stmt(
     settings.pos = *token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let op = match token {
         Token::Equals => "".into(),
@@ -1195,15 +1486,31 @@ fn parse_op_assignment_stmt(
     make_assignment_stmt(op, state, lhs, rhs, pos)
 }
 
+/// Record the properties in a dot chain with the role `IdentRole::Property`,
+/// as they have been parsed as variables.
+#[cfg(not(feature = "no_object"))]
+fn record_properties(state: &mut ParseState, expr: &Expr) {
+    match expr {
+        _ if state.idents.is_none() => (),
+        Expr::Property(x) => state.record_ident(x.1.pos, IdentRole::Property),
+        Expr::Dot(x, _) | Expr::Index(x, _, _) => {
+            record_properties(state, &x.lhs);
+            record_properties(state, &x.rhs);
+        }
+        _ => (),
+    }
+}
+
 /// Make a dot expression.
 #[cfg(not(feature = "no_object"))]
 fn make_dot_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseError> {
     Ok(match (lhs, rhs) {
         // idx_lhs[idx_expr].rhs
         // Attach dot chain to the bottom level of indexing chain
-        (Expr::Index(mut x, pos), rhs) => {
+        (Expr::Index(mut x, pos, _), rhs) => {
             x.rhs = make_dot_expr(x.rhs, rhs, op_pos)?;
-            Expr::Index(x, pos)
+            let end = x.rhs.end_position();
+            Expr::Index(x, pos, end)
         }
         // lhs.id
         (lhs, Expr::Variable(x)) if x.1.is_none() => {
@@ -1234,18 +1541,19 @@ fn make_dot_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseEr
             Expr::Dot(Box::new(BinaryExpr { lhs, rhs }), op_pos)
         }
         // lhs.idx_lhs[idx_rhs]
-        (lhs, Expr::Index(x, pos)) => {
+        (lhs, Expr::Index(x, pos, end)) => {
             let rhs = Expr::Index(
                 Box::new(BinaryExpr {
                     lhs: x.lhs.into_property(),
                     rhs: x.rhs,
                 }),
                 pos,
+                end,
             );
             Expr::Dot(Box::new(BinaryExpr { lhs, rhs }), op_pos)
         }
         // lhs.Fn() or lhs.eval()
-        (_, Expr::FnCall(x, pos))
+        (_, Expr::FnCall(x, pos, _))
             if x.args.len() == 0 && [KEYWORD_FN_PTR, KEYWORD_EVAL].contains(&x.name.as_ref()) =>
         {
             return Err(PERR::BadInput(LexError::ImproperSymbol(format!(
@@ -1255,14 +1563,14 @@ fn make_dot_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseEr
             .into_err(pos));
         }
         // lhs.func!(...)
-        (_, Expr::FnCall(x, pos)) if x.capture => {
+        (_, Expr::FnCall(x, pos, _)) if x.capture => {
             return Err(PERR::MalformedCapture(
                 "method-call style does not support capturing".into(),
             )
             .into_err(pos));
         }
         // lhs.func(...)
-        (lhs, func @ Expr::FnCall(_, _)) => {
+        (lhs, func @ Expr::FnCall(_, _, _)) => {
             Expr::Dot(Box::new(BinaryExpr { lhs, rhs: func }), op_pos)
         }
         // lhs.rhs
@@ -1273,13 +1581,13 @@ fn make_dot_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseEr
 /// Make an 'in' expression.
 fn make_in_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseError> {
     match (&lhs, &rhs) {
-        (_, x @ Expr::IntegerConstant(_, _))
+        (_, x @ Expr::IntegerConstant(_, _, _))
         | (_, x @ Expr::And(_, _))
         | (_, x @ Expr::Or(_, _))
         | (_, x @ Expr::In(_, _))
         | (_, x @ Expr::True(_))
         | (_, x @ Expr::False(_))
-        | (_, x @ Expr::Unit(_)) => {
+        | (_, x @ Expr::Unit(_, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression expects a string, array or object map".into(),
             )
@@ -1287,7 +1595,7 @@ fn make_in_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseErr
         }
 
         #[cfg(not(feature = "no_float"))]
-        (_, x @ Expr::FloatConstant(_, _)) => {
+        (_, x @ Expr::FloatConstant(_, _, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression expects a string, array or object map".into(),
             )
@@ -1295,19 +1603,19 @@ fn make_in_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseErr
         }
 
         // "xxx" in "xxxx", 'x' in "xxxx" - OK!
-        (Expr::StringConstant(_), Expr::StringConstant(_))
-        | (Expr::CharConstant(_, _), Expr::StringConstant(_)) => (),
+        (Expr::StringConstant(_, _), Expr::StringConstant(_, _))
+        | (Expr::CharConstant(_, _, _), Expr::StringConstant(_, _)) => (),
 
         // 123.456 in "xxxx"
         #[cfg(not(feature = "no_float"))]
-        (x @ Expr::FloatConstant(_, _), Expr::StringConstant(_)) => {
+        (x @ Expr::FloatConstant(_, _, _), Expr::StringConstant(_, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for a string expects a string, not a float".into(),
             )
             .into_err(x.position()))
         }
         // 123 in "xxxx"
-        (x @ Expr::IntegerConstant(_, _), Expr::StringConstant(_)) => {
+        (x @ Expr::IntegerConstant(_, _, _), Expr::StringConstant(_, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for a string expects a string, not a number".into(),
             )
@@ -1315,32 +1623,32 @@ fn make_in_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseErr
         }
         // (??? && ???) in "xxxx", (??? || ???) in "xxxx", (??? in ???) in "xxxx",
         //  true in "xxxx", false in "xxxx"
-        (x @ Expr::And(_, _), Expr::StringConstant(_))
-        | (x @ Expr::Or(_, _), Expr::StringConstant(_))
-        | (x @ Expr::In(_, _), Expr::StringConstant(_))
-        | (x @ Expr::True(_), Expr::StringConstant(_))
-        | (x @ Expr::False(_), Expr::StringConstant(_)) => {
+        (x @ Expr::And(_, _), Expr::StringConstant(_, _))
+        | (x @ Expr::Or(_, _), Expr::StringConstant(_, _))
+        | (x @ Expr::In(_, _), Expr::StringConstant(_, _))
+        | (x @ Expr::True(_), Expr::StringConstant(_, _))
+        | (x @ Expr::False(_), Expr::StringConstant(_, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for a string expects a string, not a boolean".into(),
             )
             .into_err(x.position()))
         }
         // [???, ???, ???] in "xxxx"
-        (x @ Expr::Array(_, _), Expr::StringConstant(_)) => {
+        (x @ Expr::Array(_, _, _), Expr::StringConstant(_, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for a string expects a string, not an array".into(),
             )
             .into_err(x.position()))
         }
         // #{...} in "xxxx"
-        (x @ Expr::Map(_, _), Expr::StringConstant(_)) => {
+        (x @ Expr::Map(_, _, _), Expr::StringConstant(_, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for a string expects a string, not an object map".into(),
             )
             .into_err(x.position()))
         }
         // () in "xxxx"
-        (x @ Expr::Unit(_), Expr::StringConstant(_)) => {
+        (x @ Expr::Unit(_, _), Expr::StringConstant(_, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for a string expects a string, not ()".into(),
             )
@@ -1348,19 +1656,19 @@ fn make_in_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseErr
         }
 
         // "xxx" in #{...}, 'x' in #{...} - OK!
-        (Expr::StringConstant(_), Expr::Map(_, _))
-        | (Expr::CharConstant(_, _), Expr::Map(_, _)) => (),
+        (Expr::StringConstant(_, _), Expr::Map(_, _, _))
+        | (Expr::CharConstant(_, _, _), Expr::Map(_, _, _)) => (),
 
         // 123.456 in #{...}
         #[cfg(not(feature = "no_float"))]
-        (x @ Expr::FloatConstant(_, _), Expr::Map(_, _)) => {
+        (x @ Expr::FloatConstant(_, _, _), Expr::Map(_, _, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for an object map expects a string, not a float".into(),
             )
             .into_err(x.position()))
         }
         // 123 in #{...}
-        (x @ Expr::IntegerConstant(_, _), Expr::Map(_, _)) => {
+        (x @ Expr::IntegerConstant(_, _, _), Expr::Map(_, _, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for an object map expects a string, not a number".into(),
             )
@@ -1368,32 +1676,32 @@ fn make_in_expr(lhs: Expr, rhs: Expr, op_pos: Position) -> Result<Expr, ParseErr
         }
         // (??? && ???) in #{...}, (??? || ???) in #{...}, (??? in ???) in #{...},
         // true in #{...}, false in #{...}
-        (x @ Expr::And(_, _), Expr::Map(_, _))
-        | (x @ Expr::Or(_, _), Expr::Map(_, _))
-        | (x @ Expr::In(_, _), Expr::Map(_, _))
-        | (x @ Expr::True(_), Expr::Map(_, _))
-        | (x @ Expr::False(_), Expr::Map(_, _)) => {
+        (x @ Expr::And(_, _), Expr::Map(_, _, _))
+        | (x @ Expr::Or(_, _), Expr::Map(_, _, _))
+        | (x @ Expr::In(_, _), Expr::Map(_, _, _))
+        | (x @ Expr::True(_), Expr::Map(_, _, _))
+        | (x @ Expr::False(_), Expr::Map(_, _, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for an object map expects a string, not a boolean".into(),
             )
             .into_err(x.position()))
         }
         // [???, ???, ???] in #{..}
-        (x @ Expr::Array(_, _), Expr::Map(_, _)) => {
+        (x @ Expr::Array(_, _, _), Expr::Map(_, _, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for an object map expects a string, not an array".into(),
             )
             .into_err(x.position()))
         }
         // #{...} in #{..}
-        (x @ Expr::Map(_, _), Expr::Map(_, _)) => {
+        (x @ Expr::Map(_, _, _), Expr::Map(_, _, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for an object map expects a string, not an object map".into(),
             )
             .into_err(x.position()))
         }
         // () in #{...}
-        (x @ Expr::Unit(_), Expr::Map(_, _)) => {
+        (x @ Expr::Unit(_, _), Expr::Map(_, _, _)) => {
             return Err(PERR::MalformedInExpr(
                 "'in' expression for an object map expects a string, not ()".into(),
             )
@@ -1418,7 +1726,7 @@ fn parse_binary_op(
     settings.pos = lhs.position();
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let mut root = lhs;
 
@@ -1481,7 +1789,7 @@ fn parse_binary_op(
         settings.pos = pos;
 
         #[cfg(not(feature = "unchecked"))]
-        settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+        settings.ensure_level_within_max_limit(state)?;
 
         let cmp_def = Some(false);
         let op = op_token.syntax();
@@ -1494,6 +1802,7 @@ fn parse_binary_op(
             ..Default::default()
         };
 
+        let end = rhs.end_position();
         let mut args = StaticVec::new();
         args.push(root);
         args.push(rhs);
@@ -1516,6 +1825,7 @@ fn parse_binary_op(
                     ..op_base
                 }),
                 pos,
+                end,
             ),
 
             // '!=' defaults to true when passed invalid operands
@@ -1527,6 +1837,7 @@ fn parse_binary_op(
                     ..op_base
                 }),
                 pos,
+                end,
             ),
 
             // Comparison operators default to false when passed invalid operands
@@ -1542,6 +1853,7 @@ fn parse_binary_op(
                     ..op_base
                 }),
                 pos,
+                end,
             ),
 
             Token::Or => {
@@ -1576,7 +1888,9 @@ fn parse_binary_op(
             Token::Period => {
                 let rhs = args.pop().unwrap();
                 let current_lhs = args.pop().unwrap();
-                make_dot_expr(current_lhs, rhs, pos)?
+                let expr = make_dot_expr(current_lhs, rhs, pos)?;
+                record_properties(state, &expr);
+                expr
             }
 
             Token::Custom(s) if state.engine.custom_keywords.contains_key(&s) => {
@@ -1589,6 +1903,7 @@ fn parse_binary_op(
                         ..op_base
                     }),
                     pos,
+                    end,
                 )
             }
 
@@ -1614,7 +1929,7 @@ fn parse_custom_syntax(
         delta if delta > 0 => {
             state.stack.resize(
                 state.stack.len() + delta as usize,
-                ("".to_string(), ScopeEntryType::Normal),
+                ("".to_string(), ScopeEntryType::Normal, None),
             );
         }
         delta if delta < 0 && state.stack.len() <= delta.abs() as usize => state.stack.clear(),
@@ -1661,8 +1976,12 @@ fn parse_custom_syntax(
                 segments.push(MARKER_EXPR.into());
             }
             MARKER_BLOCK => match parse_block(input, state, lib, settings)? {
-                Stmt::Block(statements, pos) => {
-                    exprs.push(Expr::Stmt(Box::new(statements.into()), pos));
+                Stmt::Block(statements, span) => {
+                    exprs.push(Expr::Stmt(
+                        Box::new(statements.into()),
+                        span.start(),
+                        span.end(),
+                    ));
                     segments.push(MARKER_BLOCK.into());
                 }
                 _ => unreachable!(),
@@ -1689,6 +2008,7 @@ fn parse_custom_syntax(
             func: syntax.func.clone(),
         }),
         pos,
+        input.last_end(),
     ))
 }
 
@@ -1702,7 +2022,7 @@ fn parse_expr(
     settings.pos = input.peek().unwrap().1;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     // Check if it is a custom syntax.
     if !state.engine.custom_syntax.is_empty() {
@@ -1780,7 +2100,7 @@ fn parse_if(
     settings.pos = token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     // if guard { if_body }
     ensure_not_statement_expr(input, "a boolean")?;
@@ -1820,7 +2140,7 @@ fn parse_while(
     settings.pos = token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     // while guard { body }
     ensure_not_statement_expr(input, "a boolean")?;
@@ -1845,7 +2165,7 @@ fn parse_loop(
     settings.pos = token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     // loop { body }
     settings.is_breakable = true;
@@ -1866,12 +2186,12 @@ fn parse_for(
     settings.pos = token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     // for name ...
-    let name = match input.next().unwrap() {
+    let (name, name_pos) = match input.next().unwrap() {
         // Variable name
-        (Token::Identifier(s), _) => s,
+        (Token::Identifier(s), pos) => (s, pos),
         // Reserved keyword
         (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
             return Err(PERR::Reserved(s).into_err(pos));
@@ -1882,6 +2202,8 @@ fn parse_for(
         (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
     };
 
+    state.record_ident(name_pos, IdentRole::Declaration);
+
     // for name in ...
     match input.next().unwrap() {
         (Token::In, _) => (),
@@ -1899,14 +2221,20 @@ fn parse_for(
     let expr = parse_expr(input, state, lib, settings.level_up())?;
 
     let prev_stack_len = state.stack.len();
-    state.stack.push((name.clone(), ScopeEntryType::Normal));
+    state
+        .stack
+        .push((name.clone(), ScopeEntryType::Normal, None));
 
     settings.is_breakable = true;
     let body = parse_block(input, state, lib, settings.level_up())?;
 
     state.stack.truncate(prev_stack_len);
 
-    Ok(Stmt::For(expr, Box::new((name, body)), token_pos))
+    Ok(Stmt::For(
+        expr,
+        Box::new((Ident::new(name, name_pos), body)),
+        token_pos,
+    ))
 }
 
 /// Parse a variable definition statement.
@@ -1922,7 +2250,7 @@ fn parse_let(
     settings.pos = token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     // let name ...
     let (name, pos) = match input.next().unwrap() {
@@ -1934,29 +2262,50 @@ fn parse_let(
         (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
     };
 
+    state.record_ident(pos, IdentRole::Declaration);
+
+    // let name: type ...
+    let typ = if match_token(input, Token::Colon).0 {
+        Some(parse_type_annotation(input, state)?)
+    } else {
+        None
+    };
+
     // let name = ...
     let init_value = if match_token(input, Token::Equals).0 {
         // let name = expr
-        Some(parse_expr(input, state, lib, settings.level_up())?)
+        let expr = parse_expr(input, state, lib, settings.level_up())?;
+
+        if let Some(ref typ) = typ {
+            check_static_type(state, &expr, &typ.name)?;
+        }
+
+        Some(expr)
     } else {
         None
     };
 
+    let var_type_name = typ.as_ref().map(|typ| typ.name.clone());
+
     match var_type {
         // let name = expr
         ScopeEntryType::Normal => {
-            state.stack.push((name.clone(), ScopeEntryType::Normal));
+            state
+                .stack
+                .push((name.clone(), ScopeEntryType::Normal, var_type_name));
             Ok(Stmt::Let(
-                Box::new(Ident::new(name, pos)),
+                Box::new((Ident::new(name, pos), typ)),
                 init_value,
                 token_pos,
             ))
         }
         // const name = { expr:constant }
         ScopeEntryType::Constant => {
-            state.stack.push((name.clone(), ScopeEntryType::Constant));
+            state
+                .stack
+                .push((name.clone(), ScopeEntryType::Constant, var_type_name));
             Ok(Stmt::Const(
-                Box::new(Ident::new(name, pos)),
+                Box::new((Ident::new(name, pos), typ)),
                 init_value,
                 token_pos,
             ))
@@ -1964,6 +2313,68 @@ fn parse_let(
     }
 }
 
+/// Parse a type annotation after a `:` or `->`.
+fn parse_type_annotation(
+    input: &mut TokenStream,
+    state: &ParseState,
+) -> Result<IdentX, ParseError> {
+    match input.next().unwrap() {
+        (Token::Identifier(s), pos) | (Token::Reserved(s), pos)
+            if state.engine.is_known_type_name(&s) =>
+        {
+            Ok(IdentX::new(s, pos))
+        }
+        (Token::Identifier(s), pos) => Err(PERR::UnknownType(s).into_err(pos)),
+        (Token::LexError(err), pos) => Err(err.into_err(pos)),
+        (_, pos) => Err(PERR::TypeExpected.into_err(pos)),
+    }
+}
+
+/// Get the type name of a literal expression.
+fn literal_type_name<'a>(engine: &'a Engine, expr: &Expr) -> Option<&'a str> {
+    Some(match expr {
+        Expr::IntegerConstant(_, _, _) => engine.map_type_name(type_name::<INT>()),
+        #[cfg(not(feature = "no_float"))]
+        Expr::FloatConstant(_, _, _) => engine.map_type_name(type_name::<FLOAT>()),
+        Expr::CharConstant(_, _, _) => "char",
+        Expr::StringConstant(_, _) => "string",
+        Expr::FnPointer(_, _) => "Fn",
+        Expr::True(_) | Expr::False(_) => "bool",
+        Expr::Unit(_, _) => "()",
+        #[cfg(not(feature = "no_index"))]
+        Expr::Array(_, _, _) => "array",
+        #[cfg(not(feature = "no_object"))]
+        Expr::Map(_, _, _) => "map",
+        _ => return None,
+    })
+}
+
+/// Check an expression against a type annotation, if the type of the expression
+/// is known at compile time (i.e. it is a literal or a variable with a type annotation).
+fn check_static_type(state: &ParseState, expr: &Expr, typ: &str) -> Result<(), ParseError> {
+    let engine = state.engine;
+
+    let actual = match expr {
+        Expr::Variable(x) => match x.0.and_then(|index| state.var_type(index)) {
+            Some(var_type) => engine.map_annotated_type_name(var_type),
+            None => return Ok(()),
+        },
+        expr => match literal_type_name(engine, expr) {
+            Some(actual) => actual,
+            None => return Ok(()),
+        },
+    };
+
+    if actual == engine.map_annotated_type_name(typ) {
+        Ok(())
+    } else {
+        Err(
+            PERR::MismatchedType(actual.into(), engine.map_annotated_type_name(typ).into())
+                .into_err(expr.position()),
+        )
+    }
+}
+
 /// Parse an import statement.
 #[cfg(not(feature = "no_module"))]
 fn parse_import(
@@ -1977,7 +2388,7 @@ fn parse_import(
     settings.pos = token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     // import expr ...
     let expr = parse_expr(input, state, lib, settings.level_up())?;
@@ -1988,7 +2399,7 @@ fn parse_import(
     }
 
     // import expr as name ...
-    let (name, _) = match input.next().unwrap() {
+    let (name, name_pos) = match input.next().unwrap() {
         (Token::Identifier(s), pos) => (s, pos),
         (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
             return Err(PERR::Reserved(s).into_err(pos));
@@ -1997,6 +2408,7 @@ fn parse_import(
         (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
     };
 
+    state.record_ident(name_pos, IdentRole::Module);
     state.modules.push(name.clone());
 
     Ok(Stmt::Import(
@@ -2010,7 +2422,7 @@ fn parse_import(
 #[cfg(not(feature = "no_module"))]
 fn parse_export(
     input: &mut TokenStream,
-    _state: &mut ParseState,
+    state: &mut ParseState,
     _lib: &mut FunctionsLib,
     mut settings: ParseSettings,
 ) -> Result<Stmt, ParseError> {
@@ -2018,7 +2430,7 @@ fn parse_export(
     settings.pos = token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(_state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let mut exports = Vec::new();
 
@@ -2032,9 +2444,14 @@ fn parse_export(
             (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
         };
 
+        state.record_ident(id_pos, IdentRole::Variable);
+
         let rename = if match_token(input, Token::As).0 {
             match input.next().unwrap() {
-                (Token::Identifier(s), pos) => Some(Ident::new(s.clone(), pos)),
+                (Token::Identifier(s), pos) => {
+                    state.record_ident(pos, IdentRole::Declaration);
+                    Some(Ident::new(s.clone(), pos))
+                }
                 (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
                     return Err(PERR::Reserved(s).into_err(pos));
                 }
@@ -2099,7 +2516,7 @@ fn parse_block(
     };
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let mut statements = Vec::new();
     let prev_stack_len = state.stack.len();
@@ -2107,13 +2524,35 @@ fn parse_block(
     #[cfg(not(feature = "no_module"))]
     let prev_mods_len = state.modules.len();
 
-    while !match_token(input, Token::RightBrace).0 {
+    let end = loop {
+        if let (true, pos) = match_token(input, Token::RightBrace) {
+            break pos;
+        }
+
         // Parse statements inside the block
         settings.is_global = false;
 
-        let stmt = match parse_stmt(input, state, lib, settings.level_up())? {
-            Some(s) => s,
-            None => continue,
+        let start = match input.peek().unwrap() {
+            // { ... <EOF>
+            (Token::EOF, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::RightBrace.into(),
+                    "to end this statement block".into(),
+                )
+                .into_err(*pos))
+            }
+            (_, pos) => *pos,
+        };
+
+        let stmt = match parse_stmt(input, state, lib, settings.level_up()) {
+            Ok(Some(s)) => s,
+            Ok(None) => continue,
+            Err(err) => {
+                if let Some(pos) = state.recover(input, err, start)? {
+                    break pos;
+                }
+                continue;
+            }
         };
 
         // See if it needs a terminating semicolon
@@ -2123,10 +2562,7 @@ fn parse_block(
 
         match input.peek().unwrap() {
             // { ... stmt }
-            (Token::RightBrace, _) => {
-                eat_token(input, Token::RightBrace);
-                break;
-            }
+            (Token::RightBrace, _) => break eat_token(input, Token::RightBrace),
             // { ... stmt;
             (Token::SemiColon, _) if need_semicolon => {
                 eat_token(input, Token::SemiColon);
@@ -2135,26 +2571,34 @@ fn parse_block(
             (Token::SemiColon, _) if !need_semicolon => (),
             // { ... { stmt } ???
             (_, _) if !need_semicolon => (),
+            // { ... stmt <EOF> - missing closing brace is reported next
+            (Token::EOF, _) => (),
             // { ... stmt <error>
-            (Token::LexError(err), pos) => return Err(err.into_err(*pos)),
+            (Token::LexError(err), pos) => {
+                let err = err.clone().into_err(*pos);
+                input.next().unwrap();
+                state.record_error(err)?;
+            }
             // { ... stmt ???
             (_, pos) => {
                 // Semicolons are not optional between statements
-                return Err(PERR::MissingToken(
-                    Token::SemiColon.into(),
-                    "to terminate this statement".into(),
-                )
-                .into_err(*pos));
+                state.record_error(
+                    PERR::MissingToken(
+                        Token::SemiColon.into(),
+                        "to terminate this statement".into(),
+                    )
+                    .into_err(*pos),
+                )?;
             }
         }
-    }
+    };
 
     state.stack.truncate(prev_stack_len);
 
     #[cfg(not(feature = "no_module"))]
     state.modules.truncate(prev_mods_len);
 
-    Ok(Stmt::Block(statements, settings.pos))
+    Ok(Stmt::Block(statements, Span::new(settings.pos, end)))
 }
 
 /// Parse an expression as a statement.
@@ -2167,7 +2611,7 @@ fn parse_expr_stmt(
     settings.pos = input.peek().unwrap().1;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let expr = parse_expr(input, state, lib, settings.level_up())?;
     let stmt = parse_op_assignment_stmt(input, state, lib, expr, settings.level_up())?;
@@ -2183,6 +2627,21 @@ fn parse_stmt(
 ) -> Result<Option<Stmt>, ParseError> {
     use ScopeEntryType::{Constant, Normal};
 
+    // Doc comments can only precede function definitions
+    #[cfg(not(feature = "no_function"))]
+    let comments = {
+        let mut comments = StaticVec::<String>::new();
+
+        while let (Token::Comment(_), _) = input.peek().unwrap() {
+            match input.next().unwrap() {
+                (Token::Comment(comment), _) => comments.push(comment),
+                _ => unreachable!(),
+            }
+        }
+
+        comments
+    };
+
     let (token, token_pos) = match input.peek().unwrap() {
         (Token::EOF, pos) => return Ok(Some(Stmt::Noop(*pos))),
         x => x,
@@ -2190,7 +2649,7 @@ fn parse_stmt(
     settings.pos = *token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     match token {
         // Semicolon - empty statement
@@ -2213,13 +2672,7 @@ fn parse_stmt(
 
             match input.next().unwrap() {
                 (Token::Fn, pos) => {
-                    let mut new_state = ParseState::new(
-                        state.engine,
-                        #[cfg(not(feature = "unchecked"))]
-                        state.max_function_expr_depth,
-                        #[cfg(not(feature = "unchecked"))]
-                        state.max_function_expr_depth,
-                    );
+                    let mut new_state = state.new_for_function();
 
                     let settings = ParseSettings {
                         allow_if_expr: true,
@@ -2228,16 +2681,18 @@ fn parse_stmt(
                         is_global: false,
                         is_function_scope: true,
                         is_breakable: false,
+                        is_catch: false,
                         level: 0,
                         pos: pos,
                     };
 
-                    let func = parse_fn(input, &mut new_state, lib, access, settings)?;
-
-                    // Qualifiers (none) + function name + number of arguments.
-                    let hash = calc_script_fn_hash(empty(), &func.name, func.params.len());
+                    let result = parse_fn(input, &mut new_state, lib, access, comments, settings);
+                    state.append_recorded(&mut new_state);
+                    let func = result?;
 
-                    lib.insert(hash, func);
+                    // Functions with the same name and number of parameters may be overloaded
+                    // by parameter types.
+                    lib.insert(func.calc_hash(), func);
 
                     Ok(None)
                 }
@@ -2280,22 +2735,34 @@ fn parse_stmt(
                 })
                 .unwrap();
 
+            // `throw` without a value inside a catch block re-throws the caught error
+            let empty_return_type = match return_type {
+                ReturnType::Exception if settings.is_catch => ReturnType::Rethrow,
+                return_type => return_type,
+            };
+
             match input.peek().unwrap() {
                 // `return`/`throw` at <EOF>
                 (Token::EOF, pos) => Ok(Some(Stmt::ReturnWithVal(
-                    (return_type, token_pos),
+                    (empty_return_type, token_pos),
                     None,
                     *pos,
                 ))),
                 // `return;` or `throw;`
                 (Token::SemiColon, _) => Ok(Some(Stmt::ReturnWithVal(
-                    (return_type, token_pos),
+                    (empty_return_type, token_pos),
                     None,
                     settings.pos,
                 ))),
                 // `return` or `throw` with expression
                 (_, _) => {
                     let expr = parse_expr(input, state, lib, settings.level_up())?;
+
+                    #[cfg(not(feature = "no_function"))]
+                    if let (ReturnType::Return, Some(typ)) = (&return_type, &state.fn_return_type) {
+                        check_static_type(state, &expr, typ)?;
+                    }
+
                     let pos = expr.position();
                     Ok(Some(Stmt::ReturnWithVal(
                         (return_type, token_pos),
@@ -2336,7 +2803,7 @@ fn parse_try_catch(
     settings.pos = token_pos;
 
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     // try { body }
     let body = parse_block(input, state, lib, settings.level_up())?;
@@ -2352,12 +2819,31 @@ fn parse_try_catch(
     }
 
     // try { body } catch (
-    let var_def = if match_token(input, Token::LeftParen).0 {
+    let (var_def, stack_var_def) = if match_token(input, Token::LeftParen).0 {
         let id = match input.next().unwrap() {
-            (Token::Identifier(s), pos) => Ident::new(s, pos),
+            (Token::Identifier(s), pos) => {
+                state.record_ident(pos, IdentRole::Declaration);
+                Ident::new(s, pos)
+            }
             (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
         };
 
+        // try { body } catch ( var, stack
+        #[cfg(not(feature = "no_index"))]
+        let stack_id = if match_token(input, Token::Comma).0 {
+            match input.next().unwrap() {
+                (Token::Identifier(s), pos) => {
+                    state.record_ident(pos, IdentRole::Declaration);
+                    Some(Ident::new(s, pos))
+                }
+                (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
+            }
+        } else {
+            None
+        };
+        #[cfg(feature = "no_index")]
+        let stack_id = None;
+
         let (matched, pos) = match_token(input, Token::RightParen);
 
         if !matched {
@@ -2368,16 +2854,42 @@ fn parse_try_catch(
             .into_err(pos));
         }
 
-        Some(id)
+        (Some(id), stack_id)
     } else {
-        None
+        (None, None)
     };
 
-    // try { body } catch ( var ) { catch_block }
-    let catch_body = parse_block(input, state, lib, settings.level_up())?;
+    // The catch variables are in scope within the catch block
+    let prev_stack_len = state.stack.len();
+
+    var_def
+        .iter()
+        .chain(stack_var_def.iter())
+        .for_each(|Ident { name, .. }| {
+            state
+                .stack
+                .push((name.clone(), ScopeEntryType::Normal, None))
+        });
+
+    // try { body } catch ( var, stack ) { catch_block }
+    // `throw;` inside the catch block re-throws the caught error
+    let catch_settings = ParseSettings {
+        is_catch: true,
+        ..settings.level_up()
+    };
+    let catch_body = parse_block(input, state, lib, catch_settings)?;
+
+    state.stack.truncate(prev_stack_len);
+
+    // try { body } catch ( var, stack ) { catch_block } finally { finally_block }
+    let finally_body = if match_token(input, Token::Finally).0 {
+        Some(parse_block(input, state, lib, settings.level_up())?)
+    } else {
+        None
+    };
 
     Ok(Stmt::TryCatch(
-        Box::new((body, var_def, catch_body)),
+        Box::new((body, var_def, stack_var_def, catch_body, finally_body)),
         token_pos,
         catch_pos,
     ))
@@ -2390,10 +2902,11 @@ fn parse_fn(
     state: &mut ParseState,
     lib: &mut FunctionsLib,
     access: FnAccess,
+    comments: StaticVec<String>,
     mut settings: ParseSettings,
 ) -> Result<ScriptFnDef, ParseError> {
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let (token, pos) = input.next().unwrap();
 
@@ -2404,6 +2917,8 @@ fn parse_fn(
             _ => PERR::FnMissingName.into_err(pos),
         })?;
 
+    state.record_ident(pos, IdentRole::Function);
+
     match input.peek().unwrap() {
         (Token::LeftParen, _) => eat_token(input, Token::LeftParen),
         (_, pos) => return Err(PERR::FnMissingParams(name).into_err(*pos)),
@@ -2418,8 +2933,18 @@ fn parse_fn(
             match input.next().unwrap() {
                 (Token::RightParen, _) => break,
                 (Token::Identifier(s), pos) => {
-                    state.stack.push((s.clone(), ScopeEntryType::Normal));
-                    params.push((s, pos))
+                    state.record_ident(pos, IdentRole::Declaration);
+
+                    // param: type
+                    let typ = if match_token(input, Token::Colon).0 {
+                        Some(parse_type_annotation(input, state)?.name)
+                    } else {
+                        None
+                    };
+                    state
+                        .stack
+                        .push((s.clone(), ScopeEntryType::Normal, typ.clone()));
+                    params.push((s, pos, typ))
                 }
                 (Token::LexError(err), pos) => return Err(err.into_err(pos)),
                 (_, pos) => {
@@ -2446,27 +2971,66 @@ fn parse_fn(
     params
         .iter()
         .enumerate()
-        .try_for_each(|(i, (p1, _))| {
+        .try_for_each(|(i, (p1, _, _))| {
             params
                 .iter()
                 .skip(i + 1)
-                .find(|(p2, _)| p2 == p1)
-                .map_or_else(|| Ok(()), |(p2, pos)| Err((p2, *pos)))
+                .find(|(p2, _, _)| p2 == p1)
+                .map_or_else(|| Ok(()), |(p2, pos, _)| Err((p2, *pos)))
         })
         .map_err(|(p, pos)| {
             PERR::FnDuplicatedParam(name.to_string(), p.to_string()).into_err(pos)
         })?;
 
+    // fn name(...) -> type
+    let return_type = if match_token(input, Token::RightArrow).0 {
+        Some(parse_type_annotation(input, state)?.name)
+    } else {
+        None
+    };
+    state.fn_return_type = return_type.clone();
+
     // Parse function body
-    let body = match input.peek().unwrap() {
+    let mut body = match input.peek().unwrap() {
         (Token::LeftBrace, _) => {
             settings.is_breakable = false;
+            settings.is_catch = false;
             parse_block(input, state, lib, settings.level_up())?
         }
         (_, pos) => return Err(PERR::FnMissingBody(name).into_err(*pos)),
     };
 
-    let params: StaticVec<_> = params.into_iter().map(|(p, _)| p).collect();
+    // The value of the last statement is returned
+    if let (Some(typ), Stmt::Block(statements, _)) = (&return_type, &body) {
+        if let Some(Stmt::Expr(expr)) = statements.last() {
+            let engine = state.engine;
+
+            match literal_type_name(engine, expr) {
+                Some(actual) if actual != engine.map_annotated_type_name(typ) => {
+                    return Err(PERR::MismatchedType(
+                        actual.into(),
+                        engine.map_annotated_type_name(typ).into(),
+                    )
+                    .into_err(expr.position()))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let (params, param_types): (StaticVec<_>, StaticVec<_>) =
+        params.into_iter().map(|(p, _, t)| (p, t)).unzip();
+
+    // Calls to functions overloaded by parameter types are resolved on every call,
+    // so they are never turned into loops
+    if param_types.iter().all(Option::is_none) {
+        mark_tail_calls(&mut body, &name, params.len(), true);
+    }
+
+    let span = match body {
+        Stmt::Block(_, ref span) => Span::new(settings.pos, span.end()),
+        _ => unreachable!(),
+    };
 
     #[cfg(not(feature = "no_closure"))]
     let externals: HashSet<_> = state
@@ -2481,6 +3045,11 @@ fn parse_fn(
         name: name.into(),
         access,
         params,
+        param_types,
+        return_type,
+        span,
+        source: input.source(),
+        comments,
         #[cfg(not(feature = "no_closure"))]
         externals: if externals.is_empty() {
             None
@@ -2492,26 +3061,97 @@ fn parse_fn(
     })
 }
 
+/// Mark self-recursive calls in tail position within the body of a script-defined function.
+///
+/// A call is in tail position if its value is directly returned by the function, i.e. it is the
+/// last statement of the body (or of an `if` branch in tail position), or it is the value
+/// of a `return` statement not inside a `try` block (or inside a `catch` block followed by a
+/// `finally` block).
+#[cfg(not(feature = "no_function"))]
+fn mark_tail_calls(stmt: &mut Stmt, name: &str, num_params: usize, is_tail: bool) {
+    match stmt {
+        Stmt::Block(statements, _) => {
+            let last = statements.len().saturating_sub(1);
+
+            statements.iter_mut().enumerate().for_each(|(i, stmt)| {
+                mark_tail_calls(stmt, name, num_params, is_tail && i == last)
+            });
+        }
+        Stmt::IfThenElse(_, x, _) => {
+            mark_tail_calls(&mut x.0, name, num_params, is_tail);
+
+            if let Some(ref mut stmt) = x.1 {
+                mark_tail_calls(stmt, name, num_params, is_tail);
+            }
+        }
+        Stmt::While(_, body, _) | Stmt::Loop(body, _) => {
+            mark_tail_calls(body, name, num_params, false)
+        }
+        Stmt::For(_, x, _) => mark_tail_calls(&mut x.1, name, num_params, false),
+        // `try` blocks do not return values, and errors inside the `try` block must be caught,
+        // so only `return` statements inside the `catch` block are in tail position -
+        // unless there is a `finally` block, which must run before the function returns
+        Stmt::TryCatch(x, _, _) if x.4.is_none() => {
+            mark_tail_calls(&mut x.3, name, num_params, false)
+        }
+        Stmt::TryCatch(_, _, _) => (),
+        Stmt::ReturnWithVal((ReturnType::Return, _), Some(Expr::FnCall(x, _, _)), _) => {
+            mark_tail_call(x, name, num_params)
+        }
+        Stmt::Expr(Expr::FnCall(x, _, _)) if is_tail => mark_tail_call(x, name, num_params),
+        _ => (),
+    }
+}
+
+/// Unmark self-recursive calls in tail position within functions overloaded by parameter types,
+/// because the overload to call depends on the arguments of each call.
+#[cfg(not(feature = "no_function"))]
+fn unmark_overloaded_tail_calls(lib: &mut FunctionsLib) {
+    let mut counts: HashMap<u64, usize> = Default::default();
+
+    lib.values().for_each(|f| {
+        *counts
+            .entry(calc_script_fn_hash(empty(), &f.name, f.params.len()))
+            .or_default() += 1
+    });
+
+    lib.values_mut()
+        .filter(|f| counts[&calc_script_fn_hash(empty(), &f.name, f.params.len())] > 1)
+        // No function has an empty name, so this clears all marks
+        .for_each(|f| mark_tail_calls(&mut f.body, "", f.params.len(), true));
+}
+
+/// Mark a function call as a self-recursive tail call if it calls the named function.
+#[cfg(not(feature = "no_function"))]
+fn mark_tail_call(x: &mut FnCallInfo, name: &str, num_params: usize) {
+    x.tail_call = x.name == name
+        && x.args.len() == num_params
+        && x.namespace.is_none()
+        && !x.capture
+        && !x.native_only
+        && !is_keyword_function(name);
+}
+
 /// Creates a curried expression from a list of external variables
 #[cfg(not(feature = "no_function"))]
-fn make_curry_from_externals(fn_expr: Expr, externals: StaticVec<Ident>, pos: Position) -> Expr {
+#[cfg(not(feature = "no_closure"))]
+fn make_curry_from_externals(
+    fn_expr: Expr,
+    externals: StaticVec<(Ident, Option<NonZeroUsize>)>,
+    pos: Position,
+) -> Expr {
     if externals.is_empty() {
         return fn_expr;
     }
 
     let num_externals = externals.len();
+    let end = fn_expr.end_position();
     let mut args: StaticVec<_> = Default::default();
 
     args.push(fn_expr);
 
-    #[cfg(not(feature = "no_closure"))]
-    externals.iter().for_each(|x| {
-        args.push(Expr::Variable(Box::new((None, None, 0, x.clone()))));
-    });
-
-    #[cfg(feature = "no_closure")]
-    externals.into_iter().for_each(|x| {
-        args.push(Expr::Variable(Box::new((None, None, 0, x.clone()))));
+    externals.iter().for_each(|(x, index)| {
+        args.push(Expr::Variable(Box::new((*index, None, 0, x.clone()))));
     });
 
     let hash = calc_script_fn_hash(empty(), KEYWORD_FN_PTR_CURRY, num_externals + 1);
@@ -2524,23 +3164,17 @@ fn make_curry_from_externals(fn_expr: Expr, externals: StaticVec<Ident>, pos: Po
             ..Default::default()
         }),
         pos,
+        end,
     );
 
     // If there are captured variables, convert the entire expression into a statement block,
     // then insert the relevant `Share` statements.
-    #[cfg(not(feature = "no_closure"))]
-    {
-        // Statement block
-        let mut statements: StaticVec<_> = Default::default();
-        // Insert `Share` statements
-        statements.extend(externals.into_iter().map(|x| Stmt::Share(Box::new(x))));
-        // Final expression
-        statements.push(Stmt::Expr(expr));
-        Expr::Stmt(Box::new(statements), pos)
-    }
-
-    #[cfg(feature = "no_closure")]
-    return expr;
+    let mut statements: StaticVec<_> = Default::default();
+    // Insert `Share` statements
+    statements.extend(externals.into_iter().map(|x| Stmt::Share(Box::new(x))));
+    // Final expression
+    statements.push(Stmt::Expr(expr));
+    Expr::Stmt(Box::new(statements), pos, end)
 }
 
 /// Parse an anonymous function definition.
@@ -2552,7 +3186,7 @@ fn parse_anon_fn(
     mut settings: ParseSettings,
 ) -> Result<(Expr, ScriptFnDef), ParseError> {
     #[cfg(not(feature = "unchecked"))]
-    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+    settings.ensure_level_within_max_limit(state)?;
 
     let mut params = Vec::new();
 
@@ -2562,7 +3196,8 @@ fn parse_anon_fn(
                 match input.next().unwrap() {
                     (Token::Pipe, _) => break,
                     (Token::Identifier(s), pos) => {
-                        state.stack.push((s.clone(), ScopeEntryType::Normal));
+                        state.record_ident(pos, IdentRole::Declaration);
+                        state.stack.push((s.clone(), ScopeEntryType::Normal, None));
                         params.push((s, pos))
                     }
                     (Token::LexError(err), pos) => return Err(err.into_err(pos)),
@@ -2606,34 +3241,24 @@ fn parse_anon_fn(
 
     // Parse function body
     settings.is_breakable = false;
+    settings.is_catch = false;
     let pos = input.peek().unwrap().1;
     let body = parse_stmt(input, state, lib, settings.level_up())
         .map(|stmt| stmt.unwrap_or_else(|| Stmt::Noop(pos)))?;
 
-    // External variables may need to be processed in a consistent order,
-    // so extract them into a list.
-    let externals: StaticVec<Ident> = {
-        #[cfg(not(feature = "no_closure"))]
-        {
-            state
-                .externals
-                .iter()
-                .map(|(k, &v)| Ident::new(k.clone(), v))
-                .collect()
-        }
-        #[cfg(feature = "no_closure")]
-        Default::default()
-    };
+    // Captured variables go before the parameters, in reverse order of first access,
+    // so that the first one sits right below the parameters.
+    #[cfg(not(feature = "no_closure"))]
+    let params: StaticVec<_> = state
+        .externals
+        .iter()
+        .rev()
+        .map(|(k, _)| k.clone())
+        .chain(params.into_iter().map(|(v, _)| v))
+        .collect();
 
-    let params: StaticVec<_> = if cfg!(not(feature = "no_closure")) {
-        externals
-            .iter()
-            .map(|k| k.name.clone())
-            .chain(params.into_iter().map(|(v, _)| v))
-            .collect()
-    } else {
-        params.into_iter().map(|(v, _)| v).collect()
-    };
+    #[cfg(feature = "no_closure")]
+    let params: StaticVec<_> = params.into_iter().map(|(v, _)| v).collect();
 
     // Calculate hash
     #[cfg(feature = "no_std")]
@@ -2649,24 +3274,28 @@ fn parse_anon_fn(
     // Create unique function name
     let fn_name: ImmutableString = format!("{}{:016x}", FN_ANONYMOUS, hash).into();
 
+    let span = match body {
+        Stmt::Block(_, ref span) => Span::new(settings.pos, span.end()),
+        _ => Span::new(settings.pos, body.position()),
+    };
+
     // Define the function
     let script = ScriptFnDef {
         name: fn_name.clone(),
         access: FnAccess::Public,
+        param_types: params.iter().map(|_| None).collect(),
         params,
+        return_type: None,
+        span,
+        source: input.source(),
+        comments: Default::default(),
         #[cfg(not(feature = "no_closure"))]
         externals: Default::default(),
         body,
         lib: None,
     };
 
-    let expr = Expr::FnPointer(Box::new(IdentX::new(fn_name, settings.pos)));
-
-    let expr = if cfg!(not(feature = "no_closure")) {
-        make_curry_from_externals(expr, externals, settings.pos)
-    } else {
-        expr
-    };
+    let expr = Expr::FnPointer(Box::new(IdentX::new(fn_name, settings.pos)), span.end());
 
     Ok((expr, script))
 }
@@ -2677,7 +3306,24 @@ impl Engine {
         input: &mut TokenStream,
         scope: &Scope,
         optimization_level: OptimizationLevel,
+        strict: bool,
+    ) -> Result<AST, ParseError> {
+        self.parse_global_expr_raw(input, scope, optimization_level, strict)
+            .map_err(|err| input.locate_error(err))
+    }
+
+    /// Parse a single expression, without locating the `Span` or the source of any error.
+    fn parse_global_expr_raw(
+        &self,
+        input: &mut TokenStream,
+        scope: &Scope,
+        optimization_level: OptimizationLevel,
+        strict: bool,
     ) -> Result<AST, ParseError> {
+        // Measure native stack usage from here
+        #[cfg(not(feature = "unchecked"))]
+        let _guard = StackGuard::new();
+
         let mut functions = Default::default();
         let mut state = ParseState::new(
             self,
@@ -2695,6 +3341,7 @@ impl Engine {
             is_global: true,
             is_function_scope: false,
             is_breakable: false,
+            is_catch: false,
             level: 0,
             pos: NO_POS,
         };
@@ -2715,6 +3362,12 @@ impl Engine {
 
         let expr = vec![Stmt::Expr(expr)];
 
+        if strict {
+            if let Some(err) = self.check_strict(scope, &expr, &[]).into_iter().next() {
+                return Err(err);
+            }
+        }
+
         Ok(
             // Optimize AST
             optimize_into_ast(self, scope, expr, Default::default(), optimization_level),
@@ -2722,9 +3375,16 @@ impl Engine {
     }
 
     /// Parse the global level statements.
+    ///
+    /// If `errors` is provided, parse in error-recovery mode, collecting all syntax errors into it
+    /// and skipping the affected statements.
+    ///
+    /// If `idents` is provided, record the role of each identifier parsed into it.
     fn parse_global_level(
         &self,
         input: &mut TokenStream,
+        errors: Option<&mut Vec<ParseError>>,
+        idents: Option<&mut Vec<(Position, IdentRole)>>,
     ) -> Result<(Vec<Stmt>, Vec<ScriptFnDef>), ParseError> {
         let mut statements: Vec<Stmt> = Default::default();
         let mut functions = Default::default();
@@ -2737,6 +3397,13 @@ impl Engine {
             self.max_function_expr_depth(),
         );
 
+        if errors.is_some() {
+            state.errors = Some(Default::default());
+        }
+        if idents.is_some() {
+            state.idents = Some(Default::default());
+        }
+
         while !input.peek().unwrap().0.is_eof() {
             let settings = ParseSettings {
                 allow_if_expr: true,
@@ -2745,13 +3412,20 @@ impl Engine {
                 is_global: true,
                 is_function_scope: false,
                 is_breakable: false,
+                is_catch: false,
                 level: 0,
                 pos: NO_POS,
             };
 
-            let stmt = match parse_stmt(input, &mut state, &mut functions, settings)? {
-                Some(s) => s,
-                None => continue,
+            let start = input.peek().unwrap().1;
+
+            let stmt = match parse_stmt(input, &mut state, &mut functions, settings) {
+                Ok(Some(s)) => s,
+                Ok(None) => continue,
+                Err(err) => {
+                    state.recover(input, err, start)?;
+                    continue;
+                }
             };
 
             let need_semicolon = !stmt.is_self_terminated();
@@ -2770,64 +3444,147 @@ impl Engine {
                 // { stmt } ???
                 (_, _) if !need_semicolon => (),
                 // stmt <error>
-                (Token::LexError(err), pos) => return Err(err.into_err(*pos)),
+                (Token::LexError(err), pos) => {
+                    let err = err.clone().into_err(*pos);
+                    input.next().unwrap();
+                    state.record_error(err)?;
+                }
                 // stmt ???
                 (_, pos) => {
                     // Semicolons are not optional between statements
-                    return Err(PERR::MissingToken(
-                        Token::SemiColon.into(),
-                        "to terminate this statement".into(),
-                    )
-                    .into_err(*pos));
+                    state.record_error(
+                        PERR::MissingToken(
+                            Token::SemiColon.into(),
+                            "to terminate this statement".into(),
+                        )
+                        .into_err(*pos),
+                    )?;
                 }
             }
         }
 
+        if let (Some(errors), Some(mut recovered)) = (errors, state.errors) {
+            errors.append(&mut recovered);
+        }
+        if let (Some(idents), Some(mut recorded)) = (idents, state.idents) {
+            idents.append(&mut recorded);
+        }
+
+        // Statements from a named source start with its name
+        if let Some(source) = input.source() {
+            if !statements.is_empty() {
+                statements.insert(0, Stmt::Source(Some(source)));
+            }
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        unmark_overloaded_tail_calls(&mut functions);
+
         Ok((statements, functions.into_iter().map(|(_, v)| v).collect()))
     }
 
     /// Run the parser on an input stream, returning an AST.
+    ///
+    /// If `strict` is true, also check that all variables and functions used are defined.
     #[inline(always)]
     pub(crate) fn parse(
         &self,
         input: &mut TokenStream,
         scope: &Scope,
         optimization_level: OptimizationLevel,
+        strict: bool,
     ) -> Result<AST, ParseError> {
-        let (statements, lib) = self.parse_global_level(input)?;
+        // Measure native stack usage from here
+        #[cfg(not(feature = "unchecked"))]
+        let _guard = StackGuard::new();
+
+        let (statements, lib) = self
+            .parse_global_level(input, None, None)
+            .map_err(|err| input.locate_error(err))?;
+
+        if strict {
+            if let Some(err) = self
+                .check_strict(scope, &statements, &lib)
+                .into_iter()
+                .next()
+            {
+                return Err(input.locate_error(err));
+            }
+        }
 
         Ok(
             // Optimize AST
             optimize_into_ast(self, scope, statements, lib, optimization_level),
         )
     }
+
+    /// Run the parser on an input stream in error-recovery mode, returning an AST containing
+    /// all the statements and functions parsed successfully, together with all syntax errors found.
+    ///
+    /// If `idents` is provided, also record the role of each identifier parsed into it.
+    pub(crate) fn parse_with_errors(
+        &self,
+        input: &mut TokenStream,
+        scope: &Scope,
+        optimization_level: OptimizationLevel,
+        idents: Option<&mut Vec<(Position, IdentRole)>>,
+    ) -> (AST, Vec<ParseError>) {
+        // Measure native stack usage from here
+        #[cfg(not(feature = "unchecked"))]
+        let _guard = StackGuard::new();
+
+        let mut errors = Vec::new();
+
+        let (statements, lib) = self
+            .parse_global_level(input, Some(&mut errors), idents)
+            .unwrap_or_else(|err| {
+                errors.push(err);
+                Default::default()
+            });
+
+        if self.strict_mode {
+            errors.extend(self.check_strict(scope, &statements, &lib));
+            errors.sort_by_key(|err| err.position());
+        }
+
+        input.locate_errors(&mut errors);
+
+        // Optimize AST
+        let ast = optimize_into_ast(self, scope, statements, lib, optimization_level);
+
+        (ast, errors)
+    }
 }
 
 /// Map a `Dynamic` value to an expression.
 ///
+/// The expression takes the `Position`s of the start and the last character of the expression
+/// it replaces.
+///
 /// Returns Some(expression) if conversion is successful.  Otherwise None.
-pub fn map_dynamic_to_expr(value: Dynamic, pos: Position) -> Option<Expr> {
+pub fn map_dynamic_to_expr(value: Dynamic, pos: Position, end: Position) -> Option<Expr> {
     match value.0 {
         #[cfg(not(feature = "no_float"))]
-        Union::Float(value) => Some(Expr::FloatConstant(FloatWrapper(value), pos)),
+        Union::Float(value) => Some(Expr::FloatConstant(FloatWrapper(value), pos, end)),
 
-        Union::Unit(_) => Some(Expr::Unit(pos)),
-        Union::Int(value) => Some(Expr::IntegerConstant(value, pos)),
-        Union::Char(value) => Some(Expr::CharConstant(value, pos)),
-        Union::Str(value) => Some(Expr::StringConstant(Box::new(IdentX::new(value, pos)))),
+        Union::Unit(_) => Some(Expr::Unit(pos, end)),
+        Union::Int(value) => Some(Expr::IntegerConstant(value, pos, end)),
+        Union::Char(value) => Some(Expr::CharConstant(value, pos, end)),
+        Union::Str(value) => Some(Expr::StringConstant(Box::new(IdentX::new(value, pos)), end)),
         Union::Bool(true) => Some(Expr::True(pos)),
         Union::Bool(false) => Some(Expr::False(pos)),
         #[cfg(not(feature = "no_index"))]
         Union::Array(array) => {
-            let items: Vec<_> = array
+            let items: Vec<_> = shared_take_or_clone(array)
                 .into_iter()
-                .map(|x| map_dynamic_to_expr(x, pos))
+                .map(|x| map_dynamic_to_expr(x, pos, end))
                 .collect();
 
             if items.iter().all(Option::is_some) {
                 Some(Expr::Array(
                     Box::new(items.into_iter().map(Option::unwrap).collect()),
                     pos,
+                    end,
                 ))
             } else {
                 None
@@ -2835,9 +3592,9 @@ pub fn map_dynamic_to_expr(value: Dynamic, pos: Position) -> Option<Expr> {
         }
         #[cfg(not(feature = "no_object"))]
         Union::Map(map) => {
-            let items: Vec<_> = map
+            let items: Vec<_> = shared_take_or_clone(map)
                 .into_iter()
-                .map(|(k, v)| (IdentX::new(k, pos), map_dynamic_to_expr(v, pos)))
+                .map(|(k, v)| (IdentX::new(k, pos), map_dynamic_to_expr(v, pos, end)))
                 .collect();
 
             if items.iter().all(|(_, expr)| expr.is_some()) {
@@ -2849,6 +3606,7 @@ pub fn map_dynamic_to_expr(value: Dynamic, pos: Position) -> Option<Expr> {
                             .collect(),
                     ),
                     pos,
+                    end,
                 ))
             } else {
                 None
@@ -0,0 +1,463 @@
+//! Module defining the script profiler.
+
+use crate::engine::{Engine, State};
+use crate::fn_native::{Locked, Shared};
+use crate::token::Position;
+use crate::utils::ImmutableString;
+
+use crate::stdlib::{
+    boxed::Box,
+    collections::HashMap,
+    fmt,
+    string::{String, ToString},
+    time::Duration,
+    vec::Vec,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stdlib::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+
+#[cfg(not(feature = "sync"))]
+use crate::stdlib::cell::RefMut;
+
+#[cfg(feature = "sync")]
+use crate::stdlib::sync::RwLockWriteGuard;
+
+/// Name of the outer-most frame in folded stacks, which holds all code outside of any function.
+const PROFILE_ROOT: &str = "<script>";
+
+/// Which measurement to report in folded stacks.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ProfileMetric {
+    /// Number of operations.
+    Operations,
+    /// Wall time, in microseconds.
+    Time,
+}
+
+/// Profile of a single function.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FunctionProfile {
+    /// Name of the function.
+    pub name: String,
+    /// Is this a native Rust function (as opposed to a script-defined function)?
+    pub native: bool,
+    /// Number of calls.
+    pub calls: u64,
+    /// Number of operations performed inside the function, including the functions it calls.
+    pub operations: u64,
+    /// Number of operations performed inside the function, excluding the functions it calls.
+    pub self_operations: u64,
+    /// Wall time spent inside the function, including the functions it calls.
+    pub time: Duration,
+    /// Wall time spent inside the function, excluding the functions it calls.
+    pub self_time: Duration,
+}
+
+/// Profile of a single source line.
+///
+/// Operations and wall time spent inside script-defined functions called from a line are
+/// attributed to the lines of those functions instead.  Native functions called from a line are
+/// included.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LineProfile {
+    /// Source of the script (e.g. the script file path), if any.
+    pub source: Option<String>,
+    /// Line number (starting from 1).
+    pub line: usize,
+    /// Number of statements executed on this line.
+    pub hits: u64,
+    /// Number of operations.
+    pub operations: u64,
+    /// Wall time.
+    pub time: Duration,
+}
+
+/// A structured report of a `Profiler`.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileReport {
+    /// Profiles of functions, sorted by self wall time, highest first.
+    functions: Vec<FunctionProfile>,
+    /// Profiles of lines, sorted by source and line number.
+    lines: Vec<LineProfile>,
+    /// Call stacks (outer-most first, separated by `;`) with their self operations and wall time.
+    stacks: Vec<(String, u64, Duration)>,
+}
+
+impl ProfileReport {
+    /// Get the profiles of all functions called, sorted by self wall time, highest first.
+    #[inline(always)]
+    pub fn functions(&self) -> &[FunctionProfile] {
+        &self.functions
+    }
+    /// Get the profile of a function by name.
+    #[inline(always)]
+    pub fn function(&self, name: &str) -> Option<&FunctionProfile> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+    /// Get the profiles of all lines executed, sorted by source and line number.
+    #[inline(always)]
+    pub fn lines(&self) -> &[LineProfile] {
+        &self.lines
+    }
+    /// Get the profile of a line.
+    #[inline(always)]
+    pub fn line(&self, source: Option<&str>, line: usize) -> Option<&LineProfile> {
+        self.lines
+            .iter()
+            .find(|l| l.line == line && l.source.as_deref() == source)
+    }
+    /// Total number of operations profiled.
+    #[inline(always)]
+    pub fn operations(&self) -> u64 {
+        self.stacks.iter().map(|(_, ops, _)| ops).sum()
+    }
+    /// Total wall time profiled.
+    #[inline(always)]
+    pub fn time(&self) -> Duration {
+        self.stacks.iter().map(|(_, _, time)| *time).sum()
+    }
+    /// Export the report as folded stacks, one call stack per line followed by the measurement,
+    /// which can be turned into a flamegraph by tools such as `inferno` or `flamegraph.pl`.
+    ///
+    /// Call stacks with a zero measurement are skipped.
+    pub fn to_folded(&self, metric: ProfileMetric) -> String {
+        self.stacks
+            .iter()
+            .map(|(stack, ops, time)| {
+                let value = match metric {
+                    ProfileMetric::Operations => *ops,
+                    ProfileMetric::Time => time.as_micros() as u64,
+                };
+                (stack, value)
+            })
+            .filter(|(_, value)| *value > 0)
+            .map(|(stack, value)| format!("{} {}\n", stack, value))
+            .collect()
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>8} {:>12} {:>12} {:>12} {:>12}",
+            "function", "calls", "ops", "self ops", "time", "self time"
+        )?;
+
+        for func in &self.functions {
+            let name = if func.native {
+                format!("{} (native)", func.name)
+            } else {
+                func.name.clone()
+            };
+
+            writeln!(
+                f,
+                "{:<24} {:>8} {:>12} {:>12} {:>12} {:>12}",
+                name,
+                func.calls,
+                func.operations,
+                func.self_operations,
+                format!("{:?}", func.time),
+                format!("{:?}", func.self_time)
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<24} {:>8} {:>12} {:>12}",
+            "line", "hits", "ops", "time"
+        )?;
+
+        for line in &self.lines {
+            let name = match line.source {
+                Some(ref source) => format!("{}:{}", source, line.line),
+                None => line.line.to_string(),
+            };
+
+            writeln!(
+                f,
+                "{:<24} {:>8} {:>12} {:>12}",
+                name,
+                line.hits,
+                line.operations,
+                format!("{:?}", line.time)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Measurements collected by a `Profiler`.
+#[derive(Debug, Clone, Default)]
+struct ProfileData {
+    /// Profiles of functions.
+    functions: Vec<FunctionProfile>,
+    /// Index into `functions` by name and whether the function is native.
+    function_index: HashMap<(String, bool), usize>,
+    /// Profiles of lines, by source and line number.
+    lines: HashMap<(Option<ImmutableString>, usize), (u64, u64, Duration)>,
+    /// Self operations and wall time by call stack.
+    stacks: HashMap<String, (u64, Duration)>,
+}
+
+/// A profiler that collects operation counts and wall time per function and per source line.
+///
+/// A `Profiler` is a cheap handle to shared measurements: clone it, pass one copy to
+/// `Engine::set_profiler`, then call `report` on the other after evaluation.
+/// Measurements accumulate over all evaluation runs until `reset` is called.
+///
+/// Scripts called back from native Rust functions (e.g. closures passed to `Array::map`)
+/// are profiled as separate evaluation runs, so their measurements are also included in
+/// those of the native function.
+///
+/// Not available under the `no_std` feature.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler(Shared<Locked<ProfileData>>);
+
+impl Profiler {
+    /// Create a new `Profiler`.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Lock the measurements for update.
+    #[cfg(not(feature = "sync"))]
+    #[inline(always)]
+    fn data(&self) -> RefMut<'_, ProfileData> {
+        self.0.borrow_mut()
+    }
+    /// Lock the measurements for update.
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    fn data(&self) -> RwLockWriteGuard<'_, ProfileData> {
+        self.0.write().unwrap()
+    }
+    /// Clear all measurements.
+    #[inline(always)]
+    pub fn reset(&self) {
+        *self.data() = Default::default();
+    }
+    /// Get a report of all measurements so far.
+    pub fn report(&self) -> ProfileReport {
+        let data = self.data();
+
+        let mut functions = data.functions.clone();
+        functions.sort_by(|a, b| b.self_time.cmp(&a.self_time).then(a.name.cmp(&b.name)));
+
+        let mut lines: Vec<_> = data
+            .lines
+            .iter()
+            .map(|((source, line), &(hits, operations, time))| LineProfile {
+                source: source.as_ref().map(|name| name.to_string()),
+                line: *line,
+                hits,
+                operations,
+                time,
+            })
+            .collect();
+        lines.sort_by(|a, b| a.source.cmp(&b.source).then(a.line.cmp(&b.line)));
+
+        let mut stacks: Vec<_> = data
+            .stacks
+            .iter()
+            .map(|(stack, &(ops, time))| (stack.clone(), ops, time))
+            .collect();
+        stacks.sort();
+
+        ProfileReport {
+            functions,
+            lines,
+            stacks,
+        }
+    }
+}
+
+/// A function call being profiled.
+#[derive(Debug, Clone)]
+struct ProfileFrame {
+    /// Index of the function in the profile data.
+    index: usize,
+    /// Number of operations on entry.
+    operations: u64,
+    /// Time of entry.
+    time: Instant,
+    /// Line of the caller.
+    line: Option<(Option<ImmutableString>, usize)>,
+    /// Length of the call stack path of the caller.
+    path_len: usize,
+}
+
+/// State of the profiler during an evaluation run.
+#[derive(Debug, Clone)]
+pub struct ProfileState {
+    /// Stack of function calls, outer-most call first.
+    frames: Vec<ProfileFrame>,
+    /// Call stack path, separated by `;`.
+    path: String,
+    /// Current source line, if any.
+    line: Option<(Option<ImmutableString>, usize)>,
+    /// Number of operations at the last checkpoint.
+    operations: u64,
+    /// Time of the last checkpoint.
+    time: Instant,
+}
+
+impl ProfileState {
+    /// Create a new `ProfileState`.
+    fn new(operations: u64) -> Self {
+        Self {
+            frames: Default::default(),
+            path: PROFILE_ROOT.into(),
+            line: None,
+            operations,
+            time: Instant::now(),
+        }
+    }
+    /// Attribute the operations and wall time since the last checkpoint to the current line,
+    /// function and call stack.
+    fn checkpoint(&mut self, data: &mut ProfileData, operations: u64) {
+        let now = Instant::now();
+        let ops = operations - self.operations;
+        let time = now.duration_since(self.time);
+
+        self.operations = operations;
+        self.time = now;
+
+        if let Some(ref line) = self.line {
+            let (_, line_ops, line_time) = data.lines.entry(line.clone()).or_default();
+            *line_ops += ops;
+            *line_time += time;
+        }
+
+        if let Some(frame) = self.frames.last() {
+            let func = &mut data.functions[frame.index];
+            func.self_operations += ops;
+            func.self_time += time;
+        }
+
+        if let Some((stack_ops, stack_time)) = data.stacks.get_mut(&self.path) {
+            *stack_ops += ops;
+            *stack_time += time;
+        } else {
+            data.stacks.insert(self.path.clone(), (ops, time));
+        }
+    }
+}
+
+impl Engine {
+    /// Get the profiler state for the current evaluation run, creating it if necessary.
+    fn get_profile_state<'s>(&self, state: &'s mut State) -> &'s mut ProfileState {
+        let operations = state.operations;
+        state
+            .profile
+            .get_or_insert_with(|| Box::new(ProfileState::new(operations)))
+    }
+
+    /// Run the profiler, if any, before evaluating a statement.
+    #[inline]
+    pub(crate) fn profile_statement(&self, state: &mut State, pos: Position) {
+        let profiler = match self.profiler {
+            Some(ref profiler) => profiler,
+            None => return,
+        };
+
+        let operations = state.operations;
+        let source = state.source.clone();
+        let profile = self.get_profile_state(state);
+        let data = &mut profiler.data();
+
+        profile.checkpoint(data, operations);
+
+        profile.line = pos.line().map(|line| (source, line));
+
+        if let Some(ref line) = profile.line {
+            data.lines.entry(line.clone()).or_default().0 += 1;
+        }
+    }
+
+    /// Run the profiler, if any, on entering a function.
+    #[inline]
+    pub(crate) fn profile_enter_function(&self, state: &mut State, fn_name: &str, native: bool) {
+        let profiler = match self.profiler {
+            Some(ref profiler) => profiler,
+            None => return,
+        };
+
+        let operations = state.operations;
+        let profile = self.get_profile_state(state);
+        let data = &mut profiler.data();
+
+        profile.checkpoint(data, operations);
+
+        let key = (fn_name.to_string(), native);
+
+        let index = match data.function_index.get(&key) {
+            Some(&index) => index,
+            None => {
+                let index = data.functions.len();
+                data.functions.push(FunctionProfile {
+                    name: key.0.clone(),
+                    native,
+                    calls: 0,
+                    operations: 0,
+                    self_operations: 0,
+                    time: Default::default(),
+                    self_time: Default::default(),
+                });
+                data.function_index.insert(key, index);
+                index
+            }
+        };
+
+        data.functions[index].calls += 1;
+
+        profile.frames.push(ProfileFrame {
+            index,
+            operations,
+            time: profile.time,
+            line: profile.line.clone(),
+            path_len: profile.path.len(),
+        });
+
+        // The function runs on the line of the caller until its first statement
+        profile.path.push(';');
+        profile.path.push_str(fn_name);
+    }
+
+    /// Run the profiler, if any, on leaving a function.
+    #[inline]
+    pub(crate) fn profile_leave_function(&self, state: &mut State) {
+        let profiler = match self.profiler {
+            Some(ref profiler) => profiler,
+            None => return,
+        };
+
+        let operations = state.operations;
+        let profile = self.get_profile_state(state);
+        let data = &mut profiler.data();
+
+        profile.checkpoint(data, operations);
+
+        let frame = match profile.frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        // Only count the outer-most call of a recursive function
+        if profile.frames.iter().all(|f| f.index != frame.index) {
+            let func = &mut data.functions[frame.index];
+            func.operations += operations - frame.operations;
+            func.time += profile.time.duration_since(frame.time);
+        }
+
+        profile.line = frame.line;
+        profile.path.truncate(frame.path_len);
+    }
+}
@@ -2,23 +2,27 @@
 
 use crate::dynamic::Dynamic;
 use crate::parse_error::ParseErrorType;
-use crate::token::{Position, NO_POS};
+use crate::token::{Location, Position, Span};
 use crate::utils::ImmutableString;
 use crate::INT;
 
 #[cfg(not(feature = "no_function"))]
 use crate::engine::is_anonymous_fn;
 
+#[cfg(not(feature = "no_object"))]
+use crate::engine::Map;
+
 use crate::stdlib::{
     boxed::Box,
     error::Error,
     fmt,
     string::{String, ToString},
+    vec::Vec,
 };
 
 /// Evaluation result.
 ///
-/// All wrapped `Position` values represent the location in the script where the error occurs.
+/// All wrapped `Location` values represent the location in the script where the error occurs.
 ///
 /// # Thread Safety
 ///
@@ -34,69 +38,82 @@ pub enum EvalAltResult {
     ErrorSystem(String, Box<dyn Error + Send + Sync>),
 
     /// Syntax error.
-    ErrorParsing(ParseErrorType, Position),
+    ErrorParsing(ParseErrorType, Location),
 
     /// Usage of an unknown variable. Wrapped value is the variable name.
-    ErrorVariableNotFound(String, Position),
+    ErrorVariableNotFound(String, Location),
     /// Call to an unknown function. Wrapped value is the function signature.
-    ErrorFunctionNotFound(String, Position),
+    ErrorFunctionNotFound(String, Location),
     /// An error has occurred inside a called function.
-    /// Wrapped values are the function name and the interior error.
-    ErrorInFunctionCall(String, Box<EvalAltResult>, Position),
+    /// Wrapped values are the function name, the number of arguments, the name of the source
+    /// (e.g. the script file) the function is defined in (if any) and the interior error.
+    ///
+    /// There is one level of nesting per function call, so the interior error may itself be
+    /// an `ErrorInFunctionCall`. Use `call_stack` to get all the levels as a list.
+    ErrorInFunctionCall(
+        String,
+        usize,
+        Option<ImmutableString>,
+        Box<EvalAltResult>,
+        Location,
+    ),
     /// Usage of an unknown module. Wrapped value is the module name.
-    ErrorModuleNotFound(String, Position),
+    ErrorModuleNotFound(String, Location),
     /// An error has occurred while loading a module.
     /// Wrapped value are the module name and the interior error.
-    ErrorInModule(String, Box<EvalAltResult>, Position),
+    ErrorInModule(String, Box<EvalAltResult>, Location),
     /// Access to `this` that is not bound.
-    ErrorUnboundThis(Position),
+    ErrorUnboundThis(Location),
     /// Data is not of the required type.
     /// Wrapped values are the type requested and type of the actual result.
-    ErrorMismatchDataType(String, String, Position),
+    ErrorMismatchDataType(String, String, Location),
     /// Returned type is not the same as the required output type.
     /// Wrapped values are the type requested and type of the actual result.
-    ErrorMismatchOutputType(String, String, Position),
+    ErrorMismatchOutputType(String, String, Location),
     /// Array access out-of-bounds.
     /// Wrapped values are the current number of elements in the array and the index number.
-    ErrorArrayBounds(usize, INT, Position),
+    ErrorArrayBounds(usize, INT, Location),
     /// String indexing out-of-bounds.
     /// Wrapped values are the current number of characters in the string and the index number.
-    ErrorStringBounds(usize, INT, Position),
+    ErrorStringBounds(usize, INT, Location),
     /// Trying to index into a type that is not an array, an object map, or a string, and has no indexer function defined.
     /// Wrapped value is the type name.
-    ErrorIndexingType(String, Position),
+    ErrorIndexingType(String, Location),
     /// Invalid arguments for `in` operator.
-    ErrorInExpr(Position),
+    ErrorInExpr(Location),
     /// The `for` statement encounters a type that is not an iterator.
-    ErrorFor(Position),
+    ErrorFor(Location),
     /// Data race detected when accessing a variable. Wrapped value is the variable name.
-    ErrorDataRace(String, Position),
+    ErrorDataRace(String, Location),
     /// Assignment to a constant variable. Wrapped value is the variable name.
-    ErrorAssignmentToConstant(String, Position),
+    ErrorAssignmentToConstant(String, Location),
     /// Inappropriate property access. Wrapped value is the property name.
-    ErrorDotExpr(String, Position),
+    ErrorDotExpr(String, Location),
     /// Arithmetic error encountered. Wrapped value is the error message.
-    ErrorArithmetic(String, Position),
+    ErrorArithmetic(String, Location),
     /// Number of operations over maximum limit.
-    ErrorTooManyOperations(Position),
+    ErrorTooManyOperations(Location),
     /// Modules over maximum limit.
-    ErrorTooManyModules(Position),
+    ErrorTooManyModules(Location),
     /// Call stack over maximum limit.
-    ErrorStackOverflow(Position),
+    ErrorStackOverflow(Location),
     /// Data value over maximum size limit. Wrapped value is the type name.
-    ErrorDataTooLarge(String, Position),
+    ErrorDataTooLarge(String, Location),
     /// The script is prematurely terminated. Wrapped value is the termination token.
-    ErrorTerminated(Dynamic, Position),
+    ErrorTerminated(Dynamic, Location),
     /// Run-time error encountered. Wrapped value is the error token.
-    ErrorRuntime(Dynamic, Position),
+    ErrorRuntime(Dynamic, Location),
 
     /// Breaking out of loops - not an error if within a loop.
     /// The wrapped value, if true, means breaking clean out of the loop (i.e. a `break` statement).
     /// The wrapped value, if false, means breaking the current context (i.e. a `continue` statement).
-    LoopBreak(bool, Position),
+    LoopBreak(bool, Location),
     /// Not an error: Value returned from a script via the `return` keyword.
     /// Wrapped value is the result value.
-    Return(Dynamic, Position),
+    Return(Dynamic, Location),
+    /// Not an error: `throw` without a value inside a `catch` block, re-throwing the error
+    /// caught by that block.
+    Rethrow(Location),
 }
 
 impl EvalAltResult {
@@ -105,7 +122,7 @@ impl EvalAltResult {
             #[allow(deprecated)]
             Self::ErrorSystem(_, s) => s.description(),
             Self::ErrorParsing(p, _) => p.desc(),
-            Self::ErrorInFunctionCall(_, _, _) => "Error in called function",
+            Self::ErrorInFunctionCall(_, _, _, _, _) => "Error in called function",
             Self::ErrorInModule(_, _, _) => "Error in module",
             Self::ErrorFunctionNotFound(_, _) => "Function not found",
             Self::ErrorUnboundThis(_) => "'this' is not bound",
@@ -141,16 +158,17 @@ impl EvalAltResult {
             Self::LoopBreak(true, _) => "Break statement not inside a loop",
             Self::LoopBreak(false, _) => "Continue statement not inside a loop",
             Self::Return(_, _) => "[Not Error] Function returns value",
+            Self::Rethrow(_) => "[Not Error] Catch block re-throws error",
         }
     }
 }
 
 impl Error for EvalAltResult {}
 
-impl fmt::Display for EvalAltResult {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl EvalAltResult {
+    /// Write the error message, without the position.
+    fn fmt_message(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let desc = self.desc();
-        let pos = self.position();
 
         match self {
             Self::ErrorSystem(s, _) if s.is_empty() => f.write_str(desc)?,
@@ -159,10 +177,10 @@ impl fmt::Display for EvalAltResult {
             Self::ErrorParsing(p, _) => write!(f, "Syntax error: {}", p)?,
 
             #[cfg(not(feature = "no_function"))]
-            Self::ErrorInFunctionCall(s, err, _) if is_anonymous_fn(s) => {
+            Self::ErrorInFunctionCall(s, _, _, err, _) if is_anonymous_fn(s) => {
                 write!(f, "Error in call to closure: {}", err)?
             }
-            Self::ErrorInFunctionCall(s, err, _) => {
+            Self::ErrorInFunctionCall(s, _, _, err, _) => {
                 write!(f, "Error in call to function '{}': {}", s, err)?
             }
             Self::ErrorInModule(s, err, _) if s.is_empty() => {
@@ -210,6 +228,7 @@ impl fmt::Display for EvalAltResult {
 
             Self::LoopBreak(_, _) => f.write_str(desc)?,
             Self::Return(_, _) => f.write_str(desc)?,
+            Self::Rethrow(_) => f.write_str(desc)?,
 
             Self::ErrorArrayBounds(_, index, _) if *index < 0 => {
                 write!(f, "{}: {} < 0", desc, index)?
@@ -242,9 +261,18 @@ impl fmt::Display for EvalAltResult {
             Self::ErrorDataTooLarge(typ, _) => write!(f, "{} exceeds maximum limit", typ)?,
         }
 
+        Ok(())
+    }
+}
+
+impl fmt::Display for EvalAltResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_message(f)?;
+
         // Do not write any position if None
-        if !pos.is_none() {
-            write!(f, " ({})", pos)?;
+        match self.location() {
+            Some(loc) if !loc.is_none() => write!(f, " ({})", loc)?,
+            _ => (),
         }
 
         Ok(())
@@ -254,7 +282,7 @@ impl fmt::Display for EvalAltResult {
 impl<T: AsRef<str>> From<T> for EvalAltResult {
     #[inline(always)]
     fn from(err: T) -> Self {
-        Self::ErrorRuntime(err.as_ref().to_string().into(), NO_POS)
+        Self::ErrorRuntime(err.as_ref().to_string().into(), Location::NONE)
     }
 }
 
@@ -263,7 +291,7 @@ impl<T: AsRef<str>> From<T> for Box<EvalAltResult> {
     fn from(err: T) -> Self {
         Box::new(EvalAltResult::ErrorRuntime(
             err.as_ref().to_string().into(),
-            NO_POS,
+            Location::NONE,
         ))
     }
 }
@@ -273,10 +301,10 @@ impl EvalAltResult {
     pub fn is_catchable(&self) -> bool {
         match self {
             Self::ErrorSystem(_, _) => false,
-            Self::ErrorParsing(_, _) => unreachable!(),
+            Self::ErrorParsing(_, _) => false,
 
             Self::ErrorFunctionNotFound(_, _)
-            | Self::ErrorInFunctionCall(_, _, _)
+            | Self::ErrorInFunctionCall(_, _, _, _, _)
             | Self::ErrorInModule(_, _, _)
             | Self::ErrorUnboundThis(_)
             | Self::ErrorMismatchDataType(_, _, _)
@@ -300,7 +328,7 @@ impl EvalAltResult {
             | Self::ErrorDataTooLarge(_, _)
             | Self::ErrorTerminated(_, _) => false,
 
-            Self::LoopBreak(_, _) | Self::Return(_, _) => unreachable!(),
+            Self::LoopBreak(_, _) | Self::Return(_, _) | Self::Rethrow(_) => false,
         }
     }
 
@@ -308,7 +336,7 @@ impl EvalAltResult {
     pub fn is_system_exception(&self) -> bool {
         match self {
             Self::ErrorSystem(_, _) => true,
-            Self::ErrorParsing(_, _) => unreachable!(),
+            Self::ErrorParsing(_, _) => false,
 
             Self::ErrorTooManyOperations(_)
             | Self::ErrorTooManyModules(_)
@@ -317,77 +345,228 @@ impl EvalAltResult {
 
             Self::ErrorTerminated(_, _) => true,
 
-            Self::LoopBreak(_, _) | Self::Return(_, _) => unreachable!(),
+            Self::LoopBreak(_, _) | Self::Return(_, _) => false,
 
             _ => false,
         }
     }
 
+    /// Get the `Location` of this error, or `None` if it has no location.
+    fn location(&self) -> Option<&Location> {
+        match self {
+            Self::ErrorSystem(_, _) => None,
+
+            Self::ErrorParsing(_, loc)
+            | Self::ErrorFunctionNotFound(_, loc)
+            | Self::ErrorInFunctionCall(_, _, _, _, loc)
+            | Self::ErrorInModule(_, _, loc)
+            | Self::ErrorUnboundThis(loc)
+            | Self::ErrorMismatchDataType(_, _, loc)
+            | Self::ErrorArrayBounds(_, _, loc)
+            | Self::ErrorStringBounds(_, _, loc)
+            | Self::ErrorIndexingType(_, loc)
+            | Self::ErrorFor(loc)
+            | Self::ErrorVariableNotFound(_, loc)
+            | Self::ErrorModuleNotFound(_, loc)
+            | Self::ErrorDataRace(_, loc)
+            | Self::ErrorAssignmentToConstant(_, loc)
+            | Self::ErrorMismatchOutputType(_, _, loc)
+            | Self::ErrorInExpr(loc)
+            | Self::ErrorDotExpr(_, loc)
+            | Self::ErrorArithmetic(_, loc)
+            | Self::ErrorTooManyOperations(loc)
+            | Self::ErrorTooManyModules(loc)
+            | Self::ErrorStackOverflow(loc)
+            | Self::ErrorDataTooLarge(_, loc)
+            | Self::ErrorTerminated(_, loc)
+            | Self::ErrorRuntime(_, loc)
+            | Self::LoopBreak(_, loc)
+            | Self::Return(_, loc)
+            | Self::Rethrow(loc) => Some(loc),
+        }
+    }
+
+    /// Get a mutable reference to the `Location` of this error, or `None` if it has no location.
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            Self::ErrorSystem(_, _) => None,
+
+            Self::ErrorParsing(_, loc)
+            | Self::ErrorFunctionNotFound(_, loc)
+            | Self::ErrorInFunctionCall(_, _, _, _, loc)
+            | Self::ErrorInModule(_, _, loc)
+            | Self::ErrorUnboundThis(loc)
+            | Self::ErrorMismatchDataType(_, _, loc)
+            | Self::ErrorArrayBounds(_, _, loc)
+            | Self::ErrorStringBounds(_, _, loc)
+            | Self::ErrorIndexingType(_, loc)
+            | Self::ErrorFor(loc)
+            | Self::ErrorVariableNotFound(_, loc)
+            | Self::ErrorModuleNotFound(_, loc)
+            | Self::ErrorDataRace(_, loc)
+            | Self::ErrorAssignmentToConstant(_, loc)
+            | Self::ErrorMismatchOutputType(_, _, loc)
+            | Self::ErrorInExpr(loc)
+            | Self::ErrorDotExpr(_, loc)
+            | Self::ErrorArithmetic(_, loc)
+            | Self::ErrorTooManyOperations(loc)
+            | Self::ErrorTooManyModules(loc)
+            | Self::ErrorStackOverflow(loc)
+            | Self::ErrorDataTooLarge(_, loc)
+            | Self::ErrorTerminated(_, loc)
+            | Self::ErrorRuntime(_, loc)
+            | Self::LoopBreak(_, loc)
+            | Self::Return(_, loc)
+            | Self::Rethrow(loc) => Some(loc),
+        }
+    }
+
     /// Get the `Position` of this error.
+    #[inline(always)]
     pub fn position(&self) -> Position {
+        self.location().map_or(Position::NONE, |loc| loc.position())
+    }
+
+    /// Get the `Span` of the expression or statement where this error occurs.
+    ///
+    /// If the `Span` is not known, it only covers the `Position` of this error.
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.location().map_or(Span::NONE, |loc| loc.span())
+    }
+
+    /// Get the name of the source (e.g. the script file path) where this error occurs, if any.
+    #[inline(always)]
+    pub fn source(&self) -> Option<&str> {
+        self.location().and_then(|loc| loc.source())
+    }
+
+    /// Get the name of the kind of this error, which is the name of the variant without the
+    /// `Error` prefix (e.g. `"VariableNotFound"` for `ErrorVariableNotFound`).
+    pub(crate) fn kind(&self) -> &'static str {
         match self {
-            Self::ErrorSystem(_, _) => NO_POS,
-
-            Self::ErrorParsing(_, pos)
-            | Self::ErrorFunctionNotFound(_, pos)
-            | Self::ErrorInFunctionCall(_, _, pos)
-            | Self::ErrorInModule(_, _, pos)
-            | Self::ErrorUnboundThis(pos)
-            | Self::ErrorMismatchDataType(_, _, pos)
-            | Self::ErrorArrayBounds(_, _, pos)
-            | Self::ErrorStringBounds(_, _, pos)
-            | Self::ErrorIndexingType(_, pos)
-            | Self::ErrorFor(pos)
-            | Self::ErrorVariableNotFound(_, pos)
-            | Self::ErrorModuleNotFound(_, pos)
-            | Self::ErrorDataRace(_, pos)
-            | Self::ErrorAssignmentToConstant(_, pos)
-            | Self::ErrorMismatchOutputType(_, _, pos)
-            | Self::ErrorInExpr(pos)
-            | Self::ErrorDotExpr(_, pos)
-            | Self::ErrorArithmetic(_, pos)
-            | Self::ErrorTooManyOperations(pos)
-            | Self::ErrorTooManyModules(pos)
-            | Self::ErrorStackOverflow(pos)
-            | Self::ErrorDataTooLarge(_, pos)
-            | Self::ErrorTerminated(_, pos)
-            | Self::ErrorRuntime(_, pos)
-            | Self::LoopBreak(_, pos)
-            | Self::Return(_, pos) => *pos,
+            Self::ErrorSystem(_, _) => "System",
+            Self::ErrorParsing(_, _) => "Parsing",
+            Self::ErrorVariableNotFound(_, _) => "VariableNotFound",
+            Self::ErrorFunctionNotFound(_, _) => "FunctionNotFound",
+            Self::ErrorInFunctionCall(_, _, _, _, _) => "InFunctionCall",
+            Self::ErrorModuleNotFound(_, _) => "ModuleNotFound",
+            Self::ErrorInModule(_, _, _) => "InModule",
+            Self::ErrorUnboundThis(_) => "UnboundThis",
+            Self::ErrorMismatchDataType(_, _, _) => "MismatchDataType",
+            Self::ErrorMismatchOutputType(_, _, _) => "MismatchOutputType",
+            Self::ErrorArrayBounds(_, _, _) => "ArrayBounds",
+            Self::ErrorStringBounds(_, _, _) => "StringBounds",
+            Self::ErrorIndexingType(_, _) => "IndexingType",
+            Self::ErrorInExpr(_) => "InExpr",
+            Self::ErrorFor(_) => "For",
+            Self::ErrorDataRace(_, _) => "DataRace",
+            Self::ErrorAssignmentToConstant(_, _) => "AssignmentToConstant",
+            Self::ErrorDotExpr(_, _) => "DotExpr",
+            Self::ErrorArithmetic(_, _) => "Arithmetic",
+            Self::ErrorTooManyOperations(_) => "TooManyOperations",
+            Self::ErrorTooManyModules(_) => "TooManyModules",
+            Self::ErrorStackOverflow(_) => "StackOverflow",
+            Self::ErrorDataTooLarge(_, _) => "DataTooLarge",
+            Self::ErrorTerminated(_, _) => "Terminated",
+            Self::ErrorRuntime(_, _) => "Runtime",
+            Self::LoopBreak(_, _) => "LoopBreak",
+            Self::Return(_, _) => "Return",
+            Self::Rethrow(_) => "Rethrow",
+        }
+    }
+
+    /// Get the value bound to the error variable of a `catch` block that catches this error.
+    ///
+    /// Values thrown by `throw` (even from within function calls) are returned unchanged.
+    /// Other errors are converted into an object map with the fields `kind`, `message`, `line`,
+    /// `position`, `function` and `source`, describing the inner-most error, or into the error
+    /// message under `no_object`.
+    ///
+    pub(crate) fn catch_value(&self) -> Dynamic {
+        /// Error message without the position.
+        struct Message<'a>(&'a EvalAltResult);
+
+        impl fmt::Display for Message<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt_message(f)
+            }
+        }
+
+        let mut err = self;
+        let mut _function = None;
+
+        while let Self::ErrorInFunctionCall(fn_name, _, _, inner, _) = err {
+            _function = Some(fn_name);
+            err = inner;
+        }
+
+        if let Self::ErrorRuntime(value, _) = err {
+            return value.clone();
+        }
+
+        let message = Message(err).to_string();
+
+        #[cfg(not(feature = "no_object"))]
+        {
+            let pos = err.position();
+
+            let mut map = Map::new();
+            map.insert("kind".into(), err.kind().into());
+            map.insert("message".into(), message.into());
+            map.insert(
+                "line".into(),
+                pos.line()
+                    .map_or_else(Dynamic::default, |line| (line as INT).into()),
+            );
+            map.insert(
+                "position".into(),
+                pos.position()
+                    .map_or_else(Dynamic::default, |pos| (pos as INT).into()),
+            );
+            map.insert(
+                "function".into(),
+                _function.map_or_else(Dynamic::default, |name| name.clone().into()),
+            );
+            map.insert(
+                "source".into(),
+                err.source().map_or_else(Dynamic::default, Into::into),
+            );
+            map.into()
         }
+
+        #[cfg(feature = "no_object")]
+        {
+            message.into()
+        }
+    }
+
+    /// Get the script call stack leading to this error, inner-most call first.
+    ///
+    /// The call stack is empty if the error did not occur inside a function call.
+    pub fn call_stack(&self) -> Vec<CallFrame> {
+        let mut frames = Vec::new();
+        let mut err = self;
+
+        while let Self::ErrorInFunctionCall(fn_name, num_args, source, inner, pos) = err {
+            frames.push(CallFrame {
+                fn_name: fn_name.clone(),
+                num_args: *num_args,
+                pos: pos.position(),
+                source: source.clone(),
+            });
+            err = inner;
+        }
+
+        frames.reverse();
+        frames
     }
 
     /// Override the `Position` of this error.
+    #[inline(always)]
     pub fn set_position(&mut self, new_position: Position) {
-        match self {
-            Self::ErrorSystem(_, _) => (),
-
-            Self::ErrorParsing(_, pos)
-            | Self::ErrorFunctionNotFound(_, pos)
-            | Self::ErrorInFunctionCall(_, _, pos)
-            | Self::ErrorInModule(_, _, pos)
-            | Self::ErrorUnboundThis(pos)
-            | Self::ErrorMismatchDataType(_, _, pos)
-            | Self::ErrorArrayBounds(_, _, pos)
-            | Self::ErrorStringBounds(_, _, pos)
-            | Self::ErrorIndexingType(_, pos)
-            | Self::ErrorFor(pos)
-            | Self::ErrorVariableNotFound(_, pos)
-            | Self::ErrorModuleNotFound(_, pos)
-            | Self::ErrorDataRace(_, pos)
-            | Self::ErrorAssignmentToConstant(_, pos)
-            | Self::ErrorMismatchOutputType(_, _, pos)
-            | Self::ErrorInExpr(pos)
-            | Self::ErrorDotExpr(_, pos)
-            | Self::ErrorArithmetic(_, pos)
-            | Self::ErrorTooManyOperations(pos)
-            | Self::ErrorTooManyModules(pos)
-            | Self::ErrorStackOverflow(pos)
-            | Self::ErrorDataTooLarge(_, pos)
-            | Self::ErrorTerminated(_, pos)
-            | Self::ErrorRuntime(_, pos)
-            | Self::LoopBreak(_, pos)
-            | Self::Return(_, pos) => *pos = new_position,
+        if let Some(loc) = self.location_mut() {
+            loc.set_position(new_position);
         }
     }
 
@@ -400,6 +579,28 @@ impl EvalAltResult {
         }
         self
     }
+
+    /// Consume the current `EvalAltResult` and return a new one with the `Span` of the expression
+    /// or statement containing its `Position`, if it has no `Span` yet.
+    #[inline(always)]
+    pub(crate) fn fill_span(mut self: Box<Self>, span: Span) -> Box<Self> {
+        if let Some(loc) = self.location_mut() {
+            loc.fill_span(span);
+        }
+        self
+    }
+
+    /// Consume the current `EvalAltResult` and return a new one with the name of the source of
+    /// its `Position`, if it has no source yet.
+    ///
+    /// This is called when the error leaves the script (or function body) it occurs in.
+    #[inline(always)]
+    pub(crate) fn fill_source(mut self: Box<Self>, source: &Option<ImmutableString>) -> Box<Self> {
+        if let Some(loc) = self.location_mut() {
+            loc.fill_source(source);
+        }
+        self
+    }
 }
 
 impl<T> From<EvalAltResult> for Result<T, Box<EvalAltResult>> {
@@ -408,3 +609,85 @@ impl<T> From<EvalAltResult> for Result<T, Box<EvalAltResult>> {
         Err(err.into())
     }
 }
+
+/// A level in the script call stack of an error, as returned by `EvalAltResult::call_stack`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CallFrame {
+    /// Name of the function called.
+    pub fn_name: String,
+    /// Number of arguments passed to the function.
+    pub num_args: usize,
+    /// Position of the function call.
+    pub pos: Position,
+    /// Name of the source (e.g. the script file) that the function is defined in, if known.
+    pub source: Option<ImmutableString>,
+}
+
+impl fmt::Display for CallFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(not(feature = "no_function"))]
+        let is_closure = is_anonymous_fn(&self.fn_name);
+        #[cfg(feature = "no_function")]
+        let is_closure = false;
+
+        if is_closure {
+            f.write_str("closure")?;
+        } else {
+            write!(f, "{}", self.fn_name)?;
+        }
+
+        match self.num_args {
+            1 => f.write_str(" (1 argument)")?,
+            n => write!(f, " ({} arguments)", n)?,
+        }
+
+        if let Some(ref source) = self.source {
+            write!(f, " in '{}'", source)?;
+        }
+
+        if !self.pos.is_none() {
+            write!(f, ", called at {}", self.pos)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<CallFrame> for Dynamic {
+    /// Convert a `CallFrame` into an object map with the fields `fn_name`, `num_args`, `line`,
+    /// `position` and `source`.
+    ///
+    /// Under `no_object`, the `CallFrame` is converted into a string instead.
+    fn from(frame: CallFrame) -> Self {
+        #[cfg(not(feature = "no_object"))]
+        {
+            let mut map = Map::new();
+            map.insert("fn_name".into(), frame.fn_name.into());
+            map.insert("num_args".into(), (frame.num_args as INT).into());
+            map.insert(
+                "line".into(),
+                frame
+                    .pos
+                    .line()
+                    .map_or_else(Dynamic::default, |line| (line as INT).into()),
+            );
+            map.insert(
+                "position".into(),
+                frame
+                    .pos
+                    .position()
+                    .map_or_else(Dynamic::default, |pos| (pos as INT).into()),
+            );
+            map.insert(
+                "source".into(),
+                frame.source.map_or_else(Dynamic::default, Into::into),
+            );
+            map.into()
+        }
+
+        #[cfg(feature = "no_object")]
+        {
+            frame.to_string().into()
+        }
+    }
+}
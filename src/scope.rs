@@ -1,8 +1,9 @@
 //! Module that defines the `Scope` type representing a function call-stack scope.
 
 use crate::dynamic::{Dynamic, Variant};
+use crate::utils::ImmutableString;
 
-use crate::stdlib::{borrow::Cow, boxed::Box, iter, string::String, vec::Vec};
+use crate::stdlib::{borrow::Cow, boxed::Box, iter, ops::Range, string::String, vec::Vec};
 
 /// Type of an entry in the Scope.
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
@@ -70,8 +71,8 @@ pub struct Scope<'a> {
     values: Vec<Dynamic>,
     /// Type of the entry.
     types: Vec<EntryType>,
-    /// (Name, alias) of the entry. The alias is Boxed because it occurs rarely.
-    names: Vec<(Cow<'a, str>, Option<Box<String>>)>,
+    /// (Name, alias, type annotation) of the entry. The alias is Boxed because it occurs rarely.
+    names: Vec<(Cow<'a, str>, Option<Box<String>>, Option<ImmutableString>)>,
 }
 
 impl<'a> Scope<'a> {
@@ -248,7 +249,7 @@ impl<'a> Scope<'a> {
         entry_type: EntryType,
         value: Dynamic,
     ) -> &mut Self {
-        self.names.push((name.into(), None));
+        self.names.push((name.into(), None, None));
         self.types.push(entry_type);
         self.values.push(value.into());
         self
@@ -304,7 +305,7 @@ impl<'a> Scope<'a> {
         self.names
             .iter()
             .rev() // Always search a Scope in reverse order
-            .any(|(key, _)| name == key.as_ref())
+            .any(|(key, _, _)| name == key.as_ref())
     }
     /// Find an entry in the Scope, starting from the last.
     #[inline(always)]
@@ -313,7 +314,10 @@ impl<'a> Scope<'a> {
             .iter()
             .enumerate()
             .rev() // Always search a Scope in reverse order
-            .find_map(|(index, (key, _))| {
+            .find_map(|(index, (key, _, _))| {
+                #[cfg(all(test, not(feature = "no_std")))]
+                tests::count_name_comparison();
+
                 if name == key.as_ref() {
                     Some((index, self.types[index]))
                 } else {
@@ -321,6 +325,21 @@ impl<'a> Scope<'a> {
                 }
             })
     }
+    /// Find an entry within a range of the Scope, starting from the last.
+    #[inline(always)]
+    pub(crate) fn get_index_in(&self, name: &str, range: Range<usize>) -> Option<usize> {
+        let start = range.start;
+
+        self.names[range]
+            .iter()
+            .rposition(|(key, _, _)| {
+                #[cfg(all(test, not(feature = "no_std")))]
+                tests::count_name_comparison();
+
+                name == key.as_ref()
+            })
+            .map(|index| start + index)
+    }
     /// Get the value of an entry in the Scope, starting from the last.
     ///
     /// # Example
@@ -339,7 +358,7 @@ impl<'a> Scope<'a> {
             .iter()
             .enumerate()
             .rev()
-            .find(|(_, (key, _))| name == key.as_ref())
+            .find(|(_, (key, _, _))| name == key.as_ref())
             .and_then(|(index, _)| self.values[index].flatten_clone().try_cast())
     }
     /// Update the value of the named entry.
@@ -376,12 +395,42 @@ impl<'a> Scope<'a> {
         }
         self
     }
-    /// Get a mutable reference to an entry in the Scope.
+    /// Get a mutable reference to the value of a variable in the Scope, starting from the last.
+    ///
+    /// Returns `None` if no entry matching the specified name is found, or if it is a constant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Dynamic, Scope};
+    ///
+    /// let mut my_scope = Scope::new();
+    ///
+    /// my_scope.push("x", 42_i64);
+    /// my_scope.push_constant("y", 1_i64);
+    ///
+    /// *my_scope.get_value_mut("x").unwrap() = Dynamic::from(0_i64);
+    /// assert_eq!(my_scope.get_value::<i64>("x").unwrap(), 0);
+    ///
+    /// assert!(my_scope.get_value_mut("y").is_none());
+    /// ```
+    #[inline(always)]
+    pub fn get_value_mut(&mut self, name: &str) -> Option<&mut Dynamic> {
+        match self.get_index(name)? {
+            (index, EntryType::Normal) => Some(self.get_mut(index).0),
+            (_, EntryType::Constant) => None,
+        }
+    }
+    /// Get a mutable reference to an entry in the Scope, together with its type annotation (if any).
     #[inline(always)]
-    pub(crate) fn get_mut(&mut self, index: usize) -> (&mut Dynamic, EntryType) {
+    pub(crate) fn get_mut(
+        &mut self,
+        index: usize,
+    ) -> (&mut Dynamic, EntryType, Option<&ImmutableString>) {
         (
             self.values.get_mut(index).expect("invalid index in Scope"),
             self.types[index],
+            self.names[index].2.as_ref(),
         )
     }
     /// Update the access type of an entry in the Scope.
@@ -392,6 +441,13 @@ impl<'a> Scope<'a> {
         entry.1 = Some(Box::new(alias));
         self
     }
+    /// Set the type annotation of an entry in the Scope.
+    #[inline(always)]
+    pub(crate) fn set_entry_annotation(&mut self, index: usize, typ: ImmutableString) -> &mut Self {
+        let entry = self.names.get_mut(index).expect("invalid index in Scope");
+        entry.2 = Some(typ);
+        self
+    }
     /// Clone the Scope, keeping only the last instances of each variable name.
     /// Shadowed variables are omitted in the copy.
     #[inline]
@@ -402,9 +458,11 @@ impl<'a> Scope<'a> {
             .iter()
             .enumerate()
             .rev()
-            .for_each(|(index, (name, alias))| {
-                if !entries.names.iter().any(|(key, _)| key == name) {
-                    entries.names.push((name.clone(), alias.clone()));
+            .for_each(|(index, (name, alias, typ))| {
+                if !entries.names.iter().any(|(key, _, _)| key == name) {
+                    entries
+                        .names
+                        .push((name.clone(), alias.clone(), typ.clone()));
                     entries.types.push(self.types[index]);
                     entries.values.push(self.values[index].clone());
                 }
@@ -420,7 +478,7 @@ impl<'a> Scope<'a> {
         self.names
             .into_iter()
             .zip(self.types.into_iter().zip(self.values.into_iter()))
-            .map(|((name, alias), (typ, value))| (name, typ, value, alias.map(|v| *v)))
+            .map(|((name, alias, _), (typ, value))| (name, typ, value, alias.map(|v| *v)))
     }
     /// Get an iterator to entries in the Scope.
     /// Shared values are flatten-cloned.
@@ -459,7 +517,7 @@ impl<'a> Scope<'a> {
         self.names
             .iter()
             .zip(self.types.iter().zip(self.values.iter()))
-            .map(|((name, _), (typ, value))| (name.as_ref(), typ.is_constant(), value))
+            .map(|((name, _, _), (typ, value))| (name.as_ref(), typ.is_constant(), value))
     }
 }
 
@@ -467,9 +525,30 @@ impl<'a, K: Into<Cow<'a, str>>> iter::Extend<(K, EntryType, Dynamic)> for Scope<
     #[inline(always)]
     fn extend<T: IntoIterator<Item = (K, EntryType, Dynamic)>>(&mut self, iter: T) {
         iter.into_iter().for_each(|(name, typ, value)| {
-            self.names.push((name.into(), None));
+            self.names.push((name.into(), None, None));
             self.types.push(typ);
             self.values.push(value);
         });
     }
 }
+
+#[cfg(test)]
+#[cfg(not(feature = "no_std"))]
+pub(crate) mod tests {
+    use std::cell::Cell;
+
+    std::thread_local! {
+        /// Number of variable names compared while searching a `Scope` on the current thread.
+        static NAME_COMPARISONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Count a variable name comparison.
+    pub(crate) fn count_name_comparison() {
+        NAME_COMPARISONS.with(|n| n.set(n.get() + 1));
+    }
+
+    /// Get the number of variable names compared on the current thread, resetting it to zero.
+    pub(crate) fn take_name_comparisons() -> usize {
+        NAME_COMPARISONS.with(|n| n.replace(0))
+    }
+}
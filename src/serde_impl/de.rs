@@ -45,8 +45,12 @@ impl<'de> DynamicDeserializer<'de> {
     }
     /// Shortcut for a type conversion error.
     fn type_error_str<T>(&self, error: &str) -> Result<T, Box<EvalAltResult>> {
-        EvalAltResult::ErrorMismatchOutputType(error.into(), self.value.type_name().into(), NO_POS)
-            .into()
+        EvalAltResult::ErrorMismatchOutputType(
+            error.into(),
+            self.value.type_name().into(),
+            NO_POS.into(),
+        )
+        .into()
     }
     fn deserialize_int<V: Visitor<'de>>(
         &mut self,
@@ -125,7 +129,7 @@ impl Error for Box<EvalAltResult> {
     fn custom<T: fmt::Display>(err: T) -> Self {
         EvalAltResult::ErrorParsing(
             ParseErrorType::BadInput(LexError::ImproperSymbol(err.to_string())),
-            NO_POS,
+            NO_POS.into(),
         )
         .into()
     }
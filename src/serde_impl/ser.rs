@@ -99,7 +99,7 @@ pub fn to_dynamic<T: Serialize>(value: T) -> Result<Dynamic, Box<EvalAltResult>>
 
 impl Error for Box<EvalAltResult> {
     fn custom<T: fmt::Display>(err: T) -> Self {
-        EvalAltResult::ErrorRuntime(err.to_string().into(), NO_POS).into()
+        EvalAltResult::ErrorRuntime(err.to_string().into(), NO_POS.into()).into()
     }
 }
 
@@ -295,16 +295,24 @@ impl Serializer for &mut DynamicSerializer {
             make_variant(_variant, content)
         }
         #[cfg(feature = "no_object")]
-        return EvalAltResult::ErrorMismatchOutputType("Dynamic".into(), "map".into(), NO_POS)
-            .into();
+        return EvalAltResult::ErrorMismatchOutputType(
+            "Dynamic".into(),
+            "map".into(),
+            NO_POS.into(),
+        )
+        .into();
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Box<EvalAltResult>> {
         #[cfg(not(feature = "no_index"))]
         return Ok(DynamicSerializer::new(Array::new().into()));
         #[cfg(feature = "no_index")]
-        return EvalAltResult::ErrorMismatchOutputType("Dynamic".into(), "array".into(), NO_POS)
-            .into();
+        return EvalAltResult::ErrorMismatchOutputType(
+            "Dynamic".into(),
+            "array".into(),
+            NO_POS.into(),
+        )
+        .into();
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Box<EvalAltResult>> {
@@ -337,7 +345,8 @@ impl Serializer for &mut DynamicSerializer {
             let err_type = "map";
             #[cfg(not(feature = "no_object"))]
             let err_type = "array";
-            EvalAltResult::ErrorMismatchOutputType("Dynamic".into(), err_type.into(), NO_POS).into()
+            EvalAltResult::ErrorMismatchOutputType("Dynamic".into(), err_type.into(), NO_POS.into())
+                .into()
         }
     }
 
@@ -345,8 +354,12 @@ impl Serializer for &mut DynamicSerializer {
         #[cfg(not(feature = "no_object"))]
         return Ok(DynamicSerializer::new(Map::new().into()));
         #[cfg(feature = "no_object")]
-        return EvalAltResult::ErrorMismatchOutputType("Dynamic".into(), "map".into(), NO_POS)
-            .into();
+        return EvalAltResult::ErrorMismatchOutputType(
+            "Dynamic".into(),
+            "map".into(),
+            NO_POS.into(),
+        )
+        .into();
     }
 
     fn serialize_struct(
@@ -370,8 +383,12 @@ impl Serializer for &mut DynamicSerializer {
             map: Map::with_capacity(_len),
         });
         #[cfg(feature = "no_object")]
-        return EvalAltResult::ErrorMismatchOutputType("Dynamic".into(), "map".into(), NO_POS)
-            .into();
+        return EvalAltResult::ErrorMismatchOutputType(
+            "Dynamic".into(),
+            "map".into(),
+            NO_POS.into(),
+        )
+        .into();
     }
 }
 
@@ -480,7 +497,11 @@ impl SerializeMap for DynamicSerializer {
             let key = mem::take(&mut self._key)
                 .take_immutable_string()
                 .map_err(|typ| {
-                    EvalAltResult::ErrorMismatchOutputType("string".into(), typ.into(), NO_POS)
+                    EvalAltResult::ErrorMismatchOutputType(
+                        "string".into(),
+                        typ.into(),
+                        NO_POS.into(),
+                    )
                 })?;
             let _value = _value.serialize(&mut *self)?;
             let map = self._value.downcast_mut::<Map>().unwrap();
@@ -500,7 +521,7 @@ impl SerializeMap for DynamicSerializer {
         {
             let _key: Dynamic = _key.serialize(&mut *self)?;
             let _key = _key.take_immutable_string().map_err(|typ| {
-                EvalAltResult::ErrorMismatchOutputType("string".into(), typ.into(), NO_POS)
+                EvalAltResult::ErrorMismatchOutputType("string".into(), typ.into(), NO_POS.into())
             })?;
             let _value = _value.serialize(&mut *self)?;
             let map = self._value.downcast_mut::<Map>().unwrap();
@@ -20,8 +20,12 @@ impl<'a> ImmutableStringDeserializer<'a> {
     }
     /// Shortcut for a type conversion error.
     fn type_error<T>(&self) -> Result<T, Box<EvalAltResult>> {
-        EvalAltResult::ErrorMismatchOutputType(type_name::<T>().into(), "string".into(), NO_POS)
-            .into()
+        EvalAltResult::ErrorMismatchOutputType(
+            type_name::<T>().into(),
+            "string".into(),
+            NO_POS.into(),
+        )
+        .into()
     }
 }
 
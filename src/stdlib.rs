@@ -16,6 +16,7 @@ mod inner {
     pub use core_error as error;
 
     pub mod collections {
+        pub use alloc::collections::{BTreeMap, VecDeque};
         pub use hashbrown::{hash_map, hash_set, HashMap, HashSet};
     }
 }
@@ -0,0 +1,387 @@
+//! Module implementing the strict mode checks of variables and functions.
+
+use crate::ast::{Expr, ScriptFnDef, Stmt};
+use crate::engine::{Engine, KEYWORD_EVAL, KEYWORD_THIS};
+use crate::parse_error::{ParseError, ParseErrorType};
+use crate::scope::Scope;
+use crate::token::{is_keyword_function, Position};
+
+#[cfg(not(feature = "no_module"))]
+use crate::ast::FnAccess;
+
+#[cfg(not(feature = "no_module"))]
+use crate::module::{Module, ModuleRef};
+
+#[cfg(not(feature = "no_module"))]
+use crate::stdlib::collections::HashMap;
+
+use crate::stdlib::{collections::HashSet, format, string::ToString, vec::Vec};
+
+/// State of a running strict mode check.
+struct Checker<'a> {
+    /// The `Engine`.
+    engine: &'a Engine,
+    /// Names and numbers of parameters of all functions that can be called without a namespace.
+    functions: HashSet<(&'a str, usize)>,
+    /// The `Scope` passed to the compiler.
+    scope: &'a Scope<'a>,
+    /// Variables declared in the current function (or at global level).
+    vars: Vec<&'a str>,
+    /// Is the current code inside a function?
+    in_function: bool,
+    /// Can variables be defined at run-time in the current function (e.g. by `eval`)?
+    dynamic: bool,
+    /// Modules imported in the current function (or at global level), with their paths
+    /// (`None` if not a constant).
+    #[cfg(not(feature = "no_module"))]
+    modules: Vec<(&'a str, Option<&'a str>)>,
+    /// Modules imported at global level, which are also available to functions.
+    #[cfg(not(feature = "no_module"))]
+    global_modules: Vec<(&'a str, Option<&'a str>)>,
+    /// Modules resolved so far, by path (`None` if the module cannot be resolved).
+    #[cfg(not(feature = "no_module"))]
+    resolved: HashMap<&'a str, Option<Module>>,
+    /// Errors found.
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Checker<'a> {
+    /// Check a block of statements.
+    fn statements(&mut self, statements: &'a [Stmt]) {
+        let num_vars = self.vars.len();
+        #[cfg(not(feature = "no_module"))]
+        let num_modules = self.modules.len();
+
+        statements.iter().for_each(|stmt| self.stmt(stmt));
+
+        self.vars.truncate(num_vars);
+        #[cfg(not(feature = "no_module"))]
+        self.modules.truncate(num_modules);
+    }
+
+    /// Check a script-defined function.
+    fn function(&mut self, f: &'a ScriptFnDef) {
+        self.vars = f.params.iter().map(|p| p.as_str()).collect();
+        self.in_function = true;
+        self.dynamic = false;
+        #[cfg(not(feature = "no_module"))]
+        self.modules.clear();
+
+        match f.body {
+            Stmt::Block(ref statements, _) => self.statements(statements),
+            ref stmt => self.stmt(stmt),
+        }
+    }
+
+    /// Check a statement.
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Noop(_) | Stmt::Continue(_) | Stmt::Break(_) => (),
+            Stmt::IfThenElse(guard, x, _) => {
+                self.expr(guard);
+                self.stmt(&x.0);
+                if let Some(ref stmt) = x.1 {
+                    self.stmt(stmt);
+                }
+            }
+            Stmt::While(guard, body, _) => {
+                self.expr(guard);
+                self.stmt(body);
+            }
+            Stmt::Loop(body, _) => self.stmt(body),
+            Stmt::For(expr, x, _) => {
+                self.expr(expr);
+                self.vars.push(&x.0.name);
+                self.stmt(&x.1);
+                self.vars.pop();
+            }
+            Stmt::Let(x, expr, _) | Stmt::Const(x, expr, _) => {
+                if let Some(expr) = expr {
+                    self.expr(expr);
+                }
+                self.vars.push(&x.0.name);
+            }
+            Stmt::Assignment(x, _) => {
+                self.expr(&x.2);
+                self.expr(&x.0);
+            }
+            Stmt::Block(statements, _) => self.statements(statements),
+            Stmt::TryCatch(x, _, _) => {
+                self.stmt(&x.0);
+
+                let num_vars = self.vars.len();
+                self.vars
+                    .extend(x.1.iter().chain(x.2.iter()).map(|v| v.name.as_str()));
+                self.stmt(&x.3);
+                self.vars.truncate(num_vars);
+
+                if let Some(ref stmt) = x.4 {
+                    self.stmt(stmt);
+                }
+            }
+            Stmt::Expr(expr) => self.expr(expr),
+            Stmt::ReturnWithVal(_, expr, _) => {
+                if let Some(expr) = expr {
+                    self.expr(expr);
+                }
+            }
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Import(expr, alias, _) => {
+                self.expr(expr);
+
+                if let Some(alias) = alias {
+                    self.modules.push((&alias.name, import_path(expr)));
+                }
+            }
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Export(list, _) => list
+                .iter()
+                .for_each(|(var, _)| self.variable(&var.name, var.pos)),
+            #[cfg(not(feature = "no_closure"))]
+            Stmt::Share(_) => (),
+            Stmt::Source(_) => (),
+        }
+    }
+
+    /// Check an expression.
+    fn expr(&mut self, expr: &'a Expr) {
+        match expr {
+            #[cfg(not(feature = "no_module"))]
+            Expr::Variable(x) if x.1.is_some() => {
+                let namespace = x.1.as_ref().unwrap();
+                let found = self.in_module(namespace, |m| m.contains_var(&x.3.name));
+
+                if !found {
+                    self.errors.push(
+                        ParseErrorType::VariableNotDefined(format!("{}{}", namespace, x.3.name))
+                            .into_err(x.3.pos),
+                    );
+                }
+            }
+            Expr::Variable(x) => self.variable(&x.3.name, x.3.pos),
+            Expr::Stmt(statements, _, _) => self.statements(statements),
+            Expr::Expr(x) => self.expr(x),
+            Expr::FnCall(x, pos, _) => {
+                x.args.iter().for_each(|e| self.expr(e));
+
+                // Operators
+                if x.native_only {
+                    return;
+                }
+
+                let num_args = x.args.len();
+
+                let found = match x.namespace {
+                    #[cfg(not(feature = "no_module"))]
+                    Some(ref namespace) => self.in_module(namespace, |m| {
+                        m.iter_fn().any(|f| {
+                            f.access == FnAccess::Public && f.name == x.name && f.params == num_args
+                        })
+                    }),
+                    #[cfg(feature = "no_module")]
+                    Some(_) => true,
+                    None if x.name == KEYWORD_EVAL => {
+                        // 'eval' can define new variables
+                        self.dynamic = true;
+                        true
+                    }
+                    None => {
+                        is_keyword_function(&x.name)
+                            || self.functions.contains(&(x.name.as_ref(), num_args))
+                    }
+                };
+
+                if !found {
+                    let name = match x.namespace {
+                        Some(ref namespace) => format!("{}{}", namespace, x.name),
+                        None => x.name.to_string(),
+                    };
+                    self.errors
+                        .push(ParseErrorType::FnNotDefined(name, num_args).into_err(*pos));
+                }
+            }
+            Expr::Dot(x, _) => {
+                self.expr(&x.lhs);
+                self.dot_rhs(&x.rhs);
+            }
+            Expr::Index(x, _, _) => {
+                self.expr(&x.lhs);
+                self.index_rhs(&x.rhs);
+            }
+            Expr::In(x, _) | Expr::And(x, _) | Expr::Or(x, _) => {
+                self.expr(&x.lhs);
+                self.expr(&x.rhs);
+            }
+            Expr::Array(x, _, _) => x.iter().for_each(|e| self.expr(e)),
+            Expr::Map(x, _, _) => x.iter().for_each(|(_, e)| self.expr(e)),
+            // Custom syntax can define new variables, and its symbols may not be variables at all
+            Expr::Custom(_, _, _) => self.dynamic = true,
+            _ => (),
+        }
+    }
+
+    /// Check the right-hand side of a `.` property access, which starts with a property or a
+    /// method call.
+    ///
+    /// Method calls are not checked because they may call function pointers stored in
+    /// object map properties.
+    fn dot_rhs(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Property(_) => (),
+            Expr::FnCall(x, _, _) => x.args.iter().for_each(|e| self.expr(e)),
+            Expr::Dot(x, _) => {
+                self.dot_rhs(&x.lhs);
+                self.dot_rhs(&x.rhs);
+            }
+            Expr::Index(x, _, _) => {
+                self.dot_rhs(&x.lhs);
+                self.index_rhs(&x.rhs);
+            }
+            Expr::Expr(x) => self.dot_rhs(x),
+            expr => self.expr(expr),
+        }
+    }
+
+    /// Check the right-hand side of a `[` ... `]` indexing operation, which starts with the index.
+    fn index_rhs(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Index(x, _, _) => {
+                self.expr(&x.lhs);
+                self.index_rhs(&x.rhs);
+            }
+            Expr::Dot(x, _) => {
+                self.expr(&x.lhs);
+                self.dot_rhs(&x.rhs);
+            }
+            expr => self.expr(expr),
+        }
+    }
+
+    /// Check that a variable is defined.
+    fn variable(&mut self, name: &str, pos: Position) {
+        let found = self.dynamic
+            || self.vars.contains(&name)
+            || self.engine.declared_vars.contains(name)
+            || (self.in_function && name == KEYWORD_THIS)
+            || (!self.in_function && self.scope.contains(name));
+
+        if !found {
+            self.errors
+                .push(ParseErrorType::VariableNotDefined(name.into()).into_err(pos));
+        }
+    }
+
+    /// Test an imported module.
+    ///
+    /// Returns `true` if the test passes, or if the module is not known statically (i.e. its path
+    /// is not a constant or it cannot be resolved at compile time).
+    /// Returns `false` if the test fails, or if the module is not imported at all.
+    #[cfg(not(feature = "no_module"))]
+    fn in_module(&mut self, namespace: &ModuleRef, test: impl FnOnce(&Module) -> bool) -> bool {
+        let root = namespace[0].name.as_str();
+
+        // Functions can also use modules imported at global level
+        let global = if self.in_function {
+            &self.global_modules[..]
+        } else {
+            &[]
+        };
+
+        let path = match self
+            .modules
+            .iter()
+            .rev()
+            .chain(global.iter().rev())
+            .find(|(alias, _)| *alias == root)
+        {
+            Some(&(_, Some(path))) => path,
+            Some(&(_, None)) => return true,
+            None => return false,
+        };
+
+        let (engine, pos) = (self.engine, namespace[0].pos);
+
+        let module = self.resolved.entry(path).or_insert_with(|| {
+            engine
+                .module_resolver
+                .as_ref()
+                .and_then(|resolver| resolver.resolve(engine, path, pos).ok())
+        });
+
+        match module {
+            Some(module) => match namespace
+                .iter()
+                .skip(1)
+                .try_fold(&*module, |m, name| m.get_sub_module(&name.name))
+            {
+                Some(module) => test(module),
+                None => false,
+            },
+            None => true,
+        }
+    }
+}
+
+/// Get the path of an `import` statement if it is a constant.
+#[cfg(not(feature = "no_module"))]
+fn import_path(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::StringConstant(x, _) => Some(&x.name),
+        Expr::Expr(x) => import_path(x),
+        _ => None,
+    }
+}
+
+impl Engine {
+    /// Check that all variables and functions used by statements and script-defined functions
+    /// are defined, returning all errors found, sorted by position.
+    pub(crate) fn check_strict(
+        &self,
+        scope: &Scope,
+        statements: &[Stmt],
+        functions: &[ScriptFnDef],
+    ) -> Vec<ParseError> {
+        let mut checker = Checker {
+            engine: self,
+            functions: self
+                .global_module
+                .iter_fn()
+                .chain(self.packages.iter_fn())
+                .map(|f| (f.name.as_str(), f.params))
+                .chain(functions.iter().map(|f| (f.name.as_str(), f.params.len())))
+                .collect(),
+            scope,
+            vars: Default::default(),
+            in_function: false,
+            dynamic: false,
+            #[cfg(not(feature = "no_module"))]
+            modules: Default::default(),
+            #[cfg(not(feature = "no_module"))]
+            global_modules: Default::default(),
+            #[cfg(not(feature = "no_module"))]
+            resolved: Default::default(),
+            errors: Default::default(),
+        };
+
+        checker.statements(statements);
+
+        #[cfg(not(feature = "no_module"))]
+        {
+            checker.global_modules = statements
+                .iter()
+                .filter_map(|stmt| match stmt {
+                    Stmt::Import(expr, Some(alias), _) => {
+                        Some((alias.name.as_str(), import_path(expr)))
+                    }
+                    _ => None,
+                })
+                .collect();
+        }
+
+        functions.iter().for_each(|f| checker.function(f));
+
+        let mut errors = checker.errors;
+        errors.sort_by_key(|err| err.position());
+        errors
+    }
+}
@@ -0,0 +1,230 @@
+//! Module implementing the runner of test functions in scripts.
+
+use crate::ast::AST;
+use crate::engine::Engine;
+use crate::result::EvalAltResult;
+
+#[cfg(not(feature = "no_function"))]
+use crate::scope::Scope;
+
+use crate::stdlib::{
+    boxed::Box,
+    fmt::Write,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Name of the source for scripts without one in test reports.
+const NO_SOURCE: &str = "<script>";
+
+/// Options for running test functions in scripts.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TestOptions {
+    /// Prefix of the names of test functions.  Default `test_`.
+    pub prefix: String,
+    /// Only run test functions with names containing this text.  Default `None`.
+    pub filter: Option<String>,
+}
+
+impl Default for TestOptions {
+    fn default() -> Self {
+        Self {
+            prefix: "test_".into(),
+            filter: None,
+        }
+    }
+}
+
+/// Result of running a single test function.
+#[derive(Debug)]
+pub struct TestResult {
+    /// Source of the script defining the test function, if any.
+    pub source: Option<String>,
+    /// Name of the test function.
+    pub name: String,
+    /// Error raised by the test function, or `None` if it passed.
+    pub error: Option<Box<EvalAltResult>>,
+}
+
+impl TestResult {
+    /// Did the test pass?
+    #[inline(always)]
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Results of running test functions.
+#[derive(Debug, Default)]
+pub struct TestReport {
+    /// Results of all tests, in order of running.
+    pub results: Vec<TestResult>,
+}
+
+impl TestReport {
+    /// Number of tests that passed.
+    pub fn num_passed(&self) -> usize {
+        self.results.iter().filter(|r| r.passed()).count()
+    }
+    /// Number of tests that failed.
+    pub fn num_failed(&self) -> usize {
+        self.results.len() - self.num_passed()
+    }
+    /// Did all tests pass?
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(TestResult::passed)
+    }
+    /// Add the results of another report to the end of this one.
+    pub fn append(&mut self, mut other: Self) {
+        self.results.append(&mut other.results);
+    }
+    /// Export the report in the [Test Anything Protocol](https://testanything.org) (TAP)
+    /// version 13 format.
+    ///
+    /// Each failed test is followed by a YAML block with the error message and, if known,
+    /// its line and position.  Tests in scripts without a source are named under `<script>`.
+    pub fn to_tap(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("TAP version 13\n");
+        let _ = writeln!(output, "1..{}", self.results.len());
+
+        for (i, r) in self.results.iter().enumerate() {
+            let source = r.source.as_deref().unwrap_or(NO_SOURCE);
+
+            let err = match r.error {
+                None => {
+                    let _ = writeln!(output, "ok {} - {}: {}", i + 1, source, r.name);
+                    continue;
+                }
+                Some(ref err) => err,
+            };
+
+            let _ = writeln!(output, "not ok {} - {}: {}", i + 1, source, r.name);
+            output.push_str("  ---\n");
+            let _ = writeln!(output, "  message: {}", yaml_string(&err.to_string()));
+
+            let pos = err.position();
+
+            if let (Some(line), Some(position)) = (pos.line(), pos.position()) {
+                let _ = writeln!(output, "  line: {}", line);
+                let _ = writeln!(output, "  position: {}", position);
+            }
+
+            output.push_str("  ...\n");
+        }
+
+        output
+    }
+}
+
+/// Quote a string for YAML.
+fn yaml_string(s: &str) -> String {
+    let mut output = String::with_capacity(s.len() + 2);
+
+    output.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            ch => output.push(ch),
+        }
+    }
+    output.push('"');
+
+    output
+}
+
+#[cfg(not(feature = "no_function"))]
+impl Engine {
+    /// Run all test functions in an `AST`, i.e. public functions without parameters and with
+    /// names starting with `test_` (see `TestOptions`), in the order they are defined.
+    ///
+    /// Each test function is called in isolation, with a fresh `Scope` and the limits of the
+    /// `Engine` applied anew.  Statements at global level are not run.
+    ///
+    /// A test fails if it raises an error, e.g. via `assert` in the
+    /// [`TestingPackage`][crate::packages::TestingPackage] or `throw`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, TestOptions};
+    /// use rhai::packages::{Package, TestingPackage};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.load_package(TestingPackage::new().get());
+    ///
+    /// let ast = engine.compile(
+    ///     "
+    ///         fn add(x, y) { x + y }
+    ///
+    ///         fn test_add() { assert_eq(add(1, 2), 3); }
+    ///         fn test_add_wrong() { assert_eq(add(1, 2), 4); }
+    ///     ",
+    /// )?;
+    ///
+    /// let report = engine.run_tests(&ast, &TestOptions::default());
+    ///
+    /// assert_eq!(report.results.len(), 2);
+    /// assert!(report.results[0].passed());
+    /// assert!(!report.results[1].passed());
+    /// assert!(report.to_tap().contains("not ok 2 - <script>: test_add_wrong"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_tests(&self, ast: &AST, options: &TestOptions) -> TestReport {
+        let mut tests: Vec<_> = ast
+            .iter_functions()
+            .filter(|(access, name, num_params, _)| {
+                !access.is_private()
+                    && *num_params == 0
+                    && name.starts_with(&options.prefix)
+                    && match options.filter {
+                        Some(ref filter) => name.contains(filter.as_str()),
+                        None => true,
+                    }
+            })
+            .map(|(_, _, _, f)| f)
+            .collect();
+
+        tests.sort_by_key(|f| f.span.start());
+
+        let results = tests
+            .into_iter()
+            .map(|f| TestResult {
+                source: f.source.as_ref().map(|name| name.to_string()),
+                name: f.name.to_string(),
+                error: self
+                    .call_fn_dynamic(&mut Scope::new(), ast, &f.name, None, [])
+                    .err()
+                    .map(|err| match *err {
+                        // Report the error within the test function itself
+                        EvalAltResult::ErrorInFunctionCall(name, _, _, err, _)
+                            if name == f.name =>
+                        {
+                            err
+                        }
+                        err => Box::new(err),
+                    }),
+            })
+            .collect();
+
+        TestReport { results }
+    }
+}
+
+#[cfg(feature = "no_function")]
+impl Engine {
+    /// Run all test functions in an `AST`.
+    ///
+    /// There are no functions under the `no_function` feature, so the report is always empty.
+    #[inline(always)]
+    pub fn run_tests(&self, _ast: &AST, _options: &TestOptions) -> TestReport {
+        Default::default()
+    }
+}
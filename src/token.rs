@@ -8,7 +8,8 @@ use crate::engine::{
 #[cfg(not(feature = "no_closure"))]
 use crate::engine::KEYWORD_IS_SHARED;
 
-use crate::parse_error::LexError;
+use crate::parse_error::{LexError, ParseError};
+use crate::utils::ImmutableString;
 use crate::StaticVec;
 use crate::INT;
 
@@ -18,40 +19,50 @@ use crate::FLOAT;
 use crate::stdlib::{
     borrow::Cow,
     boxed::Box,
-    char, fmt, format,
+    char,
+    collections::VecDeque,
+    fmt, format,
     iter::Peekable,
+    slice,
     str::{Chars, FromStr},
     string::{String, ToString},
+    vec::Vec,
 };
 
 type LERR = LexError;
 
-pub type TokenStream<'a, 't> = Peekable<TokenIterator<'a, 't>>;
-
 /// A location (line number + character position) in the input script.
 ///
 /// # Limitations
 ///
-/// In order to keep footprint small, both line number and character position have 16-bit resolution,
-/// meaning they go up to a maximum of 65,535 lines and 65,535 characters per line.
+/// In order to keep footprint small, a `Position` is packed into 48 bits.
+/// Both line number and character position have 24-bit resolution, meaning they go up to a
+/// maximum of 16,777,214 lines and 16,777,214 characters per line.
 ///
-/// Advancing beyond the maximum line length or maximum number of lines is not an error but has no effect.
+/// A script going beyond the maximum line length or maximum number of lines fails to tokenize
+/// with `LexError::PositionTooLarge`.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
 pub struct Position {
-    /// Line number - 0 = none
-    line: u16,
-    /// Character position - 0 = BOL
-    pos: u16,
+    /// Line number (0 = none) in the top 24 bits, then character position (0 = BOL) in the
+    /// bottom 24 bits, most significant part first.
+    bits: [u16; 3],
 }
 
 /// No `Position`.
 pub const NO_POS: Position = Position::NONE;
 
+/// Maximum line number or character position in a `Position`, plus one.
+///
+/// A line number or character position reaching this value is out of range.
+pub(crate) const MAX_POS: u32 = (1 << 24) - 1;
+
 impl Position {
     /// A `Position` representing no position.
-    pub const NONE: Self = Self { line: 0, pos: 0 };
+    pub const NONE: Self = Self { bits: [0, 0, 0] };
     /// A `Position` representing the first position.
-    pub const START: Self = Self { line: 1, pos: 0 };
+    pub const START: Self = Self {
+        bits: [0, 1 << 8, 0],
+    };
 
     /// Create a new `Position`.
     ///
@@ -60,32 +71,62 @@ impl Position {
     ///
     /// # Panics
     ///
-    /// Panics if `line` is zero.
+    /// Panics if `line` is zero, or if `line` or `position` is beyond the maximum.
     #[inline(always)]
-    pub fn new(line: u16, position: u16) -> Self {
+    pub fn new(line: u32, position: u32) -> Self {
         assert!(line != 0, "line cannot be zero");
 
+        Self::try_new(line, position).expect("line or position is beyond the maximum")
+    }
+    /// Create a new `Position`, or `None` if `line` is zero or if `line` or `position` is beyond
+    /// the maximum.
+    ///
+    /// If `position` is zero, then it is at the beginning of a line.
+    #[inline(always)]
+    pub fn try_new(line: u32, position: u32) -> Option<Self> {
+        if line == 0 || line >= MAX_POS || position >= MAX_POS {
+            None
+        } else {
+            Some(Self::from_raw(line, position))
+        }
+    }
+    /// Create a `Position` from a raw line number and a raw character position.
+    #[inline(always)]
+    fn from_raw(line: u32, position: u32) -> Self {
         Self {
-            line,
-            pos: position,
+            bits: [
+                (line >> 8) as u16,
+                ((line << 8) | (position >> 16)) as u16,
+                position as u16,
+            ],
         }
     }
+    /// Raw line number (0 = none).
+    #[inline(always)]
+    fn raw_line(self) -> u32 {
+        ((self.bits[0] as u32) << 8) | (self.bits[1] as u32 >> 8)
+    }
+    /// Raw character position (0 = BOL).
+    #[inline(always)]
+    fn raw_pos(self) -> u32 {
+        ((self.bits[1] as u32 & 0xff) << 16) | self.bits[2] as u32
+    }
     /// Get the line number (1-based), or `None` if there is no position.
     #[inline(always)]
     pub fn line(self) -> Option<usize> {
         if self.is_none() {
             None
         } else {
-            Some(self.line as usize)
+            Some(self.raw_line() as usize)
         }
     }
     /// Get the character position (1-based), or `None` if at beginning of a line.
     #[inline(always)]
     pub fn position(self) -> Option<usize> {
-        if self.is_none() || self.pos == 0 {
+        if self.is_none() || self.raw_pos() == 0 {
             None
         } else {
-            Some(self.pos as usize)
+            Some(self.raw_pos() as usize)
         }
     }
     /// Advance by one character position.
@@ -93,9 +134,11 @@ impl Position {
     pub(crate) fn advance(&mut self) {
         assert!(!self.is_none(), "cannot advance Position::none");
 
-        // Advance up to maximum position
-        if self.pos < u16::MAX {
-            self.pos += 1;
+        // Advance up to maximum position, which is then reported as out of range
+        let position = self.raw_pos();
+
+        if position < MAX_POS {
+            *self = Self::from_raw(self.raw_line(), position + 1);
         }
     }
     /// Go backwards by one character position.
@@ -106,29 +149,35 @@ impl Position {
     #[inline(always)]
     pub(crate) fn rewind(&mut self) {
         assert!(!self.is_none(), "cannot rewind Position::none");
-        assert!(self.pos > 0, "cannot rewind at position 0");
-        self.pos -= 1;
+        assert!(self.raw_pos() > 0, "cannot rewind at position 0");
+        *self = Self::from_raw(self.raw_line(), self.raw_pos() - 1);
     }
     /// Advance to the next line.
     #[inline(always)]
     pub(crate) fn new_line(&mut self) {
         assert!(!self.is_none(), "cannot advance Position::none");
 
-        // Advance up to maximum position
-        if self.line < u16::MAX {
-            self.line += 1;
-            self.pos = 0;
+        // Advance up to maximum position, which is then reported as out of range
+        let line = self.raw_line();
+
+        if line < MAX_POS {
+            *self = Self::from_raw(line + 1, 0);
         }
     }
+    /// Is the line number or the character position of this `Position` out of range?
+    #[inline(always)]
+    pub(crate) fn is_out_of_range(self) -> bool {
+        self.raw_line() >= MAX_POS || self.raw_pos() >= MAX_POS
+    }
     /// Is this `Position` at the beginning of a line?
     #[inline(always)]
     pub fn is_beginning_of_line(self) -> bool {
-        self.line == 0 && !self.is_none()
+        self.raw_line() == 0 && !self.is_none()
     }
     /// Is there no `Position`?
     #[inline(always)]
     pub fn is_none(self) -> bool {
-        self == Self::NONE
+        self.raw_line() == 0
     }
 }
 
@@ -145,7 +194,7 @@ impl fmt::Display for Position {
         if self.is_none() {
             write!(f, "none")
         } else {
-            write!(f, "line {}, position {}", self.line, self.pos)
+            write!(f, "line {}, position {}", self.raw_line(), self.raw_pos())
         }
     }
 }
@@ -153,7 +202,199 @@ impl fmt::Display for Position {
 impl fmt::Debug for Position {
     #[inline(always)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}:{}", self.line, self.pos)
+        write!(f, "{}:{}", self.raw_line(), self.raw_pos())
+    }
+}
+
+/// A span (start and end `Position`s, both inclusive) in the input script.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, Default)]
+pub struct Span {
+    /// Start position.
+    start: Position,
+    /// End position.
+    end: Position,
+}
+
+impl Span {
+    /// A `Span` representing no position.
+    pub const NONE: Self = Self {
+        start: Position::NONE,
+        end: Position::NONE,
+    };
+
+    /// Create a new `Span`.
+    #[inline(always)]
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+    /// Get the start `Position` of this `Span`.
+    #[inline(always)]
+    pub fn start(self) -> Position {
+        self.start
+    }
+    /// Get the end `Position` of this `Span`.
+    #[inline(always)]
+    pub fn end(self) -> Position {
+        self.end
+    }
+    /// Is there no `Span`?
+    #[inline(always)]
+    pub fn is_none(self) -> bool {
+        self.start.is_none()
+    }
+}
+
+impl fmt::Display for Span {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_none() {
+            write!(f, "none")
+        } else {
+            write!(
+                f,
+                "line {}, position {} to line {}, position {}",
+                self.start.raw_line(),
+                self.start.raw_pos(),
+                self.end.raw_line(),
+                self.end.raw_pos()
+            )
+        }
+    }
+}
+
+impl fmt::Debug for Span {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}-{}:{}",
+            self.start,
+            self.end.raw_line(),
+            self.end.raw_pos()
+        )
+    }
+}
+
+/// A location in the input script where an error occurs: a `Position`, the `Span` of the
+/// expression or statement containing it, and the name of the source (e.g. the script file path).
+#[derive(Eq, PartialEq, Hash, Clone)]
+pub struct Location {
+    /// Position.
+    pos: Position,
+    /// Span of the expression, statement or token containing the position, if known.
+    span: Span,
+    /// Name of the source, if any.
+    /// `None` if not yet known, which happens until the error leaves the script it occurs in.
+    source: Option<Option<ImmutableString>>,
+}
+
+impl Location {
+    /// A `Location` representing no position.
+    pub const NONE: Self = Self {
+        pos: Position::NONE,
+        span: Span::NONE,
+        source: None,
+    };
+
+    /// Create a new `Location` at a `Position`, with no source.
+    #[inline(always)]
+    pub fn new(pos: Position) -> Self {
+        Self {
+            pos,
+            span: Span::NONE,
+            source: None,
+        }
+    }
+    /// Get the `Position` of this `Location`.
+    #[inline(always)]
+    pub fn position(&self) -> Position {
+        self.pos
+    }
+    /// Get the `Span` of the expression, statement or token containing this `Location`.
+    ///
+    /// If the `Span` is not known, it only covers the `Position` of this `Location`.
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        if self.span.is_none() {
+            Span::new(self.pos, self.pos)
+        } else {
+            self.span
+        }
+    }
+    /// Get the name of the source (e.g. the script file path) of this `Location`, if any.
+    #[inline(always)]
+    pub fn source(&self) -> Option<&str> {
+        match self.source {
+            Some(Some(ref source)) => Some(source.as_str()),
+            _ => None,
+        }
+    }
+    /// Is there no `Position`?
+    #[inline(always)]
+    pub fn is_none(&self) -> bool {
+        self.pos.is_none()
+    }
+    /// Move this `Location` to another `Position`, forgetting its `Span`.
+    #[inline(always)]
+    pub(crate) fn set_position(&mut self, pos: Position) {
+        self.pos = pos;
+        self.span = Span::NONE;
+    }
+    /// Set the `Span` containing this `Location`, if it has a `Position` but neither a `Span`
+    /// nor a source yet.
+    #[inline(always)]
+    pub(crate) fn fill_span(&mut self, span: Span) {
+        if self.span.is_none()
+            && self.source.is_none()
+            && span.start <= self.pos
+            && self.pos <= span.end
+        {
+            self.span = span;
+        }
+    }
+    /// Set the name of the source of this `Location`, if it has a `Position` but no source yet.
+    #[inline(always)]
+    pub(crate) fn fill_source(&mut self, source: &Option<ImmutableString>) {
+        if self.source.is_none() && !self.pos.is_none() {
+            self.source = Some(source.clone());
+        }
+    }
+}
+
+impl Default for Location {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl From<Position> for Location {
+    #[inline(always)]
+    fn from(pos: Position) -> Self {
+        Self::new(pos)
+    }
+}
+
+impl fmt::Display for Location {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pos, f)?;
+
+        if let Some(source) = self.source() {
+            write!(f, " in '{}'", source)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Location {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = self.source() {
+            write!(f, "{}:", source)?;
+        }
+        fmt::Debug::fmt(&self.span(), f)
     }
 }
 
@@ -216,6 +457,8 @@ pub enum Token {
     Colon,
     /// `::`
     DoubleColon,
+    /// `->`
+    RightArrow,
     /// `,`
     Comma,
     /// `.`
@@ -285,6 +528,8 @@ pub enum Token {
     Try,
     /// `catch`
     Catch,
+    /// `finally`
+    Finally,
     /// `+=`
     PlusAssign,
     /// `-=`
@@ -371,6 +616,7 @@ impl Token {
                 SemiColon => ";",
                 Colon => ":",
                 DoubleColon => "::",
+                RightArrow => "->",
                 Comma => ",",
                 Period => ".",
                 MapStart => "#{",
@@ -402,6 +648,7 @@ impl Token {
                 Throw => "throw",
                 Try => "try",
                 Catch => "catch",
+                Finally => "finally",
                 PlusAssign => "+=",
                 MinusAssign => "-=",
                 MultiplyAssign => "*=",
@@ -455,6 +702,7 @@ impl Token {
             ";" => SemiColon,
             ":" => Colon,
             "::" => DoubleColon,
+            "->" => RightArrow,
             "," => Comma,
             "." => Period,
             "#{" => MapStart,
@@ -486,6 +734,7 @@ impl Token {
             "throw" => Throw,
             "try" => Try,
             "catch" => Catch,
+            "finally" => Finally,
             "+=" => PlusAssign,
             "-=" => MinusAssign,
             "*=" => MultiplyAssign,
@@ -521,11 +770,11 @@ impl Token {
             #[cfg(feature = "no_module")]
             "import" | "export" | "as" => Reserved(syntax.into()),
 
-            "===" | "!==" | "->" | "<-" | "=>" | ":=" | "::<" | "(*" | "*)" | "#" | "public"
-            | "new" | "use" | "module" | "package" | "var" | "static" | "shared" | "with"
-            | "do" | "each" | "then" | "goto" | "exit" | "switch" | "match" | "case"
-            | "default" | "void" | "null" | "nil" | "spawn" | "thread" | "go" | "sync"
-            | "async" | "await" | "yield" => Reserved(syntax.into()),
+            "===" | "!==" | "<-" | "=>" | ":=" | "::<" | "(*" | "*)" | "#" | "public" | "new"
+            | "use" | "module" | "package" | "var" | "static" | "shared" | "with" | "do"
+            | "each" | "then" | "goto" | "exit" | "switch" | "match" | "case" | "default"
+            | "void" | "null" | "nil" | "spawn" | "thread" | "go" | "sync" | "async" | "await"
+            | "yield" => Reserved(syntax.into()),
 
             KEYWORD_PRINT | KEYWORD_DEBUG | KEYWORD_TYPE_OF | KEYWORD_EVAL | KEYWORD_FN_PTR
             | KEYWORD_FN_PTR_CALL | KEYWORD_FN_PTR_CURRY | KEYWORD_IS_DEF_VAR
@@ -688,7 +937,7 @@ impl Token {
             Import | Export | As => true,
 
             True | False | Let | Const | If | Else | While | Loop | For | In | Continue | Break
-            | Return | Throw | Try | Catch => true,
+            | Return | Throw | Try | Catch | Finally => true,
 
             _ => false,
         }
@@ -746,7 +995,7 @@ pub struct TokenizeState {
     pub comment_level: usize,
     /// Return `None` at the end of the stream instead of `Some(Token::EOF)`?
     pub end_with_none: bool,
-    /// Include comments?
+    /// Include all comments?  Doc comments are always included.
     pub include_comments: bool,
 }
 
@@ -906,24 +1155,24 @@ fn eat_next(stream: &mut impl InputStream, pos: &mut Position) -> Option<char> {
     stream.get_next()
 }
 
-/// Scan for a block comment until the end.
+/// Scan for a block comment until the end, recording its text in `comment` (if any).
 fn scan_comment(
     stream: &mut impl InputStream,
     state: &mut TokenizeState,
     pos: &mut Position,
-    comment: &mut String,
+    mut comment: Option<&mut String>,
 ) {
     while let Some(c) = stream.get_next() {
         pos.advance();
 
-        if state.include_comments {
+        if let Some(ref mut comment) = comment {
             comment.push(c);
         }
 
         match c {
             '/' => {
                 if let Some(c2) = stream.get_next() {
-                    if state.include_comments {
+                    if let Some(ref mut comment) = comment {
                         comment.push(c2);
                     }
                     if c2 == '*' {
@@ -934,7 +1183,7 @@ fn scan_comment(
             }
             '*' => {
                 if let Some(c2) = stream.get_next() {
-                    if state.include_comments {
+                    if let Some(ref mut comment) = comment {
                         comment.push(c2);
                     }
                     if c2 == '/' {
@@ -953,6 +1202,14 @@ fn scan_comment(
     }
 }
 
+/// Is a comment a doc comment, i.e. `/// ...` (but not `//// ...`) or `/** ... */`
+/// (but not `/*** ... */` or `/**/`)?
+#[inline]
+pub fn is_doc_comment(comment: &str) -> bool {
+    (comment.starts_with("///") && !comment.starts_with("////"))
+        || (comment.starts_with("/**") && !comment.starts_with("/***") && comment != "/**/")
+}
+
 /// _[INTERNALS]_ Get the next token from the `InputStream`.
 /// Exported under the `internals` feature only.
 ///
@@ -1013,10 +1270,14 @@ fn get_next_token_inner(
     // Still inside a comment?
     if state.comment_level > 0 {
         let start_pos = *pos;
-        let mut comment = String::new();
-        scan_comment(stream, state, pos, &mut comment);
+        let mut comment = if state.include_comments {
+            Some(String::new())
+        } else {
+            None
+        };
+        scan_comment(stream, state, pos, comment.as_mut());
 
-        if state.include_comments {
+        if let Some(comment) = comment {
             return Some((Token::Comment(comment), start_pos));
         }
     }
@@ -1219,7 +1480,7 @@ fn get_next_token_inner(
             }
             ('-', '>') => {
                 eat_next(stream, pos);
-                return Some((Token::Reserved("->".into()), start_pos));
+                return Some((Token::RightArrow, start_pos));
             }
             ('-', '-') => {
                 eat_next(stream, pos);
@@ -1246,7 +1507,10 @@ fn get_next_token_inner(
             ('/', '/') => {
                 eat_next(stream, pos);
 
-                let mut comment = if state.include_comments {
+                // Doc comments are always kept
+                let keep = state.include_comments || stream.peek_next() == Some('/');
+
+                let mut comment = if keep {
                     "//".to_string()
                 } else {
                     String::new()
@@ -1258,13 +1522,13 @@ fn get_next_token_inner(
                         break;
                     }
 
-                    if state.include_comments {
+                    if keep {
                         comment.push(c);
                     }
                     pos.advance();
                 }
 
-                if state.include_comments {
+                if state.include_comments || (keep && is_doc_comment(&comment)) {
                     return Some((Token::Comment(comment), start_pos));
                 }
             }
@@ -1273,15 +1537,19 @@ fn get_next_token_inner(
 
                 eat_next(stream, pos);
 
-                let mut comment = if state.include_comments {
-                    "/*".to_string()
+                // Doc comments are always kept
+                let mut comment = if state.include_comments || stream.peek_next() == Some('*') {
+                    Some("/*".to_string())
                 } else {
-                    String::new()
+                    None
                 };
-                scan_comment(stream, state, pos, &mut comment);
+                scan_comment(stream, state, pos, comment.as_mut());
 
-                if state.include_comments {
-                    return Some((Token::Comment(comment), start_pos));
+                match comment {
+                    Some(comment) if state.include_comments || is_doc_comment(&comment) => {
+                        return Some((Token::Comment(comment), start_pos));
+                    }
+                    _ => (),
                 }
             }
 
@@ -1628,14 +1896,33 @@ pub struct TokenIterator<'a, 'e> {
     pos: Position,
     /// Input character stream.
     stream: MultiInputsStream<'a>,
+    /// The input text, kept to lex it again when locating errors.
+    input: StaticVec<&'a str>,
     /// A processor function (if any) that maps a token to another.
     map: Option<Box<dyn Fn(Token) -> Token>>,
+    /// Doc comments and the function definition following them, read ahead but not yet returned,
+    /// with the `Position`s of their last characters.
+    pending: VecDeque<(Token, Position, Position)>,
+    /// Has the input gone beyond the maximum line number or character position?
+    out_of_range: bool,
+    /// Name of the source (e.g. the script file path) of the input, if any.
+    source: Option<ImmutableString>,
 }
 
-impl<'a> Iterator for TokenIterator<'a, '_> {
-    type Item = (Token, Position);
+impl<'a> TokenIterator<'a, '_> {
+    /// Get the next token from the input stream.
+    ///
+    /// The `Position` of the last character of the token is returned together with the token.
+    fn next_token(&mut self) -> Option<(Token, Position, Position)> {
+        // Nothing more is read beyond the maximum line number or character position
+        if self.out_of_range {
+            return if self.state.end_with_none {
+                None
+            } else {
+                Some((Token::EOF, self.pos, self.pos))
+            };
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
         let token = match get_next_token(&mut self.stream, &mut self.state, &mut self.pos) {
             // {EOF}
             None => None,
@@ -1649,8 +1936,6 @@ impl<'a> Iterator for TokenIterator<'a, '_> {
                 ("!==", false) => Token::LexError(LERR::ImproperSymbol(
                     "'!==' is not a valid operator. This is not JavaScript! Should it be '!='?".to_string(),
                 )),
-                ("->", false) => Token::LexError(LERR::ImproperSymbol(
-                    "'->' is not a valid symbol. This is not C or C++!".to_string())),
                 ("<-", false) => Token::LexError(LERR::ImproperSymbol(
                     "'<-' is not a valid symbol. This is not Go! Should it be '<='?".to_string(),
                 )),
@@ -1710,17 +1995,165 @@ impl<'a> Iterator for TokenIterator<'a, '_> {
             r => r,
         };
 
+        // The token ends at the current position
+        let end = self.pos;
+
         match token {
             None => None,
+            // Stop at the first token going beyond the maximum line number or character position
+            Some(_) if end.is_out_of_range() => {
+                self.out_of_range = true;
+                let err = LERR::PositionTooLarge(MAX_POS as usize - 1);
+                Some((Token::LexError(err), end, end))
+            }
             Some((token, pos)) => {
                 if let Some(ref map) = self.map {
-                    Some((map(token), pos))
+                    Some((map(token), pos, end))
                 } else {
-                    Some((token, pos))
+                    Some((token, pos, end))
                 }
             }
         }
     }
+    /// Get the next token, together with the `Position` of its last character.
+    pub(crate) fn next_with_end(&mut self) -> Option<((Token, Position), Position)> {
+        if let Some((token, pos, end)) = self.pending.pop_front() {
+            return Some(((token, pos), end));
+        }
+
+        match self.next_token() {
+            // Doc comments are only kept when they precede a function definition
+            Some((token @ Token::Comment(_), pos, end)) if !self.state.include_comments => {
+                self.pending.push_back((token, pos, end));
+
+                loop {
+                    match self.next_token() {
+                        Some((token @ Token::Comment(_), pos, end)) => {
+                            self.pending.push_back((token, pos, end))
+                        }
+                        #[cfg(not(feature = "no_function"))]
+                        Some((token @ Token::Fn, pos, end))
+                        | Some((token @ Token::Private, pos, end)) => {
+                            self.pending.push_back((token, pos, end));
+                            return self.next_with_end();
+                        }
+                        token => {
+                            self.pending.clear();
+                            return token.map(|(token, pos, end)| ((token, pos), end));
+                        }
+                    }
+                }
+            }
+            token => token.map(|(token, pos, end)| ((token, pos), end)),
+        }
+    }
+}
+
+impl<'a> Iterator for TokenIterator<'a, '_> {
+    type Item = (Token, Position);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_end().map(|(token, _)| token)
+    }
+}
+
+/// A peekable stream of tokens, keeping track of where the last token read ends.
+pub struct TokenStream<'a, 'e> {
+    /// Token iterator.
+    iter: TokenIterator<'a, 'e>,
+    /// Token peeked at but not yet read, with the `Position` of its last character.
+    peeked: Option<Option<((Token, Position), Position)>>,
+    /// `Position` of the last character of the last token read.
+    last_end: Position,
+}
+
+impl<'a, 'e> TokenStream<'a, 'e> {
+    /// Create a new `TokenStream`.
+    #[inline(always)]
+    pub fn new(iter: TokenIterator<'a, 'e>) -> Self {
+        Self {
+            iter,
+            peeked: None,
+            last_end: NO_POS,
+        }
+    }
+    /// Peek at the next token without reading it.
+    #[inline(always)]
+    pub fn peek(&mut self) -> Option<&(Token, Position)> {
+        let iter = &mut self.iter;
+
+        self.peeked
+            .get_or_insert_with(|| iter.next_with_end())
+            .as_ref()
+            .map(|(token, _)| token)
+    }
+    /// Get the `Position` of the last character of the last token read.
+    #[inline(always)]
+    pub fn last_end(&self) -> Position {
+        self.last_end
+    }
+    /// Get the name of the source (e.g. the script file path) of the input, if any.
+    #[inline(always)]
+    pub fn source(&self) -> Option<ImmutableString> {
+        self.iter.source.clone()
+    }
+    /// Record in a `ParseError` the `Span` of the token it occurs at, and the name of the source.
+    ///
+    /// The input is lexed again from the start to find where that token ends.
+    #[inline(always)]
+    pub(crate) fn locate_error(&self, mut err: ParseError) -> ParseError {
+        self.locate_errors(slice::from_mut(&mut err));
+        err
+    }
+    /// Record in each `ParseError` the `Span` of the token it occurs at, and the name of the source.
+    ///
+    /// The input is lexed again from the start to find where those tokens end.
+    pub(crate) fn locate_errors(&self, errors: &mut [ParseError]) {
+        if errors.is_empty() {
+            return;
+        }
+
+        let mut tokens = self
+            .iter
+            .engine
+            .lex_with_source(&self.iter.input, None, None);
+        let mut spans = Vec::new();
+
+        while let Some((token, start, end)) = tokens.next_token() {
+            if token.is_eof() {
+                break;
+            }
+            spans.push(Span::new(start, end));
+        }
+
+        for err in errors {
+            let pos = err.position();
+            let span = match spans.binary_search_by_key(&pos, |span| span.start()) {
+                Ok(index) if !pos.is_none() => spans[index],
+                _ => Span::NONE,
+            };
+
+            err.fill_location(span, &self.iter.source);
+        }
+    }
+}
+
+impl Iterator for TokenStream<'_, '_> {
+    type Item = (Token, Position);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match self.peeked.take() {
+            Some(next) => next,
+            None => self.iter.next_with_end(),
+        };
+
+        next.map(|(token, end)| {
+            self.last_end = end;
+            token
+        })
+    }
 }
 
 impl Engine {
@@ -1731,6 +2164,28 @@ impl Engine {
         input: impl IntoIterator<Item = &'a &'a str>,
         map: Option<Box<dyn Fn(Token) -> Token>>,
     ) -> TokenIterator<'a, 'e> {
+        self.lex_with_source(input, None, map)
+    }
+    /// Tokenize an input text stream, retaining all comments as `Token::Comment`.
+    #[inline]
+    pub(crate) fn lex_with_comments<'a, 'e>(
+        &'e self,
+        input: impl IntoIterator<Item = &'a &'a str>,
+    ) -> TokenIterator<'a, 'e> {
+        let mut stream = self.lex_with_source(input, None, None);
+        stream.state.include_comments = true;
+        stream
+    }
+    /// Tokenize an input text stream from a named source.
+    #[inline]
+    pub(crate) fn lex_with_source<'a: 's, 'e, 's>(
+        &'e self,
+        input: impl IntoIterator<Item = &'s &'a str>,
+        source: Option<&str>,
+        map: Option<Box<dyn Fn(Token) -> Token>>,
+    ) -> TokenIterator<'a, 'e> {
+        let input: StaticVec<_> = input.into_iter().copied().collect();
+
         TokenIterator {
             engine: self,
             state: TokenizeState {
@@ -1743,13 +2198,17 @@ impl Engine {
                 end_with_none: false,
                 include_comments: false,
             },
-            pos: Position::new(1, 0),
+            pos: Position::START,
             stream: MultiInputsStream {
                 buf: None,
-                streams: input.into_iter().map(|s| s.chars().peekable()).collect(),
+                streams: input.iter().map(|s| s.chars().peekable()).collect(),
                 index: 0,
             },
+            input,
             map,
+            pending: Default::default(),
+            out_of_range: false,
+            source: source.map(Into::into),
         }
     }
 }
@@ -92,6 +92,20 @@ pub fn calc_script_fn_hash<'a>(
     calc_fn_hash(modules, fn_name, Some(num), empty())
 }
 
+/// Calculate a `u64` hash key from the parameter type annotations of a script-defined function.
+///
+/// Parameters without type annotations are passed in as empty strings.
+#[cfg(not(feature = "no_function"))]
+pub(crate) fn calc_param_types_hash<'a>(types: impl Iterator<Item = &'a str>) -> u64 {
+    #[cfg(feature = "no_std")]
+    let s: &mut AHasher = &mut Default::default();
+    #[cfg(not(feature = "no_std"))]
+    let s = &mut DefaultHasher::new();
+
+    types.for_each(|t| t.hash(s));
+    s.finish()
+}
+
 /// Calculate a `u64` hash key from a module-qualified function name and parameter types.
 ///
 /// Module names are passed in via `&str` references from an iterator.
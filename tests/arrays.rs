@@ -1,5 +1,5 @@
 #![cfg(not(feature = "no_index"))]
-use rhai::{Array, Engine, EvalAltResult, RegisterFn, INT};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, RegisterFn, Scope, INT};
 
 #[test]
 fn test_arrays() -> Result<(), Box<EvalAltResult>> {
@@ -62,6 +62,52 @@ fn test_arrays() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_arrays_value_semantics() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let x = [1, 2, 3]; let y = x; y[0] = 42; x[0]")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>("let x = [1, [2, 3]]; let y = x; y[1][0] = 42; x[1][0] + y[1][0]")?,
+        44
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            "let x = [1, [2, 3]]; let y = x; let v = y[1][1]; y[1][1] = 42; x[1][1] + v"
+        )?,
+        6
+    );
+    assert!(engine.eval::<bool>("let x = [1, [2, 3]]; let y = x; 1 in y && !(42 in x)")?);
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn change(a) { a[0] = 42; a[0] }
+                let x = [1, 2, 3];
+                let r = change(x);
+                x[0] + r
+            "
+        )?,
+        43
+    );
+
+    let mut scope = Scope::new();
+    scope.push("x", vec![Dynamic::from(1 as INT)]);
+
+    let before = scope.get_value::<Array>("x").unwrap();
+    engine.consume_with_scope(&mut scope, "x[0] = 42;")?;
+    let after = scope.get_value::<Array>("x").unwrap();
+
+    assert_eq!(before[0].as_int().unwrap(), 1);
+    assert_eq!(after[0].as_int().unwrap(), 42);
+
+    Ok(())
+}
+
 #[test]
 #[cfg(not(feature = "no_object"))]
 fn test_array_with_structs() -> Result<(), Box<EvalAltResult>> {
@@ -0,0 +1,144 @@
+#![cfg(not(feature = "no_function"))]
+use rhai::{CallFrame, Engine, EvalAltResult, Position, INT};
+
+#[test]
+fn test_call_stack() {
+    let engine = Engine::new();
+
+    let err = engine
+        .eval::<INT>(
+            r"
+                fn foo(x, y) {
+                    bar(x) + y
+                }
+                fn bar(x) {
+                    x + z
+                }
+                let a = 1;
+                foo(a, 2)
+            ",
+        )
+        .expect_err("should error");
+
+    let stack = err.call_stack();
+
+    assert_eq!(
+        stack,
+        vec![
+            CallFrame {
+                fn_name: "bar".into(),
+                num_args: 1,
+                pos: Position::new(3, 21),
+                source: None,
+            },
+            CallFrame {
+                fn_name: "foo".into(),
+                num_args: 2,
+                pos: Position::new(9, 17),
+                source: None,
+            }
+        ]
+    );
+
+    assert_eq!(
+        stack
+            .iter()
+            .map(|frame| frame.to_string())
+            .collect::<Vec<_>>(),
+        vec![
+            "bar (1 argument), called at line 3, position 21",
+            "foo (2 arguments), called at line 9, position 17"
+        ]
+    );
+
+    // One level of nesting per call
+    match *err {
+        EvalAltResult::ErrorInFunctionCall(fn_name, 2, _, err, _) if fn_name == "foo" => match *err
+        {
+            EvalAltResult::ErrorInFunctionCall(fn_name, 1, _, err, _) if fn_name == "bar" => {
+                assert!(matches!(*err, EvalAltResult::ErrorVariableNotFound(_, _)))
+            }
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+
+    // Errors outside of functions have no call stack
+    let err = engine
+        .eval::<INT>("let x = 1; x + z")
+        .expect_err("should error");
+    assert!(err.call_stack().is_empty());
+}
+
+#[test]
+fn test_call_stack_sources() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile_sources_with_scope(
+        &Default::default(),
+        &[
+            (
+                "lib.rhai",
+                "fn check(x) {\n    if x < 0 { throw \"negative\"; }\n    x\n}",
+            ),
+            ("main.rhai", "let x = check(1);\ncheck(x - 2)"),
+        ],
+    )?;
+
+    let err = engine.eval_ast::<INT>(&ast).expect_err("should error");
+
+    let call_stack = err.call_stack();
+    assert_eq!(call_stack.len(), 1);
+
+    let CallFrame {
+        fn_name,
+        num_args,
+        pos,
+        source,
+    } = &call_stack[0];
+    assert_eq!(fn_name, "check");
+    assert_eq!(*num_args, 1);
+    assert_eq!((pos.line(), pos.position()), (Some(2), Some(1)));
+    assert_eq!(err.source(), Some("main.rhai"));
+    assert_eq!(source.as_deref().unwrap(), "lib.rhai");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_call_stack_catch() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let result = engine.eval::<String>(
+        r#"
+            fn foo(x) { bar(x, 1) }
+            fn bar(x, y) { x.len() }
+
+            let frames = "";
+
+            try {
+                foo(42);
+            } catch (err, stack) {
+                for f in stack {
+                    frames += f.fn_name + "/" + f.num_args + "@" + f.line + ":" + f.position + " ";
+                }
+            }
+
+            frames
+        "#,
+    )?;
+
+    assert_eq!(result, "bar/2@2:25 foo/1@8:17 ");
+
+    // Errors outside of functions have an empty call stack
+    assert_eq!(
+        engine.eval::<INT>(
+            "let n = -1; try { throw 42; } catch (err, stack) { n = stack.len(); } n"
+        )?,
+        0
+    );
+
+    Ok(())
+}
@@ -89,6 +89,23 @@ fn test_closures() -> Result<(), Box<EvalAltResult>> {
         "#
     )?);
 
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let a = 1;
+                let b = 10;
+                let c = 100;
+                let f = |x, y| {
+                    let r = c * x + b * y + a;
+                    let a = 1000;
+                    r + a
+                };
+                f.curry(3).call(2)
+            "#
+        )?,
+        1321
+    );
+
     engine.register_fn("plus_one", |x: INT| x + 1);
 
     assert_eq!(
@@ -12,3 +12,50 @@ fn test_comments() {
         .eval::<INT>("let /* I am a multi-line comment, yay! */ x = 5; x")
         .is_ok());
 }
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_comments_doc() -> Result<(), Box<rhai::EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        r"
+            /// Hello world
+            /// Multiple lines
+            //// Not a doc comment
+            fn foo(x) { x + 1 }
+
+            /** Block doc */
+            private fn bar() { 42 }
+
+            /// Not a function
+            let x = foo(1) /// Not a function either
+            ;
+
+            /*** Not a doc comment */
+            fn baz() {}
+
+            x
+        ",
+    )?;
+
+    assert_eq!(
+        ast.get_fn_comments("foo", 1).unwrap(),
+        ["/// Hello world", "/// Multiple lines"]
+    );
+    assert_eq!(ast.get_fn_comments("bar", 0).unwrap(), ["/** Block doc */"]);
+    assert!(ast.get_fn_comments("baz", 0).unwrap().is_empty());
+    assert!(ast.get_fn_comments("foo", 0).is_none());
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 2);
+
+    assert!(matches!(
+        *engine
+            .compile("fn foo() { /// Not at global level\n fn bar() {} }")
+            .expect_err("should error")
+            .0,
+        rhai::ParseErrorType::WrongFnDefinition
+    ));
+
+    Ok(())
+}
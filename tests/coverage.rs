@@ -0,0 +1,145 @@
+use rhai::{BranchKind, Coverage, Engine, EvalAltResult, INT};
+
+#[test]
+fn test_coverage_statements() -> Result<(), Box<EvalAltResult>> {
+    let coverage = Coverage::new();
+
+    let mut engine = Engine::new();
+    engine.set_coverage(Some(coverage.clone()));
+
+    let ast = engine.compile(
+        "let x = 0;\nfor i in range(0, 5) {\n    x += i;\n}\nwhile x < 0 {\n    x = 0;\n}\nx",
+    )?;
+
+    coverage.add_ast(&ast);
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 10);
+
+    let report = coverage.report();
+
+    assert_eq!(
+        report.lines(None),
+        vec![(1, 1), (2, 1), (3, 5), (5, 1), (6, 0), (8, 1)]
+    );
+    assert!(report
+        .statements()
+        .iter()
+        .any(|s| s.pos.line() == Some(6) && s.count == 0));
+
+    // Execution counts accumulate over runs until reset
+    engine.eval_ast::<INT>(&ast)?;
+    assert_eq!(coverage.report().lines(None)[2], (3, 10));
+
+    coverage.reset();
+    assert!(coverage.report().statements().is_empty());
+
+    // Without registering the AST, only executed statements are recorded
+    engine.eval_ast::<INT>(&ast)?;
+    assert_eq!(
+        coverage.report().lines(None),
+        vec![(1, 1), (2, 1), (3, 5), (5, 1), (8, 1)]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_coverage_branches() -> Result<(), Box<EvalAltResult>> {
+    let coverage = Coverage::new();
+
+    let mut engine = Engine::new();
+    engine.set_coverage(Some(coverage.clone()));
+
+    let ast = engine.compile(
+        r#"
+            let x = 0;
+            for i in range(0, 4) {
+                if i > 0 && i < 3 { x += 1; } else { x += 10; }
+                if i == 3 || i > 5 { x += 100; }
+                try { if i == 2 { throw 42; } } catch { x += 1000; }
+            }
+            x
+        "#,
+    )?;
+
+    coverage.add_ast(&ast);
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 1122);
+
+    let branches: Vec<_> = coverage
+        .report()
+        .branches()
+        .iter()
+        .map(|b| (b.pos.line().unwrap(), b.kind, b.counts))
+        .collect();
+
+    assert_eq!(
+        branches,
+        vec![
+            (4, BranchKind::If, [2, 2]),
+            (4, BranchKind::And, [3, 1]),
+            (5, BranchKind::If, [1, 3]),
+            (5, BranchKind::Or, [3, 1]),
+            (6, BranchKind::TryCatch, [3, 1]),
+            (6, BranchKind::If, [1, 3]),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(feature = "no_function"))]
+fn test_coverage_lcov() -> Result<(), Box<EvalAltResult>> {
+    let coverage = Coverage::new();
+
+    let mut engine = Engine::new();
+    engine.set_coverage(Some(coverage.clone()));
+
+    let ast = engine.compile_sources_with_scope(
+        &Default::default(),
+        &[
+            (
+                "lib.rhai",
+                "fn check(x) {\n    if x < 0 {\n        return 0;\n    }\n    x\n}",
+            ),
+            ("main.rhai", "let x = check(1);\nx"),
+        ],
+    )?;
+
+    coverage.add_ast(&ast);
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 1);
+
+    assert_eq!(
+        coverage.report().to_lcov(),
+        [
+            "TN:",
+            "SF:lib.rhai",
+            "BRDA:2,0,0,0",
+            "BRDA:2,0,1,1",
+            "BRF:2",
+            "BRH:1",
+            "DA:2,1",
+            "DA:3,0",
+            "DA:5,1",
+            "LF:3",
+            "LH:2",
+            "end_of_record",
+            "TN:",
+            "SF:main.rhai",
+            "BRF:0",
+            "BRH:0",
+            "DA:1,1",
+            "DA:2,1",
+            "LF:2",
+            "LH:2",
+            "end_of_record",
+            "",
+        ]
+        .join("\n")
+    );
+
+    Ok(())
+}
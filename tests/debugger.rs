@@ -0,0 +1,344 @@
+use rhai::{BreakPoint, Debugger, DebuggerCommand, DebuggerEvent, Engine, EvalAltResult, INT};
+
+#[cfg(not(feature = "no_optimize"))]
+use rhai::OptimizationLevel;
+use std::sync::{Arc, RwLock};
+
+#[test]
+fn test_debugger_break_points() -> Result<(), Box<EvalAltResult>> {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        || {
+            Debugger::new(
+                vec![
+                    BreakPoint::AtLine {
+                        source: None,
+                        line: 4,
+                    },
+                    BreakPoint::AtLine {
+                        source: Some("other.rhai".into()),
+                        line: 5,
+                    },
+                ],
+                DebuggerCommand::Continue,
+            )
+        },
+        move |context, _, event, pos| {
+            let x = context.scope.get_value::<INT>("x").unwrap();
+            logger
+                .write()
+                .unwrap()
+                .push((event, pos.line().unwrap(), x));
+            context.scope.set_value("x", x * 10);
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    let result = engine.eval::<INT>(
+        r"
+            let x = 0;
+            for i in range(0, 3) {
+                x += 1; x += 1;
+                x += 1;
+            }
+            x
+        ",
+    )?;
+
+    // Line break-points stop once for each loop iteration
+    assert_eq!(
+        *log.read().unwrap(),
+        vec![
+            (DebuggerEvent::BreakPoint(0), 4, 0),
+            (DebuggerEvent::BreakPoint(0), 4, 3),
+            (DebuggerEvent::BreakPoint(0), 4, 33),
+        ]
+    );
+    assert_eq!(result, 333);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_debugger_stepping() -> Result<(), Box<EvalAltResult>> {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        || Debugger::new(Default::default(), DebuggerCommand::StepInto),
+        move |context, _, _, pos| {
+            let line = pos.line().unwrap();
+            logger.write().unwrap().push(line);
+
+            Ok(match (line, context.call_level()) {
+                // Step over the call to `foo`
+                (7, _) => DebuggerCommand::StepOver,
+                // Step into the call to `foo`, then out of it
+                (8, _) => DebuggerCommand::StepInto,
+                (_, 1) => DebuggerCommand::StepOut,
+                _ => DebuggerCommand::StepInto,
+            })
+        },
+    );
+
+    engine.consume(
+        r"
+            fn foo(x) {
+                let y = x + 1;
+                y * 2
+            }
+            let a = 1;
+            let b = foo(a);
+            let c = foo(b);
+            a + b + c
+        ",
+    )?;
+
+    assert_eq!(*log.read().unwrap(), vec![6, 7, 8, 3, 9]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_object"))]
+fn test_debugger_functions() -> Result<(), Box<EvalAltResult>> {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        || {
+            Debugger::new(
+                vec![BreakPoint::AtFunction { name: "bar".into() }],
+                DebuggerCommand::Continue,
+            )
+        },
+        move |context, debugger, event, _| {
+            let frames: Vec<_> = debugger
+                .call_stack()
+                .map(|frame| format!("{}@{}", frame.fn_name, frame.pos.line().unwrap()))
+                .collect();
+            logger.write().unwrap().push((event, frames.join(" ")));
+
+            // Modify `this` while paused
+            if let Some(this_ptr) = context.this_ptr_mut() {
+                *this_ptr = (42 as INT).into();
+            }
+
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    let result = engine.eval::<INT>(
+        r"
+            fn foo(x) {
+                x.bar()
+            }
+            fn bar() {
+                this + 1
+            }
+            foo(0)
+        ",
+    )?;
+
+    assert_eq!(
+        *log.read().unwrap(),
+        vec![(DebuggerEvent::BreakPoint(0), "bar@3 foo@8".to_string())]
+    );
+    assert_eq!(result, 43);
+
+    Ok(())
+}
+
+#[test]
+fn test_debugger_abort() {
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        || Debugger::new(Default::default(), DebuggerCommand::StepInto),
+        |_, _, _, pos| {
+            if pos.line() == Some(2) {
+                Err("abort!".into())
+            } else {
+                Ok(DebuggerCommand::StepInto)
+            }
+        },
+    );
+
+    match *engine
+        .eval::<INT>("let x = 1;\nlet y = 2;\nx + y")
+        .expect_err("should error")
+    {
+        EvalAltResult::ErrorRuntime(value, pos) => {
+            assert_eq!(value.as_str().unwrap(), "abort!");
+            assert_eq!(pos.position().line(), Some(2));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_debugger_expressions() -> Result<(), Box<EvalAltResult>> {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+
+    let mut engine = Engine::new();
+
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    engine.register_debugger(
+        || Debugger::new(Default::default(), DebuggerCommand::StepExpression),
+        move |_, _, event, pos| {
+            logger
+                .write()
+                .unwrap()
+                .push((event, pos.line().unwrap(), pos.position().unwrap()));
+            Ok(DebuggerCommand::StepExpression)
+        },
+    );
+
+    let result = engine.eval::<INT>("let x = 40;\nx + (1 + 1)")?;
+
+    assert_eq!(
+        *log.read().unwrap(),
+        vec![
+            // `let` statement, then the `40`
+            (DebuggerEvent::Step, 1, 5),
+            (DebuggerEvent::Step, 1, 9),
+            // Outer `+` statement, then the inner `+` and its operands
+            (DebuggerEvent::Step, 2, 3),
+            (DebuggerEvent::Step, 2, 8),
+            (DebuggerEvent::Step, 2, 6),
+            (DebuggerEvent::Step, 2, 10),
+        ]
+    );
+    assert_eq!(result, 42);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_debugger_nested() -> Result<(), Box<EvalAltResult>> {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+    let inits = Arc::new(RwLock::new(0));
+    let counter = inits.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        move || {
+            *counter.write().unwrap() += 1;
+            Debugger::new(Default::default(), DebuggerCommand::StepInto)
+        },
+        move |context, debugger, event, pos| {
+            let line = pos.line().unwrap();
+            let frames: Vec<_> = debugger
+                .call_stack()
+                .map(|f| f.pos.line().unwrap())
+                .collect();
+            logger
+                .write()
+                .unwrap()
+                .push((event, line, context.call_level(), frames));
+
+            Ok(match line {
+                // Add a break-point inside the closure, then step over the calls to `map`
+                2 => {
+                    debugger.break_points_mut().push(BreakPoint::AtLine {
+                        source: None,
+                        line: 5,
+                    });
+                    DebuggerCommand::StepOver
+                }
+                3 | 4 => DebuggerCommand::StepOver,
+                _ => DebuggerCommand::Continue,
+            })
+        },
+    );
+
+    let result = engine.eval::<INT>(
+        r"
+            let x = [1, 2];
+            let y = x.map(|v| v + 1);
+            let z = x.map(|v| {
+                v * 2
+            });
+            y[1] + z[1]
+        ",
+    )?;
+
+    // The break-point added in the outer evaluation run is hit inside the closures,
+    // which are called from a native function
+    assert_eq!(
+        *log.read().unwrap(),
+        vec![
+            (DebuggerEvent::Step, 2, 0, vec![]),
+            (DebuggerEvent::Step, 3, 0, vec![]),
+            (DebuggerEvent::Step, 4, 0, vec![]),
+            (DebuggerEvent::BreakPoint(0), 5, 1, vec![4]),
+            (DebuggerEvent::BreakPoint(0), 5, 1, vec![4]),
+        ]
+    );
+    assert_eq!(result, 7);
+    assert_eq!(*inits.read().unwrap(), 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_debugger_eval() -> Result<(), Box<EvalAltResult>> {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+    let inits = Arc::new(RwLock::new(0));
+    let counter = inits.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        move || {
+            *counter.write().unwrap() += 1;
+            Debugger::new(Default::default(), DebuggerCommand::StepInto)
+        },
+        move |_, debugger, event, pos| {
+            logger.write().unwrap().push((event, pos.line().unwrap()));
+
+            // Add a break-point at the function called from the `eval`
+            debugger
+                .break_points_mut()
+                .push(BreakPoint::AtFunction { name: "foo".into() });
+
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    let result = engine.eval::<INT>(
+        r#"
+            fn foo() { 42 }
+            eval("foo()")
+        "#,
+    )?;
+
+    assert_eq!(
+        *log.read().unwrap(),
+        vec![(DebuggerEvent::Step, 3), (DebuggerEvent::BreakPoint(0), 2)]
+    );
+    assert_eq!(result, 42);
+    assert_eq!(*inits.read().unwrap(), 1);
+
+    Ok(())
+}
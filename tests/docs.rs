@@ -0,0 +1,149 @@
+#![cfg(feature = "metadata")]
+
+use rhai::{DocsFormat, DocsOptions, Engine, Module, RegisterFn, INT};
+use std::any::type_name;
+
+#[derive(Debug, Clone)]
+struct TestStruct {
+    x: INT,
+}
+
+#[test]
+fn test_docs_markdown() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+    engine.register_fn("+", |x: INT, _: ()| x);
+    engine.register_fn("-", |_: ()| 0 as INT);
+
+    let mut module = Module::new();
+    let hash = module.set_fn_1("double", |x: INT| Ok(x * 2));
+    module.update_fn_metadata(hash, &["x: INT", "INT"]);
+    module.update_fn_comments(
+        hash,
+        &["/// Double a number.", "///", "/// Overflows are errors."],
+    );
+
+    let mut sub_module = Module::new();
+    sub_module.set_fn_0("answer", || Ok(42 as INT));
+    module.set_sub_module("inner", sub_module);
+
+    let options = DocsOptions {
+        title: "My API".into(),
+        include_packages: false,
+        ..Default::default()
+    };
+
+    let pages = engine.gen_docs(vec![("utils/math", &module)], &options);
+    let int = type_name::<INT>();
+
+    assert_eq!(
+        pages
+            .iter()
+            .map(|p| (p.namespace.as_str(), p.file_name.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("", "index.md"),
+            ("utils/math", "utils-math.md"),
+            ("utils/math::inner", "utils-math-inner.md"),
+        ]
+    );
+
+    let index = &pages[0].content;
+    assert!(index.starts_with("# My API\n"));
+    assert!(index.contains("* [`utils/math`](utils-math.md)"));
+    assert!(index.contains("## Functions"));
+    assert!(index.contains(&format!("fn add(_: {0}, _: {0}) -> {0}", int)));
+    assert!(index.contains("## Operators"));
+    assert!(index.contains(&format!("{0} + () -> {0}", int)));
+    assert!(index.contains(&format!("-() -> {}", int)));
+
+    let math = &pages[1].content;
+    assert!(math.starts_with("# `utils/math`\n"));
+    assert!(math.contains("[My API](index.md)"));
+    assert!(math.contains("* [`utils/math::inner`](utils-math-inner.md)"));
+    assert!(math.contains(&format!(
+        "```rust\nfn double(x: {0}) -> {0}\n```\n\nDouble a number.\n\nOverflows are errors.\n",
+        int
+    )));
+
+    assert!(pages[2]
+        .content
+        .contains(&format!("fn answer() -> {}", int)));
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_docs_html() {
+    let mut engine = Engine::new();
+
+    engine.register_type_with_name::<TestStruct>("TestStruct");
+    engine.register_get_set(
+        "x",
+        |t: &mut TestStruct| t.x,
+        |t: &mut TestStruct, x: INT| t.x = x,
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    engine.register_indexer_get(|t: &mut TestStruct, i: INT| t.x + i);
+
+    let options = DocsOptions {
+        format: DocsFormat::Html,
+        include_packages: false,
+        ..Default::default()
+    };
+
+    let pages = engine.gen_docs(vec![], &options);
+    let int = type_name::<INT>();
+
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].file_name, "index.html");
+
+    let index = &pages[0].content;
+    assert!(index.starts_with("<!DOCTYPE html>"));
+    assert!(index.contains("<title>API Reference</title>"));
+    assert!(index.contains("<h2>Types</h2>"));
+    assert!(index.contains("<li><code>TestStruct ("));
+    assert!(index.contains("<h2>Properties</h2>\n<h3><code>x</code></h3>"));
+    assert!(index.contains(&format!(
+        "<pre><code>TestStruct.x -&gt; {0}\nTestStruct.x = {0}</code></pre>",
+        int
+    )));
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(index.contains(&format!(
+        "<h2>Indexers</h2>\n<pre><code>TestStruct[{0}] -&gt; {0}</code></pre>",
+        int
+    )));
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_module"))]
+fn test_docs_script_functions() -> Result<(), Box<rhai::EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        r#"
+            /** Say hello
+             * to someone.
+             */
+            fn hello(name) { "hello, " + name }
+        "#,
+    )?;
+    let module = Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+
+    let options = DocsOptions {
+        format: DocsFormat::Html,
+        include_packages: false,
+        ..Default::default()
+    };
+
+    let pages = engine.gen_docs(vec![("greetings", &module)], &options);
+
+    assert!(pages[1].content.contains(
+        "<h3><code>hello</code></h3>\n<pre><code>fn hello(name)</code></pre>\n<p>Say hello\nto someone.</p>"
+    ));
+
+    Ok(())
+}
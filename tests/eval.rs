@@ -60,6 +60,86 @@ fn test_eval_function() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_eval_new_variables() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let x = 1;
+                eval("let y = 42;");
+                { let z = 0; }
+                x
+            "#
+        )?,
+        1
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn foo() { let z = 0; z }
+                let x = 1;
+                eval("let y = 42;");
+                foo();
+                x + y
+            "#
+        )?,
+        43
+    );
+
+    // Variables defined before and after several `eval`'s, accessed in nested blocks
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let a = 1;
+                eval("let b = 10; let c = 100;");
+                let d = 1000;
+                eval("let e = 10000;");
+                let f = 100000;
+                {
+                    let g = 1000000;
+                    a + b + c + d + e + f + g
+                }
+            "#
+        )?,
+        1111111
+    );
+
+    // A variable defined by `eval` shadows one with the same name
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let x = 1;
+                let y = 2;
+                eval("let x = 40;");
+                { x += y; }
+                x
+            "#
+        )?,
+        42
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_closure"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let x = 1;
+                eval("let y = 41;");
+                let f = || x + y;
+                x = 2;
+                f.call()
+            "#
+        )?,
+        43
+    );
+
+    Ok(())
+}
+
 #[test]
 #[cfg(not(feature = "no_function"))]
 fn test_eval_override() -> Result<(), Box<EvalAltResult>> {
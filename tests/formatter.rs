@@ -0,0 +1,130 @@
+use rhai::{Engine, EvalAltResult, FormatOptions, LexError, ParseErrorType, INT};
+
+const SCRIPT: &str = r#"
+// Compute things
+
+let x=40+2;   // the answer
+let m = #{a:1, "b c": [1,2,3]};
+
+
+fn add(a,b){ a+b }
+
+let total = 0;
+for i in range(0, 10) { if i % 2 == 0 { continue; } total += add(i, x); }
+let v = if total > 100 { "big" } else { "small" };
+let z = (1 + 2) * 3 - (4 - 5) - -x;
+/* block
+   comment */
+total + m.a + z
+"#;
+
+const FORMATTED: &str = r#"// Compute things
+
+let x = 40 + 2; // the answer
+let m = #{a: 1, "b c": [1, 2, 3]};
+
+fn add(a, b) {
+    a + b
+}
+
+let total = 0;
+for i in range(0, 10) {
+    if i % 2 == 0 {
+        continue;
+    }
+    total += add(i, x);
+}
+let v = if total > 100 { "big" } else { "small" };
+let z = (1 + 2) * 3 - (4 - 5) - -x;
+/* block
+   comment */
+total + m.a + z
+"#;
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_function"))]
+fn test_formatter() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let options = FormatOptions::default();
+
+    let output = engine.format(SCRIPT, &options)?;
+
+    assert_eq!(output, FORMATTED);
+
+    // Formatting is idempotent
+    assert_eq!(engine.format(&output, &options)?, output);
+
+    // Formatted script has the same behavior
+    assert_eq!(engine.eval::<INT>(&output)?, engine.eval::<INT>(SCRIPT)?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_function"))]
+fn test_formatter_options() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let options = FormatOptions {
+        indent: "\t".into(),
+        max_width: 20,
+    };
+
+    assert_eq!(
+        engine.format("if x { print([1, 2, 3, 4, 5]) }", &options)?,
+        "if x {\n\tprint([\n\t\t1,\n\t\t2,\n\t\t3,\n\t\t4,\n\t\t5,\n\t])\n}\n"
+    );
+
+    // Closures with block bodies stay on the same line as the call
+    assert_eq!(
+        engine.format(
+            "[1, 2].for_each(|v| { print(v); });",
+            &FormatOptions::default()
+        )?,
+        "[1, 2].for_each(|v| {\n    print(v);\n});\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_closure"))]
+fn test_formatter_ast() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile("let x = 1; let f = |y| x + y; f.call(-(x + 1)) * 2")?;
+
+    let output = engine.format_ast(&ast, &FormatOptions::default())?;
+
+    assert_eq!(
+        output,
+        "let x = 1;\nlet f = |y| x + y;\nf.call(-(x + 1)) * 2\n"
+    );
+    assert_eq!(engine.eval::<INT>(&output)?, engine.eval_ast::<INT>(&ast)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_formatter_custom_syntax() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_custom_syntax(["twice", "$expr$"], 0, |context, inputs| {
+        Ok((context.eval_expression_tree(&inputs[0])?.as_int()? * 2).into())
+    })?;
+
+    assert!(matches!(
+        *engine
+            .format("let x = twice 21;", &FormatOptions::default())
+            .expect_err("should error")
+            .0,
+        ParseErrorType::BadInput(LexError::ImproperSymbol(_))
+    ));
+
+    Ok(())
+}
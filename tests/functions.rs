@@ -1,5 +1,5 @@
 #![cfg(not(feature = "no_function"))]
-use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
+use rhai::{Engine, EvalAltResult, ParseErrorType, RegisterFn, INT};
 
 #[test]
 fn test_functions() -> Result<(), Box<EvalAltResult>> {
@@ -199,3 +199,29 @@ fn test_function_is_def() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_function_signatures() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+
+    assert_eq!(engine.gen_fn_signatures(false), vec!["add(_, _)"]);
+    assert!(engine
+        .gen_fn_signatures(true)
+        .iter()
+        .any(|signature| signature == "len(_)"));
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let ast = engine.compile("fn foo(x, y: int) -> int { x + y } private fn bar() { 42 }")?;
+        let module = rhai::Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+
+        assert_eq!(
+            module.gen_fn_signatures().collect::<Vec<_>>(),
+            vec!["foo(x, y: int) -> int"]
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use rhai::{Engine, LintId, LintOptions, ParseError, Position, Severity};
+
+fn lint(engine: &Engine, script: &str) -> Result<Vec<(LintId, Position)>, ParseError> {
+    Ok(engine
+        .lint(script, &LintOptions::new())?
+        .into_iter()
+        .map(|w| (w.id, w.pos))
+        .collect())
+}
+
+#[test]
+fn test_lint_variables() -> Result<(), ParseError> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        lint(
+            &engine,
+            r"
+                let x = 1;
+                let y = 2;
+                let _z = 3;
+                print(y);
+                {
+                    let y = 3;
+                    y += 1;
+                }
+            "
+        )?,
+        vec![
+            (LintId::UnusedVariable, Position::new(2, 21)),
+            (LintId::ShadowedVariable, Position::new(7, 25)),
+            (LintId::UnusedAssignment, Position::new(8, 21)),
+        ]
+    );
+
+    // Overwritten before being read
+    assert_eq!(
+        lint(
+            &engine,
+            r"
+                let x = 1;
+                x = 2;
+                if x > 0 { x = 3; } else { x = 4; }
+                print(x);
+            "
+        )?,
+        vec![(LintId::UnusedAssignment, Position::new(2, 21))]
+    );
+
+    // Assignments in loops may be read by the next iteration
+    assert!(lint(
+        &engine,
+        "let x = 0; let y = 0; while x < 10 { y = x; x += 1; } print(y);"
+    )?
+    .is_empty());
+
+    // 'eval' may use any variable
+    assert!(lint(&engine, r#"let x = 42; eval("x")"#)?.is_empty());
+
+    // Loop variables are reported at their own positions
+    assert_eq!(
+        lint(
+            &engine,
+            r#"let x = 1; print(x); for x in range(0, 3) { print("hi"); }"#
+        )?,
+        vec![
+            (LintId::ShadowedVariable, Position::new(1, 26)),
+            (LintId::UnusedVariable, Position::new(1, 26)),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_lint_functions() -> Result<(), ParseError> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        lint(
+            &engine,
+            r"
+                fn foo(x, y, _z) {
+                    let x = y;
+                    x
+                }
+            "
+        )?,
+        vec![
+            (LintId::UnusedParameter, Position::new(2, 17)),
+            (LintId::ShadowedVariable, Position::new(3, 25)),
+        ]
+    );
+
+    // Variables outside functions are not visible
+    assert!(lint(&engine, "let x = 1; fn foo() { let x = 2; x } print(x);")?.is_empty());
+
+    #[cfg(not(feature = "no_closure"))]
+    {
+        // Captured variables are used
+        assert!(lint(&engine, "let x = 1; let f = || x + 1; x = 2; call(f)")?.is_empty());
+
+        assert_eq!(
+            lint(&engine, "let f = |x, y| x; call(f, 1, 2)")?,
+            vec![(LintId::UnusedParameter, Position::new(1, 9))]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_control_flow() -> Result<(), ParseError> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        lint(
+            &engine,
+            r#"
+                let x = 1;
+                loop {
+                    if x > 0 { break; } else { throw "oops"; }
+                    x += 1;
+                    print(x);
+                }
+                while true && 1 < 2 { }
+                if x == 1 { return; }
+                print(x);
+                return;
+                print("done");
+            "#
+        )?,
+        vec![
+            (LintId::UnreachableCode, Position::new(5, 21)),
+            (LintId::ConstantCondition, Position::new(8, 23)),
+            (LintId::UnreachableCode, Position::new(12, 17)),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_options() -> Result<(), ParseError> {
+    let engine = Engine::new();
+
+    let mut options = LintOptions::new();
+    options
+        .set_severity(LintId::UnusedVariable, Severity::Off)
+        .set_severity(LintId::ConstantCondition, Severity::Error);
+
+    let warnings = engine.lint("let x = 1; if false { }", &options)?;
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Error);
+    assert_eq!(
+        warnings[0].to_string(),
+        "error[constant_condition]: condition is always the same (line 1, position 15)"
+    );
+
+    assert_eq!(
+        LintId::from_name("unused_parameter"),
+        Some(LintId::UnusedParameter)
+    );
+
+    Ok(())
+}
@@ -101,6 +101,35 @@ fn test_map_assign() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_map_value_semantics() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let x = #{a: 1}; let y = x; y.a = 42; x.a")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>("let x = #{a: #{b: 1}}; let y = x; y.a.b = 42; x.a.b + y.a.b")?,
+        43
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn change(m) { m.a = 42; m.a }
+                let x = #{a: 1};
+                let r = change(x);
+                x.a + r
+            "
+        )?,
+        43
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_map_return() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -187,7 +216,7 @@ fn test_map_json() -> Result<(), Box<EvalAltResult>> {
     assert!(matches!(
         *engine.parse_json("   123", true).expect_err("should error"),
         EvalAltResult::ErrorParsing(ParseErrorType::MissingToken(token, _), pos)
-            if token == "{" && pos.position() == Some(4)
+            if token == "{" && pos.position().position() == Some(4)
     ));
 
     Ok(())
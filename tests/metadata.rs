@@ -0,0 +1,225 @@
+#![cfg(feature = "metadata")]
+
+use rhai::{Engine, FnKind, ImmutableString, Module, RegisterFn, INT};
+use std::any::type_name;
+
+#[derive(Debug, Clone)]
+struct TestStruct {
+    x: INT,
+}
+
+#[test]
+fn test_metadata_functions() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+    engine.register_fn("greet", |name: ImmutableString| format!("hello, {}", name));
+    engine.register_fn("+", |x: INT, _: ()| x);
+
+    let metadata = engine.gen_metadata(false);
+    let int = type_name::<INT>();
+
+    assert_eq!(
+        metadata
+            .functions
+            .iter()
+            .map(|f| (f.kind, f.signature.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                FnKind::Operator,
+                format!("+(_: {}, _: ()) -> {}", int, int).as_str()
+            ),
+            (
+                FnKind::Function,
+                format!("add(_: {}, _: {}) -> {}", int, int, int).as_str()
+            ),
+            (FnKind::Function, "greet(_: string) -> string"),
+        ]
+    );
+
+    let add = &metadata.functions[1];
+    assert_eq!(add.namespace, "");
+    assert_eq!(add.num_params, 2);
+    assert_eq!(add.params[0].name, None);
+    assert_eq!(add.params[0].typ.as_deref(), Some(int));
+    assert_eq!(add.return_type.as_deref(), Some(int));
+    assert!(!add.is_script);
+
+    let metadata = engine.gen_metadata(true);
+
+    assert!(metadata.functions.iter().any(|f| f.name == "len"));
+    assert!(metadata.iterators.contains(&"string".to_string()));
+
+    let json = engine.gen_metadata_to_json(false).unwrap();
+
+    assert!(json.contains(r#""name": "add""#));
+    assert!(json.contains(r#""numParams": 2"#));
+    assert!(json.contains(r#""returnType": "string""#));
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_metadata_custom_types() {
+    let mut engine = Engine::new();
+
+    engine.register_type_with_name::<TestStruct>("TestStruct");
+    engine.register_get_set(
+        "x",
+        |t: &mut TestStruct| t.x,
+        |t: &mut TestStruct, x: INT| t.x = x,
+    );
+    engine.register_iterator::<std::vec::IntoIter<INT>>();
+
+    #[cfg(not(feature = "no_index"))]
+    engine.register_indexer_get(|t: &mut TestStruct, i: INT| t.x + i);
+
+    let metadata = engine.gen_metadata(false);
+    let int = type_name::<INT>();
+
+    let getter = metadata
+        .functions
+        .iter()
+        .find(|f| f.kind == FnKind::Getter)
+        .unwrap();
+    assert_eq!(getter.name, "get$x");
+    assert_eq!(
+        getter.signature,
+        format!("get$x(_: &mut TestStruct) -> {}", int)
+    );
+
+    let setter = metadata
+        .functions
+        .iter()
+        .find(|f| f.kind == FnKind::Setter)
+        .unwrap();
+    assert_eq!(
+        setter.signature,
+        format!("set$x(_: &mut TestStruct, _: {})", int)
+    );
+    assert_eq!(setter.return_type.as_deref(), Some("()"));
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(metadata
+        .functions
+        .iter()
+        .any(|f| f.kind == FnKind::IndexGetter && f.num_params == 2));
+
+    assert_eq!(metadata.types.len(), 1);
+    assert_eq!(metadata.types[0].name, "TestStruct");
+    assert!(metadata.types[0].rust_type.ends_with("TestStruct"));
+
+    assert_eq!(metadata.iterators, vec![format!("IntoIter<{}>", int)]);
+}
+
+#[test]
+fn test_metadata_modules() {
+    let engine = Engine::new();
+
+    let mut sub_module = Module::new();
+    let hash = sub_module.set_fn_1("double", |x: INT| Ok(x * 2));
+    sub_module.update_fn_metadata(hash, &["x: INT", "INT"]);
+
+    let mut module = Module::new();
+    module.set_fn_0("answer", || Ok(42 as INT));
+    module.set_sub_module("inner", sub_module);
+
+    let metadata = engine.gen_module_metadata("foo", &module);
+    let int = type_name::<INT>();
+
+    assert_eq!(metadata.functions.len(), 1);
+    assert_eq!(metadata.functions[0].namespace, "foo");
+    assert_eq!(
+        metadata.functions[0].signature,
+        format!("answer() -> {}", int)
+    );
+
+    let double = &metadata.modules["inner"].functions[0];
+    assert_eq!(double.namespace, "foo::inner");
+    assert_eq!(double.params[0].name.as_deref(), Some("x"));
+    assert_eq!(double.signature, format!("double(x: {}) -> {}", int, int));
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_module"))]
+fn test_metadata_script_functions() -> Result<(), Box<rhai::EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        "
+            /// Add two numbers.
+            fn add(x, y: int) -> int { x + y }
+
+            private fn hidden() {}
+        ",
+    )?;
+    let module = Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+
+    let metadata = engine.gen_module_metadata("script", &module);
+
+    assert_eq!(metadata.functions.len(), 1);
+    assert!(metadata.functions[0].is_script);
+    assert_eq!(metadata.functions[0].signature, "add(x, y: int) -> int");
+    assert_eq!(metadata.functions[0].doc_comments, ["/// Add two numbers."]);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_module"))]
+mod test {
+    use rhai::plugin::*;
+
+    #[export_module]
+    pub mod greetings {
+        use rhai::{ImmutableString, INT};
+
+        /// Greet someone,
+        /// many times.
+        pub fn greet(name: &str, times: INT) -> ImmutableString {
+            name.repeat(times as usize).into()
+        }
+    }
+
+    /// Double a number.
+    #[export_fn]
+    pub fn double(x: rhai::INT) -> rhai::INT {
+        x * 2
+    }
+}
+
+#[test]
+#[cfg(not(feature = "no_module"))]
+fn test_metadata_plugins() {
+    use rhai::plugin::*;
+
+    let mut engine = Engine::new();
+
+    engine.load_package(rhai::exported_module!(test::greetings));
+
+    let metadata = engine.gen_metadata(true);
+    let greet = metadata
+        .functions
+        .iter()
+        .find(|f| f.name == "greet")
+        .unwrap();
+
+    assert_eq!(
+        greet.signature,
+        format!(
+            "greet(name: string, times: {}) -> string",
+            type_name::<INT>()
+        )
+    );
+    assert_eq!(
+        greet.doc_comments,
+        ["/// Greet someone,", "/// many times."]
+    );
+
+    let mut module = Module::new();
+    set_exported_fn!(&mut module, "double", test::double);
+
+    let metadata = engine.gen_module_metadata("", &module);
+
+    assert_eq!(metadata.functions[0].doc_comments, ["/// Double a number."]);
+}
@@ -0,0 +1,104 @@
+use rhai::{Engine, EvalAltResult, LexError, ParseErrorType, Position, Scope, INT};
+
+#[test]
+fn test_parse_errors_recovery() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let script = r"
+        let x = 1 +;
+        let y = 40;
+        let z = (;
+        y += 2;
+        if y > 0 { let w = ; }
+    ";
+
+    let (ast, errors) = engine.compile_with_errors(script);
+
+    let positions: Vec<_> = errors.iter().map(|err| err.position()).collect();
+
+    assert_eq!(
+        positions,
+        vec![
+            Position::new(2, 20),
+            Position::new(4, 18),
+            Position::new(6, 28)
+        ]
+    );
+
+    // The first error is the same as in normal mode
+    assert_eq!(engine.compile(script).expect_err("should error"), errors[0]);
+
+    // The partial AST contains all statements parsed successfully
+    let mut scope = Scope::new();
+    engine.consume_ast_with_scope(&mut scope, &ast)?;
+    assert_eq!(scope.get_value::<INT>("y"), Some(42));
+
+    // No errors for a valid script
+    let (ast, errors) = engine.compile_with_errors("let x = 40; x + 2");
+    assert!(errors.is_empty());
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_errors_blocks() {
+    let engine = Engine::new();
+
+    let (_, errors) = engine.compile_with_errors(
+        r"
+            { let a = 1 + }
+            let b = 1 2;
+            }
+            let c = 'abc';
+            { let d = 1;
+        ",
+    );
+
+    let errors: Vec<_> = errors.into_iter().map(|err| *err.0).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            ParseErrorType::BadInput(LexError::UnexpectedInput("}".into())),
+            ParseErrorType::MissingToken(";".into(), "to terminate this statement".into()),
+            ParseErrorType::BadInput(LexError::UnexpectedInput("}".into())),
+            ParseErrorType::BadInput(LexError::MalformedChar("abc".into())),
+            ParseErrorType::MissingToken("}".into(), "to end this statement block".into()),
+        ]
+    );
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_parse_errors_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let (ast, errors) = engine.compile_with_errors(
+        r"
+            fn foo(x) {
+                let y = ;
+                x + 1
+            }
+            fn bar(x, x) { x }
+            fn baz(x) { x * 2 }
+            let f = |x| { x + };
+            baz(21)
+        ",
+    );
+
+    let errors: Vec<_> = errors.into_iter().map(|err| *err.0).collect();
+
+    assert_eq!(
+        errors,
+        vec![
+            ParseErrorType::BadInput(LexError::UnexpectedInput(";".into())),
+            ParseErrorType::FnDuplicatedParam("bar".into(), "x".into()),
+            ParseErrorType::BadInput(LexError::UnexpectedInput("}".into())),
+        ]
+    );
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use rhai::{Engine, EvalAltResult, LexError, ParseErrorType, Position, Scope, Span, INT};
+
+#[test]
+fn test_positions_large() {
+    let engine = Engine::new();
+
+    let script = format!("{}let x = ;", "\n".repeat(70_000));
+
+    let err = engine.compile(&script).expect_err("should error");
+    assert_eq!(err.position().line(), Some(70_001));
+
+    let script = format!("{}x", " ".repeat(70_000));
+
+    match *engine.eval::<INT>(&script).expect_err("should error") {
+        EvalAltResult::ErrorVariableNotFound(_, pos) => {
+            assert_eq!(pos.position().position(), Some(70_001))
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_positions_too_large() {
+    let engine = Engine::new();
+
+    let script = format!("{}x", " ".repeat(16_777_215));
+
+    let err = engine.compile(&script).expect_err("should error");
+    assert_eq!(
+        *err.0,
+        ParseErrorType::BadInput(LexError::PositionTooLarge(16_777_214))
+    );
+    assert_eq!(err.position().line(), Some(1));
+
+    assert!(Position::try_new(16_777_214, 16_777_214).is_some());
+    assert!(Position::try_new(16_777_215, 1).is_none());
+    assert!(Position::try_new(1, 16_777_215).is_none());
+    assert!(Position::try_new(0, 1).is_none());
+}
+
+#[test]
+fn test_positions_sources() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let err = engine
+        .compile_with_source("bad.rhai", "let x = 42;\nlet y = ;")
+        .expect_err("should error");
+
+    assert_eq!(
+        *err.0,
+        ParseErrorType::BadInput(LexError::UnexpectedInput(";".into()))
+    );
+    assert_eq!(err.source(), Some("bad.rhai"));
+    assert_eq!(err.position(), Position::new(2, 9));
+    assert_eq!(
+        err.to_string(),
+        "Unexpected ';' (line 2, position 9 in 'bad.rhai')"
+    );
+
+    let ast = engine.compile_sources_with_scope(
+        &Scope::new(),
+        &[
+            ("first.rhai", "let x = 40;"),
+            ("second.rhai", "x += 2;\nx / y"),
+        ],
+    )?;
+
+    match *engine.eval_ast::<INT>(&ast).expect_err("should error") {
+        EvalAltResult::ErrorVariableNotFound(_, loc) => {
+            assert_eq!(loc.source(), Some("second.rhai"));
+            assert_eq!(loc.to_string(), "line 2, position 5 in 'second.rhai'");
+        }
+        _ => unreachable!(),
+    }
+
+    // Scripts without a source
+    let err = engine.compile("let y = ;").expect_err("should error");
+    assert_eq!(err.source(), None);
+    assert_eq!(err.position(), Position::new(1, 9));
+    assert_eq!(err.to_string(), "Unexpected ';' (line 1, position 9)");
+
+    Ok(())
+}
+
+#[test]
+fn test_positions_sources_across_engines() -> Result<(), Box<EvalAltResult>> {
+    let engine1 = Engine::new();
+    let engine2 = Engine::new();
+
+    let ast1 = engine1.compile_with_source("one.rhai", "let x = 1;\nx + y")?;
+    let ast2 = engine2.compile_with_source("two.rhai", "z")?;
+
+    // The source name is kept by the error itself, not by the engine
+    let err1 = engine2.eval_ast::<INT>(&ast1).expect_err("should error");
+    let err2 = engine1.eval_ast::<INT>(&ast2).expect_err("should error");
+
+    assert_eq!(err1.source(), Some("one.rhai"));
+    assert_eq!(err1.position(), Position::new(2, 5));
+    assert_eq!(err2.source(), Some("two.rhai"));
+    assert_eq!(err2.position(), Position::new(1, 1));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_module"))]
+fn test_positions_modules() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let ast = engine.compile_with_source("lib.rhai", "fn foo(x) {\n    x + bar\n}")?;
+    let module = rhai::Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+
+    let mut resolver = rhai::module_resolvers::StaticModuleResolver::new();
+    resolver.insert("lib", module);
+    engine.set_module_resolver(Some(resolver));
+
+    let err = engine
+        .eval::<INT>(r#"import "lib" as lib; lib::foo(1)"#)
+        .expect_err("should error");
+
+    match *err {
+        EvalAltResult::ErrorInFunctionCall(_, _, source, err, loc) => {
+            assert_eq!(loc.source(), None);
+            assert_eq!(source.unwrap(), "lib.rhai");
+
+            match *err {
+                EvalAltResult::ErrorVariableNotFound(_, loc) => {
+                    assert_eq!(loc.source(), Some("lib.rhai"));
+                    assert_eq!(loc.position(), Position::new(2, 9));
+                }
+                _ => unreachable!(),
+            }
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_positions_error_spans() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let err = engine
+        .compile_with_source("bad.rhai", "let x = 42;\nlet y = x 123;")
+        .expect_err("should error");
+
+    assert_eq!(err.position(), Position::new(2, 11));
+    assert_eq!(
+        err.span(),
+        Span::new(Position::new(2, 11), Position::new(2, 13))
+    );
+    assert_eq!(err.source(), Some("bad.rhai"));
+
+    let err = engine
+        .eval::<INT>("let x = 40;\nx + foo(1, 2)")
+        .expect_err("should error");
+
+    match *err {
+        EvalAltResult::ErrorFunctionNotFound(_, ref loc) => {
+            assert_eq!(loc.position(), Position::new(2, 5));
+            assert_eq!(
+                loc.span(),
+                Span::new(Position::new(2, 5), Position::new(2, 13))
+            );
+        }
+        _ => unreachable!(),
+    }
+    assert_eq!(
+        err.span().to_string(),
+        "line 2, position 5 to line 2, position 13"
+    );
+
+    let err = engine
+        .eval::<INT>("let x = 40;\nx + xyz")
+        .expect_err("should error");
+
+    assert_eq!(
+        err.span(),
+        Span::new(Position::new(2, 5), Position::new(2, 7))
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_positions_spans() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile_with_source(
+        "spans.rhai",
+        r"
+            fn foo(x) {
+                x + 1
+            }
+            let f = |x| x * 2;
+        ",
+    )?;
+
+    let mut spans: Vec<_> = ast
+        .iter_functions()
+        .map(|(_, _, _, f)| (f.params.len(), f.span))
+        .collect();
+    spans.sort();
+
+    let span = spans[0].1;
+    assert_eq!(
+        (span.start().line(), span.start().position()),
+        (Some(2), Some(13))
+    );
+    assert_eq!(
+        (span.end().line(), span.end().position()),
+        (Some(4), Some(13))
+    );
+    assert_eq!(
+        span.to_string(),
+        "line 2, position 13 to line 4, position 13"
+    );
+
+    Ok(())
+}
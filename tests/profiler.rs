@@ -0,0 +1,134 @@
+#![cfg(not(feature = "no_std"))]
+use rhai::{Engine, EvalAltResult, ProfileMetric, Profiler, RegisterFn, INT};
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_profiler_functions() -> Result<(), Box<EvalAltResult>> {
+    let profiler = Profiler::new();
+
+    let mut engine = Engine::new();
+    engine.register_fn("double", |x: INT| x * 2);
+    engine.set_profiler(Some(profiler.clone()));
+
+    let result = engine.eval::<INT>(
+        r"
+            fn foo(x) {
+                bar(x) + bar(x + 1)
+            }
+            fn bar(x) {
+                double(x)
+            }
+            foo(1) + foo(2)
+        ",
+    )?;
+    assert_eq!(result, 16);
+
+    let report = profiler.report();
+
+    let foo = report.function("foo").unwrap();
+    let bar = report.function("bar").unwrap();
+    let double = report.function("double").unwrap();
+
+    assert_eq!(foo.calls, 2);
+    assert!(!foo.native);
+    assert_eq!(bar.calls, 4);
+    assert_eq!(double.calls, 4);
+    assert!(double.native);
+
+    // Inclusive measurements contain those of the functions called
+    assert!(foo.operations >= foo.self_operations + bar.operations);
+    assert!(bar.operations >= bar.self_operations + double.operations);
+    assert!(foo.time >= bar.time);
+
+    let folded = report.to_folded(ProfileMetric::Operations);
+    let stacks: Vec<_> = folded
+        .lines()
+        .map(|l| l.rsplit_once(' ').unwrap().0)
+        .collect();
+
+    assert!(stacks.contains(&"<script>;foo"));
+    assert!(stacks.contains(&"<script>;foo;bar"));
+
+    let folded_ops: u64 = folded
+        .lines()
+        .map(|l| l.rsplit_once(' ').unwrap().1.parse::<u64>().unwrap())
+        .sum();
+    assert_eq!(folded_ops, report.operations());
+
+    // All operations are attributed to lines
+    assert_eq!(
+        report.lines().iter().map(|l| l.operations).sum::<u64>(),
+        report.operations()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_profiler_lines() -> Result<(), Box<EvalAltResult>> {
+    let profiler = Profiler::new();
+
+    let mut engine = Engine::new();
+    engine.set_profiler(Some(profiler.clone()));
+
+    let ast = engine.compile_with_source(
+        "loop.rhai",
+        "let x = 0;\nfor i in range(0, 10) {\n    x += i;\n}\nx",
+    )?;
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 45);
+
+    let report = profiler.report();
+
+    let line = report.line(Some("loop.rhai"), 3).unwrap();
+    assert_eq!(line.hits, 10);
+    assert!(line.operations >= 10);
+    assert_eq!(report.line(Some("loop.rhai"), 1).unwrap().hits, 1);
+    assert!(report.line(None, 3).is_none());
+
+    // Measurements accumulate over runs until reset
+    engine.eval_ast::<INT>(&ast)?;
+    assert_eq!(
+        profiler.report().line(Some("loop.rhai"), 3).unwrap().hits,
+        20
+    );
+
+    profiler.reset();
+    assert!(profiler.report().lines().is_empty());
+
+    // Profiling is off without a profiler
+    engine.set_profiler(None);
+    engine.eval_ast::<INT>(&ast)?;
+    assert!(profiler.report().lines().is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_profiler_recursion() -> Result<(), Box<EvalAltResult>> {
+    let profiler = Profiler::new();
+
+    let mut engine = Engine::new();
+    engine.set_profiler(Some(profiler.clone()));
+
+    engine.consume(
+        r"
+            fn fib(n) {
+                if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+            }
+            fib(6);
+        ",
+    )?;
+
+    let report = profiler.report();
+    let fib = report.function("fib").unwrap();
+
+    assert_eq!(fib.calls, 25);
+
+    // Recursive calls are not counted more than once
+    assert!(fib.operations <= report.operations());
+    assert!(fib.time <= report.time());
+
+    Ok(())
+}
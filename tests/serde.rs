@@ -290,15 +290,18 @@ fn test_serde_ser_untagged_enum() -> Result<(), Box<EvalAltResult>> {
 
 #[test]
 fn test_serde_de_primary_types() -> Result<(), Box<EvalAltResult>> {
-    assert_eq!(42_u16, from_dynamic(&Dynamic::from(42_u16))?);
-    assert_eq!(42 as INT, from_dynamic(&(42 as INT).into())?);
-    assert_eq!(true, from_dynamic(&true.into())?);
+    assert_eq!(42_u16, from_dynamic::<u16>(&Dynamic::from(42_u16))?);
+    assert_eq!(42 as INT, from_dynamic::<INT>(&(42 as INT).into())?);
+    assert_eq!(true, from_dynamic::<bool>(&true.into())?);
     assert_eq!((), from_dynamic(&().into())?);
 
     #[cfg(not(feature = "no_float"))]
     {
-        assert_eq!(123.456_f64, from_dynamic(&123.456_f64.into())?);
-        assert_eq!(123.456_f32, from_dynamic(&Dynamic::from(123.456_f32))?);
+        assert_eq!(123.456_f64, from_dynamic::<f64>(&123.456_f64.into())?);
+        assert_eq!(
+            123.456_f32,
+            from_dynamic::<f32>(&Dynamic::from(123.456_f32))?
+        );
     }
 
     assert_eq!(
@@ -311,14 +314,14 @@ fn test_serde_de_primary_types() -> Result<(), Box<EvalAltResult>> {
 
 #[test]
 fn test_serde_de_integer_types() -> Result<(), Box<EvalAltResult>> {
-    assert_eq!(42_i8, from_dynamic(&Dynamic::from(42 as INT))?);
-    assert_eq!(42_i16, from_dynamic(&Dynamic::from(42 as INT))?);
-    assert_eq!(42_i32, from_dynamic(&Dynamic::from(42 as INT))?);
-    assert_eq!(42_i64, from_dynamic(&Dynamic::from(42 as INT))?);
-    assert_eq!(42_u8, from_dynamic(&Dynamic::from(42 as INT))?);
-    assert_eq!(42_u16, from_dynamic(&Dynamic::from(42 as INT))?);
-    assert_eq!(42_u32, from_dynamic(&Dynamic::from(42 as INT))?);
-    assert_eq!(42_u64, from_dynamic(&Dynamic::from(42 as INT))?);
+    assert_eq!(42_i8, from_dynamic::<i8>(&Dynamic::from(42 as INT))?);
+    assert_eq!(42_i16, from_dynamic::<i16>(&Dynamic::from(42 as INT))?);
+    assert_eq!(42_i32, from_dynamic::<i32>(&Dynamic::from(42 as INT))?);
+    assert_eq!(42_i64, from_dynamic::<i64>(&Dynamic::from(42 as INT))?);
+    assert_eq!(42_u8, from_dynamic::<u8>(&Dynamic::from(42 as INT))?);
+    assert_eq!(42_u16, from_dynamic::<u16>(&Dynamic::from(42 as INT))?);
+    assert_eq!(42_u32, from_dynamic::<u32>(&Dynamic::from(42 as INT))?);
+    assert_eq!(42_u64, from_dynamic::<u64>(&Dynamic::from(42 as INT))?);
 
     Ok(())
 }
@@ -1,6 +1,13 @@
 #![cfg(not(feature = "unchecked"))]
 use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
 
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_std"))]
+use rhai::FnPtr;
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_std"))]
+use std::any::TypeId;
+
 #[test]
 #[cfg(not(feature = "no_function"))]
 fn test_stack_overflow_fn_calls() -> Result<(), Box<EvalAltResult>> {
@@ -32,6 +39,68 @@ fn test_stack_overflow_fn_calls() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_stack_tail_calls() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn sum(n, acc) { if n == 0 { acc } else { sum(n - 1, acc + n) } }
+                sum(10000, 0)
+    ",
+        )?,
+        50005000
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn count(n, acc) {
+                    while true {
+                        if n == 0 { return acc; }
+                        return count(n - 1, acc + 1);
+                    }
+                }
+                count(10000, 0)
+    ",
+        )?,
+        10000
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn fail(n) {
+                    try {
+                        if n > 0 { throw n; }
+                    } catch (err) {
+                        return fail(err - 1);
+                    }
+                    n
+                }
+                fail(10000)
+    ",
+        )?,
+        0
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<()>(
+                r"
+            fn foo(n) { if n == 0 { 0 } else { foo(n-1) + 0 } }
+            foo(1000)
+    "
+            )
+            .expect_err("should error"),
+        EvalAltResult::ErrorStackOverflow(_)
+    ));
+
+    Ok(())
+}
+
 #[test]
 fn test_stack_overflow_parsing() -> Result<(), Box<EvalAltResult>> {
     let mut engine = Engine::new();
@@ -91,3 +160,51 @@ fn test_stack_overflow_parsing() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+fn test_stack_overflow_native() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_max_expr_depths(
+        0,
+        #[cfg(not(feature = "no_function"))]
+        0,
+    );
+
+    let script = format!("{}1{}", "(1+".repeat(100_000), ")".repeat(100_000));
+
+    assert_eq!(
+        *engine.compile(&script).expect_err("should error").0,
+        ParseErrorType::ExprTooDeep
+    );
+
+    engine.set_max_stack_size(0);
+    engine.compile("(1+(1+(1+(1+1))))")?;
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        engine.set_max_stack_size(512 * 1024);
+
+        // Each native call re-enters the `Engine` at call level zero
+        #[allow(deprecated)]
+        engine.register_raw_fn("recurse", &[TypeId::of::<FnPtr>()], |context, args| {
+            let fp = args[0].clone().cast::<FnPtr>();
+            fp.call_dynamic(context, None, [fp.clone().into()])
+        });
+
+        assert!(matches!(
+            *engine
+                .eval::<()>(
+                    r#"
+                        fn f(fp) { recurse(fp) }
+                        recurse(Fn("f"))
+                    "#
+                )
+                .expect_err("should error"),
+            EvalAltResult::ErrorStackOverflow(_)
+        ));
+    }
+
+    Ok(())
+}
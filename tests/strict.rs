@@ -0,0 +1,153 @@
+use rhai::{Engine, EvalAltResult, ParseErrorType, Position, RegisterFn, Scope, INT};
+
+#[test]
+fn test_strict_variables() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // Off by default
+    engine.compile("let x = 1; { let y = 2; } x + y")?;
+
+    engine.set_strict_mode(true);
+
+    let err = engine
+        .compile("let x = 1; { let y = 2; } x + y")
+        .expect_err("should error");
+    assert_eq!(*err.0, ParseErrorType::VariableNotDefined("y".into()));
+    assert_eq!(err.position(), Position::new(1, 31));
+    assert_eq!(
+        err.to_string(),
+        "Variable 'y' is not defined (line 1, position 31)"
+    );
+
+    // Loop variables, catch variables and variables in the scope
+    let mut scope = Scope::new();
+    scope.push("z", 40 as INT);
+
+    engine.compile_with_scope(
+        &scope,
+        "for x in range(0, 10) { z += x; } try { throw 1; } catch (e) { z += e; }",
+    )?;
+    assert!(engine.compile("z").is_err());
+
+    // 'eval' can define variables, and can use variables outside
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = 41; eval("let y = x + 1;"); y"#)?,
+        42
+    );
+
+    // Variables provided by the variable resolver
+    engine.on_var(|name, _, _| match name {
+        "answer" => Ok(Some((42 as INT).into())),
+        _ => Ok(None),
+    });
+    assert!(engine.compile("answer").is_err());
+    engine.declare_var("answer");
+    assert_eq!(engine.eval::<INT>("answer")?, 42);
+
+    Ok(())
+}
+
+#[test]
+fn test_strict_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_strict_mode(true);
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+
+    // Registered functions, package functions and operators
+    engine.compile(r#"let x = add(1, 2) * 3; print(len("hello") + x);"#)?;
+
+    // Method calls are not checked
+    #[cfg(not(feature = "no_object"))]
+    engine.compile("let x = 1; x.foo(1)")?;
+
+    let err = engine.compile("add(1)").expect_err("should error");
+    assert_eq!(*err.0, ParseErrorType::FnNotDefined("add".into(), 1));
+    assert_eq!(
+        err.to_string(),
+        "Function 'add' with 1 parameter is not defined (line 1, position 1)"
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        // Script-defined functions can be called before they are defined
+        engine.compile("foo(1, 2); fn foo(x, y) { bar(x) + y } fn bar(x) { this + x }")?;
+
+        // Functions only see their own variables
+        let err = engine
+            .compile("let x = 1; fn foo(y) { x + y }")
+            .expect_err("should error");
+        assert_eq!(*err.0, ParseErrorType::VariableNotDefined("x".into()));
+        assert_eq!(err.position(), Position::new(1, 24));
+
+        let err = engine
+            .compile("fn foo(x) { x } foo(1, 2)")
+            .expect_err("should error");
+        assert_eq!(*err.0, ParseErrorType::FnNotDefined("foo".into(), 2));
+
+        #[cfg(not(feature = "no_closure"))]
+        {
+            let err = engine
+                .compile("let x = 1; let f = |y| x + y + z; call(f, 1)")
+                .expect_err("should error");
+            assert_eq!(*err.0, ParseErrorType::VariableNotDefined("z".into()));
+        }
+    }
+
+    // All errors are reported in error-recovery mode
+    let (_, errors) = engine.compile_with_errors("let x = y;\nlet z = ;\nfoo(x)");
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| e.position().line().unwrap())
+            .collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_module"))]
+fn test_strict_modules() -> Result<(), Box<EvalAltResult>> {
+    let mut module = rhai::Module::new();
+    module.set_var("answer", 42 as INT);
+    module.set_fn_1("double", |x: INT| Ok(x * 2));
+
+    let mut resolver = rhai::module_resolvers::StaticModuleResolver::new();
+    resolver.insert("question", module);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+    engine.set_strict_mode(true);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "question" as q; q::double(q::answer / 2)"#)?,
+        42
+    );
+
+    let err = engine
+        .compile(r#"import "question" as q; q::triple(1)"#)
+        .expect_err("should error");
+    assert_eq!(*err.0, ParseErrorType::FnNotDefined("q::triple".into(), 1));
+
+    let err = engine
+        .compile(r#"import "question" as q; q::question"#)
+        .expect_err("should error");
+    assert_eq!(
+        *err.0,
+        ParseErrorType::VariableNotDefined("q::question".into())
+    );
+
+    // Module not imported
+    assert!(engine.compile("q::double(1)").is_err());
+
+    // Modules with paths not known at compile time are not checked
+    engine.compile(r#"let path = "question"; import path as q; q::anything()"#)?;
+
+    // Functions can use modules imported at global level
+    #[cfg(not(feature = "no_function"))]
+    engine.compile(r#"import "question" as q; fn foo() { q::double(q::answer) }"#)?;
+
+    Ok(())
+}
@@ -39,7 +39,7 @@ fn test_custom_syntax() -> Result<(), Box<EvalAltResult>> {
                         Box::new(EvalAltResult::ErrorMismatchDataType(
                             "bool".to_string(),
                             err.to_string(),
-                            condition.position(),
+                            condition.position().into(),
                         ))
                     })?;
 
@@ -91,7 +91,7 @@ fn test_custom_syntax_raw() -> Result<(), Box<EvalAltResult>> {
                     Box::new(ParseErrorType::BadInput(LexError::ImproperSymbol(
                         s.to_string(),
                     ))),
-                    NO_POS,
+                    NO_POS.into(),
                 )),
             },
             _ => unreachable!(),
@@ -0,0 +1,207 @@
+use rhai::{Engine, EvalAltResult, IdentRole, NodeKind, Position, TokenKind, INT};
+
+const SCRIPT: &str = r#"// Compute the answer
+fn add(a, b) {
+    a + b   /* sum */
+}
+
+let x = #{ a: [1, (2)] };	// tab before comment
+if x.a[0] > 0 { print("positive ✓"); } else { print("negative"); }
+add(x.a[0], 41)
+"#;
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_syntax_tree_lossless() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let tree = engine.parse_syntax_tree(SCRIPT);
+
+    // All text is kept
+    assert_eq!(tree.text(), SCRIPT);
+    assert_eq!(tree.root().kind(), NodeKind::Script);
+
+    // Byte offsets and texts of tokens match the source
+    for token in tree.tokens() {
+        assert_eq!(
+            &SCRIPT[token.offset()..token.offset() + token.text().len()],
+            token.text()
+        );
+    }
+
+    let comments: Vec<_> = tree
+        .tokens()
+        .filter(|t| t.kind() == TokenKind::Comment)
+        .map(|t| (t.text(), t.span().start(), t.span().end()))
+        .collect();
+
+    assert_eq!(
+        comments,
+        vec![
+            (
+                "// Compute the answer",
+                Position::new(1, 1),
+                Position::new(1, 21)
+            ),
+            ("/* sum */", Position::new(3, 13), Position::new(3, 21)),
+            (
+                "// tab before comment",
+                Position::new(6, 27),
+                Position::new(6, 47)
+            ),
+        ]
+    );
+
+    // Statements and functions
+    let kinds: Vec<_> = tree
+        .root()
+        .children()
+        .iter()
+        .filter_map(|e| e.as_node())
+        .map(|n| n.kind())
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            NodeKind::Function,
+            NodeKind::Statement,
+            NodeKind::Statement,
+            NodeKind::Statement
+        ]
+    );
+
+    let statement = tree
+        .root()
+        .children()
+        .iter()
+        .filter_map(|e| e.as_node())
+        .nth(2)
+        .unwrap();
+    assert_eq!(
+        statement.text(),
+        r#"if x.a[0] > 0 { print("positive ✓"); } else { print("negative"); }"#
+    );
+    assert_eq!(statement.span().end(), Position::new(7, 66));
+
+    // Parentheses are kept
+    assert!(tree
+        .root()
+        .descendants()
+        .any(|n| n.kind() == NodeKind::Parens && n.text() == "(2)"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_function"))]
+fn test_syntax_tree_to_ast() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let tree = engine.parse_syntax_tree(SCRIPT);
+    let ast = tree.to_ast()?;
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    // Map positions in the AST back to tokens
+    let (_, _, _, f) = ast.iter_functions().next().unwrap();
+    let token = tree.token_at(f.span.start()).unwrap();
+    assert_eq!(token.text(), "fn");
+
+    // Rename a function
+    let code: String = tree
+        .tokens()
+        .map(|t| match (t.role(), t.text()) {
+            (Some(IdentRole::Function), "add") => "plus",
+            (_, text) => text,
+        })
+        .collect();
+
+    assert!(code.contains("fn plus(a, b)"));
+    assert!(code.contains("plus(x.a[0], 41)"));
+
+    // Invalid input is kept
+    let tree = engine.parse_syntax_tree("let x = 1 $ 2;");
+    assert_eq!(tree.text(), "let x = 1 $ 2;");
+    assert!(tree.tokens().any(|t| t.kind() == TokenKind::Error));
+    assert!(tree.to_ast().is_err());
+    assert_eq!(tree.errors().len(), 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_function"))]
+fn test_syntax_tree_roles() {
+    let engine = Engine::new();
+
+    let tree = engine.parse_syntax_tree(SCRIPT);
+
+    let roles = |text| {
+        tree.tokens()
+            .filter(|t| t.text() == text)
+            .map(|t| t.role())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        roles("a"),
+        vec![
+            Some(IdentRole::Declaration),
+            Some(IdentRole::Variable),
+            Some(IdentRole::Property),
+            Some(IdentRole::Property),
+            Some(IdentRole::Property),
+        ]
+    );
+    assert_eq!(
+        roles("add"),
+        vec![Some(IdentRole::Function), Some(IdentRole::Function)]
+    );
+    assert_eq!(
+        roles("x"),
+        vec![
+            Some(IdentRole::Declaration),
+            Some(IdentRole::Variable),
+            Some(IdentRole::Variable),
+        ]
+    );
+    // Keywords are not identifiers
+    assert_eq!(roles("print"), vec![None, None]);
+    assert_eq!(roles("let"), vec![None]);
+
+    // Functions and variables of the same name are told apart
+    let tree = engine.parse_syntax_tree("fn foo(foo) { foo }\nlet foo = 1;\nfoo(foo)");
+
+    let code: String = tree
+        .tokens()
+        .map(|t| match (t.role(), t.text()) {
+            (Some(IdentRole::Function), "foo") => "bar",
+            (_, text) => text,
+        })
+        .collect();
+
+    assert_eq!(code, "fn bar(foo) { foo }\nlet foo = 1;\nbar(foo)");
+}
+
+#[test]
+#[cfg(not(feature = "no_module"))]
+fn test_syntax_tree_roles_modules() {
+    let engine = Engine::new();
+
+    let tree = engine.parse_syntax_tree(r#"import "hello" as h; let y = h::x; export y as z;"#);
+
+    let role = |text| tree.tokens().find(|t| t.text() == text).unwrap().role();
+
+    assert_eq!(role("h"), Some(IdentRole::Module));
+    assert_eq!(role("x"), Some(IdentRole::Variable));
+    assert_eq!(role("z"), Some(IdentRole::Declaration));
+    assert!(tree
+        .tokens()
+        .filter(|t| t.text() == "h")
+        .all(|t| t.role() == Some(IdentRole::Module)));
+}
@@ -0,0 +1,235 @@
+use rhai::packages::{Package, TestingPackage};
+use rhai::{Engine, EvalAltResult, RegisterFn, INT};
+
+#[test]
+fn test_testing_assert() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.load_package(TestingPackage::new().get());
+
+    engine.eval::<()>(
+        r#"
+            assert(true);
+            assert(1 < 2, "less");
+            assert_eq(1 + 2, 3);
+            assert_eq("hello", "hel" + "lo", "strings");
+            assert_ne('a', 'b');
+            assert_ne(1, "1");
+        "#,
+    )?;
+
+    let err = engine
+        .eval::<()>("let x = 1;\nassert_eq(x, 2)")
+        .expect_err("should fail");
+    assert_eq!(
+        err.to_string(),
+        "Runtime error: Assertion failed: left == right (left: 1, right: 2) (line 2, position 1)"
+    );
+
+    assert!(engine
+        .eval::<()>(r#"assert_ne(42, 42, "the answer")"#)
+        .expect_err("should fail")
+        .to_string()
+        .starts_with("Runtime error: Assertion failed: the answer (left: 42, right: 42)"));
+
+    assert!(engine
+        .eval::<()>(r#"assert(false, "oops")"#)
+        .expect_err("should fail")
+        .to_string()
+        .starts_with("Runtime error: Assertion failed: oops"));
+
+    #[cfg(not(feature = "no_float"))]
+    assert!(engine
+        .eval::<()>("assert_eq(1, 1.0)")
+        .expect_err("should fail")
+        .to_string()
+        .starts_with("Runtime error: Assertion failed: left == right (left: 1, right: 1.0)"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_testing_assert_collections() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.load_package(TestingPackage::new().get());
+
+    engine.eval::<()>(
+        r#"
+            let map = #{a: 1, b: "x"};
+            assert_eq([1, "x", map], [1, "x", #{b: "x", a: 1}]);
+            assert_ne([1, 2], [1, 2, 3]);
+            assert_ne(map, #{a: 1, b: "y"});
+        "#,
+    )?;
+
+    assert!(engine
+        .eval::<()>("assert_eq([1, 2], [1, 3])")
+        .expect_err("should fail")
+        .to_string()
+        .starts_with(
+            "Runtime error: Assertion failed: left == right (left: [1, 2], right: [1, 3])"
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_testing_assert_custom_type() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Point {
+        x: INT,
+        y: INT,
+    }
+
+    let mut engine = Engine::new();
+    engine.load_package(TestingPackage::new().get());
+
+    engine
+        .register_fn("point", |x: INT, y: INT| Point { x, y })
+        .register_fn("==", |a: &mut Point, b: Point| *a == b)
+        .register_assertions::<Point>();
+
+    engine.eval::<()>(
+        r#"
+            assert_eq(point(1, 2), point(1, 2));
+            assert_ne(point(1, 2), point(2, 1), "points");
+        "#,
+    )?;
+
+    assert!(engine
+        .eval::<()>("assert_eq(point(1, 2), point(2, 1))")
+        .expect_err("should fail")
+        .to_string()
+        .starts_with("Runtime error: Assertion failed: left == right"));
+
+    // Values of custom types without `==` cannot be compared
+    let mut engine = Engine::new();
+    engine.load_package(TestingPackage::new().get());
+    engine
+        .register_fn("point", |x: INT, y: INT| Point { x, y })
+        .register_assertions::<Point>();
+
+    assert!(matches!(
+        *engine
+            .eval::<()>("assert_eq(point(1, 2), point(1, 2))")
+            .expect_err("should fail"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f.starts_with("== (")
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_testing_runner() -> Result<(), Box<EvalAltResult>> {
+    use rhai::TestOptions;
+
+    let mut engine = Engine::new();
+    engine.load_package(TestingPackage::new().get());
+
+    let ast = engine.compile_with_source(
+        "math.rhai",
+        r#"
+            let x = 42;
+
+            fn add(x, y) { x + y }
+
+            fn test_add() {
+                assert_eq(add(1, 2), 3);
+            }
+            fn test_add_wrong() {
+                assert_eq(add(1, 2), 4, "one plus two");
+            }
+            fn test_throw() { throw "oops"; }
+
+            fn test_scope() {
+                // Global statements are not run
+                assert(!is_def_var("x"));
+            }
+
+            private fn test_private() { throw 1; }
+            fn test_with_params(x) { throw 1; }
+            fn helper() { throw 1; }
+        "#,
+    )?;
+
+    let report = engine.run_tests(&ast, &TestOptions::default());
+
+    assert_eq!(
+        report
+            .results
+            .iter()
+            .map(|r| (r.name.as_str(), r.passed()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("test_add", true),
+            ("test_add_wrong", false),
+            ("test_throw", false),
+            ("test_scope", true),
+        ]
+    );
+    assert_eq!(report.num_passed(), 2);
+    assert_eq!(report.num_failed(), 2);
+    assert!(!report.is_success());
+    assert_eq!(report.results[0].source.as_deref(), Some("math.rhai"));
+
+    let err = report.results[1].error.as_ref().unwrap();
+    assert_eq!(err.position().line(), Some(10));
+
+    let tap = report.to_tap();
+
+    assert!(tap.starts_with("TAP version 13\n1..4\nok 1 - math.rhai: test_add\n"));
+    assert!(tap.contains(
+        "not ok 2 - math.rhai: test_add_wrong\n  ---\n  message: \"Runtime error: Assertion failed: one plus two (left: 3, right: 4)"
+    ));
+    assert!(tap.contains("  line: 10\n  position: 17\n  ...\n"));
+    assert!(tap.contains("not ok 3 - math.rhai: test_throw\n"));
+
+    let options = TestOptions {
+        filter: Some("wrong".into()),
+        ..Default::default()
+    };
+    let report = engine.run_tests(&ast, &options);
+
+    assert_eq!(report.results.len(), 1);
+    assert_eq!(report.results[0].name, "test_add_wrong");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "unchecked"))]
+fn test_testing_runner_limits() -> Result<(), Box<EvalAltResult>> {
+    use rhai::TestOptions;
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(1000);
+
+    let ast = engine.compile(
+        "
+            fn test_loop() { loop {} }
+            fn test_count() { let x = 0; while x < 100 { x += 1; } }
+            fn test_count_again() { let x = 0; while x < 100 { x += 1; } }
+        ",
+    )?;
+
+    let report = engine.run_tests(&ast, &TestOptions::default());
+
+    // Each test counts operations from the start
+    assert_eq!(
+        report
+            .results
+            .iter()
+            .map(|r| r.passed())
+            .collect::<Vec<_>>(),
+        vec![false, true, true]
+    );
+    assert!(matches!(
+        report.results[0].error.as_deref(),
+        Some(EvalAltResult::ErrorTooManyOperations(_))
+    ));
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
-use rhai::{Engine, EvalAltResult, INT};
+use rhai::{Engine, EvalAltResult, Position, INT};
+#[cfg(not(feature = "no_function"))]
+use std::sync::{Arc, RwLock};
 
 #[test]
 fn test_throw() {
@@ -29,6 +31,12 @@ fn test_try_catch() -> Result<(), Box<EvalAltResult>> {
         123
     );
 
+    assert_eq!(
+        engine
+            .eval::<INT>("let x = 1; let y = 2; try { throw 40; } catch (x) { y += x; } x + y")?,
+        43
+    );
+
     #[cfg(not(feature = "unchecked"))]
     assert!(matches!(
         *engine
@@ -39,3 +47,221 @@ fn test_try_catch() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_try_catch_error_value() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let r = "";
+                try {
+                    let x = 1;
+                    x + y;
+                } catch (err) {
+                    r = err.kind + "|" + err.message + "|" + err.line + ":" + err.position;
+                    r += "|" + type_of(err.function) + "|" + type_of(err.source);
+                }
+                r
+            "#
+        )?,
+        "VariableNotFound|Variable not found: 'y'|5:25|()|()"
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                fn foo(x) { bar(x) }
+                fn bar(x) { x.len() }
+                let r = "";
+                try { foo(42); } catch (err) { r = err.kind + ":" + err.function; }
+                r
+            "#
+        )?,
+        "FunctionNotFound:bar"
+    );
+
+    // Thrown values keep their payload, even from within functions
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn foo(x) { throw #{ code: x }; }
+                let r = 0;
+                try { foo(42); } catch (err) { r = err.code; }
+                r
+            "#
+        )?,
+        42
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_try_catch_rethrow() {
+    let engine = Engine::new();
+
+    // `throw;` re-throws the original error unchanged, including its position
+    match *engine
+        .eval::<()>("let x = 1;\ntry { x + y; } catch { throw; }")
+        .expect_err("expects error")
+    {
+        EvalAltResult::ErrorVariableNotFound(name, pos) => {
+            assert_eq!(name, "y");
+            assert_eq!(pos.position(), Position::new(2, 11));
+        }
+        _ => unreachable!(),
+    }
+
+    match *engine
+        .eval::<()>("try {\n  throw 42;\n} catch (err) {\n  throw;\n}")
+        .expect_err("expects error")
+    {
+        EvalAltResult::ErrorRuntime(value, pos) => {
+            assert_eq!(value.as_int().unwrap(), 42);
+            assert_eq!(pos.position(), Position::new(2, 3));
+        }
+        _ => unreachable!(),
+    }
+
+    // Throwing `()` is not a re-throw
+    match *engine
+        .eval::<()>("try {\n  throw 42;\n} catch {\n  throw ();\n}")
+        .expect_err("expects error")
+    {
+        EvalAltResult::ErrorRuntime(value, pos) => {
+            assert!(value.is::<()>());
+            assert_eq!(pos.position(), Position::new(4, 3));
+        }
+        _ => unreachable!(),
+    }
+
+    // Only `throw;` directly inside the catch block re-throws, not in functions called from it
+    #[cfg(not(feature = "no_function"))]
+    assert!(matches!(
+        *engine
+            .eval::<()>("fn f() { throw; } try { throw 42; } catch { f(); }")
+            .expect_err("expects error"),
+        EvalAltResult::ErrorInFunctionCall(_, _, _, err, _)
+            if matches!(*err, EvalAltResult::ErrorRuntime(ref value, _) if value.is::<()>())
+    ));
+
+    // A nested `try` block does not catch the re-thrown error
+    assert!(matches!(
+        *engine
+            .eval::<()>("try { throw 42; } catch { try { throw; } catch { throw 0; } }")
+            .expect_err("expects error"),
+        EvalAltResult::ErrorRuntime(value, _) if value.as_int().unwrap() == 42
+    ));
+}
+
+#[test]
+fn test_try_catch_finally() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    // Normal completion
+    assert_eq!(
+        engine.eval::<INT>("let r = 0; try { r = 1; } catch { r = 2; } finally { r *= 10; } r")?,
+        10
+    );
+
+    // Caught error
+    assert_eq!(
+        engine.eval::<INT>(
+            "let r = 0; try { throw 1; } catch (x) { r = x + 1; } finally { r *= 10; } r"
+        )?,
+        20
+    );
+
+    // Error in the catch block
+    assert!(matches!(
+        *engine
+            .eval::<INT>("let r = 0; try { throw 1; } catch { throw; } finally { r = 42; } r")
+            .expect_err("expects error"),
+        EvalAltResult::ErrorRuntime(x, _) if x.as_int().unwrap() == 1
+    ));
+
+    // `break` and `continue`
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let r = 0;
+                for i in range(0, 5) {
+                    try {
+                        if i == 1 { continue; }
+                        if i == 3 { break; }
+                    } catch {
+                    } finally {
+                        r += 1;
+                    }
+                }
+                r
+            "
+        )?,
+        4
+    );
+
+    // An error in the finally block overrides the result
+    assert!(matches!(
+        *engine
+            .eval::<()>("try { throw 1; } catch { throw 2; } finally { throw 3; }")
+            .expect_err("expects error"),
+        EvalAltResult::ErrorRuntime(x, _) if x.as_int().unwrap() == 3
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_try_catch_finally_return() -> Result<(), Box<EvalAltResult>> {
+    let log = Arc::new(RwLock::new(Vec::<String>::new()));
+    let log1 = log.clone();
+
+    let mut engine = Engine::new();
+    engine.on_print(move |s| log1.write().unwrap().push(s.to_string()));
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn foo(x) {
+                    try { return x; } catch { } finally { print("cleanup " + x); }
+                    0
+                }
+                foo(42)
+            "#
+        )?,
+        42
+    );
+    assert_eq!(*log.read().unwrap(), vec!["cleanup 42"]);
+
+    // `return` inside `try` without `finally`
+    assert_eq!(
+        engine.eval::<INT>("fn foo(x) { try { return x; } catch { } 0 } foo(42)")?,
+        42
+    );
+
+    // Calls in `return` statements inside `catch` blocks are not tail calls
+    // when there is a `finally` block
+    log.write().unwrap().clear();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn countdown(n) {
+                    if n == 0 { return 0; }
+                    try { throw n; } catch (x) { return countdown(x - 1); } finally { print(n); }
+                }
+                countdown(3)
+            "#
+        )?,
+        0
+    );
+    assert_eq!(*log.read().unwrap(), vec!["1", "2", "3"]);
+
+    Ok(())
+}
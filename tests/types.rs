@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, RegisterFn, INT};
+use rhai::{Engine, EvalAltResult, ParseErrorType, Position, RegisterFn, Scope, INT};
 
 #[test]
 fn test_type_of() -> Result<(), Box<EvalAltResult>> {
@@ -54,3 +54,258 @@ fn test_type_of() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_type_annotations_variables() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let int_type = engine.eval::<String>("type_of(1)")?;
+
+    assert_eq!(engine.eval::<INT>("let x: int = 40; x += 2; x")?, 42);
+
+    let err = engine
+        .compile(r#"let x: int = "hello";"#)
+        .expect_err("should error");
+    assert_eq!(
+        *err.0,
+        ParseErrorType::MismatchedType("string".into(), int_type.clone())
+    );
+    assert_eq!(err.position(), Position::new(1, 14));
+
+    let err = engine
+        .compile(r#"let x: string = "hello"; let y: int = x;"#)
+        .expect_err("should error");
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Data type is incorrect: string (expecting {}) (line 1, position 39)",
+            int_type
+        )
+    );
+
+    assert_eq!(
+        *engine
+            .compile("const x: foo = 1;")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::UnknownType("foo".into())
+    );
+    assert_eq!(
+        *engine.compile("let x: = 1;").expect_err("should error").0,
+        ParseErrorType::TypeExpected
+    );
+
+    // Types not known at compile time are checked at runtime
+    assert!(matches!(
+        *engine.eval::<()>("let x: string = 1 + 2;").expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(t, e, _) if *t == int_type && e == "string"
+    ));
+    assert!(matches!(
+        *engine.eval::<()>(r#"let x: int = 1; x += "!";"#).expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(t, e, _) if t == "string" && *e == int_type
+    ));
+    assert!(matches!(
+        *engine
+            .compile(r#"let x: int = 1; x = "!";"#)
+            .expect_err("should error")
+            .0,
+        ParseErrorType::MismatchedType(_, _)
+    ));
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        assert_eq!(engine.eval::<rhai::FLOAT>("let x: float = 1.5; x")?, 1.5);
+
+        let float_type = engine.eval::<String>("type_of(1.0)")?;
+
+        assert_eq!(
+            engine
+                .eval::<()>("let x: float = 1.5; x = 1 + 1;")
+                .expect_err("should error")
+                .to_string(),
+            format!(
+                "Data type is incorrect: {} (expecting {}) (line 1, position 27)",
+                int_type, float_type
+            )
+        );
+    }
+
+    // Assignments from scripts compiled separately are checked against the annotation
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"let x: int = 1; eval("x = \"a\"");"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(t, e, _) if t == "string" && *e == int_type
+    ));
+
+    let mut scope = Scope::new();
+    engine.eval_with_scope::<()>(&mut scope, "let x: int = 1;")?;
+    assert!(matches!(
+        *engine
+            .eval_with_scope::<()>(&mut scope, r#"x = "a";"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(t, e, _) if t == "string" && *e == int_type
+    ));
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_type_annotations_custom_types() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Clone)]
+    struct TestStruct {
+        x: INT,
+    }
+
+    let mut engine = Engine::new();
+
+    engine.register_type_with_name::<TestStruct>("TestStruct");
+    engine.register_fn("new_ts", || TestStruct { x: 1 });
+
+    assert_eq!(
+        engine
+            .eval::<TestStruct>("let t: TestStruct = new_ts(); t")?
+            .x,
+        1
+    );
+    assert!(engine.compile("let t: TestStruct = 1;").is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_type_annotations_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("fn add(x: int, y: int) -> int { x + y } add(40, 2)")?,
+        42
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"fn add(x: int, y: int) { x + y } add("a", "b")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f.starts_with("add (")
+    ));
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"fn foo(x) -> int { x } foo("a")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(f, _, _, err, _)
+            if f == "foo" && matches!(*err, EvalAltResult::ErrorMismatchOutputType(_, _, _))
+    ));
+
+    assert!(matches!(
+        *engine
+            .compile(r#"fn foo() -> int { "a" }"#)
+            .expect_err("should error")
+            .0,
+        ParseErrorType::MismatchedType(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .compile("fn foo(x: int) -> string { if x > 0 { return x; } \"\" }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::MismatchedType(_, _)
+    ));
+
+    // Parameters keep their type annotations in the function body
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"fn foo(x: int) { eval("x = \"a\""); } foo(1)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(f, _, _, err, _)
+            if f == "foo" && matches!(*err, EvalAltResult::ErrorMismatchDataType(_, _, _))
+    ));
+
+    // Captured variables keep their type annotations in closures
+    #[cfg(not(feature = "no_closure"))]
+    {
+        assert!(matches!(
+            *engine
+                .compile(r#"let x: int = 1; let f = || { x = "a"; };"#)
+                .expect_err("should error")
+                .0,
+            ParseErrorType::MismatchedType(_, _)
+        ));
+        assert!(matches!(
+            *engine
+                .eval::<()>(r#"let x: int = 1; let f = || { x = "a" + 1; }; call(f);"#)
+                .expect_err("should error"),
+            EvalAltResult::ErrorInFunctionCall(_, _, _, err, _)
+                if matches!(*err, EvalAltResult::ErrorMismatchDataType(_, _, _))
+        ));
+        assert_eq!(
+            engine.eval::<INT>("let x: int = 1; let f = || { x = 42; }; call(f); x")?,
+            42
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_type_annotations_overloading() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        r#"
+            fn describe(x: int) { "int" }
+            fn describe(x: string) { "string" }
+            fn describe(x) { "other" }
+
+            fn pick(x: int, y) { 1 }
+            fn pick(x: int, y: int) { 2 }
+        "#,
+    )?;
+
+    assert_eq!(
+        engine.eval_ast::<String>(
+            &ast.merge(&engine.compile(r#"describe(1) + describe("a") + describe(true)"#)?)
+        )?,
+        "intstringother"
+    );
+    assert_eq!(
+        engine
+            .eval_ast::<INT>(&ast.merge(&engine.compile(r#"pick(1, 2) * 10 + pick(1, "a")"#)?))?,
+        21
+    );
+    assert!(engine
+        .eval_ast::<INT>(&ast.merge(&engine.compile(r#"pick("a", 1)"#)?))
+        .is_err());
+
+    let mut scope = Scope::new();
+    assert_eq!(
+        engine.call_fn::<_, String>(&mut scope, &ast, "describe", ("a",))?,
+        "string"
+    );
+    assert_eq!(
+        engine.call_fn::<_, String>(&mut scope, &ast, "describe", (42 as INT,))?,
+        "int"
+    );
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let module = rhai::Module::eval_ast_as_new(Scope::new(), &ast, &engine)?;
+        let mut resolver = rhai::module_resolvers::StaticModuleResolver::new();
+        resolver.insert("util", module);
+
+        let mut engine = Engine::new();
+        engine.set_module_resolver(Some(resolver));
+
+        assert_eq!(
+            engine.eval::<String>(
+                r#"import "util" as u; u::describe(1) + u::describe("a") + u::describe(())"#
+            )?,
+            "intstringother"
+        );
+    }
+
+    Ok(())
+}
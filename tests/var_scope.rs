@@ -67,7 +67,7 @@ fn test_var_resolver() -> Result<(), Box<EvalAltResult>> {
             "MYSTIC_NUMBER" => Ok(Some((42 as INT).into())),
             // Override a variable - make it not found even if it exists!
             "DO_NOT_USE" => {
-                Err(EvalAltResult::ErrorVariableNotFound(name.to_string(), NO_POS).into())
+                Err(EvalAltResult::ErrorVariableNotFound(name.to_string(), NO_POS.into()).into())
             }
             // Silently maps 'chameleon' into 'innocent'.
             "chameleon" => context
@@ -75,7 +75,7 @@ fn test_var_resolver() -> Result<(), Box<EvalAltResult>> {
                 .get_value("innocent")
                 .map(Some)
                 .ok_or_else(|| {
-                    EvalAltResult::ErrorVariableNotFound(name.to_string(), NO_POS).into()
+                    EvalAltResult::ErrorVariableNotFound(name.to_string(), NO_POS.into()).into()
                 }),
             // Return Ok(None) to continue with the normal variable resolution process.
             _ => Ok(None),